@@ -0,0 +1,98 @@
+//! Fixture: small inventory service used by the analyzer benchmarks
+
+use std::collections::HashMap;
+use std::fmt;
+
+/// Errors raised by inventory operations
+#[derive(Debug, Clone, PartialEq)]
+pub enum InventoryError {
+    UnknownSku(String),
+    InsufficientStock { sku: String, requested: u32, available: u32 },
+}
+
+impl fmt::Display for InventoryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InventoryError::UnknownSku(sku) => write!(f, "unknown sku {}", sku),
+            InventoryError::InsufficientStock { sku, requested, available } => write!(
+                f,
+                "cannot take {} of {}, only {} available",
+                requested, sku, available
+            ),
+        }
+    }
+}
+
+pub trait StockSource {
+    fn quantity(&self, sku: &str) -> Option<u32>;
+    fn set_quantity(&mut self, sku: &str, quantity: u32);
+}
+
+#[derive(Debug, Default)]
+pub struct Inventory {
+    items: HashMap<String, u32>,
+    reserved: HashMap<String, u32>,
+}
+
+impl Inventory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn restock(&mut self, sku: &str, amount: u32) {
+        *self.items.entry(sku.to_string()).or_insert(0) += amount;
+    }
+
+    pub fn reserve(&mut self, sku: &str, amount: u32) -> Result<(), InventoryError> {
+        let available = self.available(sku)?;
+        if available < amount {
+            return Err(InventoryError::InsufficientStock {
+                sku: sku.to_string(),
+                requested: amount,
+                available,
+            });
+        }
+        *self.reserved.entry(sku.to_string()).or_insert(0) += amount;
+        Ok(())
+    }
+
+    pub fn available(&self, sku: &str) -> Result<u32, InventoryError> {
+        let stock = self
+            .items
+            .get(sku)
+            .ok_or_else(|| InventoryError::UnknownSku(sku.to_string()))?;
+        let reserved = self.reserved.get(sku).copied().unwrap_or(0);
+        Ok(stock.saturating_sub(reserved))
+    }
+
+    pub async fn sync_with<S: StockSource>(&mut self, source: &mut S) {
+        for (sku, quantity) in self.items.iter() {
+            match source.quantity(sku) {
+                Some(remote) if remote > *quantity => continue,
+                Some(_) | None => source.set_quantity(sku, *quantity),
+            }
+        }
+    }
+}
+
+impl StockSource for Inventory {
+    fn quantity(&self, sku: &str) -> Option<u32> {
+        self.items.get(sku).copied()
+    }
+
+    fn set_quantity(&mut self, sku: &str, quantity: u32) {
+        self.items.insert(sku.to_string(), quantity);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reserve_fails_when_stock_is_short() {
+        let mut inventory = Inventory::new();
+        inventory.restock("apple", 2);
+        assert!(inventory.reserve("apple", 3).is_err());
+    }
+}
@@ -0,0 +1,44 @@
+// Fixture: Angular service used as search corpus by the benchmarks
+import { Injectable } from '@angular/core';
+import { HttpClient } from '@angular/common/http';
+import { Observable, of } from 'rxjs';
+import { catchError, map } from 'rxjs/operators';
+
+export interface User {
+  id: number;
+  name: string;
+  email: string;
+}
+
+@Injectable({ providedIn: 'root' })
+export class UserService {
+  private cache = new Map<number, User>();
+
+  constructor(private http: HttpClient) {}
+
+  getUser(id: number): Observable<User | undefined> {
+    const cached = this.cache.get(id);
+    if (cached) {
+      return of(cached);
+    }
+    return this.http.get<User>(`/api/users/${id}`).pipe(
+      map(user => {
+        this.cache.set(id, user);
+        return user;
+      }),
+      catchError(() => of(undefined))
+    );
+  }
+
+  searchUsers(term: string): Observable<User[]> {
+    return this.http.get<User[]>('/api/users', { params: { q: term } });
+  }
+
+  validateEmail(email: string): boolean {
+    return /^[^@\s]+@[^@\s]+\.[^@\s]+$/.test(email);
+  }
+
+  clearCache(): void {
+    this.cache.clear();
+  }
+}
@@ -0,0 +1,141 @@
+//! Criterion benchmarks for the analysis and search hot paths.
+//!
+//! Everything runs offline: search uses the hashing embedding and
+//! lexical-overlap reranker backends instead of the Qwen models, and the
+//! corpus is read from `benches/fixtures`.
+//!
+//! Run with `cargo bench --bench hot_paths`. Timings depend on the machine,
+//! so compare against a baseline recorded on the same one:
+//!
+//! ```text
+//! cargo bench --bench hot_paths -- --save-baseline main
+//! cargo bench --bench hot_paths -- --baseline main
+//! ```
+
+use std::path::Path;
+use std::sync::Arc;
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use parking_lot::RwLock;
+use token_optimizer::analyzers::rust_analyzer::RustAnalyzer;
+use token_optimizer::ml::services::embedding_template::FIXED_INPUT_ID;
+use token_optimizer::ml::services::enhanced_search::{
+    CodeIndexEntry, EnhancedSearchService, SearchFilters, SearchOptions, SearchRequest, SearchType,
+};
+use token_optimizer::ml::vector_db::{
+    CodeMetadata, CodeType, HashingEmbeddingBackend, OverlapRerankerBackend, VectorDBConfig,
+    VectorEntry, VectorStoreFactory,
+};
+use token_optimizer::ml::MLConfig;
+
+const EMBEDDING_DIM: usize = 768;
+const RUST_FIXTURE: &str = include_str!("fixtures/inventory.rs");
+const TS_FIXTURE: &str = include_str!("fixtures/user.service.ts");
+
+/// Splits a fixture into blank-line separated chunks to index
+fn fixture_chunks(file_path: &str, language: &str, content: &str) -> Vec<CodeIndexEntry> {
+    let mut entries = Vec::new();
+    let mut line_start = 1;
+    for chunk in content.split("\n\n") {
+        let line_count = chunk.lines().count().max(1);
+        entries.push(CodeIndexEntry {
+            file_path: file_path.to_string(),
+            function_name: None,
+            line_start,
+            line_end: line_start + line_count - 1,
+            code_type: CodeType::Function,
+            language: language.to_string(),
+            complexity: 1.0,
+            content: chunk.to_string(),
+            embedding_template: FIXED_INPUT_ID.to_string(),
+        });
+        line_start += line_count + 1;
+    }
+    entries
+}
+
+fn synthetic_entry(embedder: &HashingEmbeddingBackend, i: usize) -> VectorEntry {
+    let content = format!("fn handler_{} process request {} validate user {}", i, i % 17, i % 31);
+    VectorEntry {
+        id: format!("synthetic_{}.rs:1:10", i),
+        embedding: embedder.embed(&content),
+        metadata: CodeMetadata {
+            file_path: format!("synthetic_{}.rs", i),
+            function_name: Some(format!("handler_{}", i)),
+            line_start: 1,
+            line_end: 10,
+            code_type: CodeType::Function,
+            language: "rust".to_string(),
+            complexity: 1.0,
+            tokens: vec![],
+            calls: Vec::new(),
+            hash: i.to_string(),
+            embedding_template: "enriched".to_string(),
+        },
+        created_at: chrono::Utc::now(),
+        updated_at: chrono::Utc::now(),
+    }
+}
+
+fn bench_rust_analyzer(c: &mut Criterion) {
+    let mut analyzer = RustAnalyzer::new().unwrap();
+    let path = Path::new("benches/fixtures/inventory.rs");
+
+    c.bench_function("rust_analyzer/analyze_file/inventory.rs", |b| {
+        b.iter(|| analyzer.analyze_file(black_box(path), black_box(RUST_FIXTURE)).unwrap())
+    });
+}
+
+fn bench_vector_db_search(c: &mut Criterion) {
+    let embedder = HashingEmbeddingBackend::new(EMBEDDING_DIM);
+    let query = embedder.embed("validate user request");
+    let mut group = c.benchmark_group("vector_db/search");
+
+    for size in [1_000, 10_000] {
+        let db = VectorStoreFactory::create_native(VectorDBConfig {
+            similarity_threshold: 0.1,
+            ..VectorDBConfig::for_testing()
+        });
+        db.write()
+            .add_vectors((0..size).map(|i| synthetic_entry(&embedder, i)).collect())
+            .unwrap();
+
+        group.bench_with_input(BenchmarkId::from_parameter(size), &size, |b, _| {
+            b.iter(|| db.read().search(black_box(&query), 10).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn bench_enhanced_search(c: &mut Criterion) {
+    let runtime = tokio::runtime::Builder::new_multi_thread().enable_all().build().unwrap();
+    let vector_db = VectorStoreFactory::create_native(VectorDBConfig {
+        similarity_threshold: 0.1,
+        ..VectorDBConfig::for_testing()
+    });
+    let service = EnhancedSearchService::with_backends(
+        MLConfig::for_testing(),
+        vector_db,
+        Arc::new(RwLock::new(HashingEmbeddingBackend::new(EMBEDDING_DIM))),
+        Arc::new(RwLock::new(OverlapRerankerBackend)),
+    );
+
+    let mut corpus = fixture_chunks("fixtures/inventory.rs", "rust", RUST_FIXTURE);
+    corpus.extend(fixture_chunks("fixtures/user.service.ts", "typescript", TS_FIXTURE));
+    runtime.block_on(service.index_code(corpus)).unwrap();
+
+    let request = SearchRequest {
+        query: "reserve stock from inventory".to_string(),
+        search_type: SearchType::General,
+        filters: SearchFilters::default(),
+        options: SearchOptions::default(),
+    };
+
+    c.bench_function("enhanced_search/search/general", |b| {
+        b.to_async(&runtime)
+            .iter(|| async { service.search(black_box(request.clone())).await.unwrap() })
+    });
+}
+
+criterion_group!(benches, bench_rust_analyzer, bench_vector_db_search, bench_enhanced_search);
+criterion_main!(benches);
@@ -1,7 +1,7 @@
 use anyhow::Result;
 use std::path::Path;
 use crate::types::{CodeSummary, FunctionInfo, ClassInfo, ComponentInfo, ServiceInfo, PipeInfo, LocationInfo};
-use crate::utils::read_file_content;
+use crate::utils::{read_to_string_limited, MAX_ANALYZED_FILE_BYTES};
 
 pub struct CodeSummarizer;
 
@@ -11,7 +11,7 @@ impl CodeSummarizer {
     }
 
     pub fn summarize_file(&self, path: &Path) -> Result<CodeSummary> {
-        let content = read_file_content(path)?;
+        let content = read_to_string_limited(path, MAX_ANALYZED_FILE_BYTES)?.content;
         let file_name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
         let file_type = self.determine_file_type(path);
 
@@ -433,6 +433,8 @@ impl CodeSummarizer {
                         lifecycle: Vec::new(), // Simplified
                         template_summary: "Angular Component".to_string(), // Simplified
                         location: LocationInfo { line: 1, column: 1 }, // Simplified
+                        dependencies: Vec::new(), // Simplified
+                        providers: Vec::new(), // Simplified
                     });
                 }
             }
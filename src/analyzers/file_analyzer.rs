@@ -1,28 +1,61 @@
 use anyhow::Result;
 use std::path::Path;
 use chrono::Utc;
-use crate::types::{FileMetadata, FileType, DetailedAnalysis, LocationInfo};
+use crate::types::{Complexity, FileMetadata, FileType, DetailedAnalysis, LocationInfo};
 use crate::utils::file_utils::*;
 use crate::analyzers::ts_ast_analyzer::TypeScriptASTAnalyzer;
 use crate::analyzers::rust_analyzer::RustAnalyzer;
+use crate::analyzers::languages::{syntax_error, AnalyzerLanguage};
+use crate::analyzers::symbol_locator::{enclosing_symbol, SymbolRef};
 
-pub struct FileAnalyzer;
+/// Hook run after each file's analysis, receiving the metadata and the raw file content
+pub type PostProcessor = Box<dyn Fn(&mut FileMetadata, &str) + Send + Sync>;
+
+pub struct FileAnalyzer {
+    post_processors: Vec<PostProcessor>,
+}
 
 impl FileAnalyzer {
     pub fn new() -> Self {
-        FileAnalyzer
+        FileAnalyzer {
+            post_processors: Vec::new(),
+        }
+    }
+
+    /// Registers a post-processor used to enrich metadata (e.g. via `FileMetadata::extra`).
+    /// Post-processors run in registration order.
+    pub fn with_post_processor(mut self, post_processor: PostProcessor) -> Self {
+        self.post_processors.push(post_processor);
+        self
     }
 
     pub fn analyze_file(&self, path: &Path) -> Result<FileMetadata> {
-        let content = read_file_content(path)?;
-        let size = get_file_size(path)?;
-        let line_count = count_lines(&content);
+        let limited = read_to_string_limited(path, MAX_ANALYZED_FILE_BYTES)?;
+        let content = limited.content;
+        let size = limited.total_bytes;
+        let line_count = if limited.truncated {
+            read_lines(path)?.count()
+        } else {
+            count_lines(&content)
+        };
         let file_type = detect_file_type_from_content(path, &content);
-        let complexity = calculate_complexity(&content, line_count);
         
-        let detailed_analysis = self.generate_detailed_analysis(&content, &file_type)?;
+        // A file that can't be parsed is still recorded, so one bad file
+        // doesn't abort a whole-project run
+        let (detailed_analysis, parse_error) = match self.generate_detailed_analysis(path, &content, &file_type) {
+            Ok(analysis) => analysis,
+            Err(error) => {
+                tracing::warn!("Skipping detailed analysis of {}: {}", path.display(), error);
+                (None, Some(error.to_string()))
+            }
+        };
+        let complexity = if detailed_analysis.is_none() && parse_error.is_some() {
+            Complexity::Low
+        } else {
+            calculate_complexity(&content, line_count)
+        };
         
-        let metadata = FileMetadata {
+        let mut metadata = FileMetadata {
             path: path.to_string_lossy().to_string(),
             size,
             line_count,
@@ -34,21 +67,56 @@ impl FileAnalyzer {
             imports: self.extract_imports(&content, &file_type),
             complexity,
             detailed_analysis,
+            extra: serde_json::Value::Null,
+            parse_error,
         };
 
+        for post_processor in &self.post_processors {
+            post_processor(&mut metadata, &content);
+        }
+
+        Ok(metadata)
+    }
+
+    /// Analyzes a file in any language. A file whose language analyzer isn't
+    /// compiled into this build is recorded as `FileType::Other`, with the
+    /// reason in `parse_error`.
+    pub fn analyze_any(&self, path: &Path) -> Result<FileMetadata> {
+        let mut metadata = self.analyze_file(path)?;
+        if let Some(language) = AnalyzerLanguage::for_path(path).filter(|language| !language.is_enabled()) {
+            metadata.file_type = FileType::Other;
+            metadata.detailed_analysis = None;
+            metadata.parse_error = Some(language.unavailable_note());
+        }
         Ok(metadata)
     }
 
-    fn generate_detailed_analysis(&self, content: &str, file_type: &FileType) -> Result<Option<DetailedAnalysis>> {
+    /// Innermost function, method or type of `content` containing the 1-based
+    /// `line` and `col`, judged from the syntax tree. `None` outside every
+    /// symbol, or when `path`'s language has no analyzer in this build.
+    pub fn symbol_at(&self, path: &Path, content: &str, line: usize, col: usize) -> Option<SymbolRef> {
+        let language = AnalyzerLanguage::for_path(path).filter(|language| language.is_enabled())?;
+        let tree = match language {
+            AnalyzerLanguage::Rust => RustAnalyzer::new().ok()?.parse(content).ok()?,
+            AnalyzerLanguage::TypeScript => TypeScriptASTAnalyzer::new().ok()?.parse_file(content).ok()?,
+        };
+        enclosing_symbol(&tree, content, language, line, col)
+    }
+
+    /// Languages whose analyzer is not compiled in get no detailed analysis
+    /// Detailed analysis of `content`, with the syntax error it recovered
+    /// from, if any
+    fn generate_detailed_analysis(&self, path: &Path, content: &str, file_type: &FileType) -> Result<(Option<DetailedAnalysis>, Option<String>)> {
         match file_type {
-            FileType::Component | FileType::Service | FileType::Pipe | FileType::Other if self.is_typescript_file(content) => {
-                self.analyze_typescript_content(content)
+            FileType::Component | FileType::Service | FileType::Pipe | FileType::Other
+                if AnalyzerLanguage::TypeScript.is_enabled() && self.is_typescript_file(content) => {
+                self.analyze_typescript_content(content, path)
             }
             FileType::RustLibrary | FileType::RustBinary | FileType::RustModule | 
-            FileType::RustTest | FileType::RustBench | FileType::RustExample => {
+            FileType::RustTest | FileType::RustBench | FileType::RustExample if AnalyzerLanguage::Rust.is_enabled() => {
                 self.analyze_rust_content(content, Path::new("dummy"))
             }
-            _ => Ok(None)
+            _ => Ok((None, None))
         }
     }
 
@@ -65,15 +133,19 @@ impl FileAnalyzer {
         content.contains("export interface")
     }
 
-    fn analyze_typescript_content(&self, content: &str) -> Result<Option<DetailedAnalysis>> {
+    fn analyze_typescript_content(&self, content: &str, path: &Path) -> Result<(Option<DetailedAnalysis>, Option<String>)> {
         let mut ts_analyzer = TypeScriptASTAnalyzer::new()?;
         let tree = ts_analyzer.parse_file(content)?;
+        let parse_error = syntax_error(&tree);
         
         let functions = ts_analyzer.extract_functions(&tree, content);
         let classes = ts_analyzer.extract_classes(&tree, content);
         let component_info = ts_analyzer.extract_component_info(&tree, content);
         let service_info = ts_analyzer.extract_service_info(&tree, content);
         let pipe_info = ts_analyzer.extract_pipe_info(&tree, content);
+        let file_path = path.to_string_lossy();
+        let mut anti_patterns = ts_analyzer.detect_subscription_leaks(&tree, content, &file_path);
+        anti_patterns.extend(ts_analyzer.detect_unawaited_promises(&tree, content, &file_path));
         
         // Extract additional elements
         let elements = ts_analyzer.extract_elements(&tree, content);
@@ -122,7 +194,7 @@ impl FileAnalyzer {
             }
         }
         
-        Ok(Some(DetailedAnalysis {
+        let analysis = DetailedAnalysis {
             functions,
             classes,
             interfaces,
@@ -134,7 +206,9 @@ impl FileAnalyzer {
             pipe_info,
             module_info: None,
             rust_module: None,
-        }))
+            anti_patterns,
+        };
+        Ok((Some(analysis), parse_error))
     }
 
     fn parse_location(&self, location_str: &str) -> LocationInfo {
@@ -370,15 +444,15 @@ impl FileAnalyzer {
     }
     
     /// Analyze Rust content using the RustAnalyzer
-    fn analyze_rust_content(&self, content: &str, path: &Path) -> Result<Option<DetailedAnalysis>> {
+    fn analyze_rust_content(&self, content: &str, path: &Path) -> Result<(Option<DetailedAnalysis>, Option<String>)> {
         // Handle Cargo.toml files separately
         if path.file_name().and_then(|n| n.to_str()) == Some("Cargo.toml") {
-            return self.analyze_cargo_toml_content(content);
+            return Ok((self.analyze_cargo_toml_content(content)?, None));
         }
         
         let mut rust_analyzer = RustAnalyzer::new()?;
         let metadata = rust_analyzer.analyze_file(path, content)?;
-        Ok(metadata.detailed_analysis)
+        Ok((metadata.detailed_analysis, metadata.parse_error))
     }
     
     /// Analyze Cargo.toml content specifically
@@ -400,6 +474,7 @@ impl FileAnalyzer {
                     pipe_info: None,
                     module_info: None,
                     rust_module: None,
+                    anti_patterns: Vec::new(),
                 };
                 
                 // Convert cargo dependencies to "functions" for display purposes
@@ -462,6 +537,81 @@ mod tests {
     use tempfile::NamedTempFile;
     use std::io::Write;
 
+    #[cfg(feature = "typescript")]
+    #[test]
+    fn test_unawaited_promise_is_recorded_on_the_analysis() -> Result<()> {
+        let temp_dir = tempfile::TempDir::new()?;
+        let path = temp_dir.path().join("profile.service.ts");
+        fs::write(&path, r#"
+@Injectable({ providedIn: 'root' })
+export class ProfileService {
+    async save(profile: Profile): Promise<void> {}
+
+    update(profile: Profile): void {
+        this.save(profile);
+    }
+}
+"#)?;
+
+        let metadata = FileAnalyzer::new().analyze_file(&path)?;
+        let anti_patterns = metadata.detailed_analysis.unwrap().anti_patterns;
+        assert_eq!(anti_patterns.len(), 1);
+        assert_eq!(anti_patterns[0].pattern_name, "Unawaited promise");
+        assert_eq!(anti_patterns[0].locations[0].line_start, 7);
+        Ok(())
+    }
+
+    #[cfg(feature = "typescript")]
+    #[test]
+    fn test_typescript_syntax_error_is_recorded_with_the_analysis() -> Result<()> {
+        let temp_dir = tempfile::TempDir::new()?;
+        let path = temp_dir.path().join("user.service.ts");
+        fs::write(&path, r#"
+@Injectable({ providedIn: 'root' })
+export class UserService {
+  name: string = '';
+
+  load(): string {
+    return this.name;
+  }
+}
+
+export function broken( {
+"#)?;
+
+        let metadata = FileAnalyzer::new().analyze_file(&path)?;
+        let parse_error = metadata.parse_error.expect("parse error should be reported");
+        assert!(parse_error.starts_with("syntax error at line"), "{}", parse_error);
+        let analysis = metadata.detailed_analysis.expect("recovered items should be kept");
+        assert_eq!(analysis.classes[0].name, "UserService");
+        Ok(())
+    }
+
+    /// Built by `cargo test --no-default-features --features rust`: the crate
+    /// compiles without the TypeScript grammar and skips TypeScript files
+    #[cfg(all(feature = "rust", not(feature = "typescript")))]
+    #[test]
+    fn test_rust_only_build_skips_typescript() -> Result<()> {
+        let temp_dir = tempfile::TempDir::new()?;
+        let ts_path = temp_dir.path().join("user.service.ts");
+        fs::write(&ts_path, "@Injectable()\nexport class UserService {\n  name: string = '';\n}\n")?;
+        let rs_path = temp_dir.path().join("lib.rs");
+        fs::write(&rs_path, "pub fn add(a: i32, b: i32) -> i32 {\n    a + b\n}\n")?;
+
+        let analyzer = FileAnalyzer::new();
+        let ts = analyzer.analyze_any(&ts_path)?;
+        assert_eq!(ts.file_type, FileType::Other);
+        assert!(ts.detailed_analysis.is_none());
+        assert!(ts.parse_error.unwrap().contains("`typescript` feature"));
+        assert!(TypeScriptASTAnalyzer::new().is_err());
+
+        let rs = analyzer.analyze_any(&rs_path)?;
+        assert!(rs.parse_error.is_none());
+        assert_eq!(rs.detailed_analysis.unwrap().functions[0].name, "add");
+        assert_eq!(crate::analyzers::enabled_languages(), vec![AnalyzerLanguage::Rust]);
+        Ok(())
+    }
+
     #[test]
     fn test_analyze_typescript_component() -> Result<()> {
         let mut temp_file = NamedTempFile::new()?;
@@ -938,4 +1088,32 @@ function anotherFunction() {
         
         Ok(())
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_post_processor_extra_survives_serialization() -> Result<()> {
+        let mut temp_file = NamedTempFile::with_suffix(".ts")?;
+        write!(temp_file, "// Refs: TICKET-4821\nexport function load() {{ return 1; }}\n")?;
+
+        let analyzer = FileAnalyzer::new().with_post_processor(Box::new(|metadata, content| {
+            let tickets: Vec<&str> = content
+                .split_whitespace()
+                .filter(|word| word.starts_with("TICKET-"))
+                .collect();
+            metadata.extra = serde_json::json!({ "tickets": tickets });
+        }));
+        let metadata = analyzer.analyze_file(temp_file.path())?;
+        assert_eq!(metadata.extra["tickets"][0], "TICKET-4821");
+
+        let json = serde_json::to_string(&metadata)?;
+        let restored: crate::types::FileMetadata = serde_json::from_str(&json)?;
+        assert_eq!(restored.extra, metadata.extra);
+
+        // Metadata serialized before `extra` existed still deserializes
+        let mut legacy = serde_json::to_value(&metadata)?;
+        legacy.as_object_mut().unwrap().remove("extra");
+        let restored: crate::types::FileMetadata = serde_json::from_value(legacy)?;
+        assert!(restored.extra.is_null());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,79 @@
+use std::path::Path;
+use tree_sitter::{Node, Tree};
+
+/// Languages with a tree-sitter analyzer. Each grammar sits behind its own
+/// cargo feature so builds only bundle the languages they need.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnalyzerLanguage {
+    Rust,
+    TypeScript,
+}
+
+impl AnalyzerLanguage {
+    /// Language analyzed for `path`, judged by its extension
+    pub fn for_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "rs" => Some(Self::Rust),
+            "ts" | "tsx" | "js" | "jsx" | "mjs" | "cjs" => Some(Self::TypeScript),
+            _ => None,
+        }
+    }
+
+    /// Cargo feature that compiles in this language's grammar
+    pub fn feature(self) -> &'static str {
+        match self {
+            Self::Rust => "rust",
+            Self::TypeScript => "typescript",
+        }
+    }
+
+    pub fn is_enabled(self) -> bool {
+        match self {
+            Self::Rust => cfg!(feature = "rust"),
+            Self::TypeScript => cfg!(feature = "typescript"),
+        }
+    }
+
+    /// Explains that this language's analyzer is not part of the build
+    pub fn unavailable_note(self) -> String {
+        format!(
+            "{:?} analysis is not available in this build (enable the `{}` feature)",
+            self,
+            self.feature()
+        )
+    }
+}
+
+/// Position of the first syntax error tree-sitter recovered from in `tree`,
+/// `None` when the source parsed cleanly. Analyzers keep the items recovered
+/// around the error and report this alongside them.
+pub fn syntax_error(tree: &Tree) -> Option<String> {
+    let root = tree.root_node();
+    if !root.has_error() {
+        return None;
+    }
+
+    let mut first_error: Option<Node> = None;
+    let mut stack = vec![root];
+    while let Some(node) = stack.pop() {
+        if node.is_error() || node.is_missing() {
+            if first_error.is_none_or(|first| node.start_byte() < first.start_byte()) {
+                first_error = Some(node);
+            }
+            continue;
+        }
+        let mut cursor = node.walk();
+        stack.extend(node.children(&mut cursor).filter(|child| child.has_error()));
+    }
+
+    let position = first_error.map(|node| node.start_position()).unwrap_or_default();
+    Some(format!("syntax error at line {}, column {}", position.row + 1, position.column + 1))
+}
+
+/// Languages whose analyzers are compiled into this build
+pub fn enabled_languages() -> Vec<AnalyzerLanguage> {
+    [AnalyzerLanguage::Rust, AnalyzerLanguage::TypeScript]
+        .into_iter()
+        .filter(|language| language.is_enabled())
+        .collect()
+}
@@ -5,9 +5,14 @@ pub mod ts_ast_analyzer;
 pub mod routing_analyzer;
 pub mod interceptor_analyzer;
 pub mod state_analyzer;
+pub mod scope_analyzer;
+pub mod tech_stack_analyzer;
+pub mod module_classifier;
 pub mod rust_analyzer;
+pub mod languages;
+pub mod symbol_locator;
 
-#[cfg(test)]
+#[cfg(all(test, feature = "rust", feature = "typescript"))]
 pub mod tree_sitter_tests;
 
 pub use file_analyzer::*;
@@ -17,3 +22,8 @@ pub use ts_ast_analyzer::*;
 pub use routing_analyzer::*;
 pub use interceptor_analyzer::*;
 pub use state_analyzer::*;
+pub use scope_analyzer::*;
+pub use tech_stack_analyzer::*;
+pub use module_classifier::*;
+pub use languages::*;
+pub use symbol_locator::{SymbolKind, SymbolRef};
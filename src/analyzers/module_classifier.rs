@@ -0,0 +1,192 @@
+use std::path::Path;
+use crate::types::{ModuleInfo, ModuleType};
+
+/// Angular modules that shared modules commonly re-export
+const REEXPORTED_ANGULAR_MODULES: &[&str] = &["CommonModule", "FormsModule", "ReactiveFormsModule"];
+
+/// Classifies NgModules from their metadata first and their file name second,
+/// so misnamed modules still land in the right bucket:
+///
+/// 1. `Root` when it bootstraps a component
+/// 2. `Lazy` when a `loadChildren` route points at it
+/// 3. `Routing` when it only wires up `RouterModule.forRoot`/`forChild`
+/// 4. `Shared` when it exports components, directives or pipes for reuse
+/// 5. Name patterns (`app.module`, `routing`, `shared`, `core`) otherwise
+/// 6. `Feature` for everything else
+#[derive(Debug, Clone, Default)]
+pub struct ModuleClassifier {
+    lazy_targets: Vec<LazyTarget>,
+}
+
+/// A `loadChildren` target: the imported file and, when given, the module class
+#[derive(Debug, Clone, PartialEq)]
+struct LazyTarget {
+    path: String,
+    module_name: Option<String>,
+}
+
+impl ModuleClassifier {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the modules lazily loaded by a routes file. Handles both
+    /// `loadChildren: () => import('./a/a.module').then(m => m.AModule)` and the
+    /// legacy `loadChildren: './a/a.module#AModule'` string form.
+    pub fn add_lazy_routes(&mut self, content: &str) {
+        for (index, _) in content.match_indices("loadChildren") {
+            // The rest of the route object
+            let rest = &content[index..];
+            let expression = &rest[..rest.find('}').unwrap_or(rest.len())];
+
+            let Some(target) = quoted(expression) else {
+                continue;
+            };
+            let (path, legacy_name) = match target.split_once('#') {
+                Some((path, name)) => (path, Some(name.to_string())),
+                None => (target, None),
+            };
+            let module_name = legacy_name.or_else(|| {
+                let after_arrow = &expression[expression.find("=>")? + 2..];
+                let after_arrow = &after_arrow[after_arrow.find("=>")? + 2..];
+                let member = after_arrow.trim().split('.').nth(1)?;
+                let name: String = member.chars().take_while(|c| c.is_alphanumeric() || *c == '_').collect();
+                (!name.is_empty()).then_some(name)
+            });
+
+            self.lazy_targets.push(LazyTarget {
+                path: path.trim_start_matches("./").trim_start_matches("../").to_string(),
+                module_name,
+            });
+        }
+    }
+
+    pub fn classify(&self, path: &str, module: Option<&ModuleInfo>) -> ModuleType {
+        let file_name = Path::new(path)
+            .file_name()
+            .map(|name| name.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+
+        if module.is_some_and(|m| !m.bootstrap.is_empty()) {
+            ModuleType::Root
+        } else if self.is_lazy_loaded(path, module) {
+            ModuleType::Lazy
+        } else if module.is_some_and(is_routing_module) {
+            ModuleType::Routing
+        } else if module.is_some_and(exports_declarables) {
+            ModuleType::Shared
+        } else if file_name.starts_with("app.module") {
+            ModuleType::Root
+        } else if file_name.contains("routing") {
+            ModuleType::Routing
+        } else if file_name.contains("shared") || file_name.contains("core") {
+            ModuleType::Shared
+        } else {
+            ModuleType::Feature
+        }
+    }
+
+    fn is_lazy_loaded(&self, path: &str, module: Option<&ModuleInfo>) -> bool {
+        let normalized = path.replace('\\', "/");
+        let without_extension = normalized.strip_suffix(".ts").unwrap_or(&normalized);
+
+        self.lazy_targets.iter().any(|target| {
+            let path_matches = !target.path.is_empty() && without_extension.ends_with(&target.path);
+            match (&target.module_name, module) {
+                (Some(name), Some(module)) => path_matches || *name == module.name,
+                _ => path_matches,
+            }
+        })
+    }
+}
+
+/// First single- or double-quoted string in `text`
+fn quoted(text: &str) -> Option<&str> {
+    let start = text.find(['\'', '"'])?;
+    let quote = text[start..].chars().next()?;
+    let end = text[start + 1..].find(quote)?;
+    Some(&text[start + 1..start + 1 + end])
+}
+
+/// Only configures the router: imports `RouterModule.forRoot/forChild` and
+/// declares nothing of its own
+fn is_routing_module(module: &ModuleInfo) -> bool {
+    module.declarations.is_empty()
+        && module.imports.iter().any(|import| import.starts_with("RouterModule.for"))
+}
+
+/// Exports its own declarations, anything named like a declarable, or
+/// re-exports common Angular modules for importers
+fn exports_declarables(module: &ModuleInfo) -> bool {
+    module.exports.iter().any(|export| {
+        module.declarations.contains(export)
+            || export.ends_with("Component")
+            || export.ends_with("Directive")
+            || export.ends_with("Pipe")
+            || REEXPORTED_ANGULAR_MODULES.contains(&export.as_str())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::LocationInfo;
+
+    fn module(name: &str, imports: &[&str], declarations: &[&str], exports: &[&str]) -> ModuleInfo {
+        let strings = |items: &[&str]| items.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        ModuleInfo {
+            name: name.to_string(),
+            imports: strings(imports),
+            exports: strings(exports),
+            declarations: strings(declarations),
+            providers: vec![],
+            bootstrap: vec![],
+            schemas: vec![],
+            is_root_module: false,
+            is_feature_module: true,
+            is_shared_module: false,
+            lazy_routes: vec![],
+            location: LocationInfo { line: 1, column: 1 },
+        }
+    }
+
+    #[test]
+    fn test_core_module_exporting_components_is_shared() {
+        let classifier = ModuleClassifier::new();
+        let core = module(
+            "CoreModule",
+            &["CommonModule"],
+            &["HeaderComponent", "FooterComponent"],
+            &["HeaderComponent", "FooterComponent"],
+        );
+        assert_eq!(classifier.classify("src/app/layout/chrome.module.ts", Some(&core)), ModuleType::Shared);
+
+        // Naming alone doesn't make a routed module shared
+        let routes = module("CoreRoutingModule", &["RouterModule.forChild(routes)"], &[], &["RouterModule"]);
+        assert_eq!(classifier.classify("src/app/core/core-routing.module.ts", Some(&routes)), ModuleType::Routing);
+
+        let mut app = module("AppModule", &["BrowserModule"], &["AppComponent"], &[]);
+        app.bootstrap = vec!["AppComponent".to_string()];
+        assert_eq!(classifier.classify("src/app/main.module.ts", Some(&app)), ModuleType::Root);
+    }
+
+    #[test]
+    fn test_lazy_loaded_feature_module() {
+        let mut classifier = ModuleClassifier::new();
+        classifier.add_lazy_routes(r#"
+const routes: Routes = [
+  { path: 'orders', loadChildren: () => import('./orders/orders.module').then(m => m.OrdersModule) },
+  { path: 'legacy', loadChildren: './reports/reports.module#ReportsModule' },
+];
+"#);
+
+        let orders = module("OrdersModule", &["CommonModule", "OrdersRoutingModule"], &["OrderListComponent"], &[]);
+        assert_eq!(classifier.classify("src/app/orders/orders.module.ts", Some(&orders)), ModuleType::Lazy);
+
+        let reports = module("ReportsModule", &["CommonModule"], &["ReportComponent"], &["ReportComponent"]);
+        assert_eq!(classifier.classify("src/app/reports/reports.module.ts", Some(&reports)), ModuleType::Lazy);
+
+        let users = module("UsersModule", &["CommonModule"], &["UserListComponent"], &[]);
+        assert_eq!(classifier.classify("src/app/users/users.module.ts", Some(&users)), ModuleType::Feature);
+    }
+}
@@ -1,11 +1,17 @@
 use anyhow::Result;
 use std::path::Path;
 use walkdir::WalkDir;
-use crate::types::{RoutingAnalysis, RouteSummary, GuardSummary, GuardType};
+use crate::types::{RoutingAnalysis, RouteSummary, GuardSummary, GuardType, RoutingConflict, RoutingConflictKind};
 use crate::utils::file_utils;
 
 pub struct RoutingAnalyzer;
 
+/// A parsed route and whether it declares `children`
+struct ParsedRoute {
+    summary: RouteSummary,
+    has_children: bool,
+}
+
 impl RoutingAnalyzer {
     pub fn new() -> Self {
         Self
@@ -18,13 +24,21 @@ impl RoutingAnalyzer {
             protected_routes: Vec::new(),
             redirects: Vec::new(),
             lazy_routes: Vec::new(),
+            conflicts: Vec::new(),
         };
 
         // Find and analyze route files
         let route_files = self.find_route_files(project_path)?;
         for route_file in route_files {
-            let routes = self.analyze_route_file(&route_file)?;
-            routing_analysis.routes.extend(routes);
+            let routes = self.parse_route_file(&route_file)?;
+            // A parent with children is matched through them: its own path
+            // equals that of its `''` child without being a second route
+            let leaves: Vec<RouteSummary> = routes.iter()
+                .filter(|route| !route.has_children)
+                .map(|route| route.summary.clone())
+                .collect();
+            routing_analysis.conflicts.extend(self.find_conflicts(&route_file, &leaves));
+            routing_analysis.routes.extend(routes.into_iter().map(|route| route.summary));
         }
 
         // Find and analyze guard files
@@ -92,17 +106,25 @@ impl RoutingAnalyzer {
         Ok(guard_files)
     }
 
+    #[cfg(test)]
     fn analyze_route_file(&self, file_path: &str) -> Result<Vec<RouteSummary>> {
+        Ok(self.parse_route_file(file_path)?.into_iter().map(|route| route.summary).collect())
+    }
+
+    /// Routes of a file in declaration order. A child route's path is its
+    /// full path below its parents, e.g. `users/:id` for `:id` under `users`.
+    fn parse_route_file(&self, file_path: &str) -> Result<Vec<ParsedRoute>> {
         let content = file_utils::read_file_content(Path::new(file_path))?;
-        let mut routes = Vec::new();
+        let mut routes: Vec<ParsedRoute> = Vec::new();
 
         // Parse route definitions from TypeScript content
         // This is a simplified parser - in a real implementation, you'd use a proper TS parser
-        let lines: Vec<&str> = content.lines().collect();
         let mut in_routes_array = false;
-        let mut current_route: Option<RouteSummary> = None;
+        // Open route objects as (index in `routes`, brace depth before their `{`)
+        let mut open: Vec<(usize, usize)> = Vec::new();
+        let mut depth = 0usize;
 
-        for line in lines {
+        for line in content.lines() {
             let trimmed = line.trim();
 
             if trimmed.contains("Routes = [") || trimmed.contains("routes: Routes = [") {
@@ -110,50 +132,75 @@ impl RoutingAnalyzer {
                 continue;
             }
 
-            if in_routes_array && trimmed == "];" {
-                if let Some(route) = current_route.take() {
-                    routes.push(route);
-                }
-                break;
+            if !in_routes_array {
+                continue;
             }
 
-            if in_routes_array && trimmed == "{" {
-                current_route = Some(RouteSummary {
-                    path: String::new(),
-                    component: String::new(),
-                    guards: Vec::new(),
-                    redirect_to: None,
-                    is_protected: false,
-                    lazy_loaded: false,
-                });
+            if trimmed == "];" && open.is_empty() {
+                break;
             }
 
-            if in_routes_array && trimmed == "}," {
-                if let Some(route) = current_route.take() {
-                    routes.push(route);
-                }
+            if trimmed.starts_with('{') {
+                open.push((routes.len(), depth));
+                routes.push(ParsedRoute {
+                    summary: RouteSummary {
+                        path: String::new(),
+                        component: String::new(),
+                        guards: Vec::new(),
+                        redirect_to: None,
+                        is_protected: false,
+                        lazy_loaded: false,
+                    },
+                    has_children: false,
+                });
             }
 
-            if let Some(ref mut route) = current_route {
+            if let Some(&(index, _)) = open.last() {
+                let route = &mut routes[index];
                 if let Some(path_match) = self.extract_route_path(trimmed) {
-                    route.path = path_match;
+                    route.summary.path = path_match;
                 }
 
                 if let Some(component_match) = self.extract_route_component(trimmed) {
-                    route.component = component_match;
+                    route.summary.component = component_match;
                 }
 
                 if let Some(redirect) = self.extract_redirect_to(trimmed) {
-                    route.redirect_to = Some(redirect);
+                    route.summary.redirect_to = Some(redirect);
                 }
 
                 if let Some(guards) = self.extract_guards(trimmed) {
-                    route.guards = guards;
-                    route.is_protected = !route.guards.is_empty();
+                    route.summary.guards = guards;
+                    route.summary.is_protected = !route.summary.guards.is_empty();
                 }
 
                 if trimmed.contains("loadChildren") {
-                    route.lazy_loaded = true;
+                    route.summary.lazy_loaded = true;
+                }
+
+                if trimmed.contains("children:") {
+                    route.has_children = true;
+                }
+            }
+
+            depth += trimmed.matches('{').count();
+            depth = depth.saturating_sub(trimmed.matches('}').count());
+
+            // Close the routes whose object ended on this line, innermost first
+            while let Some(&(index, open_depth)) = open.last() {
+                if depth > open_depth {
+                    break;
+                }
+                open.pop();
+                if !open.is_empty() {
+                    let full_path = open.iter()
+                        .map(|&(parent, _)| routes[parent].summary.path.as_str())
+                        .chain(std::iter::once(routes[index].summary.path.as_str()))
+                        .map(|segment| segment.trim_matches('/'))
+                        .filter(|segment| !segment.is_empty())
+                        .collect::<Vec<_>>()
+                        .join("/");
+                    routes[index].summary.path = full_path;
                 }
             }
         }
@@ -218,12 +265,9 @@ impl RoutingAnalyzer {
     fn extract_route_component(&self, line: &str) -> Option<String> {
         if line.contains("component:") {
             if let Some(start) = line.find("component:") {
-                let component_part = &line[start + 10..].trim();
-                if let Some(comma_pos) = component_part.find(',') {
-                    return Some(component_part[..comma_pos].trim().to_string());
-                } else {
-                    return Some(component_part.to_string());
-                }
+                // Ends at the next property or, for one-line routes, the closing brace
+                let component_part = line[start + 10..].split([',', '}']).next().unwrap_or_default();
+                return Some(component_part.trim().to_string());
             }
         }
         None
@@ -282,6 +326,38 @@ impl RoutingAnalyzer {
         dependencies
     }
 
+    /// Groups routes whose paths match the same URLs. Paths are compared
+    /// without surrounding slashes and with every `:param` segment treated
+    /// alike, so `users/:id` and `/users/:userId` collide.
+    fn find_conflicts(&self, file_path: &str, routes: &[RouteSummary]) -> Vec<RoutingConflict> {
+        let mut groups: Vec<(String, Vec<&RouteSummary>)> = Vec::new();
+        for route in routes {
+            let pattern = route_pattern(&route.path);
+            match groups.iter_mut().find(|(existing, _)| *existing == pattern) {
+                Some((_, group)) => group.push(route),
+                None => groups.push((pattern, vec![route])),
+            }
+        }
+
+        groups.into_iter()
+            .filter(|(_, group)| group.len() > 1)
+            .map(|(_, group)| {
+                let same_path = group.iter().all(|route| route.path.trim_matches('/') == group[0].path.trim_matches('/'));
+                RoutingConflict {
+                    path: group[0].path.clone(),
+                    file: file_path.to_string(),
+                    kind: if same_path { RoutingConflictKind::DuplicatePath } else { RoutingConflictKind::ParameterCollision },
+                    components: group.iter()
+                        .map(|route| match &route.redirect_to {
+                            Some(target) if route.component.is_empty() => format!("redirect to {}", target),
+                            _ => route.component.clone(),
+                        })
+                        .collect(),
+                }
+            })
+            .collect()
+    }
+
     fn categorize_routes(&self, analysis: &mut RoutingAnalysis) {
         for route in &analysis.routes {
             if route.is_protected {
@@ -308,6 +384,15 @@ impl RoutingAnalyzer {
     }
 }
 
+/// Route path with surrounding slashes removed and parameter names erased
+fn route_pattern(path: &str) -> String {
+    path.trim_matches('/')
+        .split('/')
+        .map(|segment| if segment.starts_with(':') { ":" } else { segment })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -462,4 +547,103 @@ export const authGuard: CanActivateFn = (route, state) => {
         
         Ok(())
     }
-}
\ No newline at end of file
+    #[test]
+    fn test_duplicate_route_paths_are_reported() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let route_file = temp_dir.path().join("app.routes.ts");
+        
+        let route_content = r#"
+export const routes: Routes = [
+    {
+        path: 'orders',
+        component: OrderListComponent
+    },
+    {
+        path: 'orders/:id',
+        component: OrderDetailComponent
+    },
+    {
+        path: 'orders',
+        component: LegacyOrdersComponent
+    },
+    {
+        path: 'orders/:orderId',
+        component: OrderEditComponent
+    },
+];
+"#;
+        
+        fs::write(&route_file, route_content)?;
+        
+        let analyzer = RoutingAnalyzer::new();
+        let file = route_file.to_str().unwrap();
+        let conflicts = analyzer.find_conflicts(file, &analyzer.analyze_route_file(file)?);
+        
+        assert_eq!(conflicts.len(), 2);
+        assert_eq!(conflicts[0].path, "orders");
+        assert_eq!(conflicts[0].kind, RoutingConflictKind::DuplicatePath);
+        assert_eq!(conflicts[0].components, vec!["OrderListComponent", "LegacyOrdersComponent"]);
+        assert_eq!(conflicts[0].file, file);
+        
+        assert_eq!(conflicts[1].path, "orders/:id");
+        assert_eq!(conflicts[1].kind, RoutingConflictKind::ParameterCollision);
+        assert_eq!(conflicts[1].components, vec!["OrderDetailComponent", "OrderEditComponent"]);
+        
+        Ok(())
+    }
+
+    #[test]
+    fn test_nested_children_are_compared_by_full_path() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let src_dir = temp_dir.path().join("src/app");
+        fs::create_dir_all(&src_dir)?;
+        let route_file = src_dir.join("app.routes.ts");
+        
+        let route_content = r#"
+export const routes: Routes = [
+    {
+        path: 'users',
+        component: UsersShellComponent,
+        children: [
+            { path: '', component: UserListComponent },
+            { path: ':id', component: UserDetailComponent },
+        ]
+    },
+    {
+        path: 'orders',
+        children: [
+            {
+                path: '',
+                component: OrderListComponent
+            },
+            {
+                path: ':orderId',
+                component: OrderDetailComponent
+            },
+            {
+                path: ':id',
+                component: OrderEditComponent
+            },
+        ]
+    },
+];
+"#;
+        
+        fs::write(&route_file, route_content)?;
+        
+        let analyzer = RoutingAnalyzer::new();
+        let analysis = analyzer.analyze_project_routing(temp_dir.path())?;
+        
+        let paths: Vec<&str> = analysis.routes.iter().map(|route| route.path.as_str()).collect();
+        assert_eq!(paths, vec!["users", "users", "users/:id", "orders", "orders", "orders/:orderId", "orders/:id"]);
+        assert_eq!(analysis.routes[1].component, "UserListComponent");
+        
+        // Only the two `:param` children of `orders` really collide
+        assert_eq!(analysis.conflicts.len(), 1, "{:?}", analysis.conflicts);
+        assert_eq!(analysis.conflicts[0].path, "orders/:orderId");
+        assert_eq!(analysis.conflicts[0].kind, RoutingConflictKind::ParameterCollision);
+        assert_eq!(analysis.conflicts[0].components, vec!["OrderDetailComponent", "OrderEditComponent"]);
+        
+        Ok(())
+    }
+}
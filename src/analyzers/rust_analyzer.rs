@@ -12,32 +12,81 @@ use crate::types::{
     Complexity, ParameterInfo
 };
 use tree_sitter::{Parser, Node, Tree};
+use crate::analyzers::languages::syntax_error;
 use chrono::Utc;
 
 // Moderno tree-sitter API - no necesitamos extern "C"
 
+/// Default nesting depth `traverse_node` descends to. Real code stays far
+/// below this; it exists so pathological input can't overflow the stack.
+pub const DEFAULT_MAX_TRAVERSAL_DEPTH: usize = 256;
+
+/// `#[cfg(test)]`, `#[test]` or a runner's test macro such as `#[tokio::test(...)]`
+fn is_test_attribute(attribute: &str) -> bool {
+    let compact: String = attribute.chars().filter(|c| !c.is_whitespace()).collect();
+    compact == "#[test]"
+        || compact.contains("cfg(test)")
+        || compact.ends_with("::test]")
+        || compact.contains("::test(")
+}
+
+#[cfg(feature = "rust")]
+fn rust_grammar() -> Result<tree_sitter::Language> {
+    Ok(tree_sitter_rust::LANGUAGE.into())
+}
+
+#[cfg(not(feature = "rust"))]
+fn rust_grammar() -> Result<tree_sitter::Language> {
+    anyhow::bail!(crate::analyzers::AnalyzerLanguage::Rust.unavailable_note())
+}
+
 /// Rust-specific code analyzer
 pub struct RustAnalyzer {
     parser: Parser,
+    max_depth: usize,
 }
 
 impl RustAnalyzer {
     /// Create a new Rust analyzer
     pub fn new() -> Result<Self> {
         let mut parser = Parser::new();
-        parser.set_language(&tree_sitter_rust::LANGUAGE.into())
+        parser.set_language(&rust_grammar()?)
             .expect("Error loading Rust grammar");
         
-        Ok(Self { parser })
+        Ok(Self { parser, max_depth: DEFAULT_MAX_TRAVERSAL_DEPTH })
+    }
+
+    /// Syntax tree depth below which items are no longer collected
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
     }
     
-    /// Analyze a Rust source file
+    /// Syntax tree of `content`
+    pub fn parse(&mut self, content: &str) -> Result<Tree> {
+        self.parser.parse(content, None)
+            .ok_or_else(|| anyhow::anyhow!("Failed to parse Rust file"))
+    }
+    
+    /// Analyze a Rust source file. A file with syntax errors keeps the items
+    /// tree-sitter recovered around them, with `parse_error` set; one that
+    /// can't be analyzed at all yields metadata with `parse_error` set and no
+    /// detailed analysis instead of an error.
     pub fn analyze_file(&mut self, path: &Path, content: &str) -> Result<FileMetadata> {
         let file_type = self.detect_rust_file_type(path, content);
-        let tree = self.parser.parse(content, None)
-            .ok_or_else(|| anyhow::anyhow!("Failed to parse Rust file"))?;
-        
-        let detailed_analysis = self.extract_detailed_analysis(&tree, content)?;
+        let parsed = self.parser.parse(content, None)
+            .ok_or_else(|| anyhow::anyhow!("Failed to parse Rust file"))
+            .and_then(|tree| Ok((self.extract_detailed_analysis(&tree, content)?, syntax_error(&tree))));
+        let (detailed_analysis, parse_error) = match parsed {
+            Ok(parsed) => parsed,
+            Err(error) => {
+                tracing::warn!("Skipping analysis of {}: {}", path.display(), error);
+                return Ok(self.unparsed_file(path, content, file_type, error.to_string()));
+            }
+        };
+        if let Some(error) = &parse_error {
+            tracing::warn!("Analyzing {} with recovered {}", path.display(), error);
+        }
         let complexity = self.calculate_complexity(&detailed_analysis, content);
         
         Ok(FileMetadata {
@@ -52,9 +101,67 @@ impl RustAnalyzer {
             imports: self.extract_imports(&detailed_analysis),
             complexity,
             detailed_analysis: Some(detailed_analysis),
+            extra: serde_json::Value::Null,
+            parse_error,
         })
     }
+
+    /// Metadata for a file whose syntax tree couldn't be analyzed
+    fn unparsed_file(&self, path: &Path, content: &str, file_type: FileType, parse_error: String) -> FileMetadata {
+        FileMetadata {
+            path: path.to_string_lossy().to_string(),
+            size: content.len() as u64,
+            line_count: content.lines().count(),
+            last_modified: Utc::now(),
+            file_type,
+            summary: format!("Rust file (not analyzed: {})", parse_error),
+            relevant_sections: Vec::new(),
+            exports: Vec::new(),
+            imports: Vec::new(),
+            complexity: Complexity::Low,
+            detailed_analysis: None,
+            extra: serde_json::Value::Null,
+            parse_error: Some(parse_error),
+        }
+    }
     
+    /// Line ranges (1-based, inclusive) of test code: `#[cfg(test)]` modules
+    /// and `#[test]`/`#[tokio::test]` functions, attributes included
+    pub fn test_line_ranges(&mut self, content: &str) -> Vec<(usize, usize)> {
+        let Some(tree) = self.parser.parse(content, None) else {
+            return Vec::new();
+        };
+        let source_bytes = content.as_bytes();
+
+        let mut ranges = Vec::new();
+        let mut stack = vec![(tree.root_node(), 0)];
+        while let Some((node, depth)) = stack.pop() {
+            if matches!(node.kind(), "mod_item" | "function_item") {
+                let mut first_attribute = None;
+                let mut is_test = false;
+                let mut sibling = node.prev_sibling();
+                while let Some(attribute) = sibling.filter(|s| s.kind() == "attribute_item") {
+                    is_test |= is_test_attribute(attribute.utf8_text(source_bytes).unwrap_or(""));
+                    first_attribute = Some(attribute);
+                    sibling = attribute.prev_sibling();
+                }
+                if is_test {
+                    let start = first_attribute.unwrap_or(node).start_position().row + 1;
+                    ranges.push((start, node.end_position().row + 1));
+                    continue;
+                }
+            }
+
+            if depth < self.max_depth {
+                let mut cursor = node.walk();
+                stack.extend(node.children(&mut cursor).map(|child| (child, depth + 1)));
+            }
+        }
+
+        ranges.sort();
+        ranges
+    }
+
     /// Detect the specific type of Rust file
     fn detect_rust_file_type(&self, path: &Path, content: &str) -> FileType {
         let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
@@ -101,9 +208,10 @@ impl RustAnalyzer {
             type_aliases: Vec::new(),
             macros: Vec::new(),
             use_statements: Vec::new(),
+            truncation_note: None,
         };
         
-        self.traverse_node(root_node, source_bytes, &mut functions, &mut rust_module)?;
+        self.traverse_node(root_node, source_bytes, &mut functions, &mut rust_module, 0)?;
         
         Ok(DetailedAnalysis {
             functions,
@@ -117,17 +225,31 @@ impl RustAnalyzer {
             pipe_info: None,
             module_info: None,
             rust_module: Some(rust_module),
+            anti_patterns: Vec::new(),
         })
     }
     
-    /// Traverse the syntax tree recursively
+    /// Traverse the syntax tree recursively, stopping at `max_depth`
     fn traverse_node(
         &self,
         node: Node,
         source_bytes: &[u8],
         functions: &mut Vec<FunctionInfo>,
         rust_module: &mut RustModuleInfo,
+        depth: usize,
     ) -> Result<()> {
+        if depth > self.max_depth {
+            if rust_module.truncation_note.is_none() {
+                let note = format!(
+                    "stopped descending at depth {} (line {}); nested items below were skipped",
+                    self.max_depth, node.start_position().row + 1
+                );
+                tracing::warn!("Rust analysis truncated: {}", note);
+                rust_module.truncation_note = Some(note);
+            }
+            return Ok(());
+        }
+
         match node.kind() {
             "function_item" => {
                 let function = self.extract_function(&node, source_bytes)?;
@@ -169,7 +291,7 @@ impl RustAnalyzer {
                 // Recursively traverse child nodes
                 let mut cursor = node.walk();
                 for child in node.children(&mut cursor) {
-                    self.traverse_node(child, source_bytes, functions, rust_module)?;
+                    self.traverse_node(child, source_bytes, functions, rust_module, depth + 1)?;
                 }
             }
         }
@@ -183,8 +305,11 @@ impl RustAnalyzer {
             .unwrap_or_else(|| "unknown".to_string());
         
         let is_public = node.to_sexp().contains("visibility_modifier");
-        let is_async = node.to_sexp().contains("async");
-        let is_unsafe = node.to_sexp().contains("unsafe");
+        // Keywords are anonymous nodes, so they only show up in the modifiers' text
+        let function_modifiers = self.find_child_text(node, "function_modifiers", source_bytes)
+            .unwrap_or_default();
+        let is_async = function_modifiers.split_whitespace().any(|m| m == "async");
+        let is_unsafe = function_modifiers.split_whitespace().any(|m| m == "unsafe");
         
         // Extract parameters
         let parameters = self.extract_function_parameters(node, source_bytes)?;
@@ -985,7 +1110,7 @@ impl CargoAnalyzer {
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "rust"))]
 mod tests {
     use super::*;
     use tempfile::NamedTempFile;
@@ -1093,6 +1218,75 @@ impl Person {
         Ok(())
     }
     
+    #[test]
+    fn test_invalid_rust_yields_parse_error_instead_of_failing() -> Result<()> {
+        let mut analyzer = RustAnalyzer::new()?;
+        let broken = "pub fn broken( -> {\n    let = ;\n    }} else match\n";
+
+        let metadata = analyzer.analyze_file(Path::new("src/broken.rs"), broken)?;
+
+        let parse_error = metadata.parse_error.expect("parse error should be reported");
+        assert!(parse_error.starts_with("syntax error at line"), "{}", parse_error);
+        assert_eq!(metadata.complexity, Complexity::Low);
+        assert_eq!(metadata.line_count, 3);
+
+        let valid = analyzer.analyze_file(Path::new("src/ok.rs"), "pub fn ok() {}\n")?;
+        assert!(valid.parse_error.is_none());
+        Ok(())
+    }
+    
+    #[test]
+    fn test_items_around_a_syntax_error_are_kept() -> Result<()> {
+        let mut analyzer = RustAnalyzer::new()?;
+        let content = "pub struct Config { pub depth: u32 }\n\npub fn load() -> Config {\n    Config { depth: 1 }\n}\n\nthis is not rust\n\npub fn save(config: &Config) {}\n";
+
+        let metadata = analyzer.analyze_file(Path::new("src/config.rs"), content)?;
+
+        let parse_error = metadata.parse_error.expect("parse error should be reported");
+        assert!(parse_error.starts_with("syntax error at line 7"), "{}", parse_error);
+        let analysis = metadata.detailed_analysis.expect("recovered items should be kept");
+        let names: Vec<_> = analysis.functions.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, vec!["load", "save"]);
+        assert_eq!(analysis.rust_module.unwrap().structs[0].name, "Config");
+        Ok(())
+    }
+    
+    #[test]
+    fn test_deeply_nested_input_is_truncated_not_overflowed() -> Result<()> {
+        let depth = 3000;
+        let content = format!(
+            "pub struct Config {{ pub depth: u32 }}\n\nfn nested_expr() -> i32 {{ {}1{} }}\n\nnested! {{ {}x{} }}\n\npub fn after() {{}}\n",
+            "(".repeat(depth), ")".repeat(depth), "[".repeat(depth), "]".repeat(depth)
+        );
+
+        let mut analyzer = RustAnalyzer::new()?;
+        let metadata = analyzer.analyze_file(Path::new("src/deep.rs"), &content)?;
+        assert!(metadata.parse_error.is_none());
+
+        let analysis = metadata.detailed_analysis.unwrap();
+        let names: Vec<_> = analysis.functions.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, vec!["nested_expr", "after"]);
+        let module = analysis.rust_module.unwrap();
+        assert_eq!(module.structs[0].name, "Config");
+        let note = module.truncation_note.expect("truncation should be noted");
+        assert!(note.contains(&format!("depth {}", DEFAULT_MAX_TRAVERSAL_DEPTH)), "{}", note);
+
+        let shallow = RustAnalyzer::new()?.with_max_depth(0)
+            .analyze_file(Path::new("src/ok.rs"), "pub fn ok() {}\n")?;
+        assert!(shallow.detailed_analysis.unwrap().rust_module.unwrap().truncation_note.is_some());
+        Ok(())
+    }
+    
+    #[test]
+    fn test_test_line_ranges() -> Result<()> {
+        let content = "pub fn add(a: i32, b: i32) -> i32 {\n    a + b\n}\n\n#[test]\nfn adds() {\n    assert_eq!(add(1, 2), 3);\n}\n\n#[cfg(test)]\nmod tests {\n    #[tokio::test(flavor = \"multi_thread\")]\n    async fn runs() {}\n}\n";
+
+        let mut analyzer = RustAnalyzer::new()?;
+        assert_eq!(analyzer.test_line_ranges(content), vec![(5, 8), (10, 14)]);
+        assert!(analyzer.test_line_ranges("pub fn add() {}\n").is_empty());
+        Ok(())
+    }
+    
     #[test]
     fn test_analyze_rust_library() -> Result<()> {
         let mut analyzer = RustAnalyzer::new()?;
@@ -1124,7 +1318,7 @@ impl Library {
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "rust"))]
 mod tests {
     use super::*;
     
@@ -1176,7 +1370,7 @@ fn run() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "rust"))]
 mod tests {
     use super::*;
     
@@ -1218,7 +1412,7 @@ fn test_string_operations() {
     assert_eq!(s.len(), 5);
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "rust"))]
 mod integration_tests {
     use super::*;
     
@@ -0,0 +1,283 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use crate::types::{ComponentInfo, InjectedDependency, ModuleSummary, ServiceInfo, ServiceScope, ScopeViolation, ScopeViolationType};
+
+#[derive(Default)]
+pub struct ScopeAnalyzer;
+
+impl ScopeAnalyzer {
+    pub fn new() -> Self {
+        Self
+    }
+
+    pub fn analyze(&self, services: &[ServiceInfo], components: &[ComponentInfo], modules: &[ModuleSummary]) -> Vec<ScopeViolation> {
+        let scopes: HashMap<&str, ServiceScope> = services
+            .iter()
+            .map(|s| (s.name.as_str(), self.effective_scope(s, components, modules)))
+            .collect();
+
+        let mut violations = Vec::new();
+        violations.extend(self.detect_scope_leakage(services, &scopes));
+        violations.extend(self.detect_duplicate_providers(services, modules));
+        violations.extend(self.detect_missing_providers(services, components, modules));
+        violations
+    }
+
+    /// Where a service is actually instantiated. `providedIn` decides when
+    /// present; otherwise the widest injector listing it in `providers` does,
+    /// since that is the instance wider-scoped consumers receive.
+    fn effective_scope(&self, service: &ServiceInfo, components: &[ComponentInfo], modules: &[ModuleSummary]) -> ServiceScope {
+        if service.provided_in.is_some() {
+            service.scope.clone()
+        } else if modules.iter().any(|m| m.providers.contains(&service.name)) {
+            ServiceScope::Module
+        } else if components.iter().any(|c| c.providers.contains(&service.name)) {
+            ServiceScope::Component
+        } else {
+            service.scope.clone()
+        }
+    }
+
+    /// A provider injected into a longer-lived one outlives its intended scope:
+    /// the wider service keeps the first instance it received forever.
+    fn detect_scope_leakage(&self, services: &[ServiceInfo], scopes: &HashMap<&str, ServiceScope>) -> Vec<ScopeViolation> {
+        let mut violations = Vec::new();
+
+        for consumer in services {
+            let consumer_scope = &scopes[consumer.name.as_str()];
+            let Some(consumer_rank) = self.scope_rank(consumer_scope) else { continue };
+
+            for dependency in &consumer.dependencies {
+                let Some((provider_name, provider_scope)) = scopes.get_key_value(dependency.injection_token()) else { continue };
+                let Some(provider_rank) = self.scope_rank(provider_scope) else { continue };
+
+                if provider_rank < consumer_rank {
+                    violations.push(ScopeViolation {
+                        service_name: consumer.name.clone(),
+                        violation_type: ScopeViolationType::ScopeLeakage,
+                        description: format!(
+                            "{:?}-scoped '{}' injects {:?}-scoped '{}', leaking it beyond its scope",
+                            consumer_scope, consumer.name, provider_scope, provider_name
+                        ),
+                        recommended_fix: format!(
+                            "Provide '{}' at {:?} scope or wider (e.g. providedIn: 'root'), or provide '{}' in the same {:?} injector instead",
+                            provider_name, consumer_scope, consumer.name, provider_scope
+                        ),
+                    });
+                }
+            }
+        }
+
+        violations
+    }
+
+    /// Every module listing a service in `providers` gets its own instance, so a
+    /// service provided by several modules is silently not a singleton.
+    fn detect_duplicate_providers(&self, services: &[ServiceInfo], modules: &[ModuleSummary]) -> Vec<ScopeViolation> {
+        let mut providing_modules: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        for module in modules {
+            for provider in &module.providers {
+                let names = providing_modules.entry(provider.as_str()).or_default();
+                if !names.contains(&module.name.as_str()) {
+                    names.push(module.name.as_str());
+                }
+            }
+        }
+
+        providing_modules
+            .into_iter()
+            .filter(|(_, module_names)| module_names.len() > 1)
+            .filter(|(service_name, _)| {
+                // Services declared providedIn: 'root' are treated as app-wide singletons
+                !services.iter().any(|s| s.name == *service_name && s.provided_in.as_deref() == Some("root"))
+            })
+            .map(|(service_name, module_names)| ScopeViolation {
+                service_name: service_name.to_string(),
+                violation_type: ScopeViolationType::DuplicateProvider,
+                description: format!(
+                    "'{}' is provided in {} modules ({}), creating one instance per module",
+                    service_name,
+                    module_names.len(),
+                    module_names.join(", ")
+                ),
+                recommended_fix: format!(
+                    "Use @Injectable({{ providedIn: 'root' }}) for '{}' or provide it once in a shared/core module",
+                    service_name
+                ),
+            })
+            .collect()
+    }
+
+    /// Project services injected somewhere but provided nowhere: not
+    /// `providedIn` an injector, not in any module and not in any component
+    /// `providers`. Framework and third-party tokens are out of scope since
+    /// their providers live outside the analyzed sources.
+    fn detect_missing_providers(&self, services: &[ServiceInfo], components: &[ComponentInfo], modules: &[ModuleSummary]) -> Vec<ScopeViolation> {
+        let project_services: HashSet<&str> = services.iter().map(|s| s.name.as_str()).collect();
+        let provided: HashSet<&str> = services
+            .iter()
+            .filter(|s| s.provided_in.is_some())
+            .map(|s| s.name.as_str())
+            .chain(modules.iter().flat_map(|m| m.providers.iter().map(String::as_str)))
+            .chain(components.iter().flat_map(|c| c.providers.iter().map(String::as_str)))
+            .collect();
+
+        let consumers = services
+            .iter()
+            .map(|s| (s.name.as_str(), &s.dependencies))
+            .chain(components.iter().map(|c| (c.name.as_str(), &c.dependencies)));
+
+        let mut violations = Vec::new();
+        for (consumer, dependencies) in consumers {
+            for dependency in dependencies.iter().filter(|d| !d.is_optional) {
+                let token = dependency.injection_token();
+                if project_services.contains(token) && !provided.contains(token) {
+                    violations.push(self.missing_provider(consumer, dependency));
+                }
+            }
+        }
+
+        violations
+    }
+
+    fn missing_provider(&self, consumer: &str, dependency: &InjectedDependency) -> ScopeViolation {
+        let token = dependency.injection_token();
+        ScopeViolation {
+            service_name: token.to_string(),
+            violation_type: ScopeViolationType::MissingProvider,
+            description: format!(
+                "'{}' injects '{}' but no module, component or providedIn makes it available (NullInjectorError at runtime)",
+                consumer, token
+            ),
+            recommended_fix: format!(
+                "Add @Injectable({{ providedIn: 'root' }}) to '{}', or list it in the providers of a module or component above '{}'",
+                token, consumer
+            ),
+        }
+    }
+
+    /// Lifetime of an injector scope, narrowest first. Transient providers
+    /// create a fresh instance per injection, so they never leak.
+    fn scope_rank(&self, scope: &ServiceScope) -> Option<u8> {
+        match scope {
+            ServiceScope::Component => Some(0),
+            ServiceScope::Module => Some(1),
+            ServiceScope::Root | ServiceScope::Singleton => Some(2),
+            ServiceScope::Platform => Some(3),
+            ServiceScope::Transient => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{InjectionStyle, LocationInfo, ModuleType};
+
+    fn injected(dependencies: &[&str]) -> Vec<InjectedDependency> {
+        dependencies
+            .iter()
+            .map(|d| InjectedDependency {
+                name: d.to_lowercase(),
+                param_type: d.to_string(),
+                token: None,
+                style: InjectionStyle::Constructor,
+                is_optional: false,
+            })
+            .collect()
+    }
+
+    fn service(name: &str, scope: ServiceScope, dependencies: &[&str]) -> ServiceInfo {
+        ServiceInfo {
+            name: name.to_string(),
+            injectable: true,
+            provided_in: None,
+            scope,
+            dependencies: injected(dependencies),
+            methods: vec![],
+            location: LocationInfo { line: 1, column: 1 },
+        }
+    }
+
+    #[test]
+    fn test_component_service_injected_into_root_service_leaks() {
+        let services = vec![
+            service("FormStateService", ServiceScope::Component, &[]),
+            service("SessionService", ServiceScope::Root, &["FormStateService", "HttpClient"]),
+            service("WizardService", ServiceScope::Component, &["SessionService"]),
+        ];
+
+        let violations: Vec<_> = ScopeAnalyzer::new()
+            .analyze(&services, &[], &[])
+            .into_iter()
+            .filter(|v| v.violation_type == ScopeViolationType::ScopeLeakage)
+            .collect();
+
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].service_name, "SessionService");
+        assert!(violations[0].description.contains("FormStateService"));
+        assert!(violations[0].recommended_fix.contains("providedIn: 'root'"));
+    }
+
+    fn module(name: &str, providers: &[&str]) -> ModuleSummary {
+        ModuleSummary {
+            name: name.to_string(),
+            path: format!("src/app/{}.ts", name),
+            module_type: ModuleType::Feature,
+            imports: vec![],
+            exports: vec![],
+            declarations: vec![],
+            providers: providers.iter().map(|p| p.to_string()).collect(),
+            lazy_routes: vec![],
+            feature_areas: vec![],
+            shared_resources: vec![],
+        }
+    }
+
+    #[test]
+    fn test_service_provided_in_two_feature_modules_is_duplicate() {
+        let mut root_service = service("AuthService", ServiceScope::Root, &[]);
+        root_service.provided_in = Some("root".to_string());
+        let services = vec![root_service, service("CartService", ServiceScope::Module, &[])];
+        let modules = vec![
+            module("ShopModule", &["CartService", "AuthService"]),
+            module("CheckoutModule", &["CartService", "AuthService"]),
+            module("AdminModule", &["AdminService"]),
+        ];
+
+        let violations = ScopeAnalyzer::new().analyze(&services, &[], &modules);
+
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].violation_type, ScopeViolationType::DuplicateProvider);
+        assert_eq!(violations[0].service_name, "CartService");
+        assert!(violations[0].description.contains("ShopModule, CheckoutModule"));
+    }
+
+    #[test]
+    fn test_component_injecting_unprovided_service_is_flagged() {
+        let mut auth = service("AuthService", ServiceScope::Root, &[]);
+        auth.provided_in = Some("root".to_string());
+        let services = vec![auth, service("ReportService", ServiceScope::Module, &[])];
+        let component = ComponentInfo {
+            name: "DashboardComponent".to_string(),
+            selector: "app-dashboard".to_string(),
+            inputs: vec![],
+            outputs: vec![],
+            lifecycle: vec![],
+            template_summary: String::new(),
+            location: LocationInfo { line: 1, column: 1 },
+            dependencies: injected(&["AuthService", "ReportService", "HttpClient"]),
+            providers: vec![],
+        };
+
+        let violations = ScopeAnalyzer::new().analyze(&services, std::slice::from_ref(&component), &[]);
+
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].violation_type, ScopeViolationType::MissingProvider);
+        assert_eq!(violations[0].service_name, "ReportService");
+        assert!(violations[0].description.contains("DashboardComponent"));
+
+        // Providing it in a module resolves the dependency
+        let modules = vec![module("ReportsModule", &["ReportService"])];
+        assert!(ScopeAnalyzer::new().analyze(&services, &[component], &modules).is_empty());
+    }
+}
@@ -527,9 +527,8 @@ impl StateAnalyzer {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use tempfile::TempDir;
-    use std::fs;
-    use crate::cache::CacheManager;
+    #[cfg(feature = "typescript")]
+    use {tempfile::TempDir, std::fs, crate::cache::CacheManager};
 
     #[test]
     fn test_state_analyzer_creation() {
@@ -567,6 +566,7 @@ mod tests {
         assert_eq!(obs.source_property, Some("authState".to_string()));
     }
 
+    #[cfg(feature = "typescript")]
     #[test]
     fn test_analyze_auth_service() -> Result<()> {
         let temp_dir = TempDir::new()?;
@@ -0,0 +1,160 @@
+/*! Symbol Locator
+ * Finds the innermost function, method, type or module enclosing a source position
+ */
+
+use serde::{Deserialize, Serialize};
+use tree_sitter::{Node, Point, Tree};
+use crate::analyzers::languages::AnalyzerLanguage;
+use crate::types::LocationInfo;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SymbolKind {
+    Function,
+    Method,
+    Struct,
+    Enum,
+    Trait,
+    Impl,
+    Class,
+    Interface,
+    Module,
+}
+
+/// A symbol enclosing a source position, with its full span
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SymbolRef {
+    pub name: String,
+    pub kind: SymbolKind,
+    pub start: LocationInfo,
+    pub end: LocationInfo,
+    /// Class, impl or trait a method belongs to
+    pub container: Option<String>,
+}
+
+/// Innermost symbol of `tree` whose span contains the 1-based `line` and
+/// byte `column`. Positions outside every symbol (imports, file headers)
+/// yield `None`.
+pub fn enclosing_symbol(
+    tree: &Tree,
+    content: &str,
+    language: AnalyzerLanguage,
+    line: usize,
+    column: usize,
+) -> Option<SymbolRef> {
+    let point = Point { row: line.checked_sub(1)?, column: column.saturating_sub(1) };
+    let source = content.as_bytes();
+    let mut node = Some(tree.root_node().descendant_for_point_range(point, point)?);
+
+    while let Some(current) = node {
+        // A symbol without a name, e.g. from error recovery, doesn't hide
+        // the named symbols around it
+        if let Some((kind, name)) = symbol_kind(current, language).zip(symbol_name(current, source)) {
+            let container = (kind == SymbolKind::Method)
+                .then(|| container_name(current, source, language))
+                .flatten();
+            return Some(SymbolRef {
+                name,
+                kind,
+                start: location(current.start_position()),
+                end: location(current.end_position()),
+                container,
+            });
+        }
+        node = current.parent();
+    }
+    None
+}
+
+fn symbol_kind(node: Node, language: AnalyzerLanguage) -> Option<SymbolKind> {
+    match (language, node.kind()) {
+        (AnalyzerLanguage::Rust, "function_item" | "function_signature_item") => {
+            // Methods sit in the declaration list of an impl or trait
+            let owner = node.parent().and_then(|list| list.parent()).map(|owner| owner.kind());
+            Some(if matches!(owner, Some("impl_item" | "trait_item")) { SymbolKind::Method } else { SymbolKind::Function })
+        }
+        (AnalyzerLanguage::Rust, "struct_item") => Some(SymbolKind::Struct),
+        (AnalyzerLanguage::Rust, "enum_item") => Some(SymbolKind::Enum),
+        (AnalyzerLanguage::Rust, "trait_item") => Some(SymbolKind::Trait),
+        (AnalyzerLanguage::Rust, "impl_item") => Some(SymbolKind::Impl),
+        (AnalyzerLanguage::Rust, "mod_item") => Some(SymbolKind::Module),
+        (AnalyzerLanguage::TypeScript, "function_declaration" | "generator_function_declaration") => Some(SymbolKind::Function),
+        // `const handler = () => ...` is named by its declarator
+        (AnalyzerLanguage::TypeScript, "arrow_function" | "function_expression")
+            if node.parent().is_some_and(|parent| parent.kind() == "variable_declarator") => Some(SymbolKind::Function),
+        (AnalyzerLanguage::TypeScript, "method_definition" | "method_signature") => Some(SymbolKind::Method),
+        (AnalyzerLanguage::TypeScript, "class_declaration" | "abstract_class_declaration") => Some(SymbolKind::Class),
+        (AnalyzerLanguage::TypeScript, "interface_declaration") => Some(SymbolKind::Interface),
+        (AnalyzerLanguage::TypeScript, "enum_declaration") => Some(SymbolKind::Enum),
+        (AnalyzerLanguage::TypeScript, "internal_module" | "module") => Some(SymbolKind::Module),
+        _ => None,
+    }
+}
+
+fn symbol_name(node: Node, source: &[u8]) -> Option<String> {
+    let name_node = match node.kind() {
+        "impl_item" => node.child_by_field_name("type"),
+        "arrow_function" | "function_expression" => node.parent()?.child_by_field_name("name"),
+        _ => node.child_by_field_name("name"),
+    }?;
+    name_node.utf8_text(source).ok().map(str::to_string)
+}
+
+/// Name of the class, impl or trait around a method
+fn container_name(node: Node, source: &[u8], language: AnalyzerLanguage) -> Option<String> {
+    let mut current = node.parent();
+    while let Some(ancestor) = current {
+        if matches!(symbol_kind(ancestor, language), Some(SymbolKind::Impl | SymbolKind::Trait | SymbolKind::Class | SymbolKind::Interface)) {
+            return symbol_name(ancestor, source);
+        }
+        current = ancestor.parent();
+    }
+    None
+}
+
+fn location(point: Point) -> LocationInfo {
+    LocationInfo { line: point.row + 1, column: point.column + 1 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzers::FileAnalyzer;
+    use std::path::Path;
+
+    #[cfg(feature = "rust")]
+    #[test]
+    fn test_position_inside_rust_method_returns_the_method() {
+        let content = "//! Accounts\nuse std::fmt;\n\npub struct Account {\n    balance: i64,\n}\n\nimpl Account {\n    pub fn deposit(&mut self, amount: i64) {\n        self.balance += amount;\n    }\n}\n\nmod audit {\n    pub fn log() {}\n}\n";
+        let analyzer = FileAnalyzer::new();
+        let path = Path::new("src/account.rs");
+
+        let symbol = analyzer.symbol_at(path, content, 10, 14).unwrap();
+        assert_eq!(symbol.name, "deposit");
+        assert_eq!(symbol.kind, SymbolKind::Method);
+        assert_eq!(symbol.container.as_deref(), Some("Account"));
+        assert_eq!((symbol.start.line, symbol.end.line), (9, 11));
+
+        assert_eq!(analyzer.symbol_at(path, content, 5, 6).unwrap().kind, SymbolKind::Struct);
+        assert_eq!(analyzer.symbol_at(path, content, 15, 12).unwrap().name, "log");
+        assert_eq!(analyzer.symbol_at(path, content, 14, 1).unwrap().kind, SymbolKind::Module);
+        assert!(analyzer.symbol_at(path, content, 1, 3).is_none());
+        assert!(analyzer.symbol_at(path, content, 2, 5).is_none());
+    }
+
+    #[cfg(feature = "typescript")]
+    #[test]
+    fn test_position_inside_typescript_method_returns_the_method() {
+        let content = "import { Injectable } from '@angular/core';\n\n@Injectable()\nexport class AuthService {\n  login(user: string): boolean {\n    return user.length > 0;\n  }\n}\n\nexport const logout = () => {\n  return true;\n};\n";
+        let analyzer = FileAnalyzer::new();
+        let path = Path::new("src/app/auth.service.ts");
+
+        let symbol = analyzer.symbol_at(path, content, 6, 5).unwrap();
+        assert_eq!(symbol.name, "login");
+        assert_eq!(symbol.kind, SymbolKind::Method);
+        assert_eq!(symbol.container.as_deref(), Some("AuthService"));
+
+        assert_eq!(analyzer.symbol_at(path, content, 11, 3).unwrap().name, "logout");
+        assert!(analyzer.symbol_at(path, content, 1, 10).is_none());
+        assert!(analyzer.symbol_at(Path::new("notes.md"), content, 6, 5).is_none());
+    }
+}
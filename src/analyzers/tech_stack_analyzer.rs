@@ -0,0 +1,150 @@
+use anyhow::Result;
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::analyzers::rust_analyzer::CargoAnalyzer;
+use crate::types::{CargoDependency, CargoDependencySource, PackageJsonInfo, TechStack};
+
+/// JavaScript frameworks, checked in order against package.json dependencies
+const JS_FRAMEWORKS: &[(&str, &str)] = &[
+    ("@angular/core", "Angular"),
+    ("react", "React"),
+    ("vue", "Vue"),
+];
+
+/// Parse package.json files
+pub struct PackageJsonAnalyzer;
+
+impl PackageJsonAnalyzer {
+    /// Analyze package.json content
+    pub fn analyze_package_json(content: &str) -> Result<PackageJsonInfo> {
+        let parsed: serde_json::Value = serde_json::from_str(content)
+            .map_err(|e| anyhow::anyhow!("Failed to parse package.json: {}", e))?;
+
+        Ok(PackageJsonInfo {
+            name: parsed["name"].as_str().map(|s| s.to_string()),
+            version: parsed["version"].as_str().map(|s| s.to_string()),
+            dependencies: Self::extract_dependencies(&parsed, "dependencies"),
+            dev_dependencies: Self::extract_dependencies(&parsed, "devDependencies"),
+        })
+    }
+
+    fn extract_dependencies(parsed: &serde_json::Value, section: &str) -> HashMap<String, String> {
+        parsed[section]
+            .as_object()
+            .map(|deps| {
+                deps.iter()
+                    .map(|(name, version)| (name.clone(), version.as_str().unwrap_or("").to_string()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// Detects framework, language and dependencies from the project manifests
+pub struct TechStackAnalyzer;
+
+impl TechStackAnalyzer {
+    /// Build the tech stack from `package.json` and `Cargo.toml` in `project_path`.
+    /// Dependencies of both manifests are merged; a JavaScript framework takes
+    /// precedence over Rust when both are present. Manifests that fail to
+    /// parse (e.g. a virtual workspace `Cargo.toml`) contribute no dependencies.
+    pub fn detect(project_path: &Path) -> TechStack {
+        let package_json = std::fs::read_to_string(project_path.join("package.json"))
+            .ok()
+            .and_then(|content| PackageJsonAnalyzer::analyze_package_json(&content).ok());
+        let cargo_manifest = std::fs::read_to_string(project_path.join("Cargo.toml")).ok();
+
+        let mut dependencies = HashMap::new();
+        let mut dev_dependencies = HashMap::new();
+        let mut framework = None;
+        let mut language = None;
+
+        if let Some(package) = &package_json {
+            framework = JS_FRAMEWORKS
+                .iter()
+                .find(|(dependency, _)| package.dependencies.contains_key(*dependency))
+                .map(|(_, name)| name.to_string());
+            let uses_typescript = package.dependencies.contains_key("typescript")
+                || package.dev_dependencies.contains_key("typescript")
+                || project_path.join("tsconfig.json").exists();
+            language = Some(if uses_typescript { "TypeScript" } else { "JavaScript" }.to_string());
+            dependencies.extend(package.dependencies.clone());
+            dev_dependencies.extend(package.dev_dependencies.clone());
+        }
+
+        if let Some(content) = cargo_manifest {
+            framework.get_or_insert_with(|| "Rust".to_string());
+            language.get_or_insert_with(|| "Rust".to_string());
+            if let Ok(cargo) = CargoAnalyzer::analyze_cargo_toml(&content) {
+                dependencies.extend(cargo.dependencies.iter().map(Self::cargo_requirement));
+                dev_dependencies.extend(cargo.dev_dependencies.iter().map(Self::cargo_requirement));
+            }
+        }
+
+        TechStack {
+            framework: framework.unwrap_or_else(|| "Unknown".to_string()),
+            language: language.unwrap_or_else(|| "Unknown".to_string()),
+            dependencies,
+            dev_dependencies,
+        }
+    }
+
+    /// Version requirement of a Cargo dependency, or its source when unversioned
+    fn cargo_requirement(dependency: &CargoDependency) -> (String, String) {
+        let requirement = match (&dependency.version, &dependency.source) {
+            (Some(version), _) => version.clone(),
+            (None, CargoDependencySource::Git { url, .. }) => format!("git+{}", url),
+            (None, CargoDependencySource::Path { path }) => format!("path:{}", path),
+            (None, CargoDependencySource::CratesIo) => "*".to_string(),
+        };
+        (dependency.name.clone(), requirement)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_angular_project_tech_stack() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("package.json"), r#"{
+            "name": "shop",
+            "dependencies": { "@angular/core": "^17.0.0", "rxjs": "~7.8.0" },
+            "devDependencies": { "typescript": "~5.2.0", "karma": "~6.4.0" }
+        }"#).unwrap();
+
+        let stack = TechStackAnalyzer::detect(temp_dir.path());
+        assert_eq!(stack.framework, "Angular");
+        assert_eq!(stack.language, "TypeScript");
+        assert_eq!(stack.dependencies.get("@angular/core").map(String::as_str), Some("^17.0.0"));
+        assert_eq!(stack.dependencies.get("rxjs").map(String::as_str), Some("~7.8.0"));
+        assert_eq!(stack.dev_dependencies.get("karma").map(String::as_str), Some("~6.4.0"));
+    }
+
+    #[test]
+    fn test_cargo_project_tech_stack() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("Cargo.toml"), r#"
+[package]
+name = "tool"
+version = "0.1.0"
+
+[dependencies]
+serde = "1.0"
+local = { path = "../local" }
+
+[dev-dependencies]
+tempfile = "3"
+"#).unwrap();
+
+        let stack = TechStackAnalyzer::detect(temp_dir.path());
+        assert_eq!(stack.framework, "Rust");
+        assert_eq!(stack.language, "Rust");
+        assert_eq!(stack.dependencies.get("serde").map(String::as_str), Some("1.0"));
+        assert_eq!(stack.dependencies.get("local").map(String::as_str), Some("path:../local"));
+        assert_eq!(stack.dev_dependencies.get("tempfile").map(String::as_str), Some("3"));
+    }
+}
@@ -1,6 +1,10 @@
 use anyhow::Result;
 use tree_sitter::{Parser, Node, Tree};
-use crate::types::{FunctionInfo, ClassInfo, ComponentInfo, ServiceInfo, PipeInfo, ParameterInfo, PropertyInfo, LocationInfo};
+use crate::types::{FunctionInfo, ClassInfo, ComponentInfo, ServiceInfo, PipeInfo, ParameterInfo, PropertyInfo, LocationInfo, InjectedDependency, InjectionStyle};
+use crate::ml::models::{AntiPattern, CodeLocation, Severity};
+
+/// Operators in a `.pipe(...)` that complete the stream on their own
+const COMPLETING_OPERATORS: &[&str] = &["takeUntil(", "takeUntilDestroyed(", "take(1)", "first("];
 
 #[derive(Debug, Clone)]
 pub struct TypeScriptElement {
@@ -11,6 +15,16 @@ pub struct TypeScriptElement {
     pub location: String,
 }
 
+#[cfg(feature = "typescript")]
+fn typescript_grammar() -> Result<tree_sitter::Language> {
+    Ok(tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into())
+}
+
+#[cfg(not(feature = "typescript"))]
+fn typescript_grammar() -> Result<tree_sitter::Language> {
+    anyhow::bail!(crate::analyzers::AnalyzerLanguage::TypeScript.unavailable_note())
+}
+
 pub struct TypeScriptASTAnalyzer {
     parser: Parser,
 }
@@ -18,8 +32,7 @@ pub struct TypeScriptASTAnalyzer {
 impl TypeScriptASTAnalyzer {
     pub fn new() -> Result<Self> {
         let mut parser = Parser::new();
-        let language = tree_sitter_typescript::LANGUAGE_TYPESCRIPT;
-        parser.set_language(&language.into())?;
+        parser.set_language(&typescript_grammar()?)?;
         
         Ok(TypeScriptASTAnalyzer { parser })
     }
@@ -65,6 +78,247 @@ impl TypeScriptASTAnalyzer {
         self.find_pipe_info(tree.root_node(), source_bytes)
     }
 
+    /// Flags `.subscribe(` calls in components that are never cleaned up: the
+    /// stream is not completed by `takeUntil`/`take(1)`/`first()` and the
+    /// subscription is not stored for an `unsubscribe()` in `ngOnDestroy`.
+    /// Template `async` pipes never reach this check as they don't subscribe in code.
+    pub fn detect_subscription_leaks(&self, tree: &Tree, source_code: &str, file_path: &str) -> Vec<AntiPattern> {
+        let mut leaks = Vec::new();
+        self.find_subscription_leaks(tree.root_node(), source_code.as_bytes(), file_path, &mut leaks);
+        leaks
+    }
+
+    fn find_subscription_leaks(&self, node: Node, source_code: &[u8], file_path: &str, leaks: &mut Vec<AntiPattern>) {
+        if node.kind() == "class_declaration" && self.is_component_class(node, source_code) {
+            let class_name = node.child_by_field_name("name").map(|n| self.node_text(n, source_code));
+            let unsubscribes_on_destroy = node.child_by_field_name("body")
+                .map(|body| self.destroy_hook_unsubscribes(body, source_code))
+                .unwrap_or(false);
+
+            let mut calls = Vec::new();
+            self.collect_subscribe_calls(node, source_code, None, &mut calls);
+
+            for (call, method_name) in calls {
+                if self.subscription_is_completed(call, source_code)
+                    || (unsubscribes_on_destroy && self.subscription_is_stored(call, source_code))
+                {
+                    continue;
+                }
+
+                leaks.push(AntiPattern {
+                    pattern_name: "Unmanaged RxJS subscription".to_string(),
+                    severity: Severity::Medium,
+                    locations: vec![CodeLocation {
+                        file_path: file_path.to_string(),
+                        line_start: call.start_position().row + 1,
+                        line_end: call.end_position().row + 1,
+                        function_name: method_name,
+                        class_name: class_name.clone(),
+                    }],
+                    description: "Component subscribes to an observable without unsubscribing, leaking the subscription after the component is destroyed".to_string(),
+                    fix_suggestion: "Pipe through takeUntil(this.destroy$) or takeUntilDestroyed(), use the async pipe in the template, or unsubscribe in ngOnDestroy".to_string(),
+                });
+            }
+            return;
+        }
+
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            self.find_subscription_leaks(child, source_code, file_path, leaks);
+        }
+    }
+
+    /// Decorators sit on the class itself or, when exported, on the export statement
+    fn is_component_class(&self, class_node: Node, source_code: &[u8]) -> bool {
+        self.has_component_decorator(class_node, source_code)
+            || class_node.parent()
+                .filter(|parent| parent.kind() == "export_statement")
+                .map(|parent| self.has_component_decorator(parent, source_code))
+                .unwrap_or(false)
+    }
+
+    fn has_component_decorator(&self, node: Node, source_code: &[u8]) -> bool {
+        let mut cursor = node.walk();
+        let decorated = node.children(&mut cursor)
+            .any(|child| child.kind() == "decorator" && self.node_text(child, source_code).starts_with("@Component"));
+        decorated
+    }
+
+    /// Collects `.subscribe(...)` call expressions with their enclosing method name
+    fn collect_subscribe_calls<'a>(&self, node: Node<'a>, source_code: &[u8], method_name: Option<String>, calls: &mut Vec<(Node<'a>, Option<String>)>) {
+        let method_name = if node.kind() == "method_definition" {
+            node.child_by_field_name("name").map(|n| self.node_text(n, source_code))
+        } else {
+            method_name
+        };
+
+        if node.kind() == "call_expression" {
+            let is_subscribe = node.child_by_field_name("function")
+                .filter(|callee| callee.kind() == "member_expression")
+                .and_then(|callee| callee.child_by_field_name("property"))
+                .map(|property| self.node_text(property, source_code) == "subscribe")
+                .unwrap_or(false);
+            if is_subscribe {
+                calls.push((node, method_name.clone()));
+            }
+        }
+
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            self.collect_subscribe_calls(child, source_code, method_name.clone(), calls);
+        }
+    }
+
+    /// True when the subscribed stream is piped through a completing operator
+    fn subscription_is_completed(&self, call: Node, source_code: &[u8]) -> bool {
+        call.child_by_field_name("function")
+            .and_then(|callee| callee.child_by_field_name("object"))
+            .map(|source| {
+                let text = self.node_text(source, source_code);
+                COMPLETING_OPERATORS.iter().any(|operator| text.contains(operator))
+            })
+            .unwrap_or(false)
+    }
+
+    /// True when the subscription is assigned or added to a container
+    /// (`this.sub = ...`, `this.subs.add(...)`, `this.subs.push(...)`)
+    fn subscription_is_stored(&self, call: Node, source_code: &[u8]) -> bool {
+        let Some(parent) = call.parent() else {
+            return false;
+        };
+
+        match parent.kind() {
+            "assignment_expression" | "variable_declarator" => true,
+            "arguments" => parent.parent()
+                .and_then(|outer| outer.child_by_field_name("function"))
+                .and_then(|callee| callee.child_by_field_name("property"))
+                .map(|property| matches!(self.node_text(property, source_code).as_str(), "add" | "push"))
+                .unwrap_or(false),
+            _ => false,
+        }
+    }
+
+    fn destroy_hook_unsubscribes(&self, class_body: Node, source_code: &[u8]) -> bool {
+        let mut cursor = class_body.walk();
+        let unsubscribes = class_body.children(&mut cursor).any(|member| {
+            member.kind() == "method_definition"
+                && member.child_by_field_name("name")
+                    .map(|name| self.node_text(name, source_code) == "ngOnDestroy")
+                    .unwrap_or(false)
+                && self.node_text(member, source_code).contains("unsubscribe()")
+        });
+        unsubscribes
+    }
+
+    /// Flags calls to the file's own async functions and methods made as bare
+    /// statements: not awaited, chained with `.then`/`.catch`, returned or
+    /// assigned, so rejections go unhandled. Prefix the call with `void` to
+    /// mark an intentional fire-and-forget. Methods only match through `this`
+    /// inside their own class, so `repo.save()` is not taken for `this.save()`.
+    pub fn detect_unawaited_promises(&self, tree: &Tree, source_code: &str, file_path: &str) -> Vec<AntiPattern> {
+        let source_bytes = source_code.as_bytes();
+        let mut async_functions = Vec::new();
+        self.collect_async_function_names(tree.root_node(), source_bytes, None, &mut async_functions);
+        if async_functions.is_empty() {
+            return Vec::new();
+        }
+
+        let mut findings = Vec::new();
+        self.find_unawaited_calls(tree.root_node(), source_bytes, file_path, &async_functions, (None, None), &mut findings);
+        findings
+    }
+
+    /// Async function declarations, methods and `const f = async () => ...`,
+    /// as (owning class, name); only methods have an owning class
+    fn collect_async_function_names(&self, node: Node, source_code: &[u8], class_name: Option<String>, names: &mut Vec<(Option<String>, String)>) {
+        let class_name = if node.kind() == "class_declaration" {
+            node.child_by_field_name("name").map(|n| self.node_text(n, source_code))
+        } else {
+            class_name
+        };
+
+        let declared = match node.kind() {
+            "method_definition" if self.is_async_node(node) => node.child_by_field_name("name").map(|name| (class_name.clone(), name)),
+            "function_declaration" if self.is_async_node(node) => node.child_by_field_name("name").map(|name| (None, name)),
+            "variable_declarator" => node.child_by_field_name("value")
+                .filter(|value| matches!(value.kind(), "arrow_function" | "function_expression") && self.is_async_node(*value))
+                .and(node.child_by_field_name("name"))
+                .map(|name| (None, name)),
+            _ => None,
+        };
+        if let Some((owner, name_node)) = declared {
+            names.push((owner, self.node_text(name_node, source_code)));
+        }
+
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            self.collect_async_function_names(child, source_code, class_name.clone(), names);
+        }
+    }
+
+    fn is_async_node(&self, node: Node) -> bool {
+        let mut cursor = node.walk();
+        let is_async = node.children(&mut cursor).any(|child| child.kind() == "async");
+        is_async
+    }
+
+    /// `scope` is the enclosing (function, class) name
+    fn find_unawaited_calls(
+        &self,
+        node: Node,
+        source_code: &[u8],
+        file_path: &str,
+        async_functions: &[(Option<String>, String)],
+        scope: (Option<String>, Option<String>),
+        findings: &mut Vec<AntiPattern>,
+    ) {
+        let (mut function_name, mut class_name) = scope;
+        match node.kind() {
+            "class_declaration" => class_name = node.child_by_field_name("name").map(|n| self.node_text(n, source_code)),
+            "function_declaration" | "method_definition" => {
+                function_name = node.child_by_field_name("name").map(|n| self.node_text(n, source_code));
+            }
+            _ => {}
+        }
+
+        // Awaited, chained, returned, assigned and `void` calls all have a
+        // parent other than the expression statement itself
+        if node.kind() == "call_expression" && node.parent().is_some_and(|parent| parent.kind() == "expression_statement") {
+            // A free function by name, or a method of the enclosing class through `this`
+            let callee = node.child_by_field_name("function").and_then(|callee| match callee.kind() {
+                "identifier" => Some((None, self.node_text(callee, source_code))),
+                "member_expression" => callee.child_by_field_name("object")
+                    .filter(|object| object.kind() == "this")
+                    .and(callee.child_by_field_name("property"))
+                    .map(|property| (class_name.clone(), self.node_text(property, source_code))),
+                _ => None,
+            });
+            if let Some(callee) = callee {
+                if async_functions.contains(&callee) {
+                    let callee = callee.1;
+                    findings.push(AntiPattern {
+                        pattern_name: "Unawaited promise".to_string(),
+                        severity: Severity::Medium,
+                        locations: vec![CodeLocation {
+                            file_path: file_path.to_string(),
+                            line_start: node.start_position().row + 1,
+                            line_end: node.end_position().row + 1,
+                            function_name: function_name.clone(),
+                            class_name: class_name.clone(),
+                        }],
+                        description: format!("Async function '{}' is called without await, so its errors are never handled and callers don't wait for it", callee),
+                        fix_suggestion: "Await the call, chain .then()/.catch(), return the promise, or prefix it with `void` if fire-and-forget is intended".to_string(),
+                    });
+                }
+            }
+        }
+
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            self.find_unawaited_calls(child, source_code, file_path, async_functions, (function_name.clone(), class_name.clone()), findings);
+        }
+    }
+
     fn extract_elements_recursive(&self, node: Node, source_code: &[u8], elements: &mut Vec<TypeScriptElement>) {
         match node.kind() {
             "interface_declaration" => {
@@ -588,6 +842,8 @@ impl TypeScriptASTAnalyzer {
                                 line: child.start_position().row + 1,
                                 column: child.start_position().column + 1,
                             },
+                            dependencies: self.extract_service_dependencies(child, source_code),
+                            providers: self.extract_decorator_providers(node, source_code),
                         });
                     }
                 }
@@ -621,11 +877,12 @@ impl TypeScriptASTAnalyzer {
                             self.extract_class_content(body, source_code, &mut methods, &mut Vec::new());
                         }
                         
+                        let provided_in = self.extract_provided_in(node, child, source_code);
                         return Some(ServiceInfo {
                             name,
                             injectable: true,
-                            provided_in: None, // TODO: Extract from @Injectable decorator
-                            scope: crate::types::ServiceScope::Root, // Default scope
+                            scope: self.provided_in_scope(provided_in.as_deref()),
+                            provided_in,
                             dependencies: self.extract_service_dependencies(child, source_code),
                             methods,
                             location: LocationInfo {
@@ -812,6 +1069,70 @@ impl TypeScriptASTAnalyzer {
         "unknown".to_string()
     }
 
+    /// Injector from `@Injectable({ providedIn: 'root' })`: `root`, `platform`,
+    /// `any` or a module class name. Only the text before the class body is
+    /// read, so a `providedIn` inside a method is not mistaken for it.
+    fn extract_provided_in(&self, node: Node, class: Node, source_code: &[u8]) -> Option<String> {
+        let end = class.child_by_field_name("body").map_or(class.end_byte(), |body| body.start_byte());
+        let decorators = std::str::from_utf8(&source_code[node.start_byte()..end]).ok()?;
+        let start = decorators.find("providedIn")? + "providedIn".len();
+        let value = decorators[start..].trim_start().strip_prefix(':')?
+            .split([',', '}', ')'])
+            .next()?
+            .trim()
+            .trim_matches(['\'', '"', '`']);
+        (!value.is_empty()).then(|| value.to_string())
+    }
+
+    /// Scope implied by `providedIn`. `any` and module classes give an instance
+    /// per module injector; without `providedIn` the service lives wherever a
+    /// module or component lists it, which `ScopeAnalyzer` resolves across files.
+    fn provided_in_scope(&self, provided_in: Option<&str>) -> crate::types::ServiceScope {
+        match provided_in {
+            Some("root") => crate::types::ServiceScope::Root,
+            Some("platform") => crate::types::ServiceScope::Platform,
+            _ => crate::types::ServiceScope::Module,
+        }
+    }
+
+    /// Tokens from `providers: [A, { provide: B, useClass: C }]`
+    fn extract_decorator_providers(&self, node: Node, source_code: &[u8]) -> Vec<String> {
+        let text = self.node_text(node, source_code);
+        let Some(start) = text.find("providers:") else { return Vec::new() };
+        let Some(open) = text[start..].find('[').map(|i| start + i + 1) else { return Vec::new() };
+        
+        // Split the array on top-level commas
+        let mut items = Vec::new();
+        let mut depth = 0;
+        let mut current = String::new();
+        for c in text[open..].chars() {
+            match c {
+                '[' | '{' | '(' => depth += 1,
+                ']' | '}' | ')' if depth > 0 => depth -= 1,
+                ']' => break,
+                ',' if depth == 0 => {
+                    items.push(std::mem::take(&mut current));
+                    continue;
+                }
+                _ => {}
+            }
+            current.push(c);
+        }
+        items.push(current);
+        
+        items
+            .iter()
+            .filter_map(|item| {
+                let item = item.trim();
+                let token = match item.find("provide:") {
+                    Some(i) => item[i + "provide:".len()..].split([',', '}']).next()?.trim(),
+                    None => item,
+                };
+                (!token.is_empty()).then(|| token.to_string())
+            })
+            .collect()
+    }
+
     fn extract_input_properties(&self, node: Node, source_code: &[u8]) -> Vec<PropertyInfo> {
         let mut inputs = Vec::new();
         let text = self.node_text(node, source_code);
@@ -952,22 +1273,28 @@ impl TypeScriptASTAnalyzer {
         lifecycle
     }
 
-    fn extract_service_dependencies(&self, node: Node, source_code: &[u8]) -> Vec<ParameterInfo> {
+    fn extract_service_dependencies(&self, node: Node, source_code: &[u8]) -> Vec<InjectedDependency> {
         let mut dependencies = Vec::new();
         
-        // Look for constructor parameters in class body
         if let Some(body) = node.child_by_field_name("body") {
             let mut cursor = body.walk();
             for child in body.children(&mut cursor) {
-                if child.kind() == "method_definition" {
-                    if let Some(name_node) = child.child_by_field_name("name") {
-                        let name = self.node_text(name_node, source_code);
-                        if name == "constructor" {
-                            if let Some(params_node) = child.child_by_field_name("parameters") {
-                                dependencies.extend(self.extract_parameter_info_list(params_node, source_code));
-                            }
+                match child.kind() {
+                    // constructor(@Optional() @Inject(TOKEN) private dep: Type)
+                    "method_definition" => {
+                        let is_constructor = child.child_by_field_name("name")
+                            .is_some_and(|n| self.node_text(n, source_code) == "constructor");
+                        if let (true, Some(params_node)) = (is_constructor, child.child_by_field_name("parameters")) {
+                            dependencies.extend(self.extract_constructor_injections(params_node, source_code));
+                        }
+                    }
+                    // private dep = inject(Type, { optional: true })
+                    "public_field_definition" | "field_definition" => {
+                        if let Some(dependency) = self.extract_inject_call(child, source_code) {
+                            dependencies.push(dependency);
                         }
                     }
+                    _ => {}
                 }
             }
         }
@@ -975,6 +1302,74 @@ impl TypeScriptASTAnalyzer {
         dependencies
     }
 
+    fn extract_constructor_injections(&self, params_node: Node, source_code: &[u8]) -> Vec<InjectedDependency> {
+        let mut dependencies = Vec::new();
+        let mut cursor = params_node.walk();
+        
+        for (param, child) in self.extract_parameter_info_list(params_node, source_code).into_iter().zip(
+            params_node.children(&mut cursor)
+                .filter(|c| c.kind() == "required_parameter" || c.kind() == "optional_parameter")
+                .filter(|c| c.child_by_field_name("pattern").is_some())
+        ) {
+            let mut token = None;
+            let mut optional_decorator = false;
+            let mut decorator_cursor = child.walk();
+            for decorator in child.children(&mut decorator_cursor).filter(|c| c.kind() == "decorator") {
+                let text = self.node_text(decorator, source_code);
+                if let Some(args) = text.strip_prefix("@Inject(") {
+                    token = args.strip_suffix(')').map(|t| t.trim().to_string());
+                } else if text.starts_with("@Optional") {
+                    optional_decorator = true;
+                }
+            }
+            
+            dependencies.push(InjectedDependency {
+                name: param.name,
+                param_type: param.param_type,
+                token,
+                style: InjectionStyle::Constructor,
+                is_optional: param.is_optional || optional_decorator,
+            });
+        }
+        
+        dependencies
+    }
+
+    fn extract_inject_call(&self, field_node: Node, source_code: &[u8]) -> Option<InjectedDependency> {
+        let name_node = field_node.child_by_field_name("name")?;
+        let value = field_node.child_by_field_name("value")?;
+        if value.kind() != "call_expression" {
+            return None;
+        }
+        let function = value.child_by_field_name("function")?;
+        if self.node_text(function, source_code) != "inject" {
+            return None;
+        }
+        
+        let arguments = self.node_text(value.child_by_field_name("arguments")?, source_code);
+        let inner = arguments.trim().trim_start_matches('(').trim_end_matches(')');
+        let (token, options) = match inner.split_once(',') {
+            Some((token, options)) => (token.trim().to_string(), options),
+            None => (inner.trim().to_string(), ""),
+        };
+        if token.is_empty() {
+            return None;
+        }
+        
+        let declared_type = field_node.child_by_field_name("type").map(|type_node| {
+            self.node_text(type_node, source_code).trim_start_matches(':').trim_start().to_string()
+        });
+        let compact_options: String = options.chars().filter(|c| !c.is_whitespace()).collect();
+        
+        Some(InjectedDependency {
+            name: self.node_text(name_node, source_code),
+            param_type: declared_type.unwrap_or_else(|| token.clone()),
+            token: Some(token),
+            style: InjectionStyle::InjectFunction,
+            is_optional: compact_options.contains("optional:true"),
+        })
+    }
+
     fn extract_dependencies(&self, node: Node, source_code: &[u8]) -> Vec<String> {
         let mut dependencies = Vec::new();
         
@@ -1083,7 +1478,7 @@ impl TypeScriptASTAnalyzer {
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "typescript"))]
 mod tests {
     use super::*;
 
@@ -1276,6 +1671,8 @@ mod tests {
         let svc = service.unwrap();
         assert_eq!(svc.name, "UserService");
         assert!(svc.injectable);
+        assert_eq!(svc.provided_in.as_deref(), Some("root"));
+        assert_eq!(svc.scope, crate::types::ServiceScope::Root);
         assert_eq!(svc.methods.len(), 2); // constructor + getUser
         assert_eq!(svc.dependencies.len(), 1);
         assert_eq!(svc.dependencies[0].name, "http");
@@ -1284,6 +1681,185 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_service_constructor_and_inject_function_dependencies() -> Result<()> {
+        let mut analyzer = TypeScriptASTAnalyzer::new()?;
+        let content = r#"
+        @Injectable({ providedIn: 'root' })
+        export class CartService {
+            private store = inject(Store);
+            private logger = inject(LOGGER, { optional: true });
+            
+            constructor(
+                private http: HttpClient,
+                @Inject(API_URL) private apiUrl: string,
+                @Optional() private analytics: AnalyticsService,
+            ) {}
+        }
+        "#;
+        let tree = analyzer.parse_file(content)?;
+        let svc = analyzer.extract_service_info(&tree, content).unwrap();
+        let dep = |name: &str| svc.dependencies.iter().find(|d| d.name == name).unwrap();
+        
+        assert_eq!(svc.dependencies.len(), 5);
+        
+        let http = dep("http");
+        assert_eq!(http.style, InjectionStyle::Constructor);
+        assert_eq!(http.token, None);
+        assert_eq!(http.injection_token(), "HttpClient");
+        assert!(!http.is_optional);
+        
+        let api_url = dep("apiUrl");
+        assert_eq!(api_url.param_type, "string");
+        assert_eq!(api_url.injection_token(), "API_URL");
+        
+        assert!(dep("analytics").is_optional);
+        
+        let store = dep("store");
+        assert_eq!(store.style, InjectionStyle::InjectFunction);
+        assert_eq!(store.injection_token(), "Store");
+        assert!(!store.is_optional);
+        
+        let logger = dep("logger");
+        assert_eq!(logger.style, InjectionStyle::InjectFunction);
+        assert_eq!(logger.token.as_deref(), Some("LOGGER"));
+        assert!(logger.is_optional);
+        
+        Ok(())
+    }
+
+    #[test]
+    fn test_component_dependencies_and_providers() -> Result<()> {
+        let mut analyzer = TypeScriptASTAnalyzer::new()?;
+        let content = r#"
+        @Component({
+            selector: 'app-cart',
+            providers: [CartStore, { provide: PRICING, useClass: EuPricing }],
+        })
+        export class CartComponent {
+            private cart = inject(CartStore);
+            constructor(private pricing: PricingService) {}
+        }
+        "#;
+        let tree = analyzer.parse_file(content)?;
+        let component = analyzer.extract_component_info(&tree, content).unwrap();
+        
+        assert_eq!(component.providers, vec!["CartStore", "PRICING"]);
+        let tokens: Vec<&str> = component.dependencies.iter().map(|d| d.injection_token()).collect();
+        assert_eq!(tokens, vec!["CartStore", "PricingService"]);
+        
+        Ok(())
+    }
+
+    #[test]
+    fn test_detect_subscription_leaks() -> Result<()> {
+        let mut analyzer = TypeScriptASTAnalyzer::new()?;
+        let leaking = r#"
+            @Component({ selector: 'app-user-list', template: '' })
+            export class UserListComponent implements OnInit {
+                constructor(private users: UserService) {}
+
+                ngOnInit(): void {
+                    this.users.getAll().subscribe(list => this.list = list);
+                }
+            }
+        "#;
+        let tree = analyzer.parse_file(leaking)?;
+        let leaks = analyzer.detect_subscription_leaks(&tree, leaking, "user-list.component.ts");
+        assert_eq!(leaks.len(), 1);
+        let location = &leaks[0].locations[0];
+        assert_eq!(location.file_path, "user-list.component.ts");
+        assert_eq!(location.line_start, 7);
+        assert_eq!(location.function_name.as_deref(), Some("ngOnInit"));
+        assert_eq!(location.class_name.as_deref(), Some("UserListComponent"));
+
+        let managed = r#"
+            @Component({ selector: 'app-user-detail', template: '' })
+            export class UserDetailComponent implements OnInit, OnDestroy {
+                private destroy$ = new Subject<void>();
+                private sub: Subscription;
+
+                ngOnInit(): void {
+                    this.users.current().pipe(takeUntil(this.destroy$)).subscribe(u => this.user = u);
+                    this.sub = this.route.params.subscribe(p => this.id = p.id);
+                }
+
+                ngOnDestroy(): void {
+                    this.sub.unsubscribe();
+                    this.destroy$.next();
+                }
+            }
+        "#;
+        let tree = analyzer.parse_file(managed)?;
+        assert!(analyzer.detect_subscription_leaks(&tree, managed, "user-detail.component.ts").is_empty());
+
+        // Services are not components and are not checked
+        let service = r#"
+            @Injectable({ providedIn: 'root' })
+            export class PollingService {
+                start(): void { interval(1000).subscribe(() => this.poll()); }
+            }
+        "#;
+        let tree = analyzer.parse_file(service)?;
+        assert!(analyzer.detect_subscription_leaks(&tree, service, "polling.service.ts").is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_detect_unawaited_promises() -> Result<()> {
+        let mut analyzer = TypeScriptASTAnalyzer::new()?;
+        let content = r#"
+            export class ProfileService {
+                async save(profile: Profile): Promise<void> {
+                    await this.http.put('/api/profile', profile).toPromise();
+                }
+
+                update(profile: Profile): void {
+                    this.save(profile);
+                }
+
+                async updateAndWait(profile: Profile): Promise<void> {
+                    await this.save(profile);
+                    this.save(profile).catch(err => console.error(err));
+                    void this.save(profile);
+                }
+
+                reload(): Promise<void> {
+                    return this.save(this.current);
+                }
+            }
+
+            export class ProfileStore {
+                constructor(private repo: ProfileService) {}
+
+                persist(profile: Profile): void {
+                    this.repo.save(profile);
+                    this.save(profile);
+                }
+
+                save(profile: Profile): void {}
+            }
+
+            const refresh = async () => fetch('/api/refresh');
+
+            export function start(): void {
+                refresh();
+            }
+        "#;
+        let tree = analyzer.parse_file(content)?;
+        let findings = analyzer.detect_unawaited_promises(&tree, content, "profile.service.ts");
+
+        let locations: Vec<_> = findings.iter()
+            .map(|finding| (finding.locations[0].line_start, finding.locations[0].function_name.as_deref()))
+            .collect();
+        // `this.repo.save()` and ProfileStore's own sync `save` are not ProfileService.save
+        assert_eq!(locations, vec![(8, Some("update")), (36, Some("start"))]);
+        assert_eq!(findings[1].locations[0].class_name, None);
+        assert_eq!(findings[0].locations[0].class_name.as_deref(), Some("ProfileService"));
+        assert_eq!(findings[0].pattern_name, "Unawaited promise");
+        Ok(())
+    }
+
     #[test]
     fn test_parse_complex_parameters() -> Result<()> {
         let mut analyzer = TypeScriptASTAnalyzer::new()?;
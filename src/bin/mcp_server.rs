@@ -12,6 +12,7 @@
 use clap::Parser;
 use anyhow::Result;
 use token_optimizer::mcp::MCPServer;
+use token_optimizer::utils::path_normalizer::PathStyle;
 
 #[derive(Parser)]
 #[command(name = "mcp-server")]
@@ -25,6 +26,10 @@ struct Cli {
     /// Enable debug logging
     #[arg(long)]
     debug: bool,
+    
+    /// How file paths are reported in tool results (relative, absolute)
+    #[arg(long, default_value = "relative")]
+    path_style: PathStyle,
 }
 
 #[tokio::main]
@@ -41,7 +46,7 @@ async fn main() -> Result<()> {
     println!();
     
     // Initialize and start MCP server
-    let server = MCPServer::new().await?;
+    let server = MCPServer::new(cli.path_style).await?;
     
     println!("🎯 Ultimate LLM Agent Token Optimization Tools:");
     println!("   • smart_context: Get optimized code context (reduces tokens 70-90%)");
@@ -34,6 +34,7 @@ async fn main() -> Result<()> {
         similarity_threshold: 0.7,
         max_results: 20,
         enable_persistence: true,
+        random_seed: None,
     };
     let vector_db = VectorStoreFactory::create_native(vector_db_config);
     
@@ -68,6 +69,7 @@ async fn main() -> Result<()> {
         rerank_threshold: 0.5,  // Moderate threshold for balance
         enable_caching: true,
         embedding_cache_size: 100,
+        ..Default::default()
     };
     
     let pipeline = SemanticSearchFactory::create_with_config(
@@ -138,7 +140,9 @@ async fn add_sample_data(
             language: "typescript".to_string(),
             complexity: 1.0 + (i as f32 * 0.5),
             tokens: content.split_whitespace().take(10).map(|s| s.to_string()).collect(),
+            calls: Vec::new(),
             hash: format!("hash_{}", i),
+            embedding_template: "enriched".to_string(),
         };
         
         let entry = VectorEntry {
@@ -1,13 +1,17 @@
 use anyhow::Result;
+use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 use chrono::Utc;
 use std::sync::{Arc, Mutex};
 use tokio::sync::mpsc;
 use rayon::prelude::*;
-use crate::types::{CacheEntry, ChangeLogEntry, ChangeType, ImpactLevel};
+use crate::types::{CacheEntry, ChangeLogEntry, ChangeType, FunctionInfo, ImpactLevel};
 use super::smart_cache::SmartCache;
 use crate::analyzers::{FileAnalyzer, CodeSummarizer};
 use crate::utils::{calculate_file_hash, walk_project_files, is_ignored_file};
+use crate::generators::context_pack::{normalize, IMPORT_CANDIDATES};
+use crate::utils::decoration::status;
+use crate::utils::path_normalizer::namespace_path;
 
 pub struct CacheManager {
     cache: SmartCache,
@@ -15,6 +19,8 @@ pub struct CacheManager {
     project_path: PathBuf,
     file_analyzer: FileAnalyzer,
     code_summarizer: CodeSummarizer,
+    /// Subtree analysis is scoped to, relative to the project root
+    focus: Option<PathBuf>,
 }
 
 /// Progress update for async cache operations
@@ -26,6 +32,65 @@ pub struct CacheProgress {
     pub percentage: f32,
 }
 
+/// Outcome of resolving a user-supplied path against the cached files
+#[derive(Debug, Clone, PartialEq)]
+pub enum PathMatch {
+    Unique(String),
+    Ambiguous(Vec<String>),
+    NotFound,
+}
+
+/// Result of checking cached entries against the files on disk
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CacheValidation {
+    pub checked: usize,
+    /// Entries whose file changed since it was analyzed
+    pub stale: Vec<String>,
+    /// Entries whose file no longer exists
+    pub missing: Vec<String>,
+    /// Entries whose file exists but could not be hashed, with the error
+    pub unreadable: Vec<(String, String)>,
+    /// Entries merged from other repos, whose files live outside the project
+    /// and are not checked
+    pub merged: usize,
+}
+
+impl CacheValidation {
+    pub fn is_valid(&self) -> bool {
+        self.stale.is_empty() && self.missing.is_empty() && self.unreadable.is_empty()
+    }
+}
+
+/// How `SourceHashes::is_fresh` decides whether indexed files changed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FreshnessMode {
+    /// `Hash` under CI (the `CI` environment variable is set), where
+    /// checkouts and restored caches reset mtimes, else `Mtime`
+    #[default]
+    Auto,
+    /// Stale when a file's modification time changed since indexing. Cheap,
+    /// but checkouts, CI caches and clock skew change mtimes without changing content
+    Mtime,
+    /// Stale when a file no longer hashes to its hash at indexing time
+    Hash,
+    /// Like `Hash`, for this many files spread evenly over the index. The
+    /// sample starts at an offset that rotates between checks, so repeated
+    /// checks cover every file, but any single check can miss a change
+    SampledHash(usize),
+}
+
+impl FreshnessMode {
+    /// The mode `Auto` stands for in this environment; explicit modes are kept
+    pub fn resolve(self) -> Self {
+        match self {
+            Self::Auto if std::env::var_os("CI").is_some() => Self::Hash,
+            Self::Auto => Self::Mtime,
+            mode => mode,
+        }
+    }
+}
+
 /// Result of async cache analysis
 #[derive(Debug)]
 pub struct AsyncAnalysisResult {
@@ -47,11 +112,58 @@ impl CacheManager {
             project_path: project_path.to_path_buf(),
             file_analyzer: FileAnalyzer::new(),
             code_summarizer: CodeSummarizer::new(),
+            focus: None,
+        })
+    }
+
+    /// Like `new`, scoped to `focus` when one is given (`--focus`)
+    pub fn new_with_focus(project_path: &Path, focus: Option<&Path>) -> Result<Self> {
+        let manager = Self::new(project_path)?;
+        match focus {
+            Some(focus) => manager.with_focus(focus),
+            None => Ok(manager),
+        }
+    }
+
+    /// Scopes analysis to the `focus` subtree, given relative to the project
+    /// root or as a path under it. Imports leaving the subtree are still
+    /// resolved and kept in `external_dependencies`. A focused manager uses its
+    /// own cache file so it never evicts the rest of the project's entries.
+    pub fn with_focus(mut self, focus: &Path) -> Result<Self> {
+        let relative = PathBuf::from(normalize(focus.strip_prefix(&self.project_path).unwrap_or(focus)));
+        if !self.project_path.join(&relative).is_dir() {
+            anyhow::bail!("Focus directory {} not found in {}", relative.display(), self.project_path.display());
+        }
+
+        let cache_name = relative.to_string_lossy().replace(['/', '\\'], "__");
+        self.cache_path = self.project_path.join(".cache").join("focus").join(format!("{}.json", cache_name));
+        self.cache = SmartCache::load_from_file(&self.cache_path).unwrap_or_default();
+        self.focus = Some(relative);
+        Ok(self)
+    }
+
+    /// The focus subtree, relative to the project, if analysis is scoped to one
+    pub fn focus(&self) -> Option<&Path> {
+        self.focus.as_deref()
+    }
+
+    /// Directory analysis walks: the focus subtree of `project_path`, or all of it
+    pub fn scope_root(&self, project_path: &Path) -> PathBuf {
+        match &self.focus {
+            Some(focus) => project_path.join(focus),
+            None => project_path.to_path_buf(),
+        }
+    }
+
+    /// Cache key lies inside the focus subtree; always true without a focus
+    pub fn is_in_focus(&self, key: &str) -> bool {
+        self.focus.as_ref().is_none_or(|focus| {
+            Path::new(key.trim_start_matches("./")).starts_with(focus)
         })
     }
 
     pub fn analyze_project(&mut self, project_path: &Path, force_reanalysis: bool) -> Result<()> {
-        let files = walk_project_files(project_path)?;
+        let files = walk_project_files(&self.scope_root(project_path))?;
         
         for file_path in files {
             let path = Path::new(&file_path);
@@ -71,8 +183,14 @@ impl CacheManager {
 
     pub fn analyze_file(&mut self, file_path: &Path) -> Result<()> {
         let file_hash = calculate_file_hash(file_path)?;
-        let metadata = self.file_analyzer.analyze_file(file_path)?;
+        let metadata = self.file_analyzer.analyze_any(file_path)?;
         let summary = self.code_summarizer.summarize_file(file_path)?;
+        let normalized_path = self.normalize_cache_key(file_path);
+        let dependencies = self.resolve_dependencies(&normalized_path, &metadata.imports);
+        let external_dependencies = dependencies.iter()
+            .filter(|dependency| !self.is_in_focus(dependency))
+            .cloned()
+            .collect();
         
         let change_log_entry = ChangeLogEntry {
             timestamp: Utc::now(),
@@ -92,16 +210,34 @@ impl CacheManager {
             summary,
             metadata,
             change_log: vec![change_log_entry],
-            dependencies: Vec::new(), // TODO: Implement dependency analysis
+            dependencies,
             dependents: Vec::new(),   // TODO: Implement dependent analysis
+            external_dependencies,
         };
 
-        // Normalize path to relative path from project root for consistency
-        let normalized_path = self.normalize_cache_key(file_path);
         self.cache.set_entry(normalized_path, cache_entry);
         Ok(())
     }
 
+    /// Cache keys of the project files `imports` resolve to from the file at
+    /// `key`. Files outside the focus count too, so edges leaving it survive.
+    fn resolve_dependencies(&self, key: &str, imports: &[String]) -> Vec<String> {
+        let base = Path::new(key).parent().unwrap_or_else(|| Path::new("."));
+        let mut dependencies: Vec<String> = imports.iter()
+            .filter(|import| import.starts_with('.'))
+            .filter_map(|import| {
+                let joined = normalize(&base.join(import));
+                IMPORT_CANDIDATES.iter()
+                    .map(|ext| format!("{}{}", joined, ext))
+                    .find(|candidate| self.project_path.join(candidate).is_file())
+                    .map(|candidate| format!("./{}", candidate))
+            })
+            .collect();
+        dependencies.sort();
+        dependencies.dedup();
+        dependencies
+    }
+
     pub fn is_file_up_to_date(&self, file_path: &Path) -> Result<bool> {
         let normalized_key = self.normalize_cache_key(file_path);
         if let Some(entry) = self.cache.get_entry(&normalized_key) {
@@ -158,6 +294,78 @@ impl CacheManager {
         Ok(entries_before)
     }
 
+    /// Merges another project's cache into this one under a repo `prefix`.
+    /// The merged repo's stats are kept and available via `SmartCache::get_repo_stats`.
+    pub fn merge(&mut self, other: SmartCache, prefix: &str) -> Result<usize> {
+        if prefix.trim_matches('/').is_empty() {
+            anyhow::bail!("Merge prefix must not be empty");
+        }
+        Ok(self.cache.merge_namespaced(other, prefix))
+    }
+
+    /// Every cached function, method and impl method matching `predicate`,
+    /// with the file it was found in. Results are ordered by path, then line.
+    pub fn query_functions(&self, predicate: impl Fn(&FunctionInfo) -> bool) -> Vec<(PathBuf, FunctionInfo)> {
+        let mut matches = Vec::new();
+        for (path, entry) in &self.cache.entries {
+            let Some(analysis) = &entry.metadata.detailed_analysis else {
+                continue;
+            };
+
+            let class_methods = analysis.classes.iter().flat_map(|class| &class.methods);
+            let rust_functions = analysis.rust_module.iter().flat_map(|module| {
+                module.functions.iter()
+                    .chain(module.impl_blocks.iter().flat_map(|block| &block.methods))
+            });
+
+            let mut file_matches: Vec<&FunctionInfo> = Vec::new();
+            for function in analysis.functions.iter().chain(class_methods).chain(rust_functions) {
+                // Analyzers may report a function in more than one collection
+                if predicate(function) && !file_matches.contains(&function) {
+                    file_matches.push(function);
+                }
+            }
+            matches.extend(file_matches.into_iter().map(|function| (PathBuf::from(path), function.clone())));
+        }
+
+        matches.sort_by(|(a_path, a), (b_path, b)| {
+            a_path.cmp(b_path).then(a.location.line.cmp(&b.location.line))
+        });
+        matches
+    }
+
+    /// Checks that every cached file still exists and still hashes to its
+    /// stored `file_hash`. Entries are reported by cache key, sorted. A file
+    /// that cannot be read is reported as unreadable rather than failing the
+    /// check. Entries merged under a repo prefix are counted but not checked.
+    pub fn validate(&self) -> Result<CacheValidation> {
+        let merged_prefixes: Vec<String> = self.cache.repo_stats.keys()
+            .map(|prefix| namespace_path(prefix, "./"))
+            .collect();
+        let mut validation = CacheValidation::default();
+        for (key, entry) in &self.cache.entries {
+            if merged_prefixes.iter().any(|prefix| key.starts_with(prefix.as_str())) {
+                validation.merged += 1;
+                continue;
+            }
+            validation.checked += 1;
+            let path = self.project_path.join(key);
+            if !path.is_file() {
+                validation.missing.push(key.clone());
+            } else {
+                match calculate_file_hash(&path) {
+                    Ok(hash) if hash != entry.file_hash => validation.stale.push(key.clone()),
+                    Ok(_) => {}
+                    Err(e) => validation.unreadable.push((key.clone(), e.to_string())),
+                }
+            }
+        }
+        validation.stale.sort();
+        validation.missing.sort();
+        validation.unreadable.sort();
+        Ok(validation)
+    }
+
     pub fn get_cache_stats(&self) -> crate::cache::CacheStats {
         self.cache.get_cache_stats()
     }
@@ -224,6 +432,50 @@ impl CacheManager {
         }
     }
     
+    /// Resolves a partial or mistyped path to a cache key. Tries, in order: an exact
+    /// lookup, keys ending with the path, keys containing it (case-insensitive) and
+    /// keys whose file name is within two edits of it. The first tier with any hits wins.
+    pub fn find_file(&self, file_path: &str) -> PathMatch {
+        let normalized_key = self.normalize_lookup_key(file_path);
+        if self.cache.get_entry(&normalized_key).is_some() {
+            return PathMatch::Unique(normalized_key);
+        }
+
+        let query = normalized_key.trim_start_matches("./").replace('\\', "/").to_lowercase();
+        if query.is_empty() {
+            return PathMatch::NotFound;
+        }
+        let query_name = query.rsplit('/').next().unwrap_or(&query).to_string();
+
+        let mut keys: Vec<&String> = self.cache.entries.keys().collect();
+        keys.sort();
+
+        let tiers: [&dyn Fn(&str) -> bool; 3] = [
+            &|key: &str| key.ends_with(&format!("/{}", query)),
+            &|key: &str| key.contains(&query),
+            &|key: &str| {
+                let name = key.rsplit('/').next().unwrap_or(key);
+                levenshtein(name, &query_name) <= 2
+            },
+        ];
+
+        for matches_tier in tiers {
+            let candidates: Vec<String> = keys
+                .iter()
+                .filter(|key| matches_tier(&key.to_lowercase()))
+                .map(|key| key.to_string())
+                .collect();
+
+            match candidates.len() {
+                0 => continue,
+                1 => return PathMatch::Unique(candidates[0].clone()),
+                _ => return PathMatch::Ambiguous(candidates),
+            }
+        }
+
+        PathMatch::NotFound
+    }
+    
     /// Attempts to strip a potential project directory prefix from the path
     fn strip_project_prefix<'a>(&self, file_path: &'a str) -> Option<&'a str> {
         // Common patterns that might appear at the start of user-provided paths
@@ -267,16 +519,14 @@ impl CacheManager {
         let project_path = project_path.to_path_buf();
         
         // Get file list async
-        let files = tokio::task::spawn_blocking({
-            let project_path = project_path.clone();
-            move || walk_project_files(&project_path)
-        }).await??;
+        let scope_root = cache_manager.lock().unwrap().scope_root(&project_path);
+        let files = tokio::task::spawn_blocking(move || walk_project_files(&scope_root)).await??;
         
         let total_files = files.len();
         let processed_count = Arc::new(std::sync::atomic::AtomicUsize::new(0));
         let errors = Arc::new(Mutex::new(Vec::new()));
         
-        println!("🚀 Starting async cache analysis: {} files", total_files);
+        status!("🚀 Starting async cache analysis: {} files", total_files);
         
         // Process files in parallel batches with Rayon + async hybrid approach
         let batch_size = 32; // Optimal for I/O + CPU balance
@@ -337,7 +587,7 @@ impl CacheManager {
         
         let duration_ms = start_time.elapsed().as_millis() as u64;
         
-        println!("✅ Async cache analysis completed:");
+        status!("✅ Async cache analysis completed:");
         println!("   Files processed: {}", files_processed);
         println!("   Files added: {}", files_added);
         println!("   Files updated: {}", files_updated);
@@ -388,6 +638,23 @@ impl CacheManager {
     }
 }
 
+/// Edit distance between two strings, used for typo-tolerant path lookups
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b_chars: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b_chars.len()).collect();
+
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b_chars.iter().enumerate() {
+            let cost = if a_char == *b_char { 0 } else { 1 };
+            current.push((previous[j] + cost).min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+
+    previous[b_chars.len()]
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -412,6 +679,17 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_only_auto_freshness_depends_on_the_environment() {
+        assert_eq!(FreshnessMode::default(), FreshnessMode::Auto);
+        assert!(matches!(FreshnessMode::Auto.resolve(), FreshnessMode::Mtime | FreshnessMode::Hash));
+        for mode in [FreshnessMode::Mtime, FreshnessMode::Hash, FreshnessMode::SampledHash(4)] {
+            assert_eq!(mode.resolve(), mode);
+        }
+        let mode: FreshnessMode = serde_json::from_str("\"mtime\"").unwrap();
+        assert_eq!(mode.resolve(), FreshnessMode::Mtime);
+    }
+
     #[test]
     fn test_path_normalization_fixes() -> Result<()> {
         let temp_dir = TempDir::new()?;
@@ -583,6 +861,92 @@ mod tests {
         Ok(())
     }
 
+    #[cfg(all(feature = "rust", feature = "typescript"))]
+    #[test]
+    fn test_query_functions_selects_async_functions() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let mut cache_manager = CacheManager::new(temp_dir.path())?;
+
+        create_test_typescript_file(&temp_dir, "user.service.ts", r#"
+export class UserService {
+    async getUser(id: number): Promise<User> {
+        return this.http.get<User>(`/api/users/${id}`).toPromise();
+    }
+
+    formatUser(user: User): string {
+        return user.name;
+    }
+}
+"#)?;
+        create_test_typescript_file(&temp_dir, "src/lib.rs", r#"
+pub async fn fetch_config(url: &str) -> Result<String> {
+    Ok(url.to_string())
+}
+
+pub fn parse_config(raw: &str) -> usize {
+    raw.len()
+}
+"#)?;
+        cache_manager.analyze_project(temp_dir.path(), false)?;
+
+        let async_functions = cache_manager.query_functions(|function| function.is_async);
+        let names: Vec<_> = async_functions.iter().map(|(_, function)| function.name.as_str()).collect();
+        assert_eq!(names, vec!["fetch_config", "getUser"]);
+        assert!(async_functions[0].0.ends_with("src/lib.rs"));
+        assert!(async_functions[1].0.ends_with("user.service.ts"));
+
+        assert!(cache_manager.query_functions(|function| function.name == "missing").is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_reports_stale_and_missing_entries() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let mut cache_manager = CacheManager::new(temp_dir.path())?;
+
+        create_test_typescript_file(&temp_dir, "src/app.ts", "export const version = 1;\n")?;
+        create_test_typescript_file(&temp_dir, "src/old.ts", "export const legacy = true;\n")?;
+        create_test_typescript_file(&temp_dir, "src/util.ts", "export const twice = (n: number) => n * 2;\n")?;
+        cache_manager.analyze_project(temp_dir.path(), false)?;
+        assert!(cache_manager.validate()?.is_valid());
+
+        fs::write(temp_dir.path().join("src/app.ts"), "export const version = 2;\n")?;
+        fs::remove_file(temp_dir.path().join("src/old.ts"))?;
+
+        let validation = cache_manager.validate()?;
+        assert_eq!(validation.checked, 3);
+        assert_eq!(validation.stale, vec!["./src/app.ts"]);
+        assert_eq!(validation.missing, vec!["./src/old.ts"]);
+        assert!(!validation.is_valid());
+        Ok(())
+    }
+
+    #[cfg(feature = "typescript")]
+    #[test]
+    fn test_focus_scopes_entries_but_keeps_external_edges() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        create_test_typescript_file(&temp_dir, "packages/app/src/main.ts",
+            "import { format } from '../../shared/format';\nimport { start } from './start';\nexport const run = () => start(format(1));\n")?;
+        create_test_typescript_file(&temp_dir, "packages/app/src/start.ts", "export const start = (s: string) => s;\n")?;
+        create_test_typescript_file(&temp_dir, "packages/shared/format.ts", "export const format = (n: number) => `${n}`;\n")?;
+
+        let mut cache_manager = CacheManager::new_with_focus(temp_dir.path(), Some(Path::new("packages/app")))?;
+        cache_manager.analyze_project(temp_dir.path(), false)?;
+
+        let mut keys: Vec<&String> = cache_manager.get_cache().entries.keys().collect();
+        keys.sort();
+        assert_eq!(keys, vec!["./packages/app/src/main.ts", "./packages/app/src/start.ts"]);
+
+        let main = cache_manager.get_cache().get_entry("./packages/app/src/main.ts").unwrap();
+        assert_eq!(main.dependencies, vec!["./packages/app/src/start.ts", "./packages/shared/format.ts"]);
+        assert_eq!(main.external_dependencies, vec!["./packages/shared/format.ts"]);
+
+        // The focused cache lives apart from the project-wide one
+        assert!(CacheManager::new(temp_dir.path())?.get_cache().entries.is_empty());
+        assert!(CacheManager::new_with_focus(temp_dir.path(), Some(Path::new("packages/missing"))).is_err());
+        Ok(())
+    }
+
     // ✨ NUEVA PRUEBA: Normalización de paths en cache
     #[test]
     fn test_cache_entry_path_normalization() -> Result<()> {
@@ -809,6 +1173,43 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_merge_namespaces_caches_by_repo_prefix() -> Result<()> {
+        let repo_a = TempDir::new()?;
+        let repo_b = TempDir::new()?;
+        create_test_typescript_file(&repo_a, "src/app/shared.service.ts", "export class SharedService { fromA(): void {} }")?;
+        create_test_typescript_file(&repo_b, "src/app/shared.service.ts", "export class SharedService { fromB(): void {} }")?;
+        create_test_typescript_file(&repo_b, "src/app/extra.ts", "export function extra() { return 1; }")?;
+
+        let mut manager_a = CacheManager::new(repo_a.path())?;
+        manager_a.analyze_project(repo_a.path(), false)?;
+        let mut manager_b = CacheManager::new(repo_b.path())?;
+        manager_b.analyze_project(repo_b.path(), false)?;
+
+        let workspace = TempDir::new()?;
+        let mut unified = CacheManager::new(workspace.path())?;
+        assert_eq!(unified.merge(manager_a.get_cache().clone(), "repo-a")?, 1);
+        assert_eq!(unified.merge(manager_b.get_cache().clone(), "repo-b")?, 2);
+
+        let entry_a = unified.get_file_summary("repo-a/src/app/shared.service.ts").expect("repo-a entry");
+        let entry_b = unified.get_file_summary("repo-b/src/app/shared.service.ts").expect("repo-b entry");
+        assert_ne!(entry_a.file_hash, entry_b.file_hash);
+        assert_eq!(entry_a.metadata.path, "./repo-a/src/app/shared.service.ts");
+        assert_eq!(unified.get_cache_stats().total_entries, 3);
+
+        assert_eq!(unified.get_cache().get_repo_stats("repo-a").unwrap().total_entries, 1);
+        assert_eq!(unified.get_cache().get_repo_stats("repo-b").unwrap().total_entries, 2);
+        assert!(unified.merge(SmartCache::new(), "/").is_err());
+
+        // Merged files live in their own repos, not under the workspace
+        let validation = unified.validate()?;
+        assert_eq!((validation.checked, validation.merged), (0, 3));
+        assert!(validation.is_valid());
+
+        Ok(())
+    }
+
+    #[cfg(feature = "typescript")]
     #[tokio::test]
     async fn test_async_cache_generation() -> Result<()> {
         let temp_dir = TempDir::new()?;
@@ -4,22 +4,34 @@ use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::fs;
 use chrono::{DateTime, Utc};
-use crate::types::CacheEntry;
+use crate::types::{CacheEntry, ProjectOverview};
 use crate::utils::hash_utils::calculate_file_hash;
+use crate::utils::path_normalizer::namespace_path;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SmartCache {
     pub entries: HashMap<String, CacheEntry>,
     pub last_updated: DateTime<Utc>,
     pub cache_version: String,
+    /// Stats of caches merged in from other repositories, keyed by repo prefix
+    #[serde(default)]
+    pub repo_stats: HashMap<String, CacheStats>,
+    /// Overviews kept for `overview --since`, keyed by commit
+    #[serde(default)]
+    pub overview_snapshots: HashMap<String, ProjectOverview>,
 }
 
+/// Overview snapshots kept before the oldest is evicted
+pub const MAX_OVERVIEW_SNAPSHOTS: usize = 10;
+
 impl SmartCache {
     pub fn new() -> Self {
         SmartCache {
             entries: HashMap::new(),
             last_updated: Utc::now(),
             cache_version: "1.0.0".to_string(),
+            repo_stats: HashMap::new(),
+            overview_snapshots: HashMap::new(),
         }
     }
 
@@ -167,6 +179,48 @@ impl SmartCache {
         }
     }
 
+    /// Merges another cache into this one, namespacing every key under `prefix`
+    /// so that identical relative paths from different repos don't collide.
+    pub fn merge_namespaced(&mut self, other: SmartCache, prefix: &str) -> usize {
+        let prefix = prefix.trim_matches('/');
+        self.repo_stats.insert(prefix.to_string(), other.get_cache_stats());
+
+        let mut merged = 0;
+        for (key, mut entry) in other.entries {
+            let namespaced_key = namespace_path(prefix, &key);
+            entry.metadata.path = namespaced_key.clone();
+            self.entries.insert(namespaced_key, entry);
+            merged += 1;
+        }
+
+        self.last_updated = Utc::now();
+        merged
+    }
+
+    pub fn get_overview_snapshot(&self, key: &str) -> Option<&ProjectOverview> {
+        self.overview_snapshots.get(key)
+    }
+
+    /// Stores `overview` under `key`, evicting the oldest snapshots beyond
+    /// `MAX_OVERVIEW_SNAPSHOTS`
+    pub fn set_overview_snapshot(&mut self, key: String, overview: ProjectOverview) {
+        self.overview_snapshots.insert(key, overview);
+        while self.overview_snapshots.len() > MAX_OVERVIEW_SNAPSHOTS {
+            let oldest = self.overview_snapshots.iter()
+                .min_by_key(|(_, snapshot)| snapshot.last_updated)
+                .map(|(key, _)| key.clone());
+            match oldest {
+                Some(key) => self.overview_snapshots.remove(&key),
+                None => break,
+            };
+        }
+        self.last_updated = Utc::now();
+    }
+
+    pub fn get_repo_stats(&self, prefix: &str) -> Option<&CacheStats> {
+        self.repo_stats.get(prefix.trim_matches('/'))
+    }
+
     pub fn update_dependents(&mut self, file_path: &str, dependents: Vec<String>) {
         if let Some(entry) = self.entries.get_mut(file_path) {
             entry.dependents = dependents;
@@ -210,6 +264,8 @@ mod tests {
             imports: vec![],
             complexity: Complexity::Low,
             detailed_analysis: None,
+            extra: serde_json::Value::Null,
+            parse_error: None,
         };
 
         let summary = CodeSummary {
@@ -237,6 +293,7 @@ mod tests {
             change_log: vec![],
             dependencies: vec![],
             dependents: vec![],
+            external_dependencies: vec![],
         }
     }
 
@@ -1,5 +1,10 @@
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 use std::path::PathBuf;
+use crate::utils::path_normalizer::PathStyle;
+use super::output::{JsonStyle, OutputFormat, RenderOptions};
+use crate::utils::decoration::Charset;
+use crate::ml::services::enhanced_search::SearchType;
+use crate::ml::config::EmbeddingContent;
 
 #[derive(Parser)]
 #[command(name = "token-optimizer")]
@@ -8,6 +13,40 @@ use std::path::PathBuf;
 pub struct Cli {
     #[command(subcommand)]
     pub command: Commands,
+    
+    /// How file paths are printed (relative, absolute)
+    #[arg(long, global = true, default_value = "relative")]
+    pub path_style: PathStyle,
+    
+    /// Cap text and markdown output of overview, search and patterns at this many lines
+    #[arg(long, global = true)]
+    pub max_output_lines: Option<usize>,
+    
+    /// Scope analysis, indexing and overview to this directory of the project;
+    /// imports leaving it are still tracked as external dependencies
+    #[arg(long, global = true)]
+    pub focus: Option<PathBuf>,
+    
+    /// Print JSON on a single line instead of indented
+    #[arg(long, global = true)]
+    pub compact_json: bool,
+    
+    /// Print ASCII tags instead of emoji and other Unicode symbols, for
+    /// consoles and logs without UTF-8
+    #[arg(long, global = true)]
+    pub ascii: bool,
+}
+
+impl Cli {
+    /// Output settings from the global flags
+    pub fn render_options(&self) -> RenderOptions {
+        RenderOptions {
+            max_lines: self.max_output_lines,
+            json_style: JsonStyle::from_compact(self.compact_json),
+            charset: Charset::from_ascii(self.ascii),
+            path_style: self.path_style,
+        }
+    }
 }
 
 #[derive(Subcommand)]
@@ -40,6 +79,29 @@ pub enum Commands {
         /// Output format (json, text)
         #[arg(short, long, default_value = "text")]
         format: String,
+        
+        /// Only include these comma-separated fields in JSON output (e.g. file_name,functions)
+        #[arg(long)]
+        fields: Option<String>,
+    },
+    
+    /// Build a token-bounded context pack for a file
+    Context {
+        /// Path to the project root
+        #[arg(short, long, default_value = ".")]
+        path: PathBuf,
+        
+        /// File to build the context pack for
+        #[arg(long)]
+        file: PathBuf,
+        
+        /// Maximum token budget
+        #[arg(long, default_value = "2000")]
+        max_tokens: usize,
+        
+        /// Output format (markdown, json; text prints markdown)
+        #[arg(short, long, value_enum, default_value_t = OutputFormat::Markdown)]
+        format: OutputFormat,
     },
     
     /// Show files changed since last analysis
@@ -66,6 +128,12 @@ pub enum Commands {
         /// Include health metrics
         #[arg(long)]
         include_health: bool,
+        
+        /// Report only the components, services, routes and scope violations
+        /// that changed since the overview taken at this commit, branch or tag.
+        /// Snapshots cover the whole project, so it cannot be combined with --focus
+        #[arg(long, conflicts_with = "focus")]
+        since: Option<String>,
     },
     
     /// Cache management commands
@@ -121,6 +189,65 @@ pub enum CacheCommands {
         #[arg(short, long, default_value = ".")]
         path: PathBuf,
     },
+    
+    /// Check cached entries against the files on disk
+    Validate {
+        /// Path to the project root
+        #[arg(short, long, default_value = ".")]
+        path: PathBuf,
+        
+        /// Exit with an error when any entry is stale or missing
+        #[arg(long)]
+        strict: bool,
+    },
+}
+
+/// Search type selected with `ml search --search-type`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum SearchKind {
+    #[default]
+    General,
+    /// Where a symbol is defined
+    Definition,
+    /// Call sites of a symbol
+    Usage,
+    /// Closest code by embedding similarity
+    Similar,
+    /// Documentation only
+    Documentation,
+}
+
+impl From<SearchKind> for SearchType {
+    fn from(kind: SearchKind) -> Self {
+        match kind {
+            SearchKind::General => SearchType::General,
+            SearchKind::Definition => SearchType::Definition,
+            SearchKind::Usage => SearchType::Usage,
+            SearchKind::Similar => SearchType::Similar,
+            SearchKind::Documentation => SearchType::Documentation,
+        }
+    }
+}
+
+/// What gets embedded, selected with `ml search --embedding-content`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum EmbeddingContentKind {
+    #[default]
+    Both,
+    /// Code only, without doc comments
+    CodeOnly,
+    /// Doc comments only
+    DocOnly,
+}
+
+impl From<EmbeddingContentKind> for EmbeddingContent {
+    fn from(kind: EmbeddingContentKind) -> Self {
+        match kind {
+            EmbeddingContentKind::Both => EmbeddingContent::Both,
+            EmbeddingContentKind::CodeOnly => EmbeddingContent::CodeOnly,
+            EmbeddingContentKind::DocOnly => EmbeddingContent::DocOnly,
+        }
+    }
 }
 
 #[derive(Subcommand)]
@@ -132,20 +259,24 @@ pub enum MLCommands {
         function: String,
         
         /// File path containing the function
-        #[arg(short, long)]
+        #[arg(long)]
         file: Option<PathBuf>,
         
         /// Enable AI-enhanced analysis
         #[arg(long)]
         ai_enhanced: bool,
         
-        /// Output format (json, text)
-        #[arg(long, default_value = "json")]
-        format: String,
+        /// Output format (json, text, markdown)
+        #[arg(long, value_enum, default_value_t = OutputFormat::Json)]
+        format: OutputFormat,
     },
     
     /// Impact analysis for code changes
     Impact {
+        /// Project whose analysis cache is used
+        #[arg(short, long, default_value = ".")]
+        path: PathBuf,
+        
         /// Changed file path
         #[arg(short, long)]
         changed_file: PathBuf,
@@ -158,9 +289,9 @@ pub enum MLCommands {
         #[arg(long)]
         ai_analysis: bool,
         
-        /// Output format (json, text)
-        #[arg(long, default_value = "json")]
-        format: String,
+        /// Output format (json, text, markdown)
+        #[arg(long, value_enum, default_value_t = OutputFormat::Json)]
+        format: OutputFormat,
     },
     
     /// Pattern detection and analysis
@@ -181,9 +312,13 @@ pub enum MLCommands {
         #[arg(long, default_value = "0.8")]
         min_similarity: f32,
         
-        /// Output format (json, text)
-        #[arg(long, default_value = "json")]
-        format: String,
+        /// Roll detected patterns up by directory, folding directories deeper than this
+        #[arg(long)]
+        group_depth: Option<usize>,
+        
+        /// Output format (json, text, markdown)
+        #[arg(long, value_enum, default_value_t = OutputFormat::Json)]
+        format: OutputFormat,
     },
     
     /// Semantic code search
@@ -208,9 +343,33 @@ pub enum MLCommands {
         #[arg(long, default_value = "10")]
         max_results: usize,
         
-        /// Output format (json, text)
-        #[arg(long, default_value = "json")]
-        format: String,
+        /// Drop results scoring below this relevance (0.0 to 1.0)
+        #[arg(long)]
+        min_relevance: Option<f32>,
+        
+        /// Include documentation (markdown files, file doc comments) in results
+        #[arg(long)]
+        include_docs: bool,
+        
+        /// Include test code (#[cfg(test)] modules, #[test] functions) when indexing and in results
+        #[arg(long)]
+        include_tests: bool,
+        
+        /// What the query looks for (general, definition, usage, similar, documentation)
+        #[arg(long, value_enum, default_value_t = SearchKind::General)]
+        search_type: SearchKind,
+        
+        /// What is embedded when indexing (both, code-only, doc-only); changing it rebuilds the index
+        #[arg(long, value_enum, default_value_t = EmbeddingContentKind::Both)]
+        embedding_content: EmbeddingContentKind,
+        
+        /// Output format (json, text, markdown)
+        #[arg(long, value_enum, default_value_t = OutputFormat::Json)]
+        format: OutputFormat,
+        
+        /// Only include these comma-separated fields of each result in JSON output (e.g. file,relevance)
+        #[arg(long)]
+        fields: Option<String>,
     },
     
     /// Token usage optimization
@@ -227,9 +386,9 @@ pub enum MLCommands {
         #[arg(long)]
         ai_enhanced: bool,
         
-        /// Output format (json, text)
-        #[arg(long, default_value = "json")]
-        format: String,
+        /// Output format (json, text, markdown)
+        #[arg(long, value_enum, default_value_t = OutputFormat::Json)]
+        format: OutputFormat,
     },
     
     /// Model management commands
@@ -237,6 +396,12 @@ pub enum MLCommands {
         #[command(subcommand)]
         action: ModelCommands,
     },
+    
+    /// ML cache management (vector index, response caches)
+    Cache {
+        #[command(subcommand)]
+        action: MLCacheCommands,
+    },
 }
 
 #[derive(Subcommand)]
@@ -271,4 +436,18 @@ pub enum ModelCommands {
     
     /// Clean model cache
     Clean,
-}
\ No newline at end of file
+}
+
+#[derive(Subcommand)]
+pub enum MLCacheCommands {
+    /// Remove the vector index and cached embedding/rerank responses
+    Clear {
+        /// Path to the project whose index is cleared
+        #[arg(short, long, default_value = ".")]
+        path: PathBuf,
+        
+        /// List what would be removed without deleting anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
@@ -1,17 +1,29 @@
 use anyhow::Result;
 use std::path::Path;
 use crate::cache::CacheManager;
+use crate::utils::path_normalizer::{PathNormalizer, PathStyle};
 
-pub fn run_analyze(path: &Path, force: bool, verbose: bool) -> Result<()> {
+pub fn run_analyze(path: &Path, force: bool, verbose: bool, path_style: PathStyle, focus: Option<&Path>) -> Result<()> {
     if verbose {
         println!("Starting analysis of project at: {}", path.display());
     }
     
-    let mut cache_manager = CacheManager::new(path)?;
+    let mut cache_manager = CacheManager::new_with_focus(path, focus)?;
     cache_manager.analyze_project(path, force)?;
     
     let stats = cache_manager.get_cache_stats();
     
+    if verbose {
+        let normalizer = PathNormalizer::new(path);
+        let mut files: Vec<String> = cache_manager.get_cache().entries.keys()
+            .map(|key| normalizer.format_path(key, path_style))
+            .collect();
+        files.sort();
+        for file in files {
+            println!("  {}", file);
+        }
+    }
+    
     println!("Analysis complete!");
     println!("- Files analyzed: {}", stats.total_entries);
     println!("- Total size: {:.2} MB", stats.total_size as f64 / 1024.0 / 1024.0);
@@ -65,4 +65,41 @@ pub fn run_cache_clear(path: &Path) -> Result<()> {
     println!("Cache cleared!");
     
     Ok(())
-}
\ No newline at end of file
+}
+
+pub fn run_cache_validate(path: &Path, strict: bool) -> Result<()> {
+    let cache_manager = CacheManager::new(path)?;
+    let validation = cache_manager.validate()?;
+    
+    println!("Cache Validation");
+    println!("================");
+    println!("Checked entries: {}", validation.checked);
+    if validation.merged > 0 {
+        println!("Merged entries (not checked): {}", validation.merged);
+    }
+    println!("Stale entries: {}", validation.stale.len());
+    for file in &validation.stale {
+        println!("  - {}", file);
+    }
+    println!("Missing files: {}", validation.missing.len());
+    for file in &validation.missing {
+        println!("  - {}", file);
+    }
+    println!("Unreadable files: {}", validation.unreadable.len());
+    for (file, error) in &validation.unreadable {
+        println!("  - {} ({})", file, error);
+    }
+    
+    if validation.is_valid() {
+        println!("\nCache is up to date");
+    } else if strict {
+        anyhow::bail!(
+            "Cache is out of date: {} stale, {} missing, {} unreadable (run `cache rebuild`)",
+            validation.stale.len(),
+            validation.missing.len(),
+            validation.unreadable.len()
+        );
+    }
+    
+    Ok(())
+}
@@ -1,10 +1,13 @@
 use anyhow::Result;
 use std::path::Path;
 use crate::analyzers::DiffAnalyzer;
+use crate::utils::path_normalizer::{PathNormalizer, PathStyle};
 
-pub fn run_changes(path: &Path, modified_only: bool) -> Result<()> {
+pub fn run_changes(path: &Path, modified_only: bool, path_style: PathStyle) -> Result<()> {
     let diff_analyzer = DiffAnalyzer::new(path)?;
     let changes = diff_analyzer.analyze_changes(path)?;
+    let normalizer = PathNormalizer::new(path);
+    let show = |file: &str| normalizer.format_path(file, path_style);
     
     println!("Change Analysis - Session: {}", changes.session_id);
     println!("Timestamp: {}", changes.timestamp.format("%Y-%m-%d %H:%M:%S"));
@@ -14,7 +17,7 @@ pub fn run_changes(path: &Path, modified_only: bool) -> Result<()> {
     if !changes.modified_files.is_empty() {
         println!("Modified Files:");
         for file in &changes.modified_files {
-            println!("  - {} ({:?})", file.path, file.change_type);
+            println!("  - {} ({:?})", show(&file.path), file.change_type);
             println!("    Lines: +{} -{}", file.lines_added, file.lines_removed);
             if !file.sections_changed.is_empty() {
                 println!("    Sections: {}", file.sections_changed.join(", "));
@@ -27,7 +30,7 @@ pub fn run_changes(path: &Path, modified_only: bool) -> Result<()> {
         if !changes.added_files.is_empty() {
             println!("Added Files:");
             for file in &changes.added_files {
-                println!("  + {}", file);
+                println!("  + {}", show(file));
             }
             println!();
         }
@@ -35,7 +38,7 @@ pub fn run_changes(path: &Path, modified_only: bool) -> Result<()> {
         if !changes.deleted_files.is_empty() {
             println!("Deleted Files:");
             for file in &changes.deleted_files {
-                println!("  - {}", file);
+                println!("  - {}", show(file));
             }
             println!();
         }
@@ -43,7 +46,7 @@ pub fn run_changes(path: &Path, modified_only: bool) -> Result<()> {
         if !changes.renamed_files.is_empty() {
             println!("Renamed Files:");
             for file in &changes.renamed_files {
-                println!("  {} -> {}", file.old_path, file.new_path);
+                println!("  {} -> {}", show(&file.old_path), show(&file.new_path));
             }
             println!();
         }
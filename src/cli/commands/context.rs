@@ -0,0 +1,33 @@
+use anyhow::Result;
+use std::path::Path;
+use crate::cache::CacheManager;
+use crate::generators::context_pack::ContextPackBuilder;
+use crate::cli::output::{OutputFormat, RenderOptions};
+
+pub fn run_context(path: &Path, file: &Path, max_tokens: usize, format: OutputFormat, options: RenderOptions) -> Result<()> {
+    let cache_manager = CacheManager::new(path)?;
+    let file_key = cache_manager.normalize_lookup_key(&file.to_string_lossy());
+
+    if cache_manager.get_cache().get_entry(&file_key).is_none() {
+        anyhow::bail!(
+            "File not found in cache: {} (run `token-optimizer analyze` first)",
+            file.display()
+        );
+    }
+
+    // Packs are Markdown documents; text output prints the same Markdown
+    let pack_format = match format {
+        OutputFormat::Json => "json",
+        OutputFormat::Text | OutputFormat::Markdown => "markdown",
+    };
+    let pack = ContextPackBuilder::new(cache_manager.get_cache())
+        .with_path_style(path, options.path_style)
+        .build(&file_key, max_tokens, pack_format)?;
+    let rendered = match format {
+        OutputFormat::Json => options.json_style.to_string(&pack)?,
+        OutputFormat::Text | OutputFormat::Markdown => pack.to_markdown(),
+    };
+    println!("{}", rendered);
+
+    Ok(())
+}
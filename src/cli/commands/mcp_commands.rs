@@ -4,6 +4,8 @@ use anyhow::Result;
 use clap::Args;
 
 use crate::mcp::MCPServer;
+use crate::utils::decoration::status;
+use crate::utils::path_normalizer::PathStyle;
 
 #[derive(Args)]
 pub struct MCPCommand {
@@ -14,15 +16,19 @@ pub struct MCPCommand {
     /// Enable debug logging
     #[clap(long)]
     pub debug: bool,
+    
+    /// How file paths are reported in tool results, from the global `--path-style`
+    #[clap(skip)]
+    pub path_style: PathStyle,
 }
 
 impl MCPCommand {
     pub async fn execute(&self) -> Result<()> {
         if self.debug {
-            println!("🔧 Debug mode enabled");
+            status!("🔧 Debug mode enabled");
         }
         
-        println!("🚀 Starting token-optimizer MCP Server...");
+        status!("🚀 Starting token-optimizer MCP Server...");
         println!("   Purpose: Provide smart context to Claude Code");
         println!("   Port: {}", self.port);
         println!("   This server solves the compactation pain point by providing");
@@ -30,14 +36,14 @@ impl MCPCommand {
         println!();
         
         // Initialize and start MCP server
-        let server = MCPServer::new().await?;
+        let server = MCPServer::new(self.path_style).await?;
         
-        println!("📋 Available MCP tools for Claude Code:");
-        println!("   • smart_context: Get optimized code context for queries");
-        println!("   • explore_codebase: Discover related files semantically");
+        status!("📋 Available MCP tools for Claude Code:");
+        status!("   • smart_context: Get optimized code context for queries");
+        status!("   • explore_codebase: Discover related files semantically");
         println!();
         
-        println!("💡 Usage in Claude Code:");
+        status!("💡 Usage in Claude Code:");
         println!("   Instead of loading entire files, Claude Code can now call:");
         println!("   - smart_context(\"authentication logic\", max_tokens=3000)");
         println!("   - explore_codebase(\"error handling patterns\")");
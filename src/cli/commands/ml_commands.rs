@@ -1,13 +1,25 @@
 //! ML command implementations
 
 use anyhow::Result;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
-use crate::ml::{MLConfig, MLService, PluginManager};
-use crate::ml::models::ModelDownloader;
+use crate::analyzers::AnalyzerLanguage;
+use crate::ml::{clear_ml_caches, MLConfig, MLService, PluginManager};
+use crate::ml::models::{AnalysisMode, ModelDownloader, PatternGroup, PatternGroupingOptions};
+use crate::ml::services::pattern::PatternDetectionService;
+use crate::ml::vector_db::VectorDBConfig;
+use crate::ml::vector_db::persistence::SourceHashes;
+use crate::ml::config::{EmbeddingContent, EmbeddingTemplate};
+use crate::ml::services::doc_index::{doc_comment_entry, is_doc_file, markdown_entries};
+use crate::ml::services::embedding_template::{embedded_source, function_signature, render_function, FIXED_INPUT_ID};
+use crate::ml::services::impact_analysis::{tests_to_run, DepGraph};
+use crate::cache::{CacheManager, FreshnessMode};
+use crate::cli::output::{apply_path_style, render, OutputFormat, RenderOptions};
+use super::ml_results::*;
+use crate::utils::decoration::{status, status_err};
 use crate::ml::services::enhanced_search::{
-    EnhancedSearchService, SearchRequest, SearchType, SearchFilters, SearchOptions, CodeIndexEntry, SearchServiceStats
+    EnhancedSearchService, SearchRequest, SearchType, SearchFilters, SearchOptions, CodeIndexEntry, apply_test_ranges
 };
 
 /// Run ML context analysis
@@ -15,175 +27,167 @@ pub async fn run_ml_context(
     function: &str,
     file: Option<&Path>,
     ai_enhanced: bool,
-    format: &str,
+    format: OutputFormat,
+    options: RenderOptions,
 ) -> Result<()> {
-    println!("🔍 Analyzing function context: {}", function);
+    status!("🔍 Analyzing function context: {}", function);
+    let mut result = ml_context_result(function, file, ai_enhanced, Arc::new(PluginManager::new())).await?;
+    if file.is_some() {
+        apply_path_style(&mut result, Path::new("."), options.path_style);
+    }
     
-    if ai_enhanced {
-        println!("🤖 AI-enhanced analysis enabled");
+    status_err!("{}", result.analysis_mode.banner());
+    render(&result, format, options, &mut std::io::stdout().lock())
+}
+
+/// Context analysis of `function`, with `plugin_manager` backing the ML service
+/// when `ai_enhanced` is set
+async fn ml_context_result(
+    function: &str,
+    file: Option<&Path>,
+    ai_enhanced: bool,
+    plugin_manager: Arc<PluginManager>,
+) -> Result<MlContextResult> {
+    let file_name = file.map(|p| p.display().to_string()).unwrap_or_else(|| "unknown".to_string());
+    
+    let result = if ai_enhanced {
+        status!("🤖 AI-enhanced analysis enabled");
         
         // Initialize ML service (basic example)
         let config = MLConfig::for_8gb_vram();
-        let plugin_manager = Arc::new(PluginManager::new());
         let mut ml_service = MLService::new(config, plugin_manager)?;
         
         // This would fail without actual models, but shows the structure
         match ml_service.initialize().await {
             Ok(_) => {
-                println!("✅ ML service initialized successfully");
+                status!("✅ ML service initialized successfully");
                 
                 // Here we would call the actual context analysis
                 // let context = ml_service.context_service().analyze_function_context(
                 //     function, file.map(|p| p.to_str().unwrap()).unwrap_or("unknown"), "// AST context"
                 // ).await?;
                 
-                println!("📊 Context analysis for function '{}':", function);
+                status!("📊 Context analysis for function '{}':", function);
                 if let Some(file_path) = file {
                     println!("   File: {}", file_path.display());
                 }
                 
-                let mock_result = format!(
-                    r#"{{
-    "function": "{}",
-    "file": "{}",
-    "ai_enhanced": {},
-    "analysis": {{
-        "complexity": "medium",
-        "dependencies": ["auth.service", "user.model"],
-        "impact_scope": "component",
-        "recommendations": ["Add error handling", "Consider memoization"]
-    }}
-}}"#,
-                    function,
-                    file.map(|p| p.to_str().unwrap()).unwrap_or("unknown"),
-                    ai_enhanced
-                );
-                
-                match format {
-                    "json" => println!("{}", mock_result),
-                    "text" => {
-                        println!("Function: {}", function);
-                        println!("Complexity: Medium");
-                        println!("Dependencies: auth.service, user.model");
-                        println!("Impact Scope: Component");
-                        println!("Recommendations:");
-                        println!("  - Add error handling");
-                        println!("  - Consider memoization");
-                    }
-                    _ => println!("Unsupported format: {}", format),
-                }
+                // The analysis below is not model output yet, so it is
+                // labelled static even when plugins loaded
+                let result = MlContextResult {
+                    analysis_mode: AnalysisMode::Static,
+                    function: function.to_string(),
+                    file: file_name,
+                    ai_enhanced,
+                    analysis: ContextAnalysis {
+                        complexity: "medium".to_string(),
+                        dependencies: vec!["auth.service".to_string(), "user.model".to_string()],
+                        impact_scope: "component".to_string(),
+                        recommendations: vec!["Add error handling".to_string(), "Consider memoization".to_string()],
+                    },
+                };
                 
                 ml_service.shutdown().await?;
+                result
             }
             Err(e) => {
-                println!("⚠️  ML service initialization failed: {}", e);
+                status!("⚠️  ML service initialization failed: {}", e);
                 println!("   Falling back to basic analysis...");
                 
                 // Basic AST analysis fallback
-                let mock_result = format!(
-                    r#"{{
-    "function": "{}",
-    "file": "{}",
-    "ai_enhanced": false,
-    "analysis": {{
-        "complexity": "unknown",
-        "dependencies": [],
-        "impact_scope": "local",
-        "recommendations": ["Run with --ai-enhanced for detailed analysis"]
-    }}
-}}"#,
-                    function,
-                    file.map(|p| p.to_str().unwrap()).unwrap_or("unknown")
-                );
-                
-                match format {
-                    "json" => println!("{}", mock_result),
-                    "text" => {
-                        println!("Function: {}", function);
-                        println!("Basic analysis only (ML models not available)");
-                        println!("Recommendation: Download models with `token-optimizer ml models download --all`");
-                    }
-                    _ => println!("Unsupported format: {}", format),
+                MlContextResult {
+                    analysis_mode: AnalysisMode::Static,
+                    function: function.to_string(),
+                    file: file_name,
+                    ai_enhanced: false,
+                    analysis: ContextAnalysis {
+                        complexity: "unknown".to_string(),
+                        dependencies: vec![],
+                        impact_scope: "local".to_string(),
+                        recommendations: vec![
+                            "Download models with `token-optimizer ml models download --all`".to_string(),
+                            "Run with --ai-enhanced for detailed analysis".to_string(),
+                        ],
+                    },
                 }
             }
         }
     } else {
-        println!("📊 Basic context analysis for function '{}':", function);
-        
-        let mock_result = format!(
-            r#"{{
-    "function": "{}",
-    "file": "{}",
-    "ai_enhanced": false,
-    "analysis": {{
-        "complexity": "medium",
-        "dependencies": [],
-        "impact_scope": "local",
-        "recommendations": ["Enable --ai-enhanced for detailed analysis"]
-    }}
-}}"#,
-            function,
-            file.map(|p| p.to_str().unwrap()).unwrap_or("unknown")
-        );
+        status!("📊 Basic context analysis for function '{}':", function);
         
-        match format {
-            "json" => println!("{}", mock_result),
-            "text" => {
-                println!("Function: {}", function);
-                println!("Basic analysis only");
-                println!("Recommendation: Use --ai-enhanced for detailed analysis");
-            }
-            _ => println!("Unsupported format: {}", format),
+        MlContextResult {
+            analysis_mode: AnalysisMode::Static,
+            function: function.to_string(),
+            file: file_name,
+            ai_enhanced: false,
+            analysis: ContextAnalysis {
+                complexity: "medium".to_string(),
+                dependencies: vec![],
+                impact_scope: "local".to_string(),
+                recommendations: vec!["Enable --ai-enhanced for detailed analysis".to_string()],
+            },
         }
-    }
+    };
     
-    Ok(())
+    Ok(result)
 }
 
 /// Run ML impact analysis
 pub async fn run_ml_impact(
+    path: &Path,
     changed_file: &Path,
     changed_functions: &[String],
     ai_analysis: bool,
-    format: &str,
+    format: OutputFormat,
+    options: RenderOptions,
 ) -> Result<()> {
-    println!("📈 Analyzing impact for: {}", changed_file.display());
+    status!("📈 Analyzing impact for: {}", changed_file.display());
     
     if ai_analysis {
-        println!("🤖 AI-enhanced impact analysis enabled");
-    }
-    
-    let mock_result = format!(
-        r#"{{
-    "changed_file": "{}",
-    "changed_functions": {:?},
-    "ai_analysis": {},
-    "impact": {{
-        "direct_impact": ["login.component.ts", "auth.guard.ts"],
-        "indirect_impact": ["dashboard.component.ts"],
-        "risk_level": "medium",
-        "tests_to_run": ["auth.service.spec.ts", "login.component.spec.ts"]
-    }}
-}}"#,
-        changed_file.display(),
-        changed_functions,
-        ai_analysis
-    );
-    
-    match format {
-        "json" => println!("{}", mock_result),
-        "text" => {
-            println!("Changed file: {}", changed_file.display());
-            println!("Changed functions: {:?}", changed_functions);
-            println!("Direct impact: login.component.ts, auth.guard.ts");
-            println!("Indirect impact: dashboard.component.ts");
-            println!("Risk level: Medium");
-            println!("Tests to run: auth.service.spec.ts, login.component.spec.ts");
-        }
-        _ => println!("Unsupported format: {}", format),
+        status!("🤖 AI-enhanced impact analysis enabled");
     }
     
-    Ok(())
+    let mut result = MlImpactResult {
+        analysis_mode: AnalysisMode::Static,
+        changed_file: changed_file.display().to_string(),
+        changed_functions: changed_functions.to_vec(),
+        ai_analysis,
+        impact: static_impact(path, changed_file)?,
+    };
+    apply_path_style(&mut result, path, options.path_style);
+    
+    status_err!("{}", result.analysis_mode.banner());
+    render(&result, format, options, &mut std::io::stdout().lock())
+}
+
+/// Files importing `changed_file` directly and through other files, and the
+/// spec files covering all of them, from the project's analysis cache. The
+/// risk level grows with the number of affected files.
+fn static_impact(project: &Path, changed_file: &Path) -> Result<ImpactReport> {
+    let mut cache_manager = CacheManager::new(project)?;
+    if cache_manager.get_cache().entries.is_empty() {
+        cache_manager.analyze_project(project, false)?;
+    }
+    let changed = cache_manager.normalize_lookup_key(&changed_file.to_string_lossy());
+    let entries = &cache_manager.get_cache().entries;
+    let graph = DepGraph::from_entries(entries);
+
+    let direct_impact: Vec<String> = graph.dependents(&changed).into_iter().map(str::to_string).collect();
+    let indirect_impact: Vec<String> = graph.transitive_dependents(&changed).into_iter()
+        .filter(|file| !direct_impact.contains(file))
+        .collect();
+    let risk_level = match direct_impact.len() + indirect_impact.len() {
+        0 => "low",
+        1..=5 => "medium",
+        _ => "high",
+    };
+
+    Ok(ImpactReport {
+        tests_to_run: tests_to_run(&[changed], &graph, entries),
+        direct_impact,
+        indirect_impact,
+        risk_level: risk_level.to_string(),
+    })
 }
 
 /// Run ML pattern detection
@@ -192,61 +196,64 @@ pub async fn run_ml_patterns(
     detect_duplicates: bool,
     ml_similarity: bool,
     min_similarity: f32,
-    format: &str,
+    group_depth: Option<usize>,
+    format: OutputFormat,
+    options: RenderOptions,
 ) -> Result<()> {
-    println!("🔍 Analyzing patterns in: {}", path.display());
+    status!("🔍 Analyzing patterns in: {}", path.display());
     
     if detect_duplicates {
-        println!("🔄 Duplicate detection enabled");
+        status!("🔄 Duplicate detection enabled");
     }
     
     if ml_similarity {
-        println!("🤖 ML similarity matching enabled (threshold: {:.2})", min_similarity);
-    }
-    
-    let mock_result = format!(
-        r#"{{
-    "path": "{}",
-    "detect_duplicates": {},
-    "ml_similarity": {},
-    "min_similarity": {},
-    "patterns": {{
-        "duplicates": [
-            {{"similarity": 0.95, "files": ["login.component.ts", "register.component.ts"]}},
-            {{"similarity": 0.89, "files": ["user.service.ts", "admin.service.ts"]}}
-        ],
-        "design_patterns": [
-            {{"pattern": "Observer", "files": ["event.service.ts"]}},
-            {{"pattern": "Singleton", "files": ["config.service.ts"]}}
-        ],
-        "anti_patterns": [
-            {{"pattern": "God Class", "files": ["dashboard.component.ts"]}}
-        ]
-    }}
-}}"#,
-        path.display(),
+        status!("🤖 ML similarity matching enabled (threshold: {:.2})", min_similarity);
+    }
+    
+    let groups = match group_depth {
+        Some(max_depth) => {
+            status!("📂 Grouping patterns by directory (depth {})", max_depth);
+            Some(pattern_groups(path, PatternGroupingOptions { max_depth, min_similarity }).await?)
+        }
+        None => None,
+    };
+    
+    let files = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+    let mut result = MlPatternsResult {
+        analysis_mode: AnalysisMode::Static,
+        path: path.display().to_string(),
         detect_duplicates,
         ml_similarity,
-        min_similarity
-    );
-    
-    match format {
-        "json" => println!("{}", mock_result),
-        "text" => {
-            println!("Pattern analysis for: {}", path.display());
-            println!("\nDuplicates found:");
-            println!("  - 95% similarity: login.component.ts, register.component.ts");
-            println!("  - 89% similarity: user.service.ts, admin.service.ts");
-            println!("\nDesign patterns:");
-            println!("  - Observer: event.service.ts");
-            println!("  - Singleton: config.service.ts");
-            println!("\nAnti-patterns:");
-            println!("  - God Class: dashboard.component.ts");
-        }
-        _ => println!("Unsupported format: {}", format),
-    }
+        min_similarity,
+        patterns: PatternReport {
+            duplicates: vec![
+                DuplicateGroup { similarity: 0.95, files: files(&["login.component.ts", "register.component.ts"]) },
+                DuplicateGroup { similarity: 0.89, files: files(&["user.service.ts", "admin.service.ts"]) },
+            ],
+            design_patterns: vec![
+                PatternMatch { pattern: "Observer".to_string(), files: files(&["event.service.ts"]) },
+                PatternMatch { pattern: "Singleton".to_string(), files: files(&["config.service.ts"]) },
+            ],
+            anti_patterns: vec![
+                PatternMatch { pattern: "God Class".to_string(), files: files(&["dashboard.component.ts"]) },
+            ],
+        },
+        groups,
+    };
+    apply_path_style(&mut result, path, options.path_style);
     
-    Ok(())
+    status_err!("{}", result.analysis_mode.banner());
+    render(&result, format, options, &mut std::io::stdout().lock())
+}
+
+/// Patterns detected under `path`, rolled up into a directory tree
+async fn pattern_groups(path: &Path, options: PatternGroupingOptions) -> Result<PatternGroup> {
+    let mut service = PatternDetectionService::new(MLConfig::for_8gb_vram(), Arc::new(PluginManager::new()))
+        .with_grouping(options);
+    service.initialize().await?;
+    let report = service.detect_patterns(&path.to_string_lossy()).await;
+    service.shutdown().await?;
+    report?.groups.ok_or_else(|| anyhow::anyhow!("Pattern detection returned no directory groups"))
 }
 
 /// Run ML semantic search
@@ -256,71 +263,62 @@ pub async fn run_ml_search(
     semantic: bool,
     include_context: bool,
     max_results: usize,
-    format: &str,
+    min_relevance: Option<f32>,
+    include_docs: bool,
+    include_tests: bool,
+    search_type: SearchType,
+    embedding_content: EmbeddingContent,
+    format: OutputFormat,
+    options: RenderOptions,
+    focus: Option<&Path>,
+    fields: &[String],
 ) -> Result<()> {
-    println!("🔍 Searching for: '{}'", query);
-    println!("📁 Path: {}", path.display());
+    status!("🔍 Searching for: '{}'", query);
+    status!("📁 Path: {}", path.display());
     
     if semantic {
-        println!("🤖 Semantic search enabled - using Qwen3-Embedding + Reranker pipeline");
+        status!("🤖 Semantic search enabled - using Qwen3-Embedding + Reranker pipeline");
         
         // Use real ML pipeline for semantic search
         
-        match run_real_semantic_search(query, path, include_context, max_results, format).await {
+        match run_real_semantic_search(query, path, include_context, max_results, min_relevance, include_docs, include_tests, search_type, embedding_content, format, options, focus, fields).await {
             Ok(_) => return Ok(()),
             Err(e) => {
-                println!("⚠️  ML semantic search failed: {}", e);
+                status!("⚠️  ML semantic search failed: {}", e);
                 println!("   Falling back to basic text matching...");
             }
         }
     }
     
     // Fallback to mock/basic search
-    println!("📝 Using basic search (no ML models loaded)");
-    let mock_result = format!(
-        r#"{{
-    "query": "{}",
-    "path": "{}",
-    "semantic": {},
-    "include_context": {},
-    "max_results": {},
-    "results": [
-        {{
-            "file": "auth.service.ts",
-            "relevance": 0.95,
-            "context": "Main authentication service handling login/logout",
-            "functions": ["login", "logout", "checkAuthStatus"]
-        }},
-        {{
-            "file": "auth.guard.ts",
-            "relevance": 0.87,
-            "context": "Route protection based on auth state",
-            "functions": ["canActivate"]
-        }}
-    ]
-}}"#,
-        query,
-        path.display(),
+    status!("📝 Using basic search (no ML models loaded)");
+    let mut result = MlSearchResult {
+        analysis_mode: AnalysisMode::Static,
+        query: query.to_string(),
+        path: path.display().to_string(),
         semantic,
         include_context,
-        max_results
-    );
-    
-    match format {
-        "json" => println!("{}", mock_result),
-        "text" => {
-            println!("Search results for: '{}'", query);
-            println!("\n1. auth.service.ts (95% relevance)");
-            println!("   Context: Main authentication service handling login/logout");
-            println!("   Functions: login, logout, checkAuthStatus");
-            println!("\n2. auth.guard.ts (87% relevance)");
-            println!("   Context: Route protection based on auth state"); 
-            println!("   Functions: canActivate");
-        }
-        _ => println!("Unsupported format: {}", format),
-    }
+        max_results,
+        fields: fields.to_vec(),
+        results: vec![
+            SearchHit {
+                file: "auth.service.ts".to_string(),
+                relevance: 0.95,
+                context: "Main authentication service handling login/logout".to_string(),
+                functions: vec!["login".to_string(), "logout".to_string(), "checkAuthStatus".to_string()],
+            },
+            SearchHit {
+                file: "auth.guard.ts".to_string(),
+                relevance: 0.87,
+                context: "Route protection based on auth state".to_string(),
+                functions: vec!["canActivate".to_string()],
+            },
+        ],
+    };
+    apply_path_style(&mut result, path, options.path_style);
     
-    Ok(())
+    status_err!("{}", result.analysis_mode.banner());
+    render(&result, format, options, &mut std::io::stdout().lock())
 }
 
 /// Real semantic search implementation using ML pipeline
@@ -329,43 +327,68 @@ async fn run_real_semantic_search(
     path: &Path,
     include_context: bool,
     max_results: usize,
-    format: &str,
+    min_relevance: Option<f32>,
+    include_docs: bool,
+    include_tests: bool,
+    search_type: SearchType,
+    embedding_content: EmbeddingContent,
+    format: OutputFormat,
+    options: RenderOptions,
+    focus: Option<&Path>,
+    fields: &[String],
 ) -> Result<()> {
-    println!("🚀 Initializing ML pipeline: Embedding → LSH → Reranker");
+    status!("🚀 Initializing ML pipeline: Embedding → LSH → Reranker");
     
     // Check if background indexing is running
     if is_background_indexing_active() {
-        println!("🔄 Background indexing service is currently running");
+        status!("🔄 Background indexing service is currently running");
         println!("   Monitor progress: journalctl --user -u claude-indexer@{} -f", std::env::var("USER").unwrap_or_else(|_| "user".to_string()));
         println!("   Check status: systemctl --user status claude-indexer@{}", std::env::var("USER").unwrap_or_else(|_| "user".to_string()));
         println!("");
-        println!("ℹ️  Will use current cache state for search. Results may be incomplete during indexing.");
+        status!("ℹ️  Will use current cache state for search. Results may be incomplete during indexing.");
         println!("");
     }
 
     // Initialize enhanced search service
-    let config = crate::ml::MLConfig::for_8gb_vram();
-    let search_service = EnhancedSearchService::new(config).await?;
+    let mut config = crate::ml::MLConfig::for_8gb_vram();
+    config.embedding_content = embedding_content;
+    let template = config.embedding_template;
+    let input_id = config.embedding_input_id();
+    let freshness = config.freshness;
+    let search_service = EnhancedSearchService::new(config, path).await?;
     
     // INTELLIGENT CACHE: Check freshness and completeness
     let stats = search_service.get_stats().await?;
-    let cache_is_fresh = is_cache_fresh(&stats)?;
+    let cache_is_fresh = is_cache_fresh(path, freshness, include_tests)?;
     let cache_is_complete = stats.total_indexed_entries >= 1500; // Expect ~1900+ entries for full coverage
-    
-    if stats.total_indexed_entries == 0 || !cache_is_fresh || !cache_is_complete {
+    let cache_matches_template = stats.embedding_templates.keys().all(|id| *id == input_id || id == FIXED_INPUT_ID);
+    
+    if let Some(focus) = focus {
+        // Unchanged functions keep their cached embeddings, so indexing just
+        // the focus directory is cheap
+        status!("🎯 Indexing focus directory {}...", focus.display());
+        let focus_files = create_expanded_dataset(path, include_tests, template, embedding_content, Some(focus))?;
+        let indexed_count = search_service.index_files(focus_files).await?;
+        save_source_hashes(path, include_tests, Some(focus))?;
+        status!("✅ Indexed {} code entries from {}", indexed_count, focus.display());
+    } else if stats.total_indexed_entries == 0 || !cache_is_fresh || !cache_is_complete || !cache_matches_template {
         if stats.total_indexed_entries == 0 {
-            println!("📂 No cached data found - indexing Rust code entries...");
+            status!("📂 No cached data found - indexing Rust code entries...");
         } else if !cache_is_fresh {
-            println!("🔄 Cache is stale - rebuilding index...");
+            status!("🔄 Cache is stale - rebuilding index...");
+        } else if !cache_matches_template {
+            status!("🔄 Cache was built with other embedding templates ({:?}) - rebuilding index with '{}'...",
+                    stats.embedding_templates.keys().collect::<Vec<_>>(), input_id);
         } else if !cache_is_complete {
-            println!("📈 Cache incomplete ({} entries) - expanding index...", stats.total_indexed_entries);
+            status!("📈 Cache incomplete ({} entries) - expanding index...", stats.total_indexed_entries);
         }
         
-        let demo_entries = create_expanded_dataset()?;
-        let indexed_count = search_service.index_code(demo_entries).await?;
-        println!("✅ Indexed {} code entries (cached for future searches)", indexed_count);
+        let project_files = create_expanded_dataset(path, include_tests, template, embedding_content, None)?;
+        let indexed_count = search_service.index_files(project_files).await?;
+        save_source_hashes(path, include_tests, None)?;
+        status!("✅ Indexed {} code entries (cached for future searches)", indexed_count);
     } else {
-        println!("🚀 Using cached index with {} entries ({} files)", 
+        status!("🚀 Using cached index with {} entries ({} files)", 
                 stats.total_indexed_entries, stats.total_files);
         println!("   Cache hit rate - Embedding: {:.1}%, Rerank: {:.1}%", 
                 stats.embedding_cache_hit_rate * 100.0, 
@@ -375,96 +398,73 @@ async fn run_real_semantic_search(
     // Create search request
     let search_request = SearchRequest {
         query: query.to_string(),
-        search_type: SearchType::General,
-        filters: SearchFilters::default(),
+        search_type,
+        filters: SearchFilters {
+            include_docs,
+            include_tests,
+            file_patterns: focus.map(|focus| vec![focus_prefix(path, focus)]),
+            ..SearchFilters::default()
+        },
         options: SearchOptions {
             max_results,
             include_metadata: include_context,
-            explain_ranking: format == "json",
+            explain_ranking: format == OutputFormat::Json,
             use_cache: true,
+            min_relevance,
+            ..SearchOptions::default()
         },
     };
     
-    println!("🔄 Executing semantic search...");
+    status!("🔄 Executing semantic search...");
     let search_start = std::time::Instant::now();
     
     // Perform search
     let response = search_service.search(search_request).await?;
     let search_time = search_start.elapsed();
     
-    println!("✅ Search completed in {:?}", search_time);
-    println!("📊 Found {} results from {} candidates", 
+    status!("✅ Search completed in {:?}", search_time);
+    status!("📊 Found {} results from {} candidates", 
              response.results.len(), response.total_candidates);
     
     // Format output
-    match format {
-        "json" => {
-            let json_output = serde_json::json!({
-                "query": query,
-                "path": path.to_string_lossy(),
-                "semantic": true,
-                "include_context": include_context,
-                "max_results": max_results,
-                "search_time_ms": response.search_time_ms,
-                "total_candidates": response.total_candidates,
-                "results": response.results.iter().map(|r| {
-                    serde_json::json!({
-                        "file": r.entry.metadata.file_path,
-                        "relevance": r.rerank_score,
-                        "context": r.entry.metadata.function_name.as_ref().unwrap_or(&"".to_string()),
-                        "match_type": format!("{:?}", r.entry.metadata.code_type),
-                        "line_range": [r.entry.metadata.line_start, r.entry.metadata.line_end],
-                        "language": r.entry.metadata.language,
-                        "complexity": r.entry.metadata.complexity,
-                        "embedding_similarity": r.embedding_similarity,
-                        "combined_score": r.combined_score,
-                        "confidence": r.confidence
-                    })
-                }).collect::<Vec<_>>(),
-                "explanation": response.explanation,
-                "suggestions": response.suggestions
-            });
-            println!("{}", serde_json::to_string_pretty(&json_output)?);
-        }
-        "text" => {
-            println!("🔍 Semantic search results for: '{}'", query);
-            println!("⚡ Pipeline: Qwen3-Embedding → LSH → Qwen3-Reranker");
-            println!("⏱️  Search time: {}ms", response.search_time_ms);
-            println!();
-            
-            for (idx, result) in response.results.iter().enumerate() {
-                println!("{}. {} ({:.1}% relevance)", 
-                         idx + 1, result.entry.metadata.file_path, result.rerank_score * 100.0);
-                
-                if let Some(function_name) = &result.entry.metadata.function_name {
-                    println!("   Function: {}", function_name);
-                }
-                
-                println!("   Lines: {}-{}", result.entry.metadata.line_start, result.entry.metadata.line_end);
-                println!("   Language: {}", result.entry.metadata.language);
-                println!("   Code type: {:?}", result.entry.metadata.code_type);
-                println!("   Complexity: {:.2}", result.entry.metadata.complexity);
-                println!("   Embedding similarity: {:.3}", result.embedding_similarity);
-                println!("   Combined score: {:.3}", result.combined_score);
-                println!("   Confidence: {:.3}", result.confidence);
-                println!();
-            }
-            
-            if let Some(explanation) = &response.explanation {
-                println!("💡 Ranking explanation: {}", explanation);
-            }
-            
-            if !response.suggestions.is_empty() {
-                println!("🔍 Suggestions:");
-                for suggestion in &response.suggestions {
-                    println!("  - {}", suggestion);
-                }
-            }
-        }
-        _ => println!("Unsupported format: {}", format),
-    }
+    let mut result = SemanticSearchResult {
+        analysis_mode: search_service.analysis_mode(),
+        query: query.to_string(),
+        path: path.to_string_lossy().to_string(),
+        semantic: true,
+        include_context,
+        max_results,
+        search_time_ms: response.search_time_ms,
+        total_candidates: response.total_candidates,
+        no_confident_matches: response.no_confident_matches(),
+        fields: fields.to_vec(),
+        results: response.results.iter().map(|r| SemanticSearchHit {
+            file: r.entry.metadata.file_path.clone(),
+            relevance: r.rerank_score,
+            context: r.entry.metadata.function_name.clone().unwrap_or_default(),
+            match_type: format!("{:?}", r.entry.metadata.code_type),
+            line_range: [r.entry.metadata.line_start, r.entry.metadata.line_end],
+            language: r.entry.metadata.language.clone(),
+            complexity: r.entry.metadata.complexity,
+            embedding_similarity: r.embedding_similarity,
+            combined_score: r.combined_score,
+            confidence: r.confidence,
+        }).collect(),
+        low_confidence_match: response.low_confidence_match.as_ref().map(|r| LowConfidenceMatch {
+            file: r.entry.metadata.file_path.clone(),
+            context: r.entry.metadata.function_name.clone().unwrap_or_default(),
+            line_range: [r.entry.metadata.line_start, r.entry.metadata.line_end],
+            combined_score: r.combined_score,
+            confidence: r.confidence,
+            low_confidence: true,
+        }),
+        explanation: response.explanation,
+        suggestions: response.suggestions,
+    };
+    apply_path_style(&mut result, path, options.path_style);
     
-    Ok(())
+    status_err!("{}", result.analysis_mode.banner());
+    render(&result, format, options, &mut std::io::stdout().lock())
 }
 
 /// Run ML token optimization
@@ -472,74 +472,58 @@ pub async fn run_ml_optimize(
     task: &str,
     max_tokens: usize,
     ai_enhanced: bool,
-    format: &str,
+    format: OutputFormat,
+    options: RenderOptions,
 ) -> Result<()> {
-    println!("⚡ Optimizing tokens for task: '{}'", task);
-    println!("📊 Token budget: {}", max_tokens);
+    status!("⚡ Optimizing tokens for task: '{}'", task);
+    status!("📊 Token budget: {}", max_tokens);
     
     if ai_enhanced {
-        println!("🤖 AI-enhanced optimization enabled");
-    }
-    
-    let mock_result = format!(
-        r#"{{
-    "task": "{}",
-    "token_budget": {},
-    "ai_enhanced": {},
-    "optimization": {{
-        "recommended_files": [
-            {{"file": "auth.service.ts", "priority": "critical", "estimated_tokens": 800}},
-            {{"file": "login.component.ts", "priority": "high", "estimated_tokens": 600}}
-        ],
-        "excluded_files": ["dashboard.component.ts", "profile.component.ts"],
-        "total_estimated": 1400,
-        "optimization_ratio": 0.85
-    }}
-}}"#,
-        task,
-        max_tokens,
-        ai_enhanced
-    );
-    
-    match format {
-        "json" => println!("{}", mock_result),
-        "text" => {
-            println!("Token optimization for: '{}'", task);
-            println!("Budget: {} tokens", max_tokens);
-            println!("\nRecommended files:");
-            println!("  - auth.service.ts (critical, ~800 tokens)");
-            println!("  - login.component.ts (high, ~600 tokens)");
-            println!("\nExcluded files: dashboard.component.ts, profile.component.ts");
-            println!("Total estimated: 1400 tokens");
-            println!("Optimization ratio: 85%");
-        }
-        _ => println!("Unsupported format: {}", format),
+        status!("🤖 AI-enhanced optimization enabled");
     }
     
-    Ok(())
+    let mut result = MlOptimizeResult {
+        analysis_mode: AnalysisMode::Static,
+        task: task.to_string(),
+        token_budget: max_tokens,
+        ai_enhanced,
+        optimization: OptimizationPlan {
+            recommended_files: vec![
+                RecommendedFile { file: "auth.service.ts".to_string(), priority: "critical".to_string(), estimated_tokens: 800 },
+                RecommendedFile { file: "login.component.ts".to_string(), priority: "high".to_string(), estimated_tokens: 600 },
+            ],
+            excluded_files: vec!["dashboard.component.ts".to_string(), "profile.component.ts".to_string()],
+            total_estimated: 1400,
+            optimization_ratio: 0.85,
+        },
+    };
+    apply_path_style(&mut result, Path::new("."), options.path_style);
+    
+    status_err!("{}", result.analysis_mode.banner());
+    render(&result, format, options, &mut std::io::stdout().lock())
 }
 
 /// List available models
 pub async fn run_model_list(local_only: bool) -> Result<()> {
-    println!("📦 Available models:");
+    status!("📦 Available models:");
     
     let config = MLConfig::for_8gb_vram();
     let downloader = ModelDownloader::new(config);
     
     if local_only {
-        println!("🔍 Checking local models...");
+        status!("🔍 Checking local models...");
         let local_models = downloader.check_local_models();
         
         for (name, available) in local_models {
             let status = if available { "✅ Available" } else { "❌ Not downloaded" };
-            println!("  {} - {}", name, status);
+            status!("  {} - {}", name, status);
         }
     } else {
-        println!("🌐 All available models:");
+        status!("🌐 All available models:");
         let models = downloader.get_available_models();
         
         for model in models {
-            println!("  📄 {}", model.name);
+            status!("  📄 {}", model.name);
             println!("     Size: {:.1}GB", model.size_gb);
             println!("     Description: {}", model.description);
             println!("     Filename: {}", model.filename);
@@ -556,18 +540,18 @@ pub async fn run_model_download(model: Option<&str>, all: bool) -> Result<()> {
     let downloader = ModelDownloader::new(config);
     
     if all {
-        println!("📥 Downloading all models...");
+        status!("📥 Downloading all models...");
         let paths = downloader.download_all_models().await?;
         
         for path in paths {
-            println!("✅ Downloaded: {}", path.display());
+            status!("✅ Downloaded: {}", path.display());
         }
     } else if let Some(model_name) = model {
-        println!("📥 Downloading model: {}", model_name);
+        status!("📥 Downloading model: {}", model_name);
         let path = downloader.download_model(model_name).await?;
-        println!("✅ Downloaded: {}", path.display());
+        status!("✅ Downloaded: {}", path.display());
     } else {
-        println!("❌ Error: Please specify a model name or use --all");
+        status!("❌ Error: Please specify a model name or use --all");
         println!("   Example: token-optimizer ml models download --model deepseek-r1");
         println!("   Or: token-optimizer ml models download --all");
     }
@@ -577,20 +561,20 @@ pub async fn run_model_download(model: Option<&str>, all: bool) -> Result<()> {
 
 /// Delete model from cache
 pub async fn run_model_delete(model: &str) -> Result<()> {
-    println!("🗑️  Deleting model: {}", model);
+    status!("🗑️  Deleting model: {}", model);
     
     let config = MLConfig::for_8gb_vram();
     let downloader = ModelDownloader::new(config);
     
     downloader.delete_model(model)?;
-    println!("✅ Model deleted: {}", model);
+    status!("✅ Model deleted: {}", model);
     
     Ok(())
 }
 
 /// Show model cache status
 pub async fn run_model_status() -> Result<()> {
-    println!("📊 Model cache status:");
+    status!("📊 Model cache status:");
     
     let config = MLConfig::for_8gb_vram();
     let downloader = ModelDownloader::new(config.clone());
@@ -607,7 +591,7 @@ pub async fn run_model_status() -> Result<()> {
     println!("   Local models:");
     for (name, available) in local_models {
         let status = if available { "✅ Available" } else { "❌ Not downloaded" };
-        println!("     {} - {}", name, status);
+        status!("     {} - {}", name, status);
     }
     
     Ok(())
@@ -615,13 +599,39 @@ pub async fn run_model_status() -> Result<()> {
 
 /// Clean model cache
 pub async fn run_model_clean() -> Result<()> {
-    println!("🧹 Cleaning model cache...");
+    status!("🧹 Cleaning model cache...");
     
     let config = MLConfig::for_8gb_vram();
     let downloader = ModelDownloader::new(config);
     
     downloader.clean_cache()?;
-    println!("✅ Model cache cleaned");
+    status!("✅ Model cache cleaned");
+    
+    Ok(())
+}
+
+/// Clear the vector index and ML response caches
+pub fn run_ml_cache_clear(path: &Path, dry_run: bool) -> Result<()> {
+    let config = MLConfig::for_8gb_vram();
+    let vector_cache_dir = std::path::PathBuf::from(VectorDBConfig::for_project(path).cache_dir);
+    let response_cache_dir = config.model_cache_dir.join("ml-cache");
+    
+    let report = clear_ml_caches(&vector_cache_dir, &response_cache_dir, dry_run)?;
+    
+    if report.paths.is_empty() {
+        status!("✅ No ML caches to clear");
+        return Ok(());
+    }
+    
+    status!("{}", if dry_run { "🔎 Would remove:" } else { "🧹 Removed:" });
+    for path in &report.paths {
+        println!("  {}", path.display());
+    }
+    status!("📊 {} indexed vectors, {} cached responses", report.vector_entries, report.response_entries);
+    
+    if !dry_run {
+        status!("✅ ML caches cleared");
+    }
     
     Ok(())
 }
@@ -644,118 +654,170 @@ fn is_background_indexing_active() -> bool {
     }
 }
 
-/// Check if cache is fresh by comparing file modification times
-fn is_cache_fresh(_stats: &SearchServiceStats) -> Result<bool> {
-    use walkdir::WalkDir;
-    
-    // Check if .cache/vector-db directory exists
-    let cache_dir = std::path::Path::new(".cache/vector-db");
-    if !cache_dir.exists() {
+/// Check if the index still matches the project's Rust sources, judged by
+/// `mode` against the hashes saved with the index, and was built with the
+/// same `include_tests`. An index without hashes, e.g. one built by an older
+/// version, counts as stale.
+fn is_cache_fresh(project: &Path, mode: FreshnessMode, include_tests: bool) -> Result<bool> {
+    let cache_dir = PathBuf::from(VectorDBConfig::for_project(project).cache_dir);
+    let Some(hashes) = SourceHashes::load(&cache_dir).ok().flatten() else {
+        return Ok(false);
+    };
+    if hashes.include_tests != include_tests {
         return Ok(false);
     }
-    
-    // Get cache creation time from vectors.json
-    let cache_time = match std::fs::metadata(cache_dir.join("vectors.json")) {
-        Ok(metadata) => match metadata.modified() {
-            Ok(time) => time,
-            Err(_) => return Ok(false),
-        },
-        Err(_) => return Ok(false),
+    let project_root = project.canonicalize()?;
+    hashes.is_fresh(&project_root, &rust_source_files(&project_root, Path::new("src")), mode)
+}
+
+/// Saves the hashes of the indexed Rust files with the project's index.
+/// Indexing a focus directory updates just the hashes of its files, unless
+/// the rest was indexed with another `include_tests`: then only the focus
+/// files are recorded, so the next full search rebuilds.
+fn save_source_hashes(project: &Path, include_tests: bool, focus: Option<&Path>) -> Result<()> {
+    let cache_dir = PathBuf::from(VectorDBConfig::for_project(project).cache_dir);
+    let project_root = project.canonicalize()?;
+    let mut hashes = match focus {
+        Some(_) => SourceHashes::load(&cache_dir).ok().flatten()
+            .filter(|hashes| hashes.include_tests == include_tests)
+            .unwrap_or_default(),
+        None => SourceHashes::default(),
     };
-    
-    // Check if any Rust source files were modified after cache creation
-    for entry in WalkDir::new("src")
+    hashes.include_tests = include_tests;
+    hashes.record(&project_root, &rust_source_files(&project_root, focus.unwrap_or(Path::new("src"))))?;
+    hashes.save(&cache_dir)
+}
+
+/// Project-relative paths of the Rust files under `dir`
+fn rust_source_files(project_root: &Path, dir: &Path) -> Vec<String> {
+    use walkdir::WalkDir;
+
+    WalkDir::new(project_root.join(dir))
+        .follow_links(false)
         .into_iter()
-        .filter_entry(|e| {
-            e.path().extension().map_or(false, |ext| ext == "rs")
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().extension().is_some_and(|ext| ext == "rs"))
+        .map(|e| {
+            e.path().strip_prefix(project_root)
+                .unwrap_or(e.path())
+                .to_string_lossy()
+                .replace('\\', "/")
         })
-    {
-        let entry = match entry {
-            Ok(e) => e,
-            Err(_) => continue,
-        };
-        
-        if let Ok(metadata) = entry.metadata() {
-            if let Ok(modified_time) = metadata.modified() {
-                if modified_time > cache_time {
-                    // Found a file modified after cache - cache is stale
-                    return Ok(false);
-                }
-            }
-        }
-    }
-    
-    // All source files are older than cache - cache is fresh
-    Ok(true)
+        .collect()
 }
 
-/// Create expanded dataset from current Rust project with AST-aware precision
-fn create_expanded_dataset() -> Result<Vec<CodeIndexEntry>> {
+/// `focus` as a prefix of indexed file paths, which are relative to
+/// `project`; an absolute focus inside the project is made relative first
+fn focus_prefix(project: &Path, focus: &Path) -> String {
+    let project_root = project.canonicalize().unwrap_or_else(|_| project.to_path_buf());
+    let focus = focus.strip_prefix(&project_root)
+        .or_else(|_| focus.strip_prefix(project))
+        .unwrap_or(focus)
+        .to_string_lossy()
+        .replace('\\', "/");
+    format!("{}/", focus.trim_start_matches("./").trim_end_matches('/'))
+}
+
+/// Create expanded dataset from current Rust project with AST-aware precision.
+/// Test code is left out unless `include_tests` is set; function entries use
+/// `template`, with `embedding_content` applied to each function's source.
+/// Indexes the Rust files under the project's `src`, or under `focus` when one
+/// is given. Files are analyzed lazily, one per item, as indexing pulls them.
+fn create_expanded_dataset(
+    project: &Path,
+    include_tests: bool,
+    template: EmbeddingTemplate,
+    embedding_content: EmbeddingContent,
+    focus: Option<&Path>,
+) -> Result<impl Iterator<Item = Vec<CodeIndexEntry>>> {
     use std::fs;
     use walkdir::WalkDir;
     use crate::analyzers::rust_analyzer::RustAnalyzer;
     
-    let mut entries = Vec::new();
-    let project_root = std::env::current_dir()?;
+    let project_root = project.canonicalize()?;
     let mut rust_analyzer = RustAnalyzer::new()?;
     
-    println!("🧠 Creating precision-optimized dataset using AST analysis...");
+    status!("🧠 Creating precision-optimized dataset using AST analysis...");
     
-    // Walk through src directory and find Rust files
-    for entry in WalkDir::new(project_root.join("src"))
-        .follow_links(false)
-        .into_iter()
+    // Markdown documentation in the project root and docs/
+    let doc_files: Vec<PathBuf> = fs::read_dir(&project_root)?
         .filter_map(|e| e.ok())
-        .filter(|e| e.path().extension().map_or(false, |ext| ext == "rs"))
-        // Process entire codebase for comprehensive coverage
-    {
-        let path = entry.path();
-        let relative_path = path.strip_prefix(&project_root)
-            .unwrap_or(path)
-            .to_string_lossy()
-            .replace('\\', "/");
+        .map(|e| e.path())
+        .filter(|p| p.is_file())
+        .chain(
+            WalkDir::new(project_root.join("docs"))
+                .into_iter()
+                .filter_map(|e| e.ok())
+                .map(|e| e.into_path())
+        )
+        .filter(|p| is_doc_file(p))
+        .collect();
+    
+    // Walk through src directory and find Rust files
+    let source_files = rust_source_files(&project_root, focus.unwrap_or(Path::new("src")));
+    let root = project_root.clone();
+    let sources = source_files.into_iter().filter_map(move |relative_path| {
+        let path = &root.join(&relative_path);
         
         // Read file content
-        if let Ok(content) = fs::read_to_string(path) {
-            println!("🔍 Analyzing {} with AST precision...", relative_path);
-            
-            // CRITICAL: Extract actual function bodies with full context
-            match rust_analyzer.analyze_file(path, &content) {
-                Ok(file_metadata) => {
-                    // Extract real function bodies with semantic context
-                    let function_bodies = extract_function_bodies_with_context(&file_metadata, &content, &relative_path);
-                    println!("  ✅ Extracted {} function bodies with full context", function_bodies.len());
-                    entries.extend(function_bodies);
-                    
-                    // Extract error handling patterns
-                    let error_patterns = extract_error_handling_patterns(&content, &relative_path);
-                    println!("  ✅ Extracted {} error handling patterns", error_patterns.len());
-                    entries.extend(error_patterns);
-                    
-                    // Extract algorithm implementations
-                    let algorithms = extract_algorithm_implementations(&content, &relative_path);
-                    println!("  ✅ Extracted {} algorithm implementations", algorithms.len());
-                    entries.extend(algorithms);
-                }
-                Err(e) => {
-                    println!("  ⚠️  AST analysis failed, using regex extraction: {}", e);
-                    // Still extract function bodies, not just metadata
-                    let function_bodies = extract_function_bodies_regex(&content, &relative_path);
-                    entries.extend(function_bodies);
-                }
+        let content = fs::read_to_string(path).ok()?;
+        status!("🔍 Analyzing {} with AST precision...", relative_path);
+        
+        // Module-level doc comment, searchable with --include-docs
+        let mut entries: Vec<CodeIndexEntry> = doc_comment_entry(&relative_path, &content).into_iter().collect();
+        
+        // CRITICAL: Extract actual function bodies with full context
+        let mut file_entries = Vec::new();
+        match rust_analyzer.analyze_file(path, &content) {
+            Ok(file_metadata) => {
+                // Extract real function bodies with semantic context
+                let function_bodies = extract_function_bodies_with_context(&file_metadata, &content, &relative_path, template, embedding_content);
+                status!("  ✅ Extracted {} function bodies with full context", function_bodies.len());
+                file_entries.extend(function_bodies);
+                
+                // Extract error handling patterns
+                let error_patterns = extract_error_handling_patterns(&content, &relative_path);
+                status!("  ✅ Extracted {} error handling patterns", error_patterns.len());
+                file_entries.extend(error_patterns);
+                
+                // Extract algorithm implementations
+                let algorithms = extract_algorithm_implementations(&content, &relative_path);
+                status!("  ✅ Extracted {} algorithm implementations", algorithms.len());
+                file_entries.extend(algorithms);
+            }
+            Err(e) => {
+                status!("  ⚠️  AST analysis failed, using regex extraction: {}", e);
+                // Still extract function bodies, not just metadata
+                let function_bodies = extract_function_bodies_regex(&content, &relative_path);
+                file_entries.extend(function_bodies);
             }
         }
-    }
+        
+        // Skip or tag #[cfg(test)] modules and #[test] functions
+        let test_ranges = rust_analyzer.test_line_ranges(&content);
+        entries.extend(apply_test_ranges(file_entries, &test_ranges, include_tests));
+        Some(entries)
+    });
+    
+    let docs = doc_files.into_iter().filter_map(move |path| {
+        let content = fs::read_to_string(&path).ok()?;
+        let relative_path = path.strip_prefix(&project_root)
+            .unwrap_or(&path)
+            .to_string_lossy()
+            .replace('\\', "/");
+        Some(markdown_entries(&relative_path, &content))
+    });
     
-    println!("🎯 Created precision dataset: {} AST-enhanced entries", entries.len());
-    Ok(entries)
+    Ok(sources.chain(docs))
 }
 
 /// Extract actual function bodies with full semantic context for REAL utility
 fn extract_function_bodies_with_context(
     file_metadata: &crate::types::FileMetadata, 
     content: &str, 
-    file_path: &str
+    file_path: &str,
+    template: EmbeddingTemplate,
+    embedding_content: EmbeddingContent,
 ) -> Vec<CodeIndexEntry> {
     let mut entries = Vec::new();
     
@@ -763,21 +825,26 @@ fn extract_function_bodies_with_context(
     if let Some(detailed_analysis) = &file_metadata.detailed_analysis {
         if let Some(rust_module) = &detailed_analysis.rust_module {
             
-            // 1. Extract COMPLETE function bodies with full context
-            for function in &rust_module.functions {
+            // 1. Extract COMPLETE function bodies with full context. The
+            // analyzer reports free functions on the analysis, not the module
+            for function in &detailed_analysis.functions {
                 // Get the actual function body code
                 let function_body = extract_complete_function_body(&function.name, content);
                 if function_body.len() < 20 { // Skip trivial functions
                     continue;
                 }
                 
-                // Create rich semantic context with ACTUAL CODE
-                let semantic_content = create_function_body_semantic_content(
-                    function, 
-                    &function_body, 
-                    file_metadata, 
-                    content
-                );
+                // Create rich semantic context with ACTUAL CODE, unless the
+                // configured template asks for less
+                let semantic_content = render_function(template, function, || {
+                    let source = extract_function_source(function, content);
+                    create_function_body_semantic_content(
+                        function, 
+                        &embedded_source(embedding_content, &source, AnalyzerLanguage::Rust), 
+                        file_metadata, 
+                        content
+                    )
+                });
                 
                 let complexity = calculate_function_complexity(function, content);
                 
@@ -790,6 +857,7 @@ fn extract_function_bodies_with_context(
                     language: "rust".to_string(),
                     complexity,
                     content: semantic_content,
+                    embedding_template: template.input_id(embedding_content),
                 });
             }
             
@@ -806,6 +874,7 @@ fn extract_function_bodies_with_context(
                     language: "rust".to_string(),
                     complexity: 1.5 + (struct_info.fields.len() as f32 * 0.2),
                     content: semantic_content,
+                    embedding_template: FIXED_INPUT_ID.to_string(),
                 });
             }
             
@@ -822,6 +891,7 @@ fn extract_function_bodies_with_context(
                     language: "rust".to_string(),
                     complexity: 2.0 + (impl_block.methods.len() as f32 * 0.5),
                     content: semantic_content,
+                    embedding_template: FIXED_INPUT_ID.to_string(),
                 });
             }
             
@@ -838,6 +908,7 @@ fn extract_function_bodies_with_context(
                     language: "rust".to_string(),
                     complexity: 1.8 + (trait_info.methods.len() as f32 * 0.3),
                     content: semantic_content,
+                    embedding_template: FIXED_INPUT_ID.to_string(),
                 });
             }
         }
@@ -1310,6 +1381,7 @@ fn extract_code_snippets(content: &str, file_path: &str) -> Vec<CodeIndexEntry>
                     language: "rust".to_string(),
                     complexity,
                     content: snippet,
+                    embedding_template: FIXED_INPUT_ID.to_string(),
                 });
             }
         }
@@ -1325,6 +1397,7 @@ fn extract_code_snippets(content: &str, file_path: &str) -> Vec<CodeIndexEntry>
                     language: "rust".to_string(),
                     complexity: 1.5,
                     content: snippet,
+                    embedding_template: FIXED_INPUT_ID.to_string(),
                 });
             }
         }
@@ -1340,6 +1413,7 @@ fn extract_code_snippets(content: &str, file_path: &str) -> Vec<CodeIndexEntry>
                     language: "rust".to_string(),
                     complexity: 2.0,
                     content: snippet,
+                    embedding_template: FIXED_INPUT_ID.to_string(),
                 });
             }
         }
@@ -1355,6 +1429,7 @@ fn extract_code_snippets(content: &str, file_path: &str) -> Vec<CodeIndexEntry>
                     language: "rust".to_string(),
                     complexity: 1.2,
                     content: snippet,
+                    embedding_template: FIXED_INPUT_ID.to_string(),
                 });
             }
         }
@@ -1652,6 +1727,41 @@ fn extract_complete_function_body(function_name: &str, content: &str) -> String
     body_lines.join("\n")
 }
 
+/// Source of `function` from its declaration to its closing brace, with the
+/// comments and attributes directly above it
+fn extract_function_source(function: &crate::types::FunctionInfo, content: &str) -> String {
+    let lines: Vec<&str> = content.lines().collect();
+    let declaration = function.location.line.saturating_sub(1);
+    if declaration >= lines.len() {
+        return String::new();
+    }
+    
+    let mut start = declaration;
+    while start > 0 {
+        let previous = lines[start - 1].trim_start();
+        if previous.starts_with("//") || previous.starts_with("/*") || previous.starts_with('*') || previous.starts_with("#[") {
+            start -= 1;
+        } else {
+            break;
+        }
+    }
+    
+    let mut end = declaration;
+    let mut depth = 0;
+    let mut opened = false;
+    for (line_idx, line) in lines.iter().enumerate().skip(declaration) {
+        end = line_idx;
+        depth += line.matches('{').count() as i32 - line.matches('}').count() as i32;
+        opened |= line.contains('{');
+        // Stop at the closing brace, or at the `;` of a body-less declaration
+        if (opened && depth <= 0) || (!opened && line.trim_end().ends_with(';')) {
+            break;
+        }
+    }
+    
+    lines[start..=end].join("\n")
+}
+
 /// Create semantic content with ACTUAL function body code - the most useful format
 fn create_function_body_semantic_content(
     function: &crate::types::FunctionInfo,
@@ -1662,31 +1772,7 @@ fn create_function_body_semantic_content(
     let mut content_parts = Vec::new();
     
     // 1. FUNCTION SIGNATURE (clear and complete)
-    let mut signature = format!("fn {}", function.name);
-    if !function.parameters.is_empty() {
-        let params: Vec<String> = function.parameters.iter()
-            .map(|p| format!("{}: {}", p.name, p.param_type))
-            .collect();
-        signature.push_str(&format!("({})", params.join(", ")));
-    }
-    if !function.return_type.is_empty() {
-        signature.push_str(&format!(" -> {}", function.return_type));
-    }
-    
-    // Add modifiers for context
-    let mut modifiers = Vec::new();
-    if function.is_async { modifiers.push("async"); }
-    for modifier in &function.modifiers {
-        if modifier == "pub" || modifier == "unsafe" || modifier == "const" {
-            modifiers.push(modifier);
-        }
-    }
-    
-    if !modifiers.is_empty() {
-        signature = format!("{} {}", modifiers.join(" "), signature);
-    }
-    
-    content_parts.push(format!("FUNCTION: {}", signature));
+    content_parts.push(format!("FUNCTION: {}", function_signature(function)));
     content_parts.push(format!("FILE: {}", file_metadata.path));
     
     // 2. PURPOSE from function name analysis
@@ -1742,6 +1828,7 @@ fn extract_error_handling_patterns(content: &str, file_path: &str) -> Vec<CodeIn
                 language: "rust".to_string(),
                 complexity: 2.0,
                 content: format!("ERROR HANDLING PATTERN (? operator):\n{}", context),
+                embedding_template: FIXED_INPUT_ID.to_string(),
             });
         }
         
@@ -1758,6 +1845,7 @@ fn extract_error_handling_patterns(content: &str, file_path: &str) -> Vec<CodeIn
                     language: "rust".to_string(),
                     complexity: 3.0,
                     content: format!("MATCH ERROR HANDLING:\n{}", context),
+                    embedding_template: FIXED_INPUT_ID.to_string(),
                 });
             }
         }
@@ -1774,6 +1862,7 @@ fn extract_error_handling_patterns(content: &str, file_path: &str) -> Vec<CodeIn
                 language: "rust".to_string(),
                 complexity: 2.5,
                 content: format!("IF LET PATTERN:\n{}", context),
+                embedding_template: FIXED_INPUT_ID.to_string(),
             });
         }
     }
@@ -1802,6 +1891,7 @@ fn extract_algorithm_implementations(content: &str, file_path: &str) -> Vec<Code
                     language: "rust".to_string(),
                     complexity: 3.5,
                     content: format!("LOOP ALGORITHM:\n{}", context),
+                    embedding_template: FIXED_INPUT_ID.to_string(),
                 });
             }
         }
@@ -1819,6 +1909,7 @@ fn extract_algorithm_implementations(content: &str, file_path: &str) -> Vec<Code
                     language: "rust".to_string(),
                     complexity: 4.0,
                     content: format!("COMPLEX MATCH ALGORITHM:\n{}", context),
+                    embedding_template: FIXED_INPUT_ID.to_string(),
                 });
             }
         }
@@ -1836,6 +1927,7 @@ fn extract_algorithm_implementations(content: &str, file_path: &str) -> Vec<Code
                     language: "rust".to_string(),
                     complexity: 3.0,
                     content: format!("ITERATOR CHAIN:\n{}", context),
+                    embedding_template: FIXED_INPUT_ID.to_string(),
                 });
             }
         }
@@ -1869,6 +1961,7 @@ fn extract_function_bodies_regex(content: &str, file_path: &str) -> Vec<CodeInde
                         language: "rust".to_string(),
                         complexity,
                         content: format!("FUNCTION: {}\nIMPLEMENTATION:\n{}", name, body),
+                        embedding_template: FIXED_INPUT_ID.to_string(),
                     });
                 }
                 current_line = end_line + 1;
@@ -2023,3 +2116,223 @@ fn extract_function_with_body(lines: &[&str], start_line: usize) -> Option<(Stri
     None
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_context_is_static_even_with_plugins_loaded() {
+        let mut plugin_manager = PluginManager::new();
+        plugin_manager.initialize_with_mocks(&MLConfig::for_testing()).await.unwrap();
+        plugin_manager.load_plugin("deepseek").await.unwrap();
+        assert!(plugin_manager.get_active_plugin_count() > 0);
+
+        let result = ml_context_result("login", Some(Path::new("src/auth.ts")), true, Arc::new(plugin_manager)).await.unwrap();
+        assert_eq!(result.analysis_mode, AnalysisMode::Static);
+
+        let result = ml_context_result("login", None, false, Arc::new(PluginManager::new())).await.unwrap();
+        assert_eq!(result.analysis_mode, AnalysisMode::Static);
+    }
+
+    #[cfg(feature = "typescript")]
+    #[test]
+    fn test_impact_follows_imports_of_the_given_project() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let project = temp_dir.path();
+        let files = [
+            ("src/app/token.ts", "export const token = () => 'abc';\n"),
+            ("src/app/token.spec.ts", "import { token } from './token';\n"),
+            ("src/app/auth.service.ts", "import { token } from './token';\nexport const login = () => token();\n"),
+            ("src/app/login.component.ts", "import { login } from './auth.service';\nexport const submit = () => login();\n"),
+            ("src/app/footer.component.ts", "export const year = 2024;\n"),
+        ];
+        for (file, content) in files {
+            std::fs::create_dir_all(project.join(file).parent().unwrap()).unwrap();
+            std::fs::write(project.join(file), content).unwrap();
+        }
+
+        let impact = static_impact(project, Path::new("src/app/token.ts")).unwrap();
+        assert_eq!(impact.direct_impact, vec!["./src/app/auth.service.ts", "./src/app/token.spec.ts"]);
+        assert_eq!(impact.indirect_impact, vec!["./src/app/login.component.ts"]);
+        assert_eq!(impact.tests_to_run, vec!["./src/app/token.spec.ts"]);
+        assert_eq!(impact.risk_level, "medium");
+    }
+
+    #[tokio::test]
+    async fn test_pattern_groups_roll_duplicates_up_by_directory() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let project = temp_dir.path();
+        let body = "{\n  const users = fetchUsers();\n  return users.filter(u => u.active).map(u => u.name);\n}\n";
+        for file in ["src/users/list.ts", "src/users/detail.ts"] {
+            std::fs::create_dir_all(project.join(file).parent().unwrap()).unwrap();
+            std::fs::write(project.join(file), format!("function activeNames() {}", body)).unwrap();
+        }
+
+        let options = PatternGroupingOptions { max_depth: 2, min_similarity: 0.8 };
+        let root = pattern_groups(project, options).await.unwrap();
+        assert!(root.duplicate_count > 0);
+        let users = &root.children[0].children[0];
+        assert_eq!(users.path, "src/users");
+        assert_eq!(users.duplicate_count, root.duplicate_count);
+    }
+
+    #[cfg(feature = "rust")]
+    #[test]
+    fn test_excluded_tests_are_not_in_the_dataset() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let project = temp_dir.path();
+        std::fs::create_dir_all(project.join("src")).unwrap();
+        std::fs::write(project.join("src/header.rs"), concat!(
+            "pub fn parse_header(input: &str) -> usize {\n    let trimmed = input.trim();\n    trimmed.len()\n}\n\n",
+            "#[test]\nfn parse_header_counts_bytes() {\n    assert_eq!(parse_header(\"ab\"), 2);\n}\n",
+        )).unwrap();
+        
+        let functions = |include_tests: bool| create_expanded_dataset(project, include_tests, EmbeddingTemplate::Enriched, EmbeddingContent::Both, None).unwrap()
+            .flatten()
+            .filter_map(|entry| entry.function_name)
+            .collect::<Vec<_>>();
+        assert!(functions(false).contains(&"parse_header".to_string()));
+        assert!(!functions(false).contains(&"parse_header_counts_bytes".to_string()));
+        assert!(functions(true).contains(&"parse_header_counts_bytes".to_string()));
+    }
+
+    #[cfg(feature = "rust")]
+    #[test]
+    fn test_embedding_content_applies_to_the_function_source() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let project = temp_dir.path();
+        std::fs::create_dir_all(project.join("src")).unwrap();
+        std::fs::write(project.join("src/cache.rs"), concat!(
+            "/// Retries the cache lookup before giving up\n",
+            "pub fn fetch_entry(key: &str) -> Option<String> {\n",
+            "    // second attempt after a miss\n",
+            "    lookup(key).or_else(|| lookup(key))\n",
+            "}\n",
+        )).unwrap();
+        
+        let content = |embedding_content: EmbeddingContent| create_expanded_dataset(project, false, EmbeddingTemplate::Enriched, embedding_content, None).unwrap()
+            .flatten()
+            .find(|entry| entry.function_name.as_deref() == Some("fetch_entry"))
+            .unwrap()
+            .content;
+        
+        let both = content(EmbeddingContent::Both);
+        assert!(both.contains("/// Retries the cache lookup"));
+        assert!(both.contains("lookup(key).or_else"));
+        
+        let code_only = content(EmbeddingContent::CodeOnly);
+        assert!(!code_only.contains("Retries the cache lookup"));
+        assert!(!code_only.contains("second attempt"));
+        assert!(code_only.contains("lookup(key).or_else"));
+        // The enrichment around the source is kept
+        assert!(code_only.starts_with("FUNCTION: ") && code_only.contains("fn fetch_entry"));
+        
+        let doc_only = content(EmbeddingContent::DocOnly);
+        assert!(doc_only.contains("/// Retries the cache lookup before giving up\n// second attempt after a miss"));
+        assert!(!doc_only.contains("lookup(key).or_else"));
+    }
+
+    #[cfg(feature = "rust")]
+    #[test]
+    fn test_only_function_entries_record_the_embedding_template() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let project = temp_dir.path();
+        std::fs::create_dir_all(project.join("src")).unwrap();
+        std::fs::write(project.join("README.md"), "# Cache\n\nLookups are retried once.\n").unwrap();
+        std::fs::write(project.join("src/cache.rs"), concat!(
+            "//! Entry cache\n",
+            "\n",
+            "pub struct Cache {\n",
+            "    entries: Vec<String>,\n",
+            "}\n",
+            "\n",
+            "pub fn fetch_entry(key: &str) -> Result<String, String> {\n",
+            "    let value = lookup(key)?;\n",
+            "    Ok(value)\n",
+            "}\n",
+        )).unwrap();
+        
+        let entries: Vec<CodeIndexEntry> = create_expanded_dataset(project, false, EmbeddingTemplate::Signature, EmbeddingContent::CodeOnly, None).unwrap()
+            .flatten()
+            .collect();
+        let template_of = |name: &str| entries.iter()
+            .find(|entry| entry.function_name.as_deref() == Some(name))
+            .unwrap_or_else(|| panic!("no entry for {}", name))
+            .embedding_template.as_str();
+        
+        assert_eq!(template_of("fetch_entry"), "signature+code_only");
+        assert_eq!(template_of("Cache"), FIXED_INPUT_ID);
+        let others: Vec<&CodeIndexEntry> = entries.iter().filter(|entry| entry.function_name.as_deref() != Some("fetch_entry")).collect();
+        assert!(others.iter().any(|entry| entry.file_path == "README.md"));
+        assert!(others.iter().all(|entry| entry.embedding_template == FIXED_INPUT_ID));
+    }
+
+    #[test]
+    fn test_focus_prefix_is_relative_to_the_project_not_the_cwd() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let project = temp_dir.path();
+        std::fs::create_dir_all(project.join("src/ml")).unwrap();
+
+        assert_eq!(focus_prefix(project, Path::new("src/ml")), "src/ml/");
+        assert_eq!(focus_prefix(project, Path::new("./src/ml/")), "src/ml/");
+        assert_eq!(focus_prefix(project, &project.join("src/ml")), "src/ml/");
+        assert_eq!(focus_prefix(project, &project.canonicalize().unwrap().join("src/ml")), "src/ml/");
+    }
+
+    #[cfg(feature = "typescript")]
+    #[test]
+    fn test_path_style_reports_a_file_identically_across_commands() {
+        use crate::generators::ProjectOverviewGenerator;
+        use crate::utils::path_normalizer::{PathNormalizer, PathStyle};
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let project = temp_dir.path();
+        std::fs::create_dir_all(project.join("src/app")).unwrap();
+        std::fs::write(project.join("src/app/cart.service.ts"), r#"
+import { Injectable } from '@angular/core';
+
+@Injectable()
+export class CartService {
+    total(items: number[]): number {
+        return items.length;
+    }
+}
+"#).unwrap();
+
+        let mut cache_manager = CacheManager::new(project).unwrap();
+        cache_manager.analyze_project(project, false).unwrap();
+        let cache_key = cache_manager.get_cache().entries.keys().next().unwrap().clone();
+        let overview = ProjectOverviewGenerator::new(cache_manager).generate_overview(project).unwrap();
+        // The vector index stores project-relative paths without a `./` prefix
+        let indexed = "src/app/cart.service.ts".to_string();
+
+        let root = project.canonicalize().unwrap().to_string_lossy().replace('\\', "/");
+        for (style, expected) in [
+            (PathStyle::Relative, "src/app/cart.service.ts".to_string()),
+            (PathStyle::Absolute, format!("{}/src/app/cart.service.ts", root)),
+        ] {
+            // `analyze --verbose` lists cache keys through the normalizer
+            let analyzed = PathNormalizer::new(project).format_path(&cache_key, style);
+
+            let mut search = MlSearchResult {
+                analysis_mode: AnalysisMode::Static,
+                query: "cart".to_string(),
+                path: project.display().to_string(),
+                semantic: true,
+                include_context: false,
+                max_results: 1,
+                fields: Vec::new(),
+                results: vec![SearchHit { file: indexed.clone(), relevance: 1.0, context: String::new(), functions: Vec::new() }],
+            };
+            apply_path_style(&mut search, project, style);
+
+            let mut styled = overview.clone();
+            styled.apply_path_style(&PathNormalizer::new(project), style);
+
+            assert_eq!(analyzed, expected);
+            assert_eq!(search.results[0].file, expected);
+            assert_eq!(styled.structure.services[0].path, expected);
+        }
+    }
+}
@@ -0,0 +1,514 @@
+//! Result types printed by the `ml` commands
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::cli::output::Renderable;
+use crate::ml::models::{AnalysisMode, PatternGroup};
+use crate::utils::projection::project_fields;
+
+fn join_or_none(items: &[String]) -> String {
+    if items.is_empty() {
+        "none".to_string()
+    } else {
+        items.join(", ")
+    }
+}
+
+fn markdown_list(out: &mut String, heading: &str, items: &[String]) {
+    out.push_str(&format!("\n## {}\n\n", heading));
+    if items.is_empty() {
+        out.push_str("_None_\n");
+    }
+    for item in items {
+        out.push_str(&format!("- {}\n", item));
+    }
+}
+
+/// Analysis section of `ml context`
+#[derive(Debug, Clone, Serialize)]
+pub struct ContextAnalysis {
+    pub complexity: String,
+    pub dependencies: Vec<String>,
+    pub impact_scope: String,
+    pub recommendations: Vec<String>,
+}
+
+/// Result of `ml context`
+#[derive(Debug, Clone, Serialize)]
+pub struct MlContextResult {
+    pub analysis_mode: AnalysisMode,
+    pub function: String,
+    pub file: String,
+    pub ai_enhanced: bool,
+    pub analysis: ContextAnalysis,
+}
+
+impl Renderable for MlContextResult {
+    fn to_json(&self) -> Result<serde_json::Value> {
+        Ok(serde_json::to_value(self)?)
+    }
+
+    fn to_text(&self) -> String {
+        let mut out = format!("Function: {}\n", self.function);
+        out.push_str(&format!("File: {}\n", self.file));
+        out.push_str(&format!("Complexity: {}\n", self.analysis.complexity));
+        out.push_str(&format!("Dependencies: {}\n", join_or_none(&self.analysis.dependencies)));
+        out.push_str(&format!("Impact Scope: {}\n", self.analysis.impact_scope));
+        out.push_str("Recommendations:\n");
+        for recommendation in &self.analysis.recommendations {
+            out.push_str(&format!("  - {}\n", recommendation));
+        }
+        out
+    }
+
+    fn to_markdown(&self) -> String {
+        let mut out = format!("# Context: `{}`\n\n", self.function);
+        out.push_str(&format!("- **File:** {}\n", self.file));
+        out.push_str(&format!("- **Complexity:** {}\n", self.analysis.complexity));
+        out.push_str(&format!("- **Impact scope:** {}\n", self.analysis.impact_scope));
+        markdown_list(&mut out, "Dependencies", &self.analysis.dependencies);
+        markdown_list(&mut out, "Recommendations", &self.analysis.recommendations);
+        out
+    }
+
+    fn paths_mut(&mut self) -> Vec<&mut String> {
+        vec![&mut self.file]
+    }
+}
+
+/// Impact section of `ml impact`
+#[derive(Debug, Clone, Serialize)]
+pub struct ImpactReport {
+    pub direct_impact: Vec<String>,
+    pub indirect_impact: Vec<String>,
+    pub risk_level: String,
+    pub tests_to_run: Vec<String>,
+}
+
+/// Result of `ml impact`
+#[derive(Debug, Clone, Serialize)]
+pub struct MlImpactResult {
+    pub analysis_mode: AnalysisMode,
+    pub changed_file: String,
+    pub changed_functions: Vec<String>,
+    pub ai_analysis: bool,
+    pub impact: ImpactReport,
+}
+
+impl Renderable for MlImpactResult {
+    fn to_json(&self) -> Result<serde_json::Value> {
+        Ok(serde_json::to_value(self)?)
+    }
+
+    fn to_text(&self) -> String {
+        let mut out = format!("Changed file: {}\n", self.changed_file);
+        out.push_str(&format!("Changed functions: {}\n", join_or_none(&self.changed_functions)));
+        out.push_str(&format!("Direct impact: {}\n", join_or_none(&self.impact.direct_impact)));
+        out.push_str(&format!("Indirect impact: {}\n", join_or_none(&self.impact.indirect_impact)));
+        out.push_str(&format!("Risk level: {}\n", self.impact.risk_level));
+        out.push_str(&format!("Tests to run: {}\n", join_or_none(&self.impact.tests_to_run)));
+        out
+    }
+
+    fn to_markdown(&self) -> String {
+        let mut out = format!("# Impact: `{}`\n\n", self.changed_file);
+        out.push_str(&format!("- **Risk level:** {}\n", self.impact.risk_level));
+        out.push_str(&format!("- **Changed functions:** {}\n", join_or_none(&self.changed_functions)));
+        markdown_list(&mut out, "Direct impact", &self.impact.direct_impact);
+        markdown_list(&mut out, "Indirect impact", &self.impact.indirect_impact);
+        markdown_list(&mut out, "Tests to run", &self.impact.tests_to_run);
+        out
+    }
+
+    fn paths_mut(&mut self) -> Vec<&mut String> {
+        let impact = &mut self.impact;
+        std::iter::once(&mut self.changed_file)
+            .chain(impact.direct_impact.iter_mut())
+            .chain(impact.indirect_impact.iter_mut())
+            .chain(impact.tests_to_run.iter_mut())
+            .collect()
+    }
+}
+
+/// Files that look alike
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicateGroup {
+    pub similarity: f32,
+    pub files: Vec<String>,
+}
+
+/// A named (anti-)pattern and the files it was found in
+#[derive(Debug, Clone, Serialize)]
+pub struct PatternMatch {
+    pub pattern: String,
+    pub files: Vec<String>,
+}
+
+/// Patterns section of `ml patterns`
+#[derive(Debug, Clone, Serialize)]
+pub struct PatternReport {
+    pub duplicates: Vec<DuplicateGroup>,
+    pub design_patterns: Vec<PatternMatch>,
+    pub anti_patterns: Vec<PatternMatch>,
+}
+
+/// Result of `ml patterns`
+#[derive(Debug, Clone, Serialize)]
+pub struct MlPatternsResult {
+    pub analysis_mode: AnalysisMode,
+    pub path: String,
+    pub detect_duplicates: bool,
+    pub ml_similarity: bool,
+    pub min_similarity: f32,
+    pub patterns: PatternReport,
+    /// Detected patterns rolled up by directory, with `--group-depth`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub groups: Option<PatternGroup>,
+}
+
+impl MlPatternsResult {
+    fn duplicate_lines(&self) -> Vec<String> {
+        self.patterns.duplicates.iter()
+            .map(|group| format!("{:.0}% similarity: {}", group.similarity * 100.0, group.files.join(", ")))
+            .collect()
+    }
+
+    fn pattern_lines(matches: &[PatternMatch]) -> Vec<String> {
+        matches.iter()
+            .map(|m| format!("{}: {}", m.pattern, m.files.join(", ")))
+            .collect()
+    }
+
+    /// One line per directory group with its depth in the tree
+    fn group_lines(&self) -> Vec<(usize, String)> {
+        fn walk(group: &PatternGroup, depth: usize, lines: &mut Vec<(usize, String)>) {
+            let path = if group.path.is_empty() { "." } else { group.path.as_str() };
+            lines.push((depth, format!(
+                "{}: {} duplicates, {} clusters, ~{:.1}h to refactor",
+                path, group.duplicate_count, group.cluster_count, group.estimated_refactoring_hours
+            )));
+            for child in &group.children {
+                walk(child, depth + 1, lines);
+            }
+        }
+        let mut lines = Vec::new();
+        if let Some(root) = &self.groups {
+            walk(root, 0, &mut lines);
+        }
+        lines
+    }
+}
+
+impl Renderable for MlPatternsResult {
+    fn to_json(&self) -> Result<serde_json::Value> {
+        Ok(serde_json::to_value(self)?)
+    }
+
+    fn to_text(&self) -> String {
+        let mut out = format!("Pattern analysis for: {}\n", self.path);
+        let sections = [
+            ("Duplicates found", self.duplicate_lines()),
+            ("Design patterns", Self::pattern_lines(&self.patterns.design_patterns)),
+            ("Anti-patterns", Self::pattern_lines(&self.patterns.anti_patterns)),
+        ];
+        for (heading, lines) in sections {
+            out.push_str(&format!("\n{}:\n", heading));
+            for line in lines {
+                out.push_str(&format!("  - {}\n", line));
+            }
+        }
+        if self.groups.is_some() {
+            out.push_str("\nGroups by directory:\n");
+            for (depth, line) in self.group_lines() {
+                out.push_str(&format!("{}  - {}\n", "  ".repeat(depth), line));
+            }
+        }
+        out
+    }
+
+    fn to_markdown(&self) -> String {
+        let mut out = format!("# Pattern analysis: `{}`\n", self.path);
+        markdown_list(&mut out, "Duplicates", &self.duplicate_lines());
+        markdown_list(&mut out, "Design patterns", &Self::pattern_lines(&self.patterns.design_patterns));
+        markdown_list(&mut out, "Anti-patterns", &Self::pattern_lines(&self.patterns.anti_patterns));
+        if self.groups.is_some() {
+            out.push_str("\n## Groups by directory\n\n");
+            for (depth, line) in self.group_lines() {
+                out.push_str(&format!("{}- {}\n", "  ".repeat(depth), line));
+            }
+        }
+        out
+    }
+
+    fn paths_mut(&mut self) -> Vec<&mut String> {
+        let patterns = &mut self.patterns;
+        let mut paths: Vec<&mut String> = patterns.duplicates.iter_mut()
+            .flat_map(|group| group.files.iter_mut())
+            .chain(patterns.design_patterns.iter_mut().chain(patterns.anti_patterns.iter_mut())
+                .flat_map(|pattern| pattern.files.iter_mut()))
+            .collect();
+        if let Some(groups) = &mut self.groups {
+            paths.extend(groups.paths_mut());
+        }
+        paths
+    }
+}
+
+/// One hit of the basic (non-semantic) search
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchHit {
+    pub file: String,
+    pub relevance: f32,
+    pub context: String,
+    pub functions: Vec<String>,
+}
+
+/// Result of `ml search` when the ML pipeline is not used
+#[derive(Debug, Clone, Serialize)]
+pub struct MlSearchResult {
+    pub analysis_mode: AnalysisMode,
+    pub query: String,
+    pub path: String,
+    pub semantic: bool,
+    pub include_context: bool,
+    pub max_results: usize,
+    pub results: Vec<SearchHit>,
+    /// Fields of each hit kept in JSON output, all when empty (`--fields`)
+    #[serde(skip)]
+    pub fields: Vec<String>,
+}
+
+impl Renderable for MlSearchResult {
+    fn to_json(&self) -> Result<serde_json::Value> {
+        let mut json = serde_json::to_value(self)?;
+        json["results"] = project_fields(&json["results"], &self.fields);
+        Ok(json)
+    }
+
+    fn to_text(&self) -> String {
+        let mut out = format!("Search results for: '{}'\n", self.query);
+        for (idx, hit) in self.results.iter().enumerate() {
+            out.push_str(&format!("\n{}. {} ({:.0}% relevance)\n", idx + 1, hit.file, hit.relevance * 100.0));
+            out.push_str(&format!("   Context: {}\n", hit.context));
+            out.push_str(&format!("   Functions: {}\n", hit.functions.join(", ")));
+        }
+        out
+    }
+
+    fn to_markdown(&self) -> String {
+        let mut out = format!("# Search: `{}`\n\n", self.query);
+        out.push_str("| # | File | Relevance | Context | Functions |\n");
+        out.push_str("|---|------|-----------|---------|-----------|\n");
+        for (idx, hit) in self.results.iter().enumerate() {
+            out.push_str(&format!(
+                "| {} | `{}` | {:.0}% | {} | {} |\n",
+                idx + 1, hit.file, hit.relevance * 100.0, hit.context, hit.functions.join(", ")
+            ));
+        }
+        out
+    }
+
+    fn paths_mut(&mut self) -> Vec<&mut String> {
+        self.results.iter_mut().map(|hit| &mut hit.file).collect()
+    }
+}
+
+/// One hit of the semantic search pipeline
+#[derive(Debug, Clone, Serialize)]
+pub struct SemanticSearchHit {
+    pub file: String,
+    pub relevance: f32,
+    pub context: String,
+    pub match_type: String,
+    pub line_range: [usize; 2],
+    pub language: String,
+    pub complexity: f32,
+    pub embedding_similarity: f32,
+    pub combined_score: f32,
+    pub confidence: f32,
+}
+
+/// Best-effort hit reported when every result fell below `--min-relevance`
+#[derive(Debug, Clone, Serialize)]
+pub struct LowConfidenceMatch {
+    pub file: String,
+    pub context: String,
+    pub line_range: [usize; 2],
+    pub combined_score: f32,
+    pub confidence: f32,
+    pub low_confidence: bool,
+}
+
+/// Result of `ml search --semantic`
+#[derive(Debug, Clone, Serialize)]
+pub struct SemanticSearchResult {
+    pub analysis_mode: AnalysisMode,
+    pub query: String,
+    pub path: String,
+    pub semantic: bool,
+    pub include_context: bool,
+    pub max_results: usize,
+    pub search_time_ms: u64,
+    pub total_candidates: usize,
+    pub results: Vec<SemanticSearchHit>,
+    pub explanation: Option<String>,
+    pub suggestions: Vec<String>,
+    pub no_confident_matches: bool,
+    pub low_confidence_match: Option<LowConfidenceMatch>,
+    /// Fields of each hit kept in JSON output, all when empty (`--fields`)
+    #[serde(skip)]
+    pub fields: Vec<String>,
+}
+
+impl Renderable for SemanticSearchResult {
+    fn to_json(&self) -> Result<serde_json::Value> {
+        let mut json = serde_json::to_value(self)?;
+        json["results"] = project_fields(&json["results"], &self.fields);
+        Ok(json)
+    }
+
+    fn to_text(&self) -> String {
+        let mut out = format!("🔍 Semantic search results for: '{}'\n", self.query);
+        out.push_str("⚡ Pipeline: Qwen3-Embedding → LSH → Qwen3-Reranker\n");
+        out.push_str(&format!("⏱️  Search time: {}ms\n\n", self.search_time_ms));
+
+        for (idx, hit) in self.results.iter().enumerate() {
+            out.push_str(&format!("{}. {} ({:.1}% relevance)\n", idx + 1, hit.file, hit.relevance * 100.0));
+            if !hit.context.is_empty() {
+                out.push_str(&format!("   Function: {}\n", hit.context));
+            }
+            out.push_str(&format!("   Lines: {}-{}\n", hit.line_range[0], hit.line_range[1]));
+            out.push_str(&format!("   Language: {}\n", hit.language));
+            out.push_str(&format!("   Code type: {}\n", hit.match_type));
+            out.push_str(&format!("   Complexity: {:.2}\n", hit.complexity));
+            out.push_str(&format!("   Embedding similarity: {:.3}\n", hit.embedding_similarity));
+            out.push_str(&format!("   Combined score: {:.3}\n", hit.combined_score));
+            out.push_str(&format!("   Confidence: {:.3}\n\n", hit.confidence));
+        }
+
+        if let Some(best) = &self.low_confidence_match {
+            out.push_str("⚠️  No confident matches. Best-effort (low confidence):\n");
+            out.push_str(&format!("   {} (combined score: {:.3})\n\n", best.file, best.combined_score));
+        }
+
+        if let Some(explanation) = &self.explanation {
+            out.push_str(&format!("💡 Ranking explanation: {}\n", explanation));
+        }
+
+        if !self.suggestions.is_empty() {
+            out.push_str("🔍 Suggestions:\n");
+            for suggestion in &self.suggestions {
+                out.push_str(&format!("  - {}\n", suggestion));
+            }
+        }
+        out
+    }
+
+    fn to_markdown(&self) -> String {
+        let mut out = format!("# Semantic search: `{}`\n\n", self.query);
+        out.push_str(&format!(
+            "{} results from {} candidates in {}ms\n\n",
+            self.results.len(), self.total_candidates, self.search_time_ms
+        ));
+        out.push_str("| # | File | Lines | Context | Relevance | Confidence |\n");
+        out.push_str("|---|------|-------|---------|-----------|------------|\n");
+        for (idx, hit) in self.results.iter().enumerate() {
+            out.push_str(&format!(
+                "| {} | `{}` | {}-{} | {} | {:.1}% | {:.3} |\n",
+                idx + 1, hit.file, hit.line_range[0], hit.line_range[1], hit.context,
+                hit.relevance * 100.0, hit.confidence
+            ));
+        }
+
+        if let Some(best) = &self.low_confidence_match {
+            out.push_str(&format!(
+                "\n> No confident matches. Best-effort: `{}` (combined score {:.3})\n",
+                best.file, best.combined_score
+            ));
+        }
+        if let Some(explanation) = &self.explanation {
+            out.push_str(&format!("\n**Ranking:** {}\n", explanation));
+        }
+        if !self.suggestions.is_empty() {
+            markdown_list(&mut out, "Suggestions", &self.suggestions);
+        }
+        out
+    }
+
+    fn paths_mut(&mut self) -> Vec<&mut String> {
+        self.results.iter_mut()
+            .map(|hit| &mut hit.file)
+            .chain(self.low_confidence_match.iter_mut().map(|best| &mut best.file))
+            .collect()
+    }
+}
+
+/// A file selected for the token budget
+#[derive(Debug, Clone, Serialize)]
+pub struct RecommendedFile {
+    pub file: String,
+    pub priority: String,
+    pub estimated_tokens: usize,
+}
+
+/// Optimization section of `ml optimize`
+#[derive(Debug, Clone, Serialize)]
+pub struct OptimizationPlan {
+    pub recommended_files: Vec<RecommendedFile>,
+    pub excluded_files: Vec<String>,
+    pub total_estimated: usize,
+    pub optimization_ratio: f32,
+}
+
+/// Result of `ml optimize`
+#[derive(Debug, Clone, Serialize)]
+pub struct MlOptimizeResult {
+    pub analysis_mode: AnalysisMode,
+    pub task: String,
+    pub token_budget: usize,
+    pub ai_enhanced: bool,
+    pub optimization: OptimizationPlan,
+}
+
+impl Renderable for MlOptimizeResult {
+    fn to_json(&self) -> Result<serde_json::Value> {
+        Ok(serde_json::to_value(self)?)
+    }
+
+    fn to_text(&self) -> String {
+        let mut out = format!("Token optimization for: '{}'\n", self.task);
+        out.push_str(&format!("Budget: {} tokens\n", self.token_budget));
+        out.push_str("\nRecommended files:\n");
+        for file in &self.optimization.recommended_files {
+            out.push_str(&format!("  - {} ({}, ~{} tokens)\n", file.file, file.priority, file.estimated_tokens));
+        }
+        out.push_str(&format!("\nExcluded files: {}\n", join_or_none(&self.optimization.excluded_files)));
+        out.push_str(&format!("Total estimated: {} tokens\n", self.optimization.total_estimated));
+        out.push_str(&format!("Optimization ratio: {:.0}%\n", self.optimization.optimization_ratio * 100.0));
+        out
+    }
+
+    fn to_markdown(&self) -> String {
+        let mut out = format!("# Token optimization: {}\n\n", self.task);
+        out.push_str(&format!(
+            "Budget: {} tokens, estimated: {} tokens ({:.0}% optimization)\n\n",
+            self.token_budget, self.optimization.total_estimated, self.optimization.optimization_ratio * 100.0
+        ));
+        out.push_str("| File | Priority | Estimated tokens |\n");
+        out.push_str("|------|----------|------------------|\n");
+        for file in &self.optimization.recommended_files {
+            out.push_str(&format!("| `{}` | {} | {} |\n", file.file, file.priority, file.estimated_tokens));
+        }
+        markdown_list(&mut out, "Excluded files", &self.optimization.excluded_files);
+        out
+    }
+
+    fn paths_mut(&mut self) -> Vec<&mut String> {
+        let optimization = &mut self.optimization;
+        optimization.recommended_files.iter_mut()
+            .map(|file| &mut file.file)
+            .chain(optimization.excluded_files.iter_mut())
+            .collect()
+    }
+}
@@ -1,13 +1,16 @@
 pub mod analyze;
 pub mod summary;
+pub mod context;
 pub mod changes;
 pub mod overview;
 pub mod cache;
 pub mod ml_commands;
+pub mod ml_results;
 pub mod mcp_commands;
 
 pub use analyze::*;
 pub use summary::*;
+pub use context::*;
 pub use changes::*;
 pub use overview::*;
 pub use cache::*;
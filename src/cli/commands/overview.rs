@@ -2,20 +2,40 @@ use anyhow::Result;
 use std::path::Path;
 use crate::generators::{ProjectOverviewGenerator, ReportGenerator};
 use crate::cache::CacheManager;
+use crate::utils::path_normalizer::PathNormalizer;
+use crate::cli::output::RenderOptions;
 
-pub fn run_overview(path: &Path, format: &str, include_health: bool) -> Result<()> {
+pub fn run_overview(
+    path: &Path,
+    format: &str,
+    include_health: bool,
+    options: RenderOptions,
+    focus: Option<&Path>,
+    since: Option<&str>,
+) -> Result<()> {
     // Ensure we analyze the project first to have cache data
-    let mut cache_manager = CacheManager::new(path)?;
+    let mut cache_manager = CacheManager::new_with_focus(path, focus)?;
     
     // Check if cache exists and is populated, if not analyze project
     if cache_manager.get_cache().entries.is_empty() {
         cache_manager.analyze_project(path, false)?;
     }
     
-    let generator = ProjectOverviewGenerator::new(cache_manager);
+    let mut generator = ProjectOverviewGenerator::new(cache_manager);
     let report_generator = ReportGenerator::new();
     
     let mut overview = generator.generate_overview(path)?;
+    let delta = since.map(|since| generator.delta_since(path, since, &overview)).transpose()?;
+    generator.save_snapshot(path, &overview)?;
+    
+    if let Some(delta) = delta {
+        match format {
+            "json" => println!("{}", options.json_style.to_string(&delta)?),
+            "markdown" => println!("{}", options.decorate(&delta.to_markdown())),
+            _ => println!("{}", options.decorate(&delta.to_text())),
+        }
+        return Ok(());
+    }
     
     if !include_health {
         // Simplified health metrics if not requested
@@ -24,20 +44,22 @@ pub fn run_overview(path: &Path, format: &str, include_health: bool) -> Result<(
         overview.health_metrics.performance.memory_usage = 0;
     }
     
+    overview.apply_path_style(&PathNormalizer::new(path), options.path_style);
+    
     match format {
         "json" => {
-            let json = report_generator.generate_json_report(&overview)?;
+            let json = options.json_style.to_string(&overview)?;
             println!("{}", json);
         }
         "markdown" => {
             let markdown = report_generator.generate_markdown_report(&overview)?;
-            println!("{}", markdown);
+            println!("{}", options.decorate(&markdown));
         }
         _ => {
             let text = report_generator.generate_text_report(&overview)?;
-            println!("{}", text);
+            println!("{}", options.decorate(&text));
         }
     }
     
     Ok(())
-}
\ No newline at end of file
+}
@@ -1,4 +1,5 @@
 pub mod commands;
 pub mod cli;
+pub mod output;
 
-pub use cli::*;
\ No newline at end of file
+pub use cli::*;
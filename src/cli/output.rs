@@ -0,0 +1,300 @@
+//! Output formats shared by commands that produce structured results
+
+use anyhow::Result;
+use clap::ValueEnum;
+use serde::Serialize;
+use std::io::Write;
+use std::path::Path;
+use crate::utils::decoration::Charset;
+use crate::utils::path_normalizer::{PathNormalizer, PathStyle};
+
+/// Output format selected with `--format`; unknown values are rejected by clap
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum OutputFormat {
+    #[default]
+    Json,
+    Text,
+    Markdown,
+}
+
+/// Layout of JSON output; `--compact-json` selects `Compact`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum JsonStyle {
+    /// Indented, for reading
+    #[default]
+    Pretty,
+    /// A single line, for piping and logs
+    Compact,
+}
+
+impl JsonStyle {
+    pub fn from_compact(compact: bool) -> Self {
+        if compact { Self::Compact } else { Self::Pretty }
+    }
+
+    pub fn to_string<T: Serialize + ?Sized>(self, value: &T) -> Result<String> {
+        Ok(match self {
+            Self::Pretty => serde_json::to_string_pretty(value)?,
+            Self::Compact => serde_json::to_string(value)?,
+        })
+    }
+}
+
+/// Output settings shared by every command, taken from the global flags
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RenderOptions {
+    /// Text and markdown output stop after this many lines (`--max-output-lines`)
+    pub max_lines: Option<usize>,
+    pub json_style: JsonStyle,
+    /// Symbols used in text and markdown output (`--ascii`)
+    pub charset: Charset,
+    /// How file paths are printed (`--path-style`)
+    pub path_style: PathStyle,
+}
+
+impl RenderOptions {
+    /// Text or markdown `output` truncated to `max_lines` in `charset`
+    pub fn decorate(&self, output: &str) -> String {
+        truncate_lines(&self.charset.decorate(output.trim_end()), self.max_lines)
+    }
+}
+
+/// A command result that can be printed in every `OutputFormat`
+pub trait Renderable {
+    fn to_json(&self) -> Result<serde_json::Value>;
+    fn to_text(&self) -> String;
+    fn to_markdown(&self) -> String;
+
+    /// File paths the result reports, rewritten by `apply_path_style`;
+    /// results without file paths keep the default
+    fn paths_mut(&mut self) -> Vec<&mut String> {
+        Vec::new()
+    }
+}
+
+/// Rewrite every file path reported by `result` in `style`, relative to
+/// `project_root`, so all commands print the same file the same way
+pub fn apply_path_style(result: &mut dyn Renderable, project_root: &Path, style: PathStyle) {
+    let normalizer = PathNormalizer::new(project_root);
+    for path in result.paths_mut() {
+        *path = normalizer.format_path(path, style);
+    }
+}
+
+/// Write `result` to `writer` in the requested format. Text and markdown
+/// output stop after `options.max_lines` lines and use `options.charset`;
+/// JSON is always complete and undecorated.
+pub fn render(
+    result: &dyn Renderable,
+    format: OutputFormat,
+    options: RenderOptions,
+    writer: &mut dyn Write,
+) -> Result<()> {
+    match format {
+        OutputFormat::Json => writeln!(writer, "{}", options.json_style.to_string(&result.to_json()?)?)?,
+        OutputFormat::Text => writeln!(writer, "{}", options.decorate(&result.to_text()))?,
+        OutputFormat::Markdown => writeln!(writer, "{}", options.decorate(&result.to_markdown()))?,
+    }
+    Ok(())
+}
+
+/// First `max_lines` lines of `text`, followed by a footer counting the
+/// omitted lines when there were more
+pub fn truncate_lines(text: &str, max_lines: Option<usize>) -> String {
+    let Some(max_lines) = max_lines else {
+        return text.to_string();
+    };
+    let total = text.lines().count();
+    if total <= max_lines {
+        return text.to_string();
+    }
+
+    let mut kept: Vec<&str> = text.lines().take(max_lines).collect();
+    let footer = format!("... {} more lines omitted (use --format json for full data)", total - max_lines);
+    kept.push(&footer);
+    kept.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::{Cli, Commands, MLCommands};
+    use clap::Parser;
+
+    struct Greeting;
+
+    impl Renderable for Greeting {
+        fn to_json(&self) -> Result<serde_json::Value> {
+            Ok(serde_json::json!({ "greeting": "hello" }))
+        }
+
+        fn to_text(&self) -> String {
+            "hello\n".to_string()
+        }
+
+        fn to_markdown(&self) -> String {
+            "# hello\n".to_string()
+        }
+    }
+
+    #[test]
+    fn test_unknown_format_is_rejected_at_parse_time() {
+        for command in ["search", "patterns", "optimize", "impact", "context"] {
+            let args = match command {
+                "search" => vec!["token-optimizer", "ml", "search", "--query", "auth", "--format", "xml"],
+                "optimize" => vec!["token-optimizer", "ml", "optimize", "--task", "auth", "--format", "xml"],
+                "impact" => vec!["token-optimizer", "ml", "impact", "--changed-file", "a.ts", "--format", "xml"],
+                "context" => vec!["token-optimizer", "ml", "context", "--function", "login", "--format", "xml"],
+                _ => vec!["token-optimizer", "ml", "patterns", "--format", "xml"],
+            };
+            let error = Cli::try_parse_from(args).err().unwrap_or_else(|| panic!("ml {} accepted --format xml", command));
+            assert_eq!(error.kind(), clap::error::ErrorKind::InvalidValue);
+        }
+
+        let cli = Cli::try_parse_from(["token-optimizer", "ml", "patterns", "--format", "markdown"]).unwrap();
+        match cli.command {
+            Commands::ML { action: MLCommands::Patterns { format, .. } } => assert_eq!(format, OutputFormat::Markdown),
+            _ => panic!("expected ml patterns"),
+        }
+
+        let error = Cli::try_parse_from(["token-optimizer", "context", "--file", "a.ts", "--format", "xml"]).err().unwrap();
+        assert_eq!(error.kind(), clap::error::ErrorKind::InvalidValue);
+    }
+
+    #[test]
+    fn test_overview_since_conflicts_with_focus() {
+        let args = ["token-optimizer", "overview", "--since", "HEAD", "--focus", "src/app"];
+        let error = Cli::try_parse_from(args).err().unwrap();
+        assert_eq!(error.kind(), clap::error::ErrorKind::ArgumentConflict);
+
+        assert!(Cli::try_parse_from(["token-optimizer", "overview", "--since", "HEAD"]).is_ok());
+        assert!(Cli::try_parse_from(["token-optimizer", "--focus", "src/app", "overview"]).is_ok());
+    }
+
+    #[test]
+    fn test_render_writes_selected_format() {
+        let mut json = Vec::new();
+        render(&Greeting, OutputFormat::Json, RenderOptions::default(), &mut json).unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        assert_eq!(value["greeting"], "hello");
+
+        let mut text = Vec::new();
+        render(&Greeting, OutputFormat::Text, RenderOptions::default(), &mut text).unwrap();
+        assert_eq!(String::from_utf8(text).unwrap(), "hello\n");
+
+        let mut markdown = Vec::new();
+        render(&Greeting, OutputFormat::Markdown, RenderOptions::default(), &mut markdown).unwrap();
+        assert_eq!(String::from_utf8(markdown).unwrap(), "# hello\n");
+    }
+
+    #[test]
+    fn test_output_respects_max_lines() {
+        struct Listing;
+
+        impl Renderable for Listing {
+            fn to_json(&self) -> Result<serde_json::Value> {
+                Ok(serde_json::json!({ "lines": (1..=10).collect::<Vec<_>>() }))
+            }
+
+            fn to_text(&self) -> String {
+                (1..=10).map(|i| format!("line {}\n", i)).collect()
+            }
+
+            fn to_markdown(&self) -> String {
+                self.to_text()
+            }
+        }
+
+        let limited = RenderOptions { max_lines: Some(3), ..RenderOptions::default() };
+        let mut text = Vec::new();
+        render(&Listing, OutputFormat::Text, limited, &mut text).unwrap();
+        assert_eq!(
+            String::from_utf8(text).unwrap(),
+            "line 1\nline 2\nline 3\n... 7 more lines omitted (use --format json for full data)\n"
+        );
+
+        let mut json = Vec::new();
+        render(&Listing, OutputFormat::Json, limited, &mut json).unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        assert_eq!(value["lines"].as_array().unwrap().len(), 10);
+
+        assert_eq!(truncate_lines("a\nb", Some(2)), "a\nb");
+        let cli = Cli::try_parse_from(["token-optimizer", "overview", "--max-output-lines", "5"]).unwrap();
+        assert_eq!(cli.max_output_lines, Some(5));
+    }
+
+    #[test]
+    fn test_compact_json_is_a_single_line() {
+        struct Report;
+
+        impl Renderable for Report {
+            fn to_json(&self) -> Result<serde_json::Value> {
+                Ok(serde_json::json!({ "files": ["a.ts", "b.ts"], "total": 2 }))
+            }
+
+            fn to_text(&self) -> String {
+                "2 files\n".to_string()
+            }
+
+            fn to_markdown(&self) -> String {
+                self.to_text()
+            }
+        }
+
+        let output = |json_style| {
+            let mut out = Vec::new();
+            render(&Report, OutputFormat::Json, RenderOptions { json_style, ..RenderOptions::default() }, &mut out).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+        let compact = output(JsonStyle::Compact);
+        let pretty = output(JsonStyle::Pretty);
+
+        assert!(!compact.trim_end().contains('\n'));
+        assert!(pretty.trim_end().contains('\n'));
+        let compact_value: serde_json::Value = serde_json::from_str(&compact).unwrap();
+        let pretty_value: serde_json::Value = serde_json::from_str(&pretty).unwrap();
+        assert_eq!(compact_value, pretty_value);
+
+        let cli = Cli::try_parse_from(["token-optimizer", "--compact-json", "overview"]).unwrap();
+        assert_eq!(cli.render_options().json_style, JsonStyle::Compact);
+        let cli = Cli::try_parse_from(["token-optimizer", "overview", "--max-output-lines", "5"]).unwrap();
+        assert_eq!(cli.render_options(), RenderOptions { max_lines: Some(5), json_style: JsonStyle::Pretty, charset: Charset::Unicode, path_style: PathStyle::Relative });
+        let cli = Cli::try_parse_from(["token-optimizer", "--path-style", "absolute", "ml", "impact", "--changed-file", "a.ts"]).unwrap();
+        assert_eq!(cli.render_options().path_style, PathStyle::Absolute);
+    }
+
+    #[test]
+    fn test_ascii_output_has_no_non_ascii_bytes() {
+        struct Status;
+
+        impl Renderable for Status {
+            fn to_json(&self) -> Result<serde_json::Value> {
+                Ok(serde_json::json!({ "status": "✅ indexed" }))
+            }
+
+            fn to_text(&self) -> String {
+                "🔍 Results for 'auth'\n⚠️  No confident matches\n  • login → src/auth.rs\n".to_string()
+            }
+
+            fn to_markdown(&self) -> String {
+                "## 💡 Suggestions\n- ✅ Indexed\n".to_string()
+            }
+        }
+
+        let cli = Cli::try_parse_from(["token-optimizer", "--ascii", "ml", "search", "--query", "auth"]).unwrap();
+        let options = cli.render_options();
+        assert_eq!(options.charset, Charset::Ascii);
+
+        for format in [OutputFormat::Text, OutputFormat::Markdown] {
+            let mut out = Vec::new();
+            render(&Status, format, options, &mut out).unwrap();
+            assert!(out.is_ascii(), "{}", String::from_utf8_lossy(&out));
+        }
+
+        // JSON keeps the data as is
+        let mut json = Vec::new();
+        render(&Status, OutputFormat::Json, options, &mut json).unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        assert_eq!(value["status"], "✅ indexed");
+    }
+}
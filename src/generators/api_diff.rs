@@ -0,0 +1,337 @@
+//! Public API comparison between two analyses of the same file.
+//!
+//! Reports removed public functions, signature changes and removed exports so
+//! semver tooling can decide whether a change is breaking.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use crate::types::{CodeSummary, FunctionInfo};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum ApiChangeSeverity {
+    Breaking,
+    NonBreaking,
+    Addition,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum ApiChangeKind {
+    FunctionRemoved,
+    FunctionAdded,
+    ParametersChanged,
+    ReturnTypeChanged,
+    ExportRemoved,
+    ExportAdded,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ApiChange {
+    pub symbol: String,
+    pub kind: ApiChangeKind,
+    pub severity: ApiChangeSeverity,
+    pub old_signature: Option<String>,
+    pub new_signature: Option<String>,
+    pub description: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ApiChangeSet {
+    pub file_name: String,
+    pub changes: Vec<ApiChange>,
+}
+
+impl ApiChangeSet {
+    pub fn is_breaking(&self) -> bool {
+        self.changes.iter().any(|c| c.severity == ApiChangeSeverity::Breaking)
+    }
+
+    pub fn breaking_changes(&self) -> Vec<&ApiChange> {
+        self.by_severity(ApiChangeSeverity::Breaking)
+    }
+
+    pub fn additions(&self) -> Vec<&ApiChange> {
+        self.by_severity(ApiChangeSeverity::Addition)
+    }
+
+    fn by_severity(&self, severity: ApiChangeSeverity) -> Vec<&ApiChange> {
+        self.changes.iter().filter(|c| c.severity == severity).collect()
+    }
+}
+
+/// Compares the public API of two summaries of the same file
+pub fn compare(old: &CodeSummary, new: &CodeSummary) -> ApiChangeSet {
+    let old_functions = public_functions(old);
+    let new_functions = public_functions(new);
+    let mut changes = Vec::new();
+
+    for (name, old_fn) in &old_functions {
+        match new_functions.get(name) {
+            None => changes.push(ApiChange {
+                symbol: name.clone(),
+                kind: ApiChangeKind::FunctionRemoved,
+                severity: ApiChangeSeverity::Breaking,
+                old_signature: Some(signature(old_fn)),
+                new_signature: None,
+                description: format!("Public function '{}' was removed", name),
+            }),
+            Some(new_fn) => changes.extend(compare_signatures(name, old_fn, new_fn)),
+        }
+    }
+
+    for (name, new_fn) in &new_functions {
+        if !old_functions.contains_key(name) {
+            changes.push(ApiChange {
+                symbol: name.clone(),
+                kind: ApiChangeKind::FunctionAdded,
+                severity: ApiChangeSeverity::Addition,
+                old_signature: None,
+                new_signature: Some(signature(new_fn)),
+                description: format!("Public function '{}' was added", name),
+            });
+        }
+    }
+
+    // Exports that aren't functions (classes, constants, types); function exports
+    // are already covered above.
+    for export in &old.exports {
+        if !new.exports.contains(export) && !old_functions.contains_key(export) {
+            changes.push(ApiChange {
+                symbol: export.clone(),
+                kind: ApiChangeKind::ExportRemoved,
+                severity: ApiChangeSeverity::Breaking,
+                old_signature: None,
+                new_signature: None,
+                description: format!("Export '{}' was removed", export),
+            });
+        }
+    }
+
+    for export in &new.exports {
+        if !old.exports.contains(export) && !new_functions.contains_key(export) {
+            changes.push(ApiChange {
+                symbol: export.clone(),
+                kind: ApiChangeKind::ExportAdded,
+                severity: ApiChangeSeverity::Addition,
+                old_signature: None,
+                new_signature: None,
+                description: format!("Export '{}' was added", export),
+            });
+        }
+    }
+
+    changes.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+
+    ApiChangeSet {
+        file_name: new.file_name.clone(),
+        changes,
+    }
+}
+
+fn compare_signatures(name: &str, old_fn: &FunctionInfo, new_fn: &FunctionInfo) -> Vec<ApiChange> {
+    let mut changes = Vec::new();
+
+    if old_fn.return_type != new_fn.return_type || old_fn.is_async != new_fn.is_async {
+        changes.push(ApiChange {
+            symbol: name.to_string(),
+            kind: ApiChangeKind::ReturnTypeChanged,
+            severity: ApiChangeSeverity::Breaking,
+            old_signature: Some(signature(old_fn)),
+            new_signature: Some(signature(new_fn)),
+            description: format!(
+                "Return type of '{}' changed from '{}' to '{}'",
+                name, old_fn.return_type, new_fn.return_type
+            ),
+        });
+    }
+
+    if parameter_types(old_fn) != parameter_types(new_fn) {
+        // Appending optional parameters keeps existing call sites valid
+        let only_optional_appended = new_fn.parameters.len() > old_fn.parameters.len()
+            && parameter_types(old_fn) == parameter_types_prefix(new_fn, old_fn.parameters.len())
+            && new_fn.parameters[old_fn.parameters.len()..]
+                .iter()
+                .all(|p| p.is_optional || p.default_value.is_some());
+
+        changes.push(ApiChange {
+            symbol: name.to_string(),
+            kind: ApiChangeKind::ParametersChanged,
+            severity: if only_optional_appended {
+                ApiChangeSeverity::NonBreaking
+            } else {
+                ApiChangeSeverity::Breaking
+            },
+            old_signature: Some(signature(old_fn)),
+            new_signature: Some(signature(new_fn)),
+            description: format!("Parameters of '{}' changed", name),
+        });
+    }
+
+    changes
+}
+
+fn public_functions(summary: &CodeSummary) -> HashMap<String, &FunctionInfo> {
+    let mut functions = HashMap::new();
+
+    for function in &summary.functions {
+        if is_public(function, summary) {
+            functions.insert(function.name.clone(), function);
+        }
+    }
+
+    for class in &summary.classes {
+        if !summary.exports.contains(&class.name) && !has_public_modifier(&class.modifiers) {
+            continue;
+        }
+        for method in &class.methods {
+            if !is_hidden(&method.modifiers) {
+                functions.insert(format!("{}.{}", class.name, method.name), method);
+            }
+        }
+    }
+
+    functions
+}
+
+fn is_public(function: &FunctionInfo, summary: &CodeSummary) -> bool {
+    !is_hidden(&function.modifiers)
+        && (summary.exports.contains(&function.name) || has_public_modifier(&function.modifiers))
+}
+
+fn has_public_modifier(modifiers: &[String]) -> bool {
+    modifiers.iter().any(|m| m == "pub" || m == "export" || m == "public")
+}
+
+fn is_hidden(modifiers: &[String]) -> bool {
+    modifiers.iter().any(|m| m == "private" || m == "protected" || m.starts_with("pub("))
+}
+
+fn parameter_types(function: &FunctionInfo) -> Vec<&str> {
+    parameter_types_prefix(function, function.parameters.len())
+}
+
+fn parameter_types_prefix(function: &FunctionInfo, len: usize) -> Vec<&str> {
+    function.parameters.iter().take(len).map(|p| p.param_type.as_str()).collect()
+}
+
+/// Renders a one-line signature, e.g. `async load(path: &Path) -> String`
+pub fn signature(function: &FunctionInfo) -> String {
+    let params: Vec<String> = function
+        .parameters
+        .iter()
+        .map(|p| format!("{}{}: {}", p.name, if p.is_optional { "?" } else { "" }, p.param_type))
+        .collect();
+    format!(
+        "{}{}({}) -> {}",
+        if function.is_async { "async " } else { "" },
+        function.name,
+        params.join(", "),
+        function.return_type
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{LocationInfo, ParameterInfo};
+
+    fn function(name: &str, params: &[(&str, &str, bool)], return_type: &str) -> FunctionInfo {
+        FunctionInfo {
+            name: name.to_string(),
+            parameters: params
+                .iter()
+                .map(|(n, t, optional)| ParameterInfo {
+                    name: n.to_string(),
+                    param_type: t.to_string(),
+                    is_optional: *optional,
+                    default_value: None,
+                })
+                .collect(),
+            return_type: return_type.to_string(),
+            is_async: false,
+            modifiers: vec!["pub".to_string()],
+            location: LocationInfo { line: 1, column: 1 },
+            description: None,
+        }
+    }
+
+    fn summary(functions: Vec<FunctionInfo>, exports: &[&str]) -> CodeSummary {
+        CodeSummary {
+            file_name: "lib.rs".to_string(),
+            file_type: "rust".to_string(),
+            exports: exports.iter().map(|e| e.to_string()).collect(),
+            imports: vec![],
+            functions,
+            classes: vec![],
+            components: vec![],
+            services: vec![],
+            pipes: vec![],
+            modules: vec![],
+            key_patterns: vec![],
+            dependencies: vec![],
+            scss_variables: None,
+            scss_mixins: None,
+        }
+    }
+
+    #[test]
+    fn test_removed_public_function_is_breaking() {
+        let old = summary(vec![function("parse", &[("input", "&str", false)], "Result<Ast>"),
+                               function("render", &[], "String")], &[]);
+        let new = summary(vec![function("render", &[], "String")], &[]);
+
+        let changes = compare(&old, &new);
+        assert!(changes.is_breaking());
+        assert_eq!(changes.changes.len(), 1);
+        assert_eq!(changes.changes[0].symbol, "parse");
+        assert_eq!(changes.changes[0].kind, ApiChangeKind::FunctionRemoved);
+        assert_eq!(changes.changes[0].severity, ApiChangeSeverity::Breaking);
+    }
+
+    #[test]
+    fn test_added_public_function_is_addition() {
+        let old = summary(vec![function("render", &[], "String")], &[]);
+        let new = summary(vec![function("render", &[], "String"),
+                               function("render_html", &[], "String")], &[]);
+
+        let changes = compare(&old, &new);
+        assert!(!changes.is_breaking());
+        assert_eq!(changes.additions().len(), 1);
+        assert_eq!(changes.additions()[0].symbol, "render_html");
+        assert_eq!(changes.additions()[0].kind, ApiChangeKind::FunctionAdded);
+    }
+
+    #[test]
+    fn test_changed_return_type_is_breaking() {
+        let old = summary(vec![function("load", &[("path", "&Path", false)], "String")], &[]);
+        let new = summary(vec![function("load", &[("path", "&Path", false)], "Result<String>")], &[]);
+
+        let changes = compare(&old, &new);
+        assert!(changes.is_breaking());
+        assert_eq!(changes.breaking_changes()[0].kind, ApiChangeKind::ReturnTypeChanged);
+        assert_eq!(changes.breaking_changes()[0].new_signature.as_deref(), Some("load(path: &Path) -> Result<String>"));
+    }
+
+    #[test]
+    fn test_optional_parameter_and_export_changes() {
+        let old = summary(vec![function("fetch", &[("url", "string", false)], "Promise<Data>")], &["fetch", "Config"]);
+        let new = summary(vec![function("fetch", &[("url", "string", false), ("retries", "number", true)], "Promise<Data>")], &["fetch"]);
+
+        let changes = compare(&old, &new);
+        let params = changes.changes.iter().find(|c| c.kind == ApiChangeKind::ParametersChanged).unwrap();
+        assert_eq!(params.severity, ApiChangeSeverity::NonBreaking);
+
+        let export = changes.changes.iter().find(|c| c.kind == ApiChangeKind::ExportRemoved).unwrap();
+        assert_eq!(export.symbol, "Config");
+        assert_eq!(export.severity, ApiChangeSeverity::Breaking);
+    }
+
+    #[test]
+    fn test_private_functions_are_ignored() {
+        let mut helper = function("helper", &[], "void");
+        helper.modifiers = vec!["private".to_string()];
+        let old = summary(vec![helper], &[]);
+        let new = summary(vec![], &[]);
+
+        assert!(compare(&old, &new).changes.is_empty());
+    }
+}
@@ -0,0 +1,247 @@
+//! Barrel file analysis.
+//!
+//! A barrel is a module such as `index.ts` that only re-exports other modules.
+//! Barrels hide the real dependency edges and defeat tree-shaking, wildcard
+//! re-exports (`export * from`) most of all, so this reports every barrel,
+//! what it re-exports and which files import through it.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use crate::generators::context_pack::{normalize, IMPORT_CANDIDATES};
+
+/// One `export ... from '...'` statement of a barrel
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ReExport {
+    /// Module specifier as written
+    pub source: String,
+    /// Project path the specifier resolves to, when it is a project file
+    pub resolved: Option<String>,
+    /// Names re-exported under, empty for `export *`
+    pub names: Vec<String>,
+    /// `export * from`, which re-exports everything the module exports
+    pub wildcard: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BarrelInfo {
+    pub path: String,
+    pub reexports: Vec<ReExport>,
+    /// Files importing from the barrel, sorted
+    pub consumers: Vec<String>,
+}
+
+impl BarrelInfo {
+    pub fn has_wildcard(&self) -> bool {
+        self.reexports.iter().any(|reexport| reexport.wildcard)
+    }
+
+    /// Project files reachable through the barrel
+    pub fn reexported_files(&self) -> Vec<&str> {
+        self.reexports.iter().filter_map(|reexport| reexport.resolved.as_deref()).collect()
+    }
+}
+
+/// Finds the barrels among `entries`, which maps project paths to source text.
+/// A file is a barrel when it re-exports at least one module and is either
+/// named `index.*` or made up mostly of re-exports. Sorted by path.
+pub fn analyze(entries: &HashMap<String, String>) -> Vec<BarrelInfo> {
+    let modules: HashMap<&str, ModuleStatements> = entries.iter()
+        .map(|(path, content)| (path.as_str(), ModuleStatements::parse(content)))
+        .collect();
+
+    let mut barrels: Vec<BarrelInfo> = modules.iter()
+        .filter(|(path, module)| module.is_barrel(path))
+        .map(|(path, module)| {
+            let reexports = module.reexports.iter()
+                .map(|reexport| ReExport {
+                    resolved: resolve(path, &reexport.source, entries),
+                    ..reexport.clone()
+                })
+                .collect();
+
+            let mut consumers: Vec<String> = modules.iter()
+                .filter(|(other, _)| *other != path)
+                .filter(|(other, module)| {
+                    module.specifiers()
+                        .any(|specifier| resolve(other, specifier, entries).as_deref() == Some(*path))
+                })
+                .map(|(other, _)| other.to_string())
+                .collect();
+            consumers.sort();
+
+            BarrelInfo { path: path.to_string(), reexports, consumers }
+        })
+        .collect();
+
+    barrels.sort_by(|a, b| a.path.cmp(&b.path));
+    barrels
+}
+
+/// Import and re-export statements of one module
+#[derive(Debug, Default)]
+struct ModuleStatements {
+    imports: Vec<String>,
+    reexports: Vec<ReExport>,
+    /// Statements other than imports and re-exports
+    other: usize,
+}
+
+impl ModuleStatements {
+    fn parse(content: &str) -> Self {
+        let mut module = Self::default();
+
+        for statement in statements(content) {
+            let statement = statement.trim_end_matches(';').trim();
+            let Some((head, source)) = statement.rsplit_once(" from ").and_then(|(head, rest)| Some((head, quoted(rest)?))) else {
+                module.other += 1;
+                continue;
+            };
+
+            if head.starts_with("import ") {
+                module.imports.push(source.to_string());
+            } else if let Some(clause) = head.strip_prefix("export ") {
+                let clause = clause.trim().strip_prefix("type ").unwrap_or(clause.trim());
+                module.reexports.push(ReExport {
+                    source: source.to_string(),
+                    resolved: None,
+                    names: exported_names(clause),
+                    wildcard: clause == "*",
+                });
+            } else {
+                module.other += 1;
+            }
+        }
+
+        module
+    }
+
+    fn is_barrel(&self, path: &str) -> bool {
+        let is_index = Path::new(path)
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().starts_with("index."));
+        !self.reexports.is_empty() && (is_index || self.reexports.len() > self.other)
+    }
+
+    /// Every module this one imports or re-exports from
+    fn specifiers(&self) -> impl Iterator<Item = &str> {
+        self.imports.iter().map(String::as_str)
+            .chain(self.reexports.iter().map(|reexport| reexport.source.as_str()))
+    }
+}
+
+/// Top-level statements: a line, or several while braces are open, with
+/// comment lines dropped
+fn statements(content: &str) -> Vec<String> {
+    let mut statements = Vec::new();
+    let mut current = String::new();
+    let mut depth = 0i32;
+
+    for line in content.lines() {
+        let line = line.trim();
+        if current.is_empty() && (line.is_empty() || line.starts_with("//") || line.starts_with("/*") || line.starts_with('*')) {
+            continue;
+        }
+
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(line);
+        depth += line.matches('{').count() as i32 - line.matches('}').count() as i32;
+
+        if depth <= 0 {
+            statements.push(std::mem::take(&mut current));
+            depth = 0;
+        }
+    }
+    if !current.is_empty() {
+        statements.push(current);
+    }
+
+    statements
+}
+
+/// Names an export clause makes visible: `{ a, b as c }` -> `[a, c]`,
+/// `* as ns` -> `[ns]`, `*` -> `[]`
+fn exported_names(clause: &str) -> Vec<String> {
+    if let Some(namespace) = clause.strip_prefix("* as ") {
+        return vec![namespace.trim().to_string()];
+    }
+
+    clause.trim_start_matches('{').trim_end_matches('}')
+        .split(',')
+        .filter_map(|name| name.split(" as ").last())
+        .map(|name| name.trim().trim_start_matches("type ").to_string())
+        .filter(|name| !name.is_empty() && name != "*")
+        .collect()
+}
+
+/// First single- or double-quoted string in `text`
+fn quoted(text: &str) -> Option<&str> {
+    let start = text.find(['\'', '"'])?;
+    let quote = text[start..].chars().next()?;
+    let end = text[start + 1..].find(quote)?;
+    Some(&text[start + 1..start + 1 + end])
+}
+
+/// Resolves a relative specifier against the importing file to a key of `entries`
+fn resolve(from: &str, specifier: &str, entries: &HashMap<String, String>) -> Option<String> {
+    if !specifier.starts_with('.') {
+        return None;
+    }
+
+    let base = Path::new(from).parent().unwrap_or_else(|| Path::new("."));
+    let joined = normalize(&base.join(specifier));
+    let prefix = if from.starts_with("./") { "./" } else { "" };
+
+    IMPORT_CANDIDATES
+        .iter()
+        .map(|ext| format!("{}{}{}", prefix, joined, ext))
+        .find(|candidate| entries.contains_key(candidate))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project(files: &[(&str, &str)]) -> HashMap<String, String> {
+        files.iter().map(|(path, content)| (path.to_string(), content.to_string())).collect()
+    }
+
+    #[test]
+    fn test_index_reexporting_two_modules_is_a_barrel() {
+        let entries = project(&[
+            ("src/auth/index.ts", "// Public API of the auth package\nexport { AuthService, type Session } from './auth.service';\nexport * from './auth.guard';\n"),
+            ("src/auth/auth.service.ts", "export class AuthService {}\nexport interface Session {}\n"),
+            ("src/auth/auth.guard.ts", "import { AuthService } from './auth.service';\nexport class AuthGuard {}\n"),
+            ("src/app/app.component.ts", "import { AuthService } from '../auth';\nexport class AppComponent {}\n"),
+            ("src/app/login.ts", "import {\n  AuthGuard,\n} from '../auth/index';\n\nexport const login = () => new AuthGuard();\n"),
+        ]);
+
+        let barrels = analyze(&entries);
+        assert_eq!(barrels.len(), 1);
+        let barrel = &barrels[0];
+        assert_eq!(barrel.path, "src/auth/index.ts");
+        assert_eq!(barrel.reexported_files(), vec!["src/auth/auth.service.ts", "src/auth/auth.guard.ts"]);
+        assert_eq!(barrel.reexports[0].names, vec!["AuthService", "Session"]);
+        assert!(!barrel.reexports[0].wildcard);
+        assert!(barrel.reexports[1].wildcard);
+        assert!(barrel.has_wildcard());
+        assert_eq!(barrel.consumers, vec!["src/app/app.component.ts", "src/app/login.ts"]);
+    }
+
+    #[test]
+    fn test_module_with_own_code_is_not_a_barrel() {
+        let entries = project(&[
+            ("src/utils.ts", "export { clamp } from './math';\nexport function pad() {}\nexport function trim() {}\n"),
+            ("src/math.ts", "export function clamp() {}\n"),
+            ("src/shared.ts", "export * as math from './math';\nexport { clamp } from './math';\n"),
+        ]);
+
+        let barrels = analyze(&entries);
+        assert_eq!(barrels.len(), 1);
+        assert_eq!(barrels[0].path, "src/shared.ts");
+        assert_eq!(barrels[0].reexports[0].names, vec!["math"]);
+        assert!(!barrels[0].has_wildcard());
+    }
+}
@@ -0,0 +1,444 @@
+//! Token-bounded context packs for a single file.
+//!
+//! A pack bundles the file's own summary with the signatures of the files it
+//! imports, the names of the files importing it and its related tests, so it can
+//! be pasted into an LLM prompt as-is. Sections are added in that order of
+//! priority until the token budget is exhausted.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::{Component, Path, PathBuf};
+use crate::cache::SmartCache;
+use crate::generators::api_diff::signature;
+use crate::mcp::context_optimizer::ContextOptimizer;
+use crate::types::{CacheEntry, FunctionInfo};
+use crate::utils::path_normalizer::{PathNormalizer, PathStyle};
+
+/// Extensions tried when resolving extensionless relative imports
+pub(crate) const IMPORT_CANDIDATES: &[&str] = &[
+    "", ".ts", ".tsx", ".js", ".jsx", "/index.ts", "/index.js",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DependencySignatures {
+    pub path: String,
+    pub signatures: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ContextPack {
+    pub file: String,
+    pub summary: String,
+    pub dependencies: Vec<DependencySignatures>,
+    pub dependents: Vec<String>,
+    pub tests: Vec<String>,
+    pub token_budget: usize,
+    pub estimated_tokens: usize,
+    /// True when sections were dropped or shortened to fit the budget
+    pub truncated: bool,
+}
+
+impl ContextPack {
+    /// Renders the pack as `json` or, for any other format, Markdown
+    pub fn render(&self, format: &str) -> Result<String> {
+        match format {
+            "json" => Ok(serde_json::to_string_pretty(self)?),
+            _ => Ok(self.to_markdown()),
+        }
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!("# Context: {}\n\n## Summary\n\n{}\n", self.file, self.summary);
+
+        if !self.dependencies.is_empty() {
+            out.push_str("\n## Dependencies\n");
+            for dependency in &self.dependencies {
+                out.push_str(&format!("\n### {}\n\n", dependency.path));
+                for sig in &dependency.signatures {
+                    out.push_str(&format!("- `{}`\n", sig));
+                }
+            }
+        }
+
+        if !self.dependents.is_empty() {
+            out.push_str("\n## Dependents\n\n");
+            for dependent in &self.dependents {
+                out.push_str(&format!("- {}\n", dependent));
+            }
+        }
+
+        if !self.tests.is_empty() {
+            out.push_str("\n## Tests\n\n");
+            for test in &self.tests {
+                out.push_str(&format!("- {}\n", test));
+            }
+        }
+
+        out
+    }
+}
+
+pub struct ContextPackBuilder<'a> {
+    cache: &'a SmartCache,
+    optimizer: ContextOptimizer,
+    /// Paths are printed as cache keys unless a style is set
+    path_style: Option<(PathNormalizer, PathStyle)>,
+}
+
+impl<'a> ContextPackBuilder<'a> {
+    pub fn new(cache: &'a SmartCache) -> Self {
+        Self {
+            cache,
+            optimizer: ContextOptimizer::new(),
+            path_style: None,
+        }
+    }
+
+    /// Print file paths in `style`, relative to `project_root`
+    pub fn with_path_style(mut self, project_root: &Path, style: PathStyle) -> Self {
+        self.path_style = Some((PathNormalizer::new(project_root), style));
+        self
+    }
+
+    /// Builds the pack for the cache entry at `file_key`, keeping the rendered
+    /// `format` within `max_tokens` as estimated by the context optimizer.
+    /// Fails when the budget cannot hold even the pack's header.
+    pub fn build(&self, file_key: &str, max_tokens: usize, format: &str) -> Result<ContextPack> {
+        let entry = self
+            .cache
+            .get_entry(file_key)
+            .ok_or_else(|| anyhow::anyhow!("File not found in cache: {}", file_key))?;
+
+        let mut pack = ContextPack {
+            file: self.show(file_key),
+            summary: self.file_summary(entry),
+            dependencies: Vec::new(),
+            dependents: Vec::new(),
+            tests: Vec::new(),
+            token_budget: max_tokens,
+            estimated_tokens: 0,
+            truncated: false,
+        };
+
+        // The file's own summary always goes in, shortened if it alone overflows
+        while self.tokens(&pack, format)? > max_tokens && !pack.summary.is_empty() {
+            let keep = pack.summary.chars().count() * 3 / 4;
+            pack.summary = pack.summary.chars().take(keep).collect();
+            pack.truncated = true;
+        }
+        let header_tokens = self.tokens(&pack, format)?;
+        if header_tokens > max_tokens {
+            anyhow::bail!(
+                "Token budget {} is too small for the context pack of {}, which needs at least {} tokens",
+                max_tokens, pack.file, header_tokens
+            );
+        }
+
+        for mut dependency in self.dependencies(file_key, entry) {
+            dependency.path = self.show(&dependency.path);
+            pack.dependencies.push(dependency);
+            if self.tokens(&pack, format)? > max_tokens {
+                pack.dependencies.pop();
+                pack.truncated = true;
+                break;
+            }
+        }
+
+        let (tests, dependents): (Vec<String>, Vec<String>) = self
+            .dependents(file_key)
+            .into_iter()
+            .partition(|path| self.optimizer.is_test_file(path));
+
+        for dependent in dependents {
+            pack.dependents.push(self.show(&dependent));
+            if self.tokens(&pack, format)? > max_tokens {
+                pack.dependents.pop();
+                pack.truncated = true;
+                break;
+            }
+        }
+
+        for test in self.related_tests(file_key, tests) {
+            pack.tests.push(self.show(&test));
+            if self.tokens(&pack, format)? > max_tokens {
+                pack.tests.pop();
+                pack.truncated = true;
+                break;
+            }
+        }
+
+        pack.estimated_tokens = self.tokens(&pack, format)?;
+        Ok(pack)
+    }
+
+    fn show(&self, path: &str) -> String {
+        match &self.path_style {
+            Some((normalizer, style)) => normalizer.format_path(path, *style),
+            None => path.to_string(),
+        }
+    }
+
+    fn tokens(&self, pack: &ContextPack, format: &str) -> Result<usize> {
+        Ok(self.optimizer.estimate_tokens(&pack.render(format)?))
+    }
+
+    fn file_summary(&self, entry: &CacheEntry) -> String {
+        let mut lines = vec![entry.metadata.summary.clone()];
+
+        if !entry.metadata.exports.is_empty() {
+            lines.push(format!("Exports: {}", entry.metadata.exports.join(", ")));
+        }
+
+        let signatures = signatures(entry);
+        if !signatures.is_empty() {
+            lines.push(format!("Signatures:\n{}", signatures.iter()
+                .map(|s| format!("- `{}`", s))
+                .collect::<Vec<_>>()
+                .join("\n")));
+        }
+
+        lines.join("\n")
+    }
+
+    fn dependencies(&self, file_key: &str, entry: &CacheEntry) -> Vec<DependencySignatures> {
+        let mut dependencies = Vec::new();
+
+        for import in &entry.metadata.imports {
+            if let Some(path) = self.resolve_import(file_key, import) {
+                if dependencies.iter().any(|d: &DependencySignatures| d.path == path) {
+                    continue;
+                }
+                if let Some(dependency) = self.cache.get_entry(&path) {
+                    dependencies.push(DependencySignatures {
+                        signatures: signatures(dependency),
+                        path,
+                    });
+                }
+            }
+        }
+
+        dependencies
+    }
+
+    /// Files whose relative imports resolve to `file_key`, sorted by path
+    fn dependents(&self, file_key: &str) -> Vec<String> {
+        let mut dependents: Vec<String> = self
+            .cache
+            .entries
+            .iter()
+            .filter(|(key, _)| key.as_str() != file_key)
+            .filter(|(key, entry)| {
+                entry.metadata.imports.iter()
+                    .any(|import| self.resolve_import(key, import).as_deref() == Some(file_key))
+            })
+            .map(|(key, _)| key.clone())
+            .collect();
+
+        dependents.sort();
+        dependents
+    }
+
+    /// Tests importing the file plus test files named after it (`x.ts` -> `x.spec.ts`)
+    fn related_tests(&self, file_key: &str, mut tests: Vec<String>) -> Vec<String> {
+        let stem = file_stem(file_key);
+
+        for key in self.cache.entries.keys() {
+            if key != file_key
+                && self.optimizer.is_test_file(key)
+                && test_subject(key) == stem
+                && !tests.contains(key)
+            {
+                tests.push(key.clone());
+            }
+        }
+
+        tests.sort();
+        tests
+    }
+
+    /// Resolves a relative import against the importing file to an existing cache key
+    fn resolve_import(&self, from_key: &str, import: &str) -> Option<String> {
+        if !import.starts_with('.') {
+            return None;
+        }
+
+        let base = Path::new(from_key).parent().unwrap_or_else(|| Path::new("."));
+        let joined = normalize(&base.join(import));
+
+        IMPORT_CANDIDATES
+            .iter()
+            .map(|ext| format!("./{}{}", joined, ext))
+            .find(|candidate| self.cache.entries.contains_key(candidate))
+    }
+}
+
+fn signatures(entry: &CacheEntry) -> Vec<String> {
+    // Detailed analysis is richer when present; the summary is the fallback
+    let (functions, classes) = match &entry.metadata.detailed_analysis {
+        Some(analysis) => (&analysis.functions, &analysis.classes),
+        None => (&entry.summary.functions, &entry.summary.classes),
+    };
+
+    let mut signatures: Vec<String> = functions.iter().map(signature).collect();
+    for class in classes {
+        signatures.push(format!("class {}", class.name));
+        signatures.extend(class.methods.iter()
+            .filter(|m| !m.modifiers.iter().any(|modifier| modifier == "private"))
+            .map(|m: &FunctionInfo| format!("{}.{}", class.name, signature(m))));
+    }
+    signatures
+}
+
+/// Collapses `.` and `..` components, returning a path without a leading `./`
+pub(crate) fn normalize(path: &Path) -> String {
+    let mut parts: Vec<String> = Vec::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                parts.pop();
+            }
+            other => parts.push(other.as_os_str().to_string_lossy().to_string()),
+        }
+    }
+    parts.iter().collect::<PathBuf>().to_string_lossy().replace('\\', "/")
+}
+
+/// File name without its extension: `auth.service.ts` -> `auth.service`
+fn file_stem(path: &str) -> String {
+    Path::new(path).file_stem().map(|n| n.to_string_lossy().to_string()).unwrap_or_default()
+}
+
+/// Stem of the file a test is named after: `auth.service.spec.ts` -> `auth.service`
+fn test_subject(path: &str) -> String {
+    let stem = file_stem(path);
+    [".spec", ".test", "_spec", "_test"].iter()
+        .find_map(|suffix| stem.strip_suffix(suffix))
+        .unwrap_or(&stem)
+        .to_string()
+}
+
+#[cfg(all(test, feature = "typescript"))]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+    use std::fs;
+    use crate::cache::CacheManager;
+
+    fn analyzed_project() -> Result<(TempDir, CacheManager)> {
+        let temp_dir = TempDir::new()?;
+        fs::create_dir_all(temp_dir.path().join("src/app"))?;
+        fs::write(temp_dir.path().join("src/app/user.service.ts"), r#"
+import { Injectable } from '@angular/core';
+
+@Injectable()
+export class UserService {
+    getUser(id: number): string {
+        return 'user';
+    }
+}
+"#)?;
+        fs::write(temp_dir.path().join("src/app/auth.service.ts"), r#"
+import { Injectable } from '@angular/core';
+import { UserService } from './user.service';
+
+@Injectable()
+export class AuthService {
+    constructor(private users: UserService) {}
+
+    login(name: string, password: string): boolean {
+        return true;
+    }
+}
+"#)?;
+        fs::write(temp_dir.path().join("src/app/login.component.ts"), r#"
+import { Component } from '@angular/core';
+import { AuthService } from './auth.service';
+
+@Component({ selector: 'app-login', template: '' })
+export class LoginComponent {
+    constructor(private auth: AuthService) {}
+}
+"#)?;
+        fs::write(temp_dir.path().join("src/app/auth.service.spec.ts"), r#"
+import { AuthService } from './auth.service';
+
+describe('AuthService', () => {
+    it('logs in', () => {});
+});
+"#)?;
+
+        let mut cache_manager = CacheManager::new(temp_dir.path())?;
+        cache_manager.analyze_project(temp_dir.path(), false)?;
+        Ok((temp_dir, cache_manager))
+    }
+
+    #[test]
+    fn test_context_pack_collects_related_files() -> Result<()> {
+        let (_temp_dir, cache_manager) = analyzed_project()?;
+        let builder = ContextPackBuilder::new(cache_manager.get_cache());
+
+        let pack = builder.build("./src/app/auth.service.ts", 10_000, "markdown")?;
+        assert!(!pack.truncated);
+        assert_eq!(pack.dependencies.len(), 1);
+        assert_eq!(pack.dependencies[0].path, "./src/app/user.service.ts");
+        assert_eq!(pack.dependents, vec!["./src/app/login.component.ts".to_string()]);
+        assert_eq!(pack.tests, vec!["./src/app/auth.service.spec.ts".to_string()]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_context_pack_stays_within_budget() -> Result<()> {
+        let (_temp_dir, cache_manager) = analyzed_project()?;
+        let builder = ContextPackBuilder::new(cache_manager.get_cache());
+        let entry = cache_manager.get_file_summary("./src/app/auth.service.ts").unwrap();
+        let optimizer = ContextOptimizer::new();
+
+        for format in ["markdown", "json"] {
+            let full = builder.build("./src/app/auth.service.ts", 10_000, format)?;
+            let budget = full.estimated_tokens / 2;
+
+            let pack = builder.build("./src/app/auth.service.ts", budget, format)?;
+            let rendered = pack.render(format)?;
+            assert!(optimizer.estimate_tokens(&rendered) <= budget);
+            assert_eq!(pack.estimated_tokens, optimizer.estimate_tokens(&rendered));
+            assert!(pack.truncated);
+            assert!(pack.summary.contains(&entry.metadata.summary));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_context_pack_rejects_a_budget_below_the_header() -> Result<()> {
+        let (_temp_dir, cache_manager) = analyzed_project()?;
+        let builder = ContextPackBuilder::new(cache_manager.get_cache());
+
+        for format in ["markdown", "json"] {
+            let error = builder.build("./src/app/auth.service.ts", 1, format).unwrap_err();
+            assert!(error.to_string().contains("too small"), "{}", error);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_related_tests_match_the_full_stem() -> Result<()> {
+        let (temp_dir, _) = analyzed_project()?;
+        fs::write(temp_dir.path().join("src/app/user.service.spec.ts"), "describe('UserService', () => {});\n")?;
+        fs::write(temp_dir.path().join("src/app/user_settings.spec.ts"), "describe('UserSettings', () => {});\n")?;
+        fs::write(temp_dir.path().join("src/app/user.component.spec.ts"), "describe('UserComponent', () => {});\n")?;
+        let mut cache_manager = CacheManager::new(temp_dir.path())?;
+        cache_manager.analyze_project(temp_dir.path(), false)?;
+
+        let pack = ContextPackBuilder::new(cache_manager.get_cache()).build("./src/app/user.service.ts", 10_000, "markdown")?;
+        assert_eq!(pack.tests, vec!["./src/app/user.service.spec.ts".to_string()]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_context_pack_unknown_file() -> Result<()> {
+        let (_temp_dir, cache_manager) = analyzed_project()?;
+        let builder = ContextPackBuilder::new(cache_manager.get_cache());
+        assert!(builder.build("./src/app/missing.ts", 1_000, "markdown").is_err());
+        Ok(())
+    }
+}
@@ -1,5 +1,11 @@
 pub mod project_overview;
 pub mod report_generator;
+pub mod api_diff;
+pub mod barrels;
+pub mod context_pack;
+pub mod naming;
+pub mod overview_delta;
+pub mod recommendations;
 
 pub use project_overview::*;
 pub use report_generator::*;
\ No newline at end of file
@@ -0,0 +1,292 @@
+//! Naming convention checks.
+//!
+//! Compares the symbol names the analyzers extracted with the convention
+//! expected for their kind in their language, e.g. `snake_case` functions in
+//! Rust or kebab-case component selectors in Angular, and reports every name
+//! that does not follow it.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+use crate::analyzers::AnalyzerLanguage;
+use crate::cache::SmartCache;
+use crate::types::{DetailedAnalysis, LocationInfo};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum NamingConvention {
+    Snake,
+    ScreamingSnake,
+    Camel,
+    Pascal,
+    Kebab,
+}
+
+impl NamingConvention {
+    /// Whether `name` follows the convention. Leading and trailing
+    /// underscores, which mark unused or private names, are ignored.
+    pub fn matches(self, name: &str) -> bool {
+        let name = name.strip_prefix("r#").unwrap_or(name).trim_matches('_');
+        let Some(first) = name.chars().next() else {
+            return true;
+        };
+
+        match self {
+            Self::Snake => name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_'),
+            Self::ScreamingSnake => name.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_'),
+            Self::Camel => first.is_ascii_lowercase() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '$'),
+            Self::Pascal => first.is_ascii_uppercase() && name.chars().all(|c| c.is_ascii_alphanumeric()),
+            Self::Kebab => name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-'),
+        }
+    }
+}
+
+impl fmt::Display for NamingConvention {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Snake => "snake_case",
+            Self::ScreamingSnake => "SCREAMING_SNAKE_CASE",
+            Self::Camel => "camelCase",
+            Self::Pascal => "PascalCase",
+            Self::Kebab => "kebab-case",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum NameKind {
+    /// Functions and methods
+    Function,
+    /// Classes, structs, interfaces, enums, traits and type aliases
+    Type,
+    Constant,
+    /// Angular component selectors
+    Selector,
+}
+
+/// Expected convention per kind of name in one language; `None` leaves that
+/// kind unchecked
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct LanguageConventions {
+    pub functions: Option<NamingConvention>,
+    pub types: Option<NamingConvention>,
+    pub constants: Option<NamingConvention>,
+    pub selectors: Option<NamingConvention>,
+}
+
+impl LanguageConventions {
+    pub fn rust() -> Self {
+        Self {
+            functions: Some(NamingConvention::Snake),
+            types: Some(NamingConvention::Pascal),
+            constants: Some(NamingConvention::ScreamingSnake),
+            selectors: None,
+        }
+    }
+
+    pub fn typescript() -> Self {
+        Self {
+            functions: Some(NamingConvention::Camel),
+            types: Some(NamingConvention::Pascal),
+            // `const` holds module constants and plain locals alike
+            constants: None,
+            selectors: Some(NamingConvention::Kebab),
+        }
+    }
+
+    fn expected(&self, kind: NameKind) -> Option<NamingConvention> {
+        match kind {
+            NameKind::Function => self.functions,
+            NameKind::Type => self.types,
+            NameKind::Constant => self.constants,
+            NameKind::Selector => self.selectors,
+        }
+    }
+}
+
+/// A name that does not follow the convention expected for its kind
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct NamingViolation {
+    pub path: String,
+    pub line: usize,
+    pub name: String,
+    pub kind: NameKind,
+    pub expected: NamingConvention,
+}
+
+impl fmt::Display for NamingViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self.kind {
+            NameKind::Function => "Function",
+            NameKind::Type => "Type",
+            NameKind::Constant => "Constant",
+            NameKind::Selector => "Selector",
+        };
+        write!(f, "{} `{}` in {}:{} should be {}", kind, self.name, self.path, self.line, self.expected)
+    }
+}
+
+/// Expected conventions for each analyzed language
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct NamingConventions {
+    pub rust: LanguageConventions,
+    pub typescript: LanguageConventions,
+}
+
+impl Default for NamingConventions {
+    fn default() -> Self {
+        Self {
+            rust: LanguageConventions::rust(),
+            typescript: LanguageConventions::typescript(),
+        }
+    }
+}
+
+impl NamingConventions {
+    pub fn for_language(&self, language: AnalyzerLanguage) -> &LanguageConventions {
+        match language {
+            AnalyzerLanguage::Rust => &self.rust,
+            AnalyzerLanguage::TypeScript => &self.typescript,
+        }
+    }
+
+    /// Violations in every cached file, ordered by path, then line
+    pub fn check(&self, cache: &SmartCache) -> Vec<NamingViolation> {
+        let mut paths: Vec<&String> = cache.entries.keys().collect();
+        paths.sort();
+
+        paths.into_iter()
+            .filter_map(|path| Some((path, cache.entries[path].metadata.detailed_analysis.as_ref()?)))
+            .flat_map(|(path, analysis)| self.check_file(path, analysis))
+            .collect()
+    }
+
+    /// Violations among the names extracted from one file, ordered by line
+    pub fn check_file(&self, path: &str, analysis: &DetailedAnalysis) -> Vec<NamingViolation> {
+        let Some(language) = AnalyzerLanguage::for_path(Path::new(path)) else {
+            return Vec::new();
+        };
+        let conventions = self.for_language(language);
+
+        let mut violations: Vec<NamingViolation> = named_symbols(analysis).into_iter()
+            .filter_map(|(kind, name, location)| {
+                let expected = conventions.expected(kind)?;
+                (!expected.matches(name)).then(|| NamingViolation {
+                    path: path.to_string(),
+                    line: location.line,
+                    name: name.to_string(),
+                    kind,
+                    expected,
+                })
+            })
+            .collect();
+
+        violations.sort_by(|a, b| a.line.cmp(&b.line).then_with(|| a.name.cmp(&b.name)));
+        // Analyzers may report a symbol in more than one collection
+        violations.dedup();
+        violations
+    }
+}
+
+/// Every name `analysis` holds, with its kind and where it is declared
+fn named_symbols(analysis: &DetailedAnalysis) -> Vec<(NameKind, &str, &LocationInfo)> {
+    let mut symbols = Vec::new();
+
+    for function in &analysis.functions {
+        symbols.push((NameKind::Function, function.name.as_str(), &function.location));
+    }
+    for class in &analysis.classes {
+        symbols.push((NameKind::Type, class.name.as_str(), &class.location));
+        for method in class.methods.iter().filter(|method| method.name != "constructor") {
+            symbols.push((NameKind::Function, method.name.as_str(), &method.location));
+        }
+    }
+    for interface in &analysis.interfaces {
+        symbols.push((NameKind::Type, interface.name.as_str(), &interface.location));
+    }
+    for enumeration in &analysis.enums {
+        symbols.push((NameKind::Type, enumeration.name.as_str(), &enumeration.location));
+    }
+    for alias in &analysis.types {
+        symbols.push((NameKind::Type, alias.name.as_str(), &alias.location));
+    }
+    if let Some(component) = &analysis.component_info {
+        if !component.selector.is_empty() {
+            symbols.push((NameKind::Selector, component.selector.as_str(), &component.location));
+        }
+    }
+
+    if let Some(module) = &analysis.rust_module {
+        for function in &module.functions {
+            symbols.push((NameKind::Function, function.name.as_str(), &function.location));
+        }
+        // Trait impls take their method names from the trait
+        for block in module.impl_blocks.iter().filter(|block| block.trait_name.is_none()) {
+            for method in &block.methods {
+                symbols.push((NameKind::Function, method.name.as_str(), &method.location));
+            }
+        }
+        for item in &module.structs {
+            symbols.push((NameKind::Type, item.name.as_str(), &item.location));
+        }
+        for item in &module.enums {
+            symbols.push((NameKind::Type, item.name.as_str(), &item.location));
+        }
+        for item in &module.traits {
+            symbols.push((NameKind::Type, item.name.as_str(), &item.location));
+        }
+        for item in &module.type_aliases {
+            symbols.push((NameKind::Type, item.name.as_str(), &item.location));
+        }
+        for item in &module.constants {
+            symbols.push((NameKind::Constant, item.name.as_str(), &item.location));
+        }
+    }
+
+    symbols
+}
+
+#[cfg(all(test, feature = "rust"))]
+mod tests {
+    use super::*;
+    use crate::analyzers::FileAnalyzer;
+
+    #[test]
+    fn test_camel_case_rust_function_is_flagged() {
+        let source = r#"
+pub const MAX_RETRIES: u32 = 3;
+
+pub struct Fetcher;
+
+pub fn fetch_all() {}
+
+pub fn fetchOne() {}
+
+pub fn parse_config() {}
+
+fn loadFile() {}
+"#;
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("fetch.rs");
+        std::fs::write(&path, source).unwrap();
+        let metadata = FileAnalyzer::new().analyze_file(&path).unwrap();
+        let analysis = metadata.detailed_analysis.unwrap();
+
+        let violations = NamingConventions::default().check_file("src/fetch.rs", &analysis);
+        let names: Vec<&str> = violations.iter().map(|violation| violation.name.as_str()).collect();
+        assert_eq!(names, vec!["fetchOne", "loadFile"]);
+        assert!(violations.iter().all(|violation| violation.kind == NameKind::Function
+            && violation.expected == NamingConvention::Snake));
+        assert!(violations[1].to_string().starts_with("Function `loadFile` in src/fetch.rs:"));
+
+        let lenient = NamingConventions {
+            rust: LanguageConventions { functions: Some(NamingConvention::Camel), ..LanguageConventions::rust() },
+            ..NamingConventions::default()
+        };
+        let names: Vec<String> = lenient.check_file("src/fetch.rs", &analysis).into_iter().map(|violation| violation.name).collect();
+        assert_eq!(names, vec!["fetch_all", "parse_config"]);
+    }
+}
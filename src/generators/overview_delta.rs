@@ -0,0 +1,219 @@
+//! Architecture changes between two overviews of the same project.
+//!
+//! `overview --since <ref>` compares the current overview with the snapshot
+//! stored when an unfocused `overview` last ran on a clean checkout of `ref`,
+//! so CI can post what changed instead of the whole overview.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::path::Path;
+use crate::types::{ProjectOverview, RouteSummary, ScopeViolation};
+use crate::utils::GitUtils;
+
+/// Names present in only one of the two overviews, sorted
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct NameDelta {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+impl NameDelta {
+    fn between<'a>(previous: impl Iterator<Item = &'a str>, current: impl Iterator<Item = &'a str>) -> Self {
+        let previous: BTreeSet<&str> = previous.collect();
+        let current: BTreeSet<&str> = current.collect();
+        Self {
+            added: current.difference(&previous).map(|name| name.to_string()).collect(),
+            removed: previous.difference(&current).map(|name| name.to_string()).collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ProjectOverviewDelta {
+    /// Reference the previous overview was taken at
+    pub since: String,
+    pub components: NameDelta,
+    pub services: NameDelta,
+    /// Routes as `path -> component`
+    pub routes: NameDelta,
+    /// Scope violations the previous overview did not have
+    pub new_scope_violations: Vec<ScopeViolation>,
+}
+
+impl ProjectOverviewDelta {
+    pub fn between(since: &str, previous: &ProjectOverview, current: &ProjectOverview) -> Self {
+        let (old, new) = (&previous.structure, &current.structure);
+        let old_routes: Vec<String> = old.routes.iter().map(route_label).collect();
+        let new_routes: Vec<String> = new.routes.iter().map(route_label).collect();
+        let old_violations = &old.module_analysis.service_scope_analysis.scope_violations;
+
+        Self {
+            since: since.to_string(),
+            components: NameDelta::between(
+                old.components.iter().map(|component| component.name.as_str()),
+                new.components.iter().map(|component| component.name.as_str()),
+            ),
+            services: NameDelta::between(
+                old.services.iter().map(|service| service.name.as_str()),
+                new.services.iter().map(|service| service.name.as_str()),
+            ),
+            routes: NameDelta::between(old_routes.iter().map(String::as_str), new_routes.iter().map(String::as_str)),
+            new_scope_violations: new.module_analysis.service_scope_analysis.scope_violations.iter()
+                .filter(|violation| !old_violations.contains(violation))
+                .cloned()
+                .collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.components.is_empty() && self.services.is_empty() && self.routes.is_empty()
+            && self.new_scope_violations.is_empty()
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut report = format!("# 📊 Architecture changes since {}\n\n", self.since);
+        if self.is_empty() {
+            report.push_str("No components, services, routes or scope violations changed.\n");
+            return report;
+        }
+
+        for (title, delta) in [("Components", &self.components), ("Services", &self.services), ("Routes", &self.routes)] {
+            if delta.is_empty() {
+                continue;
+            }
+            report.push_str(&format!("## {}\n\n", title));
+            delta.added.iter().for_each(|name| report.push_str(&format!("- ➕ {}\n", name)));
+            delta.removed.iter().for_each(|name| report.push_str(&format!("- ➖ {}\n", name)));
+            report.push('\n');
+        }
+
+        if !self.new_scope_violations.is_empty() {
+            report.push_str("## New scope violations\n\n");
+            for violation in &self.new_scope_violations {
+                report.push_str(&format!("- ⚠️ {}: {}\n", violation.service_name, violation.description));
+            }
+        }
+        report
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut report = format!("Architecture changes since {}\n", self.since);
+        if self.is_empty() {
+            report.push_str("  none\n");
+            return report;
+        }
+
+        for (title, delta) in [("Components", &self.components), ("Services", &self.services), ("Routes", &self.routes)] {
+            delta.added.iter().for_each(|name| report.push_str(&format!("  + {}: {}\n", title, name)));
+            delta.removed.iter().for_each(|name| report.push_str(&format!("  - {}: {}\n", title, name)));
+        }
+        for violation in &self.new_scope_violations {
+            report.push_str(&format!("  ! Scope violation in {}: {}\n", violation.service_name, violation.description));
+        }
+        report
+    }
+}
+
+/// Snapshot key of `reference`: the commit it names when the project is a git
+/// repository, else the reference itself
+pub fn snapshot_key(project_path: &Path, reference: &str) -> String {
+    GitUtils::new(project_path)
+        .and_then(|git| git.resolve_commit(reference))
+        .unwrap_or_else(|_| reference.to_string())
+}
+
+fn route_label(route: &RouteSummary) -> String {
+    if route.component.is_empty() {
+        route.path.clone()
+    } else {
+        format!("{} -> {}", route.path, route.component)
+    }
+}
+
+#[cfg(all(test, feature = "typescript"))]
+mod tests {
+    use super::*;
+    use crate::cache::CacheManager;
+    use crate::generators::ProjectOverviewGenerator;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn write_component(root: &Path, file: &str, class: &str, selector: &str) {
+        let path = root.join("src/app").join(file);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, format!(
+            "import {{ Component }} from '@angular/core';\n\n@Component({{\n  selector: '{}',\n  template: '<p></p>'\n}})\nexport class {} {{}}\n",
+            selector, class
+        )).unwrap();
+    }
+
+    #[test]
+    fn test_added_component_appears_in_delta() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        write_component(root, "header.component.ts", "HeaderComponent", "app-header");
+
+        let mut cache_manager = CacheManager::new(root).unwrap();
+        cache_manager.analyze_project(root, false).unwrap();
+        let mut generator = ProjectOverviewGenerator::new(cache_manager);
+        let before = generator.generate_overview(root).unwrap();
+        let key = generator.save_snapshot(root, &before).unwrap();
+        assert_eq!(key.as_deref(), Some("HEAD"));
+
+        write_component(root, "footer.component.ts", "FooterComponent", "app-footer");
+        let mut cache_manager = CacheManager::new(root).unwrap();
+        cache_manager.analyze_project(root, false).unwrap();
+        let generator = ProjectOverviewGenerator::new(cache_manager);
+        let after = generator.generate_overview(root).unwrap();
+
+        let delta = generator.delta_since(root, "HEAD", &after).unwrap();
+        assert_eq!(delta.components.added, vec!["FooterComponent"]);
+        assert!(delta.components.removed.is_empty());
+        assert!(delta.services.is_empty());
+        assert!(delta.to_text().contains("+ Components: FooterComponent"));
+
+        assert!(generator.delta_since(root, "v0.1.0", &after).is_err());
+
+        let mut cache_manager = CacheManager::new_with_focus(root, Some(Path::new("src/app"))).unwrap();
+        cache_manager.analyze_project(root, false).unwrap();
+        let generator = ProjectOverviewGenerator::new(cache_manager);
+        let focused = generator.generate_overview(root).unwrap();
+        assert!(generator.delta_since(root, "HEAD", &focused).is_err());
+    }
+
+    #[test]
+    fn test_snapshot_is_only_saved_for_a_clean_unfocused_tree() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        write_component(root, "header.component.ts", "HeaderComponent", "app-header");
+        let repo = git2::Repository::init(root).unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_all(["src"], git2::IndexAddOption::DEFAULT, None).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = git2::Signature::now("dev", "dev@example.com").unwrap();
+        let commit = repo.commit(Some("HEAD"), &signature, &signature, "init", &tree, &[]).unwrap();
+
+        let snapshot = |focus: Option<&Path>| {
+            let mut cache_manager = CacheManager::new_with_focus(root, focus).unwrap();
+            cache_manager.analyze_project(root, false).unwrap();
+            let mut generator = ProjectOverviewGenerator::new(cache_manager);
+            let overview = generator.generate_overview(root).unwrap();
+            generator.save_snapshot(root, &overview).unwrap()
+        };
+
+        // The cache written next to the sources doesn't make the tree dirty
+        assert_eq!(snapshot(None), Some(commit.to_string()));
+        assert_eq!(snapshot(Some(Path::new("src/app"))), None);
+
+        write_component(root, "header.component.ts", "HeaderComponent", "app-page-header");
+        assert_eq!(snapshot(None), None);
+        write_component(root, "header.component.ts", "HeaderComponent", "app-header");
+        write_component(root, "footer.component.ts", "FooterComponent", "app-footer");
+        assert_eq!(snapshot(None), None);
+    }
+}
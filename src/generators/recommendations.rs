@@ -0,0 +1,313 @@
+use crate::cache::SmartCache;
+use crate::generators::naming::NamingConventions;
+use crate::ml::models::{CodeLocation, EffortLevel, RefactoringOpportunity, RefactoringType};
+use crate::types::{Complexity, DetailedAnalysis, FileType, FunctionInfo, ProjectStructure, RoutingAnalysis, ScopeViolation};
+
+/// Rules-based advice for `ProjectOverview::recommendations`. Every
+/// recommendation names the route, service or file it is about.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecommendationEngine {
+    /// Files with high complexity above this many lines should be split (default 300)
+    pub max_file_lines: usize,
+    /// Minimum share of test files before suggesting more tests (default 0.3)
+    pub min_test_ratio: f64,
+    /// Total analyzed source size that suggests lazy loading (default 5 MB)
+    pub max_total_bytes: u64,
+    /// Functions taking more parameters than this should take a parameter object (default 5)
+    pub max_parameters: usize,
+    /// Expected naming convention per language and kind of name
+    pub naming: NamingConventions,
+}
+
+impl Default for RecommendationEngine {
+    fn default() -> Self {
+        Self {
+            max_file_lines: 300,
+            min_test_ratio: 0.3,
+            max_total_bytes: 5 * 1024 * 1024,
+            max_parameters: 5,
+            naming: NamingConventions::default(),
+        }
+    }
+}
+
+impl RecommendationEngine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn generate(&self, structure: &ProjectStructure, cache: &SmartCache) -> Vec<String> {
+        let mut recommendations = Vec::new();
+        recommendations.extend(self.unguarded_lazy_routes(&structure.routing_analysis));
+        recommendations.extend(self.route_conflicts(&structure.routing_analysis));
+        recommendations.extend(self.scope_violations(&structure.module_analysis.service_scope_analysis.scope_violations));
+        recommendations.extend(self.complex_files(cache));
+        recommendations.extend(self.test_coverage(cache));
+        recommendations.extend(self.bundle_size(cache));
+        recommendations.extend(self.any_types(cache));
+        recommendations.extend(self.parameter_objects(cache).into_iter().map(|opportunity| opportunity.description));
+        recommendations.extend(self.naming.check(cache).iter().map(ToString::to_string));
+        recommendations.extend(self.anti_patterns(cache));
+        recommendations
+    }
+
+    /// Functions and methods of every cached file with more than
+    /// `max_parameters` parameters, ordered by path, then line
+    pub fn parameter_objects(&self, cache: &SmartCache) -> Vec<RefactoringOpportunity> {
+        let mut paths: Vec<&String> = cache.entries.keys().collect();
+        paths.sort();
+
+        paths.into_iter()
+            .filter_map(|path| Some((path, cache.entries[path].metadata.detailed_analysis.as_ref()?)))
+            .flat_map(|(path, analysis)| self.large_parameter_lists(path, analysis))
+            .collect()
+    }
+
+    /// Functions and methods of one file with more than `max_parameters`
+    /// parameters, each suggested as a parameter object
+    pub fn large_parameter_lists(&self, path: &str, analysis: &DetailedAnalysis) -> Vec<RefactoringOpportunity> {
+        let class_methods = analysis.classes.iter()
+            .flat_map(|class| class.methods.iter().map(move |method| (Some(class.name.as_str()), method)));
+        let rust_functions = analysis.rust_module.iter().flat_map(|module| {
+            module.functions.iter().map(|function| (None, function))
+                .chain(module.impl_blocks.iter()
+                    .flat_map(|block| block.methods.iter().map(move |method| (Some(block.target_type.as_str()), method))))
+        });
+
+        let mut seen: Vec<&FunctionInfo> = Vec::new();
+        let mut opportunities = Vec::new();
+        for (class_name, function) in class_methods.chain(rust_functions).chain(analysis.functions.iter().map(|function| (None, function))) {
+            // Analyzers may report a function in more than one collection;
+            // the first sighting, with its container, wins
+            if function.parameters.len() <= self.max_parameters || seen.contains(&function) {
+                continue;
+            }
+            seen.push(function);
+
+            let qualified = match class_name {
+                Some(class_name) => format!("{}.{}", class_name, function.name),
+                None => function.name.clone(),
+            };
+            opportunities.push(RefactoringOpportunity {
+                opportunity_type: RefactoringType::ExtractClass,
+                description: format!(
+                    "{} in {}:{} takes {} parameters (limit {}); consider grouping them into a parameter object",
+                    qualified, path, function.location.line, function.parameters.len(), self.max_parameters
+                ),
+                locations: vec![CodeLocation {
+                    file_path: path.to_string(),
+                    line_start: function.location.line,
+                    line_end: function.location.line,
+                    function_name: Some(function.name.clone()),
+                    class_name: class_name.map(str::to_string),
+                }],
+                expected_benefit: "Shorter signatures and call sites that name their arguments".to_string(),
+                effort_estimate: EffortLevel::Low,
+            });
+        }
+
+        opportunities.sort_by_key(|opportunity| opportunity.locations[0].line_start);
+        opportunities
+    }
+
+    fn unguarded_lazy_routes(&self, routing: &RoutingAnalysis) -> Option<String> {
+        let unguarded: Vec<&str> = routing.lazy_routes.iter()
+            .filter(|route| route.guards.is_empty())
+            .map(|route| route.path.as_str())
+            .collect();
+        if unguarded.is_empty() {
+            return None;
+        }
+
+        Some(format!(
+            "{} lazy route{} lack{} guards: {}",
+            unguarded.len(),
+            if unguarded.len() == 1 { "" } else { "s" },
+            if unguarded.len() == 1 { "s" } else { "" },
+            unguarded.join(", ")
+        ))
+    }
+
+    fn route_conflicts(&self, routing: &RoutingAnalysis) -> Vec<String> {
+        routing.conflicts.iter()
+            .map(|conflict| format!(
+                "Route '{}' in {} is declared by {}; only the first one is reachable",
+                conflict.path, conflict.file, conflict.components.join(", ")
+            ))
+            .collect()
+    }
+
+    fn scope_violations(&self, violations: &[ScopeViolation]) -> Vec<String> {
+        violations.iter()
+            .map(|violation| format!(
+                "Service {} has a scope violation: {}. {}",
+                violation.service_name, violation.description, violation.recommended_fix
+            ))
+            .collect()
+    }
+
+    fn complex_files(&self, cache: &SmartCache) -> Vec<String> {
+        let mut files: Vec<(&str, usize)> = cache.entries.values()
+            .filter(|entry| entry.metadata.line_count > self.max_file_lines
+                && matches!(entry.metadata.complexity, Complexity::High))
+            .map(|entry| (entry.metadata.path.as_str(), entry.metadata.line_count))
+            .collect();
+        files.sort();
+
+        files.into_iter()
+            .map(|(path, lines)| format!(
+                "File {} exceeds complexity threshold ({} lines, high complexity); consider splitting it into smaller modules",
+                path, lines
+            ))
+            .collect()
+    }
+
+    /// Test advice naming the largest source files as the place to start
+    fn test_coverage(&self, cache: &SmartCache) -> Option<String> {
+        let total_files = cache.entries.len();
+        let mut sources: Vec<(&str, usize)> = cache.entries.iter()
+            .filter(|(_, entry)| !matches!(entry.metadata.file_type, FileType::Test))
+            .map(|(path, entry)| (path.as_str(), entry.metadata.line_count))
+            .collect();
+        let test_files = total_files - sources.len();
+        sources.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        let largest = sources.iter().take(3).map(|(path, _)| *path).collect::<Vec<_>>().join(", ");
+
+        if total_files == 0 {
+            None
+        } else if test_files == 0 {
+            Some(format!("No test files found; add unit tests, starting with the largest files: {}", largest))
+        } else if (test_files as f64 / total_files as f64) < self.min_test_ratio {
+            Some(format!(
+                "Only {} of {} files are tests; consider increasing test coverage, starting with the largest files: {}",
+                test_files, total_files, largest
+            ))
+        } else {
+            None
+        }
+    }
+
+    fn bundle_size(&self, cache: &SmartCache) -> Option<String> {
+        let total_size: u64 = cache.entries.values().map(|entry| entry.metadata.size).sum();
+        (total_size > self.max_total_bytes).then(|| format!(
+            "Analyzed sources total {:.1} MB; consider implementing lazy loading to reduce initial bundle size",
+            total_size as f64 / 1024.0 / 1024.0
+        ))
+    }
+
+    /// Anti-patterns the analyzers recorded, ordered by path, then line
+    fn anti_patterns(&self, cache: &SmartCache) -> Vec<String> {
+        let mut paths: Vec<&String> = cache.entries.keys().collect();
+        paths.sort();
+
+        paths.into_iter()
+            .filter_map(|path| Some((path, cache.entries[path].metadata.detailed_analysis.as_ref()?)))
+            .flat_map(|(path, analysis)| analysis.anti_patterns.iter().map(move |pattern| {
+                let location = pattern.locations.first();
+                let entity = location
+                    .map(|location| match (&location.class_name, &location.function_name) {
+                        (Some(class_name), Some(function_name)) => format!(" ({}.{})", class_name, function_name),
+                        (Some(name), None) | (None, Some(name)) => format!(" ({})", name),
+                        (None, None) => String::new(),
+                    })
+                    .unwrap_or_default();
+                format!(
+                    "{} in {}:{}{}: {}. {}",
+                    pattern.pattern_name,
+                    path,
+                    location.map_or(1, |location| location.line_start),
+                    entity,
+                    pattern.description,
+                    pattern.fix_suggestion
+                )
+            }))
+            .collect()
+    }
+
+    fn any_types(&self, cache: &SmartCache) -> Option<String> {
+        let mut files: Vec<&str> = cache.entries.values()
+            .filter(|entry| entry.metadata.summary.contains(": any"))
+            .map(|entry| entry.metadata.path.as_str())
+            .collect();
+        if files.is_empty() {
+            return None;
+        }
+        files.sort();
+
+        Some(format!(
+            "Replace 'any' types with specific TypeScript interfaces in {}",
+            files.join(", ")
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "rust")]
+    #[test]
+    fn test_functions_over_parameter_limit_are_flagged() {
+        use crate::analyzers::FileAnalyzer;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("billing.rs");
+        std::fs::write(&path, "pub fn charge(a: u32, b: u32) -> u32 {\n    a + b\n}\n\npub fn invoice(customer: &str, amount: u64, currency: &str, due_days: u32, note: Option<String>, draft: bool) {\n}\n").unwrap();
+
+        let metadata = FileAnalyzer::new().analyze_file(&path).unwrap();
+        let engine = RecommendationEngine { max_parameters: 5, ..RecommendationEngine::default() };
+        let opportunities = engine.large_parameter_lists("src/billing.rs", metadata.detailed_analysis.as_ref().unwrap());
+
+        assert_eq!(opportunities.len(), 1);
+        let opportunity = &opportunities[0];
+        assert_eq!(opportunity.opportunity_type, RefactoringType::ExtractClass);
+        assert_eq!(opportunity.locations[0].function_name.as_deref(), Some("invoice"));
+        assert_eq!(opportunity.locations[0].file_path, "src/billing.rs");
+        assert_eq!(opportunity.locations[0].line_start, 5);
+        assert!(opportunity.description.contains("takes 6 parameters"), "{}", opportunity.description);
+    }
+
+    #[test]
+    fn test_missing_tests_recommendation_names_the_largest_files() -> anyhow::Result<()> {
+        let temp_dir = tempfile::TempDir::new()?;
+        let files = [("src/billing.rs", 40), ("src/lib.rs", 2), ("src/ledger.rs", 90), ("src/tax.rs", 25), ("src/fx.rs", 10)];
+        for (file, lines) in files {
+            let path = temp_dir.path().join(file);
+            std::fs::create_dir_all(path.parent().unwrap())?;
+            std::fs::write(&path, "// line\n".repeat(lines))?;
+        }
+        let mut cache_manager = crate::cache::CacheManager::new(temp_dir.path())?;
+        cache_manager.analyze_project(temp_dir.path(), false)?;
+
+        let recommendation = RecommendationEngine::new().test_coverage(cache_manager.get_cache()).unwrap();
+        assert!(recommendation.starts_with("No test files found"), "{}", recommendation);
+        assert!(recommendation.ends_with(": ./src/ledger.rs, ./src/billing.rs, ./src/tax.rs"), "{}", recommendation);
+        Ok(())
+    }
+
+    #[cfg(feature = "typescript")]
+    #[test]
+    fn test_leaked_subscription_is_recommended_with_its_location() -> anyhow::Result<()> {
+        let temp_dir = tempfile::TempDir::new()?;
+        let path = temp_dir.path().join("src/app/user-list.component.ts");
+        std::fs::create_dir_all(path.parent().unwrap())?;
+        std::fs::write(&path, r#"
+@Component({ selector: 'app-user-list', template: '' })
+export class UserListComponent implements OnInit {
+    constructor(private users: UserService) {}
+
+    ngOnInit(): void {
+        this.users.getAll().subscribe(list => this.list = list);
+    }
+}
+"#)?;
+        let mut cache_manager = crate::cache::CacheManager::new(temp_dir.path())?;
+        cache_manager.analyze_file(&path)?;
+
+        let recommendations = RecommendationEngine::new().anti_patterns(cache_manager.get_cache());
+        assert_eq!(recommendations.len(), 1, "{:?}", recommendations);
+        assert!(recommendations[0].starts_with("Unmanaged RxJS subscription in "), "{}", recommendations[0]);
+        assert!(recommendations[0].contains("src/app/user-list.component.ts:7 (UserListComponent.ngOnInit)"), "{}", recommendations[0]);
+        Ok(())
+    }
+}
@@ -1,5 +1,29 @@
 use anyhow::Result;
+use serde::Serialize;
+use std::path::Path;
+use crate::ml::models::FileImpactAnalysis;
 use crate::types::*;
+use crate::utils::detect_file_type;
+
+/// Share of a file's review risk that comes from its impact score; the rest
+/// comes from the size of the change
+const REVIEW_IMPACT_WEIGHT: f32 = 0.7;
+/// Changed lines at which a change counts as maximal churn
+const REVIEW_CHURN_CEILING: f32 = 200.0;
+
+/// A modified file in review order, with what changed and what to run
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ReviewItem {
+    pub path: String,
+    /// Highest impact score of the file or of the files it impacts (0.0 to 1.0)
+    pub impact_score: f32,
+    /// Lines added and removed, relative to `REVIEW_CHURN_CEILING` (0.0 to 1.0)
+    pub churn: f32,
+    /// Weighted combination of `impact_score` and `churn`
+    pub risk_score: f32,
+    pub changed_functions: Vec<String>,
+    pub suggested_tests: Vec<String>,
+}
 
 pub struct ReportGenerator;
 
@@ -103,6 +127,54 @@ impl ReportGenerator {
         Ok(report)
     }
 
+    /// Modified files ordered riskiest first, by combined impact score and
+    /// change size. Ties keep the order of `change.modified_files`.
+    pub fn review_order(&self, change: &ChangeAnalysis, impact: &[FileImpactAnalysis]) -> Vec<ReviewItem> {
+        let mut items: Vec<ReviewItem> = change.modified_files.iter()
+            .map(|file| {
+                let related = |path: &str| path == file.path || file.impacted_files.iter().any(|p| p == path);
+
+                let impact_score = impact.iter()
+                    .filter(|analysis| related(&analysis.file_path))
+                    .map(|analysis| analysis.impact_score)
+                    .fold(0.0f32, f32::max)
+                    .clamp(0.0, 1.0);
+                let churn = ((file.lines_added + file.lines_removed) as f32 / REVIEW_CHURN_CEILING).min(1.0);
+
+                let mut changed_functions = file.sections_changed.clone();
+                for analysis in impact.iter().filter(|analysis| analysis.file_path == file.path) {
+                    for function in &analysis.affected_functions {
+                        if !changed_functions.contains(function) {
+                            changed_functions.push(function.clone());
+                        }
+                    }
+                }
+
+                let stem = file_stem(&file.path);
+                let mut suggested_tests: Vec<String> = file.impacted_files.iter()
+                    .chain(impact.iter().map(|analysis| &analysis.file_path))
+                    .filter(|path| matches!(detect_file_type(Path::new(path)), FileType::Test))
+                    .filter(|path| related(path) || file_stem(path).starts_with(&stem))
+                    .cloned()
+                    .collect();
+                suggested_tests.sort();
+                suggested_tests.dedup();
+
+                ReviewItem {
+                    path: file.path.clone(),
+                    impact_score,
+                    churn,
+                    risk_score: REVIEW_IMPACT_WEIGHT * impact_score + (1.0 - REVIEW_IMPACT_WEIGHT) * churn,
+                    changed_functions,
+                    suggested_tests,
+                }
+            })
+            .collect();
+
+        items.sort_by(|a, b| b.risk_score.partial_cmp(&a.risk_score).unwrap_or(std::cmp::Ordering::Equal));
+        items
+    }
+
     pub fn generate_summary_report(&self, overview: &ProjectOverview) -> Result<String> {
         let report = format!(
             "Project: {} | Components: {} | Services: {} | Coverage: {:.1}% | Health: {:?} | Size: {:.1}MB",
@@ -116,4 +188,69 @@ impl ReportGenerator {
         
         Ok(report)
     }
-}
\ No newline at end of file
+}
+
+/// File name up to its first dot (`auth.service.spec.ts` -> `auth`)
+fn file_stem(path: &str) -> String {
+    let name = Path::new(path).file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    name.split('.').next().unwrap_or_default().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ml::models::ImpactType;
+
+    fn modified(path: &str, lines: usize, sections: &[&str], impacted: &[&str]) -> ModifiedFile {
+        ModifiedFile {
+            path: path.to_string(),
+            change_type: ChangeType::Modified,
+            lines_added: lines,
+            lines_removed: 0,
+            sections_changed: sections.iter().map(|s| s.to_string()).collect(),
+            impacted_files: impacted.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_high_impact_file_is_reviewed_first() {
+        let change = ChangeAnalysis {
+            session_id: "review".to_string(),
+            timestamp: chrono::Utc::now(),
+            modified_files: vec![
+                modified("src/app/footer.component.ts", 2, &["year"], &[]),
+                modified("src/app/auth.service.ts", 40, &["login"], &["src/app/login.component.ts", "src/app/auth.service.spec.ts"]),
+            ],
+            added_files: vec![],
+            deleted_files: vec![],
+            renamed_files: vec![],
+            impact_scope: ImpactScope::Service,
+            relevant_context: vec![],
+            suggested_actions: vec![],
+        };
+        let impact = vec![
+            FileImpactAnalysis {
+                file_path: "src/app/auth.service.ts".to_string(),
+                impact_score: 0.9,
+                impact_type: ImpactType::Direct,
+                affected_functions: vec!["login".to_string(), "refreshToken".to_string()],
+                reasoning: "token handling changed".to_string(),
+            },
+            FileImpactAnalysis {
+                file_path: "src/app/footer.component.ts".to_string(),
+                impact_score: 0.1,
+                impact_type: ImpactType::Minimal,
+                affected_functions: vec![],
+                reasoning: "copy change".to_string(),
+            },
+        ];
+
+        let items = ReportGenerator::new().review_order(&change, &impact);
+        let order: Vec<_> = items.iter().map(|item| item.path.as_str()).collect();
+        assert_eq!(order, vec!["src/app/auth.service.ts", "src/app/footer.component.ts"]);
+        assert!(items[0].risk_score > items[1].risk_score);
+        assert_eq!(items[0].changed_functions, vec!["login", "refreshToken"]);
+        assert_eq!(items[0].suggested_tests, vec!["src/app/auth.service.spec.ts"]);
+        assert!(items[1].suggested_tests.is_empty());
+    }
+}
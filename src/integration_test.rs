@@ -106,6 +106,7 @@ export class AuthService {
 }
 
 /// Integration test for StateAnalyzer improvements  
+#[cfg(feature = "typescript")]
 #[test]
 fn test_state_analyzer_improvements() -> Result<()> {
     let temp_dir = TempDir::new()?;
@@ -390,6 +391,7 @@ fn test_path_normalization() -> Result<()> {
 }
 
 /// Integration test to verify all fixes work together
+#[cfg(feature = "typescript")]
 #[test]
 fn test_complete_integration() -> Result<()> {
     let temp_dir = TempDir::new()?;
@@ -883,6 +885,7 @@ async fn test_ml_plugin_integration() -> Result<()> {
 mod tests {
     use super::*;
     
+    #[cfg(feature = "typescript")]
     #[test]
     fn test_all_improvements() -> Result<()> {
         // Test 1: Changes command fixes
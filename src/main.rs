@@ -8,29 +8,36 @@ mod ml;
 mod mcp;
 
 use clap::Parser;
-use cli::{Cli, Commands, CacheCommands, MLCommands, ModelCommands};
+use cli::{Cli, Commands, CacheCommands, MLCommands, MLCacheCommands, ModelCommands};
 use cli::commands::*;
+use utils::projection::parse_fields;
 use anyhow::Result;
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let cli = Cli::parse();
+    cli.render_options().charset.set_current();
 
     match &cli.command {
         Commands::Analyze { path, force, verbose } => {
-            run_analyze(path, *force, *verbose)?;
+            run_analyze(path, *force, *verbose, cli.path_style, cli.focus.as_deref())?;
         }
         
-        Commands::Summary { path, file, format } => {
-            run_summary(path, file.as_deref(), format)?;
+        Commands::Summary { path, file, format, fields } => {
+            let fields = fields.as_deref().map(parse_fields).unwrap_or_default();
+            run_summary(path, file.as_deref(), format, cli.path_style, cli.render_options().json_style, &fields)?;
+        }
+        
+        Commands::Context { path, file, max_tokens, format } => {
+            run_context(path, file, *max_tokens, *format, cli.render_options())?;
         }
         
         Commands::Changes { path, modified_only } => {
-            run_changes(path, *modified_only)?;
+            run_changes(path, *modified_only, cli.path_style)?;
         }
         
-        Commands::Overview { path, format, include_health } => {
-            run_overview(path, format, *include_health)?;
+        Commands::Overview { path, format, include_health, since } => {
+            run_overview(path, format, *include_health, cli.render_options(), cli.focus.as_deref(), since.as_deref())?;
         }
         
         Commands::Cache { action } => {
@@ -50,29 +57,34 @@ async fn main() -> Result<()> {
                 CacheCommands::Clear { path } => {
                     run_cache_clear(path)?;
                 }
+                
+                CacheCommands::Validate { path, strict } => {
+                    run_cache_validate(path, *strict)?;
+                }
             }
         }
         
         Commands::ML { action } => {
             match action {
                 MLCommands::Context { function, file, ai_enhanced, format } => {
-                    run_ml_context(function, file.as_deref(), *ai_enhanced, format).await?;
+                    run_ml_context(function, file.as_deref(), *ai_enhanced, *format, cli.render_options()).await?;
                 }
                 
-                MLCommands::Impact { changed_file, changed_functions, ai_analysis, format } => {
-                    run_ml_impact(changed_file, changed_functions, *ai_analysis, format).await?;
+                MLCommands::Impact { path, changed_file, changed_functions, ai_analysis, format } => {
+                    run_ml_impact(path, changed_file, changed_functions, *ai_analysis, *format, cli.render_options()).await?;
                 }
                 
-                MLCommands::Patterns { path, detect_duplicates, ml_similarity, min_similarity, format } => {
-                    run_ml_patterns(path, *detect_duplicates, *ml_similarity, *min_similarity, format).await?;
+                MLCommands::Patterns { path, detect_duplicates, ml_similarity, min_similarity, group_depth, format } => {
+                    run_ml_patterns(path, *detect_duplicates, *ml_similarity, *min_similarity, *group_depth, *format, cli.render_options()).await?;
                 }
                 
-                MLCommands::Search { query, path, semantic, include_context, max_results, format } => {
-                    run_ml_search(query, path, *semantic, *include_context, *max_results, format).await?;
+                MLCommands::Search { query, path, semantic, include_context, max_results, min_relevance, include_docs, include_tests, search_type, embedding_content, format, fields } => {
+                    let fields = fields.as_deref().map(parse_fields).unwrap_or_default();
+                    run_ml_search(query, path, *semantic, *include_context, *max_results, *min_relevance, *include_docs, *include_tests, (*search_type).into(), (*embedding_content).into(), *format, cli.render_options(), cli.focus.as_deref(), &fields).await?;
                 }
                 
                 MLCommands::Optimize { task, max_tokens, ai_enhanced, format } => {
-                    run_ml_optimize(task, *max_tokens, *ai_enhanced, format).await?;
+                    run_ml_optimize(task, *max_tokens, *ai_enhanced, *format, cli.render_options()).await?;
                 }
                 
                 MLCommands::Models { action } => {
@@ -98,6 +110,14 @@ async fn main() -> Result<()> {
                         }
                     }
                 }
+                
+                MLCommands::Cache { action } => {
+                    match action {
+                        MLCacheCommands::Clear { path, dry_run } => {
+                            run_ml_cache_clear(path, *dry_run)?;
+                        }
+                    }
+                }
             }
         }
         
@@ -105,6 +125,7 @@ async fn main() -> Result<()> {
             let mcp_command = MCPCommand {
                 port: *port,
                 debug: *debug,
+                path_style: cli.path_style,
             };
             mcp_command.execute().await?;
         }
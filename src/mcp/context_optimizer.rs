@@ -3,8 +3,11 @@
 use anyhow::Result; 
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 
 use crate::ml::vector_db::EnhancedSearchResult;
+use crate::utils::{default_tokenizer, Tokenizer};
+use crate::utils::decoration::status;
 
 /// Optimized context result
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -17,15 +20,23 @@ pub struct OptimizedContext {
 
 /// Context optimization engine
 pub struct ContextOptimizer {
-    /// Average tokens per character (empirically derived)
+    /// Average tokens per character (empirically derived), used to size snippets
     tokens_per_char: f64,
+    /// Tokenizer used for budget accounting
+    tokenizer: Arc<dyn Tokenizer>,
 }
 
 impl ContextOptimizer {
     pub fn new() -> Self {
+        Self::with_tokenizer(default_tokenizer())
+    }
+    
+    /// Create an optimizer that counts tokens with a custom tokenizer
+    pub fn with_tokenizer(tokenizer: Arc<dyn Tokenizer>) -> Self {
         Self {
             // Based on typical code: ~4.5 chars per token
             tokens_per_char: 1.0 / 4.5,
+            tokenizer,
         }
     }
     
@@ -37,7 +48,7 @@ impl ContextOptimizer {
         include_tests: bool,
         include_dependencies: bool,
     ) -> Result<OptimizedContext> {
-        println!("🎯 Optimizing context for {} tokens budget", max_tokens);
+        status!("🎯 Optimizing context for {} tokens budget", max_tokens);
         
         // Filter results based on preferences
         let mut filtered_results: Vec<&EnhancedSearchResult> = search_results.iter()
@@ -133,7 +144,7 @@ impl ContextOptimizer {
             max_score
         );
         
-        println!("✅ Context optimization complete: {} tokens", final_tokens);
+        status!("✅ Context optimization complete: {} tokens", final_tokens);
         
         Ok(OptimizedContext {
             context,
@@ -144,7 +155,7 @@ impl ContextOptimizer {
     }
     
     /// Check if file is a test file
-    fn is_test_file(&self, file_path: &str) -> bool {
+    pub fn is_test_file(&self, file_path: &str) -> bool {
         file_path.contains("test") || 
         file_path.contains("spec") || 
         file_path.ends_with(".test.ts") ||
@@ -176,8 +187,8 @@ impl ContextOptimizer {
     }
     
     /// Estimate tokens for text
-    fn estimate_tokens(&self, text: &str) -> usize {
-        (text.len() as f64 * self.tokens_per_char).ceil() as usize
+    pub fn estimate_tokens(&self, text: &str) -> usize {
+        self.tokenizer.count_tokens(text)
     }
     
     /// Create a snippet within token budget
@@ -3,24 +3,41 @@
 use axum::{
     extract::{Path, State},
     http::{Method, StatusCode},
-    response::Json,
+    response::{IntoResponse, Json, Response},
     routing::{get, post},
     Router,
 };
+use futures::stream::{self, StreamExt};
 use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, sync::{Arc, Mutex}};
+use tokio::sync::Semaphore;
 use tower_http::cors::{Any, CorsLayer};
 use anyhow::Result;
 
 use crate::cache::CacheManager;
 use crate::ml::services::enhanced_search::EnhancedSearchService;
-use super::tools::{SmartContextTool, ExploreCodebaseTool, ProjectOverviewTool, ChangesAnalysisTool, FileSummaryTool, CacheStatusTool, CacheGenerationTool, CacheGenerationStatusTool, CacheClearTool, MCPTool};
+use super::tools::{SmartContextTool, ExploreCodebaseTool, ProjectOverviewTool, ChangesAnalysisTool, FileSummaryTool, CacheStatusTool, CacheGenerationTool, CacheGenerationStatusTool, CacheClearTool, MCPTool, MCPToolResult};
+use crate::utils::decoration::status;
+use crate::utils::path_normalizer::{PathNormalizer, PathStyle};
+
+/// Tool calls executing at once, across REST calls and JSON-RPC batches
+const MAX_CONCURRENT_TOOL_CALLS: usize = 4;
+
+/// JSON-RPC 2.0 error codes
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const TOOL_ERROR: i64 = -32000;
+
+/// Keys of tool results holding a file path or a list of file paths
+const PATH_KEYS: &[&str] = &["path", "file_path", "files_included", "added_files", "deleted_files", "impacted_files"];
 
 /// MCP Server for Claude Code integration
 pub struct MCPServer {
     cache_manager: Arc<Mutex<CacheManager>>,
     search_service: Arc<EnhancedSearchService>,
     tools: Arc<HashMap<String, Box<dyn MCPTool>>>,
+    path_style: PathStyle,
 }
 
 /// MCP Tool definition for Claude Code
@@ -47,10 +64,60 @@ pub struct MCPToolResponse {
     pub metadata: Option<serde_json::Value>,
 }
 
+/// One JSON-RPC 2.0 request, alone or as a member of a batch. Requests
+/// without an `id` are notifications and get no response.
+#[derive(Debug, Deserialize)]
+pub struct JsonRpcRequest {
+    pub id: Option<serde_json::Value>,
+    pub method: String,
+    #[serde(default)]
+    pub params: serde_json::Value,
+}
+
+/// Parameters of the `tools/call` method
+#[derive(Debug, Deserialize)]
+pub struct JsonRpcToolCall {
+    pub name: String,
+    #[serde(default)]
+    pub arguments: serde_json::Value,
+}
+
+#[derive(Debug, Serialize)]
+pub struct JsonRpcResponse {
+    pub jsonrpc: &'static str,
+    pub id: serde_json::Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<JsonRpcError>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct JsonRpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl JsonRpcResponse {
+    fn new(id: serde_json::Value, outcome: Result<serde_json::Value, JsonRpcError>) -> Self {
+        let (result, error) = match outcome {
+            Ok(result) => (Some(result), None),
+            Err(error) => (None, Some(error)),
+        };
+        Self { jsonrpc: "2.0", id, result, error }
+    }
+}
+
+impl JsonRpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self { code, message: message.into() }
+    }
+}
+
 impl MCPServer {
-    /// Create new MCP server
-    pub async fn new() -> Result<Self> {
-        println!("🚀 Initializing MCP Server for Claude Code...");
+    /// Create new MCP server reporting file paths in `path_style`
+    pub async fn new(path_style: PathStyle) -> Result<Self> {
+        status!("🚀 Initializing MCP Server for Claude Code...");
         
         // Initialize components
         let project_path = std::env::current_dir()?;
@@ -68,7 +135,7 @@ impl MCPServer {
         };
         
         let search_service = Arc::new(
-            EnhancedSearchService::new(ml_config).await
+            EnhancedSearchService::new(ml_config, &project_path).await
                 .map_err(|e| anyhow::anyhow!("Failed to initialize search service: {}", e))?
         );
         
@@ -98,7 +165,7 @@ impl MCPServer {
             "project_overview".to_string(),
             Box::new(ProjectOverviewTool::new(
                 cache_manager.clone(),
-            )),
+            ).with_path_style(path_style)),
         );
         
         // Changes Analysis Tool - Git-aware context for modified files
@@ -147,7 +214,7 @@ impl MCPServer {
         );
         
         
-        println!("✅ MCP Server initialized with {} tools", tools.len());
+        status!("✅ MCP Server initialized with {} tools", tools.len());
         println!("   - smart_context: Optimized context for Claude Code");
         println!("   - explore_codebase: Semantic file discovery");
         println!("   - project_overview: Structured project analysis");
@@ -162,21 +229,22 @@ impl MCPServer {
             cache_manager,
             search_service,
             tools: Arc::new(tools),
+            path_style,
         })
     }
     
     /// Start the MCP server
     pub async fn start(&self, port: u16) -> Result<()> {
-        println!("🌐 Starting MCP Server on port {}...", port);
+        status!("🌐 Starting MCP Server on port {}...", port);
         
-        let app_state = MCPServerState {
-            tools: self.tools.clone(),
-        };
+        let project_path = std::env::current_dir()?;
+        let app_state = MCPServerState::new(self.tools.clone()).with_path_style(&project_path, self.path_style);
         
         let app = Router::new()
             .route("/", get(health_check))
             .route("/tools", get(list_tools))
             .route("/tools/:tool", post(call_tool))
+            .route("/rpc", post(json_rpc))
             .layer(
                 CorsLayer::new()
                     .allow_origin(Any)
@@ -186,11 +254,12 @@ impl MCPServer {
             .with_state(app_state);
         
         let listener = tokio::net::TcpListener::bind(format!("0.0.0.0:{}", port)).await?;
-        println!("✅ MCP Server listening on http://0.0.0.0:{}", port);
-        println!("📋 Available endpoints:");
+        status!("✅ MCP Server listening on http://0.0.0.0:{}", port);
+        status!("📋 Available endpoints:");
         println!("   GET  / - Health check");
         println!("   GET  /tools - List available tools");
         println!("   POST /tools/:tool - Call a specific tool");
+        println!("   POST /rpc - JSON-RPC 2.0 tools/list and tools/call, batches included");
         
         axum::serve(listener, app).await?;
         
@@ -202,6 +271,102 @@ impl MCPServer {
 #[derive(Clone)]
 struct MCPServerState {
     tools: Arc<HashMap<String, Box<dyn MCPTool>>>,
+    tool_permits: Arc<Semaphore>,
+    /// Tool results keep their paths as returned unless a style is set
+    path_style: Option<(Arc<PathNormalizer>, PathStyle)>,
+}
+
+impl MCPServerState {
+    fn new(tools: Arc<HashMap<String, Box<dyn MCPTool>>>) -> Self {
+        Self {
+            tools,
+            tool_permits: Arc::new(Semaphore::new(MAX_CONCURRENT_TOOL_CALLS)),
+            path_style: None,
+        }
+    }
+
+    /// Rewrite the file paths of every tool result in `style`, relative to `project_root`
+    fn with_path_style(mut self, project_root: &std::path::Path, style: PathStyle) -> Self {
+        self.path_style = Some((Arc::new(PathNormalizer::new(project_root)), style));
+        self
+    }
+
+    fn tool_definitions(&self) -> Vec<MCPToolDefinition> {
+        self.tools.iter()
+            .map(|(name, tool)| MCPToolDefinition {
+                name: name.clone(),
+                description: tool.description().to_string(),
+                parameters: tool.parameters_schema(),
+            })
+            .collect()
+    }
+
+    /// Run `tool_name` once a permit is free; `None` when there is no such tool
+    async fn execute_tool(&self, tool_name: &str, parameters: serde_json::Value) -> Option<Result<MCPToolResult>> {
+        let tool = self.tools.get(tool_name)?;
+        let _permit = self.tool_permits.acquire().await.ok()?;
+        let mut outcome = tool.execute(parameters).await;
+        if let (Ok(result), Some((normalizer, style))) = (&mut outcome, &self.path_style) {
+            style_paths(&mut result.result, normalizer, *style);
+        }
+        Some(outcome)
+    }
+
+    /// Responses to a JSON-RPC request body: an array for a batch, in the
+    /// order of its members, or `None` when there is nothing to answer
+    async fn handle_json_rpc(&self, body: serde_json::Value) -> Option<serde_json::Value> {
+        match body {
+            serde_json::Value::Array(members) if members.is_empty() => {
+                let error = JsonRpcError::new(INVALID_REQUEST, "Empty batch");
+                serde_json::to_value(JsonRpcResponse::new(serde_json::Value::Null, Err(error))).ok()
+            }
+            serde_json::Value::Array(members) => {
+                // Members run concurrently, tool calls among them bounded by `tool_permits`
+                let responses: Vec<JsonRpcResponse> = stream::iter(members)
+                    .map(|member| self.handle_json_rpc_member(member))
+                    .buffered(MAX_CONCURRENT_TOOL_CALLS)
+                    .filter_map(|response| async move { response })
+                    .collect()
+                    .await;
+                (!responses.is_empty()).then(|| serde_json::to_value(responses).ok()).flatten()
+            }
+            member => serde_json::to_value(self.handle_json_rpc_member(member).await?).ok(),
+        }
+    }
+
+    async fn handle_json_rpc_member(&self, member: serde_json::Value) -> Option<JsonRpcResponse> {
+        let request: JsonRpcRequest = match serde_json::from_value(member) {
+            Ok(request) => request,
+            Err(e) => {
+                let error = JsonRpcError::new(INVALID_REQUEST, e.to_string());
+                return Some(JsonRpcResponse::new(serde_json::Value::Null, Err(error)));
+            }
+        };
+
+        let outcome = match request.method.as_str() {
+            "tools/list" => serde_json::to_value(self.tool_definitions())
+                .map_err(|e| JsonRpcError::new(TOOL_ERROR, e.to_string())),
+            "tools/call" => self.json_rpc_tool_call(request.params).await,
+            method => Err(JsonRpcError::new(METHOD_NOT_FOUND, format!("Unknown method: {}", method))),
+        };
+
+        Some(JsonRpcResponse::new(request.id?, outcome))
+    }
+
+    async fn json_rpc_tool_call(&self, params: serde_json::Value) -> Result<serde_json::Value, JsonRpcError> {
+        let call: JsonRpcToolCall = serde_json::from_value(params)
+            .map_err(|e| JsonRpcError::new(INVALID_PARAMS, e.to_string()))?;
+
+        status!("🔧 MCP JSON-RPC tool call: {} with params: {}", call.name, call.arguments);
+        match self.execute_tool(&call.name, call.arguments).await {
+            Some(Ok(result)) => Ok(serde_json::json!({
+                "result": result.result,
+                "metadata": result.metadata,
+            })),
+            Some(Err(e)) => Err(JsonRpcError::new(TOOL_ERROR, e.to_string())),
+            None => Err(JsonRpcError::new(INVALID_PARAMS, format!("Tool not found: {}", call.name))),
+        }
+    }
 }
 
 /// Health check endpoint
@@ -214,17 +379,35 @@ async fn health_check() -> Json<serde_json::Value> {
     }))
 }
 
+/// Rewrite the strings under `PATH_KEYS`, at any depth of `value`, in `style`
+fn style_paths(value: &mut serde_json::Value, normalizer: &PathNormalizer, style: PathStyle) {
+    match value {
+        serde_json::Value::Object(fields) => {
+            for (key, field) in fields.iter_mut() {
+                match field {
+                    serde_json::Value::String(path) if PATH_KEYS.contains(&key.as_str()) => {
+                        *path = normalizer.format_path(path, style);
+                    }
+                    serde_json::Value::Array(items) if PATH_KEYS.contains(&key.as_str()) => {
+                        for item in items.iter_mut() {
+                            match item {
+                                serde_json::Value::String(path) => *path = normalizer.format_path(path, style),
+                                other => style_paths(other, normalizer, style),
+                            }
+                        }
+                    }
+                    other => style_paths(other, normalizer, style),
+                }
+            }
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(|item| style_paths(item, normalizer, style)),
+        _ => {}
+    }
+}
+
 /// List available tools
 async fn list_tools(State(state): State<MCPServerState>) -> Json<Vec<MCPToolDefinition>> {
-    let tools: Vec<MCPToolDefinition> = state.tools.iter()
-        .map(|(name, tool)| MCPToolDefinition {
-            name: name.clone(),
-            description: tool.description().to_string(),
-            parameters: tool.parameters_schema(),
-        })
-        .collect();
-    
-    Json(tools)
+    Json(state.tool_definitions())
 }
 
 /// Call a specific tool
@@ -233,13 +416,13 @@ async fn call_tool(
     State(state): State<MCPServerState>,
     Json(request): Json<MCPToolCall>,
 ) -> Result<Json<MCPToolResponse>, StatusCode> {
-    println!("🔧 MCP Tool call: {} with params: {}", tool_name, request.parameters);
+    status!("🔧 MCP Tool call: {} with params: {}", tool_name, request.parameters);
     
-    match state.tools.get(&tool_name) {
-        Some(tool) => {
-            match tool.execute(request.parameters).await {
+    match state.execute_tool(&tool_name, request.parameters).await {
+        Some(outcome) => {
+            match outcome {
                 Ok(result) => {
-                    println!("✅ Tool {} executed successfully", tool_name);
+                    status!("✅ Tool {} executed successfully", tool_name);
                     Ok(Json(MCPToolResponse {
                         success: true,
                         result: Some(result.result),
@@ -248,7 +431,7 @@ async fn call_tool(
                     }))
                 }
                 Err(e) => {
-                    println!("❌ Tool {} failed: {}", tool_name, e);
+                    status!("❌ Tool {} failed: {}", tool_name, e);
                     Ok(Json(MCPToolResponse {
                         success: false,
                         result: None,
@@ -259,8 +442,104 @@ async fn call_tool(
             }
         }
         None => {
-            println!("❌ Tool not found: {}", tool_name);
+            status!("❌ Tool not found: {}", tool_name);
             Err(StatusCode::NOT_FOUND)
         }
     }
-}
\ No newline at end of file
+}
+
+/// JSON-RPC 2.0 endpoint; a JSON array body is handled as a batch
+async fn json_rpc(State(state): State<MCPServerState>, Json(body): Json<serde_json::Value>) -> Response {
+    match state.handle_json_rpc(body).await {
+        Some(responses) => Json(responses).into_response(),
+        None => StatusCode::NO_CONTENT.into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use std::time::Duration;
+
+    /// Echoes its parameters back, after `delay_ms` when given
+    struct EchoTool;
+
+    #[async_trait]
+    impl MCPTool for EchoTool {
+        fn name(&self) -> &str {
+            "echo"
+        }
+
+        fn description(&self) -> &str {
+            "Echo the parameters"
+        }
+
+        fn parameters_schema(&self) -> serde_json::Value {
+            serde_json::json!({ "type": "object" })
+        }
+
+        async fn execute(&self, parameters: serde_json::Value) -> Result<MCPToolResult> {
+            if let Some(delay) = parameters["delay_ms"].as_u64() {
+                tokio::time::sleep(Duration::from_millis(delay)).await;
+            }
+            Ok(MCPToolResult { result: parameters, metadata: None })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_batch_responses_keep_request_ids() {
+        let mut tools: HashMap<String, Box<dyn MCPTool>> = HashMap::new();
+        tools.insert("echo".to_string(), Box::new(EchoTool));
+        let state = MCPServerState::new(Arc::new(tools));
+
+        let batch = serde_json::json!([
+            { "jsonrpc": "2.0", "id": 1, "method": "tools/call",
+              "params": { "name": "echo", "arguments": { "query": "first", "delay_ms": 50 } } },
+            { "jsonrpc": "2.0", "id": "second", "method": "tools/call",
+              "params": { "name": "echo", "arguments": { "query": "second" } } },
+            { "jsonrpc": "2.0", "method": "tools/call",
+              "params": { "name": "echo", "arguments": { "query": "notification" } } },
+        ]);
+
+        let responses = state.handle_json_rpc(batch).await.unwrap();
+        let responses = responses.as_array().unwrap();
+        assert_eq!(responses.len(), 2);
+        assert_eq!(responses[0]["id"], 1);
+        assert_eq!(responses[0]["result"]["result"]["query"], "first");
+        assert_eq!(responses[1]["id"], "second");
+        assert_eq!(responses[1]["result"]["result"]["query"], "second");
+
+        let unknown = serde_json::json!({ "jsonrpc": "2.0", "id": 3, "method": "tools/call", "params": { "name": "missing" } });
+        let response = state.handle_json_rpc(unknown).await.unwrap();
+        assert_eq!(response["id"], 3);
+        assert_eq!(response["error"]["code"], INVALID_PARAMS);
+    }
+
+    #[tokio::test]
+    async fn test_tool_results_follow_the_path_style() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let mut tools: HashMap<String, Box<dyn MCPTool>> = HashMap::new();
+        tools.insert("echo".to_string(), Box::new(EchoTool));
+        let tools = Arc::new(tools);
+        let result = serde_json::json!({
+            "file_path": "./src/app/cart.service.ts",
+            "files_included": ["./src/app/cart.service.ts"],
+            "changes": { "modified_files": [{ "path": "./src/app/cart.service.ts", "impacted_files": ["./src/app/cart.service.ts"] }] },
+            "project_path": "./shop",
+        });
+
+        let state = MCPServerState::new(tools.clone()).with_path_style(temp_dir.path(), PathStyle::Relative);
+        let styled = state.execute_tool("echo", result.clone()).await.unwrap().unwrap().result;
+        assert_eq!(styled["file_path"], "src/app/cart.service.ts");
+        assert_eq!(styled["files_included"][0], "src/app/cart.service.ts");
+        assert_eq!(styled["changes"]["modified_files"][0]["path"], "src/app/cart.service.ts");
+        assert_eq!(styled["changes"]["modified_files"][0]["impacted_files"][0], "src/app/cart.service.ts");
+        assert_eq!(styled["project_path"], "./shop");
+
+        let root = temp_dir.path().canonicalize().unwrap().to_string_lossy().replace('\\', "/");
+        let state = MCPServerState::new(tools).with_path_style(temp_dir.path(), PathStyle::Absolute);
+        let styled = state.execute_tool("echo", result).await.unwrap().unwrap().result;
+        assert_eq!(styled["file_path"], format!("{}/src/app/cart.service.ts", root));
+    }
+}
@@ -13,6 +13,9 @@ use crate::generators::ProjectOverviewGenerator;
 use crate::analyzers::DiffAnalyzer;
 use crate::types::{ChangeType, ModifiedFile};
 use super::context_optimizer::ContextOptimizer;
+use crate::utils::decoration::status;
+use crate::utils::projection::project_fields;
+use crate::utils::path_normalizer::{PathNormalizer, PathStyle};
 use std::time::SystemTime;
 use tokio::sync::RwLock;
 
@@ -114,7 +117,7 @@ impl MCPTool for SmartContextTool {
     async fn execute(&self, parameters: serde_json::Value) -> Result<MCPToolResult> {
         let params: SmartContextParams = serde_json::from_value(parameters)?;
         
-        println!("🔍 Smart Context query: '{}'", params.query);
+        status!("🔍 Smart Context query: '{}'", params.query);
         println!("   Max tokens: {}", params.max_tokens.unwrap_or(4000));
         
         // Create search request
@@ -145,7 +148,7 @@ impl MCPTool for SmartContextTool {
             params.include_dependencies.unwrap_or(true),
         ).await?;
         
-        println!("✅ Optimized context: {} tokens, {} files", 
+        status!("✅ Optimized context: {} tokens, {} files", 
                 optimized_context.total_tokens, 
                 optimized_context.files.len());
         
@@ -184,6 +187,9 @@ struct ExploreCodebaseParams {
     file_types: Option<Vec<String>>,
     max_results: Option<usize>,
     include_snippets: Option<bool>,
+    search_type: Option<String>,
+    #[serde(default)]
+    fields: Vec<String>,
 }
 
 impl ExploreCodebaseTool {
@@ -230,6 +236,17 @@ impl MCPTool for ExploreCodebaseTool {
                     "type": "boolean",
                     "description": "Include code snippets in results (default: true)",
                     "default": true
+                },
+                "search_type": {
+                    "type": "string",
+                    "enum": ["general", "definition", "usage", "similar", "documentation"],
+                    "description": "What the query looks for: a symbol's definition, its usages, similar code or documentation (default: general)",
+                    "default": "general"
+                },
+                "fields": {
+                    "type": "array",
+                    "items": {"type": "string"},
+                    "description": "Only return these fields of each file (e.g. ['file_path', 'rerank_score']); all fields when omitted"
                 }
             },
             "required": ["query"]
@@ -239,12 +256,16 @@ impl MCPTool for ExploreCodebaseTool {
     async fn execute(&self, parameters: serde_json::Value) -> Result<MCPToolResult> {
         let params: ExploreCodebaseParams = serde_json::from_value(parameters)?;
         
-        println!("🔍 Exploring codebase for: '{}'", params.query);
+        status!("🔍 Exploring codebase for: '{}'", params.query);
         
         // Create search request
+        let search_type = match params.search_type.as_deref() {
+            Some(name) => SearchType::from_name(name)?,
+            None => SearchType::General,
+        };
         let search_request = SearchRequest {
             query: params.query.clone(),
-            search_type: SearchType::General,
+            search_type,
             filters: SearchFilters {
                 file_patterns: params.file_types.clone(),
                 ..Default::default()
@@ -269,6 +290,7 @@ impl MCPTool for ExploreCodebaseTool {
                     "language": result.entry.metadata.language,
                     "complexity": result.entry.metadata.complexity,
                     "relevance_score": result.combined_score,
+                    "rerank_score": result.rerank_score,
                     "code_type": format!("{:?}", result.entry.metadata.code_type),
                 });
                 
@@ -282,7 +304,7 @@ impl MCPTool for ExploreCodebaseTool {
                     file_info["snippet"] = serde_json::Value::String(snippet);
                 }
                 
-                file_info
+                project_fields(&file_info, &params.fields)
             })
             .collect();
         
@@ -308,6 +330,7 @@ impl MCPTool for ExploreCodebaseTool {
 /// Project Overview Tool - Get structured project analysis without reading all files
 pub struct ProjectOverviewTool {
     cache_manager: Arc<Mutex<CacheManager>>,
+    path_style: PathStyle,
 }
 
 #[derive(Debug, Deserialize)]
@@ -322,8 +345,14 @@ impl ProjectOverviewTool {
     pub fn new(cache_manager: Arc<Mutex<CacheManager>>) -> Self {
         Self {
             cache_manager,
+            path_style: PathStyle::default(),
         }
     }
+
+    pub fn with_path_style(mut self, path_style: PathStyle) -> Self {
+        self.path_style = path_style;
+        self
+    }
 }
 
 #[async_trait]
@@ -369,7 +398,7 @@ impl MCPTool for ProjectOverviewTool {
     async fn execute(&self, parameters: serde_json::Value) -> Result<MCPToolResult> {
         let params: ProjectOverviewParams = serde_json::from_value(parameters)?;
         
-        println!("📊 Generating project overview...");
+        status!("📊 Generating project overview...");
         println!("   Format: {}", params.format.as_deref().unwrap_or("markdown"));
         
         // Get project path
@@ -379,9 +408,10 @@ impl MCPTool for ProjectOverviewTool {
         let generator = ProjectOverviewGenerator::new(CacheManager::new(&project_path)?);
         
         // Generate overview
-        let overview = generator.generate_overview(&project_path)?;
+        let mut overview = generator.generate_overview(&project_path)?;
+        overview.apply_path_style(&PathNormalizer::new(&project_path), self.path_style);
         
-        println!("✅ Project overview generated:");
+        status!("✅ Project overview generated:");
         println!("   Components found: {}", overview.structure.components.len());
         println!("   Services found: {}", overview.structure.services.len());
         
@@ -504,7 +534,7 @@ impl MCPTool for ChangesAnalysisTool {
     async fn execute(&self, parameters: serde_json::Value) -> Result<MCPToolResult> {
         let params: ChangesAnalysisParams = serde_json::from_value(parameters)?;
         
-        println!("📝 Analyzing recent changes...");
+        status!("📝 Analyzing recent changes...");
         println!("   Since: {}", params.since.as_deref().unwrap_or("last-commit"));
         
         // Get project path
@@ -529,7 +559,7 @@ impl MCPTool for ChangesAnalysisTool {
             }
         };
         
-        println!("✅ Changes analysis complete:");
+        status!("✅ Changes analysis complete:");
         println!("   Modified files: {}", changes.modified_files.len());
         println!("   Added files: {}", changes.added_files.len());
         println!("   Deleted files: {}", changes.deleted_files.len());
@@ -620,6 +650,8 @@ struct FileSummaryParams {
     include_complexity: Option<bool>,
     include_functions: Option<bool>,
     include_dependencies: Option<bool>,
+    #[serde(default)]
+    fields: Vec<String>,
 }
 
 impl FileSummaryTool {
@@ -668,6 +700,11 @@ impl MCPTool for FileSummaryTool {
                     "type": "boolean",
                     "description": "Include import/export analysis (default: true)",
                     "default": true
+                },
+                "fields": {
+                    "type": "array",
+                    "items": {"type": "string"},
+                    "description": "Only return these fields of the JSON summary (e.g. ['file_path', 'functions']); all fields when omitted"
                 }
             },
             "required": ["file_path"]
@@ -677,7 +714,7 @@ impl MCPTool for FileSummaryTool {
     async fn execute(&self, parameters: serde_json::Value) -> Result<MCPToolResult> {
         let params: FileSummaryParams = serde_json::from_value(parameters)?;
         
-        println!("📄 Analyzing file: '{}'", params.file_path);
+        status!("📄 Analyzing file: '{}'", params.file_path);
         println!("   Format: {}", params.format.as_deref().unwrap_or("markdown"));
         
         // Get file data from cache using proper path normalization
@@ -685,7 +722,7 @@ impl MCPTool for FileSummaryTool {
         let file_data = manager.get_file_summary(&params.file_path)
             .ok_or_else(|| anyhow::anyhow!("File not found in cache: {}. Run 'cargo run -- analyze' first.", params.file_path))?;
         
-        println!("✅ File found in cache:");
+        status!("✅ File found in cache:");
         println!("   Type: {:?}", file_data.metadata.file_type);
         println!("   Functions: {}", file_data.summary.functions.len());
         println!("   Complexity: {:?}", file_data.metadata.complexity);
@@ -711,7 +748,7 @@ impl MCPTool for FileSummaryTool {
                     summary["exports"] = serde_json::json!(file_data.metadata.exports);
                 }
                 
-                serde_json::to_string_pretty(&summary)?
+                serde_json::to_string_pretty(&project_fields(&summary, &params.fields))?
             },
             "text" | "markdown" => {
                 let mut output = format!(
@@ -861,7 +898,7 @@ impl MCPTool for CacheStatusTool {
     async fn execute(&self, parameters: serde_json::Value) -> Result<MCPToolResult> {
         let params: CacheStatusParams = serde_json::from_value(parameters)?;
         
-        println!("📊 Analyzing cache status...");
+        status!("📊 Analyzing cache status...");
         println!("   Include details: {}", params.include_details.unwrap_or(false));
         
         // Get cache statistics
@@ -914,7 +951,7 @@ impl MCPTool for CacheStatusTool {
         // Integrity check if requested
         let mut integrity_issues = Vec::new();
         if params.check_integrity.unwrap_or(false) {
-            println!("🔍 Performing integrity checks...");
+            status!("🔍 Performing integrity checks...");
             
             // Check for missing files
             for (file_path, _) in &cache.entries {
@@ -925,7 +962,7 @@ impl MCPTool for CacheStatusTool {
             }
         }
         
-        println!("✅ Cache analysis complete:");
+        status!("✅ Cache analysis complete:");
         println!("   Total files: {}", total_files);
         println!("   Rust files: {}", rust_files);
         println!("   TypeScript files: {}", typescript_files);
@@ -1146,7 +1183,7 @@ impl MCPTool for CacheGenerationTool {
         let force_rebuild = params.force_rebuild.unwrap_or(false);
         let background = params.background.unwrap_or(false);
         
-        println!("🏗️ Cache generation request:");
+        status!("🏗️ Cache generation request:");
         println!("   Project: {}", project_path.display());
         println!("   Force rebuild: {}", force_rebuild);
         println!("   Background mode: {}", background);
@@ -1283,7 +1320,7 @@ impl CacheGenerationTool {
         
         progress_task.abort();
         
-        println!("🏗️ Cache generation completed in {}ms", duration.as_millis());
+        status!("🏗️ Cache generation completed in {}ms", duration.as_millis());
         
         Ok(final_state.clone())
     }
@@ -1410,7 +1447,7 @@ impl MCPTool for CacheClearTool {
             .map(std::path::PathBuf::from)
             .unwrap_or_else(|| std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from(".")));
         
-        println!("🗑️ Clearing cache for project: {}", project_path.display());
+        status!("🗑️ Clearing cache for project: {}", project_path.display());
         
         // Get cache stats before clearing
         let entries_before = {
@@ -1432,10 +1469,10 @@ impl MCPTool for CacheClearTool {
                     std::fs::create_dir_all(parent)?;
                 }
                 std::fs::copy(&cache_file_path, &backup_path)?;
-                println!("💾 Cache backed up to: {}", backup_path.display());
+                status!("💾 Cache backed up to: {}", backup_path.display());
                 Some(backup_path)
             } else {
-                println!("⚠️ No cache file found to backup");
+                status!("⚠️ No cache file found to backup");
                 None
             }
         } else {
@@ -1447,7 +1484,7 @@ impl MCPTool for CacheClearTool {
         let actual_entries_cleared = CacheManager::clear_cache_async(self.cache_manager.clone()).await?;
         let clear_duration = start_time.elapsed()?.as_millis();
         
-        println!("✅ Cache cleared successfully in {}ms", clear_duration);
+        status!("✅ Cache cleared successfully in {}ms", clear_duration);
         println!("   Removed {} cache entries", entries_before);
         
         let result = serde_json::json!({
@@ -3,9 +3,10 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::fs;
 use sha2::{Sha256, Digest};
+use crate::ml::vector_db::{NativeVectorStore, VectorDBConfig, VectorDatabase};
 
 /// ML response cache entry with prompt hash and response
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -241,6 +242,52 @@ impl MLResponseCache {
     }
 }
 
+/// What `clear_ml_caches` removed, or would remove on a dry run
+#[derive(Debug, Clone, Default)]
+pub struct MLCacheClearReport {
+    pub paths: Vec<PathBuf>,
+    pub vector_entries: usize,
+    pub response_entries: usize,
+}
+
+/// Clears the persisted vector index and the ML response cache. With
+/// `dry_run` nothing is touched and the report lists what would be removed.
+pub fn clear_ml_caches(vector_cache_dir: &Path, response_cache_dir: &Path, dry_run: bool) -> Result<MLCacheClearReport> {
+    let mut report = MLCacheClearReport::default();
+
+    if vector_cache_dir.is_dir() {
+        let mut store = NativeVectorStore::new(VectorDBConfig {
+            cache_dir: vector_cache_dir.to_string_lossy().to_string(),
+            ..VectorDBConfig::default()
+        });
+        store.load()?;
+        report.vector_entries = store.stats().total_vectors;
+
+        let mut files: Vec<PathBuf> = fs::read_dir(vector_cache_dir)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .collect();
+        files.sort();
+        report.paths.extend(files);
+
+        if !dry_run {
+            fs::remove_dir_all(vector_cache_dir)?;
+        }
+    }
+
+    let mut response_cache = MLResponseCache::new(response_cache_dir.to_path_buf(), usize::MAX);
+    if response_cache.cache_file.exists() {
+        response_cache.load()?;
+        report.response_entries = response_cache.size();
+        report.paths.push(response_cache.cache_file.clone());
+
+        if !dry_run {
+            response_cache.clear()?;
+        }
+    }
+
+    Ok(report)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -401,4 +448,61 @@ mod tests {
         let cached = cache.get(&hash1);
         assert_eq!(cached, Some("response1".to_string()));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_clear_ml_caches() {
+        use crate::ml::vector_db::{CodeMetadata, CodeType, VectorEntry};
+
+        let temp_dir = TempDir::new().unwrap();
+        let vector_dir = temp_dir.path().join("vector-db");
+        let response_dir = temp_dir.path().join("ml-cache");
+        let vector_config = VectorDBConfig {
+            cache_dir: vector_dir.to_string_lossy().to_string(),
+            ..VectorDBConfig::default()
+        };
+
+        let mut store = NativeVectorStore::new(vector_config.clone());
+        store.add_vector(VectorEntry {
+            id: "a".to_string(),
+            embedding: vec![1.0; 768],
+            metadata: CodeMetadata {
+                file_path: "a.ts".to_string(),
+                function_name: Some("a".to_string()),
+                line_start: 1,
+                line_end: 2,
+                code_type: CodeType::Function,
+                language: "typescript".to_string(),
+                complexity: 1.0,
+                tokens: vec![],
+                calls: Vec::new(),
+                hash: "h".to_string(),
+                embedding_template: "enriched".to_string(),
+            },
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        }).unwrap();
+        store.save().unwrap();
+
+        let mut cache = MLResponseCache::new(response_dir.clone(), 100);
+        cache.put("hash1".to_string(), "response1".to_string(), "qwen_embedding".to_string()).unwrap();
+        cache.save().unwrap();
+
+        // Dry run reports but keeps everything
+        let report = clear_ml_caches(&vector_dir, &response_dir, true).unwrap();
+        assert_eq!(report.vector_entries, 1);
+        assert_eq!(report.response_entries, 1);
+        assert!(report.paths.iter().any(|p| p.ends_with("vectors.json")));
+        assert!(report.paths.contains(&cache.cache_file));
+        assert!(vector_dir.join("vectors.json").exists());
+
+        clear_ml_caches(&vector_dir, &response_dir, false).unwrap();
+
+        let mut reloaded = NativeVectorStore::new(vector_config);
+        reloaded.load().unwrap();
+        assert_eq!(reloaded.stats().total_vectors, 0);
+
+        let mut reloaded_cache = MLResponseCache::new(response_dir, 100);
+        reloaded_cache.load().unwrap();
+        assert_eq!(reloaded_cache.size(), 0);
+    }
+}
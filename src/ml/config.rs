@@ -3,12 +3,26 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
+use crate::cache::FreshnessMode;
 
 /// Memory budget in bytes
 pub const DEFAULT_MEMORY_BUDGET: usize = 6_000_000_000; // 6GB
 pub const MIN_MEMORY_BUDGET: usize = 2_000_000_000; // 2GB
 pub const MAX_MEMORY_BUDGET: usize = 12_000_000_000; // 12GB
 
+/// Candidates scored per reranker call
+pub const DEFAULT_RERANKER_BATCH_SIZE: usize = 16;
+/// Whitespace tokens a candidate may keep before reranking
+pub const DEFAULT_RERANKER_MAX_CANDIDATE_TOKENS: usize = 512;
+/// Entries embedded per batch while indexing
+pub const DEFAULT_EMBEDDING_BATCH_SIZE: usize = 32;
+/// Batches buffered between indexing stages
+pub const DEFAULT_EMBEDDING_QUEUE_DEPTH: usize = 4;
+/// Embedding batches allowed in flight at once
+pub const DEFAULT_MAX_IN_FLIGHT_EMBEDDING_BATCHES: usize = 2;
+/// Time allowed for one model download, in seconds
+pub const DEFAULT_DOWNLOAD_TIMEOUT: u64 = 2 * 60 * 60; // 2 hours
+
 /// Model loading strategies
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum ModelLoadingStrategy {
@@ -34,6 +48,73 @@ pub enum QuantizationLevel {
     F16,
 }
 
+/// How a function is turned into embedding input text
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EmbeddingTemplate {
+    /// Modifiers, name, parameters and return type only
+    Signature,
+    /// Signature followed by the doc comment, when there is one
+    SignatureWithDoc,
+    /// Signature, file, inferred purpose, body and parameter/return semantics
+    #[default]
+    Enriched,
+}
+
+impl EmbeddingTemplate {
+    /// Stable id stored with every indexed entry
+    pub fn id(&self) -> &'static str {
+        match self {
+            Self::Signature => "signature",
+            Self::SignatureWithDoc => "signature_with_doc",
+            Self::Enriched => "enriched",
+        }
+    }
+
+    /// Id recorded on function entries embedded with this template from `content`
+    pub fn input_id(&self, content: EmbeddingContent) -> String {
+        format!("{}{}", self.id(), content.id_suffix())
+    }
+}
+
+/// How function content is hashed to decide whether its embedding can be
+/// reused when the function is indexed again
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ContentHashing {
+    /// Re-embed every function on every index
+    Off,
+    /// Reuse the embedding when the content is byte-for-byte identical
+    #[default]
+    Exact,
+    /// Reuse the embedding when only whitespace changed
+    IgnoreWhitespace,
+}
+
+/// Which part of each indexed function's source is embedded
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EmbeddingContent {
+    /// Code and comments as written
+    #[default]
+    Both,
+    /// Code with its comments removed
+    CodeOnly,
+    /// Comments only; entries without comments embed their code
+    DocOnly,
+}
+
+impl EmbeddingContent {
+    /// Suffix of the embedding input id, empty for `Both`
+    fn id_suffix(&self) -> &'static str {
+        match self {
+            Self::Both => "",
+            Self::CodeOnly => "+code_only",
+            Self::DocOnly => "+doc_only",
+        }
+    }
+}
+
 /// ML configuration for resource management
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MLConfig {
@@ -63,6 +144,61 @@ pub struct MLConfig {
     pub embedding_timeout: u64,
     /// Enable external timeout command wrapper
     pub enable_external_timeout: bool,
+    /// Template used to build the embedding input for functions
+    #[serde(default)]
+    pub embedding_template: EmbeddingTemplate,
+    /// Whether code, comments or both of each function's source are embedded when indexing
+    #[serde(default)]
+    pub embedding_content: EmbeddingContent,
+    /// How a cached index is checked against the sources before reuse;
+    /// the default `Auto` hashes under CI and compares mtimes elsewhere
+    #[serde(default)]
+    pub freshness: FreshnessMode,
+    /// Number of candidates sent to the reranker per call
+    #[serde(default = "default_reranker_batch_size")]
+    pub reranker_batch_size: usize,
+    /// Candidates longer than this many tokens are truncated before reranking
+    #[serde(default = "default_reranker_max_candidate_tokens")]
+    pub reranker_max_candidate_tokens: usize,
+    /// Number of entries embedded per batch while indexing
+    #[serde(default = "default_embedding_batch_size")]
+    pub embedding_batch_size: usize,
+    /// Batches buffered between the prepare, embed and insert stages of indexing
+    #[serde(default = "default_embedding_queue_depth")]
+    pub embedding_queue_depth: usize,
+    /// Embedding batches submitted to the model at the same time
+    #[serde(default = "default_max_in_flight_embedding_batches")]
+    pub max_in_flight_embedding_batches: usize,
+    /// Hashing used to skip re-embedding unchanged functions
+    #[serde(default)]
+    pub content_hashing: ContentHashing,
+    /// Total time allowed for one model download in seconds
+    #[serde(default = "default_download_timeout")]
+    pub download_timeout: u64,
+}
+
+fn default_reranker_batch_size() -> usize {
+    DEFAULT_RERANKER_BATCH_SIZE
+}
+
+fn default_reranker_max_candidate_tokens() -> usize {
+    DEFAULT_RERANKER_MAX_CANDIDATE_TOKENS
+}
+
+fn default_embedding_batch_size() -> usize {
+    DEFAULT_EMBEDDING_BATCH_SIZE
+}
+
+fn default_embedding_queue_depth() -> usize {
+    DEFAULT_EMBEDDING_QUEUE_DEPTH
+}
+
+fn default_max_in_flight_embedding_batches() -> usize {
+    DEFAULT_MAX_IN_FLIGHT_EMBEDDING_BATCHES
+}
+
+fn default_download_timeout() -> u64 {
+    DEFAULT_DOWNLOAD_TIMEOUT
 }
 
 impl Default for MLConfig {
@@ -81,6 +217,16 @@ impl Default for MLConfig {
             reasoning_timeout: 240,         // 4 minutes for DeepSeek thinking
             embedding_timeout: 60,          // 1 minute for Qwen embeddings
             enable_external_timeout: true,  // Enable external timeout control
+            embedding_template: EmbeddingTemplate::default(),
+            embedding_content: EmbeddingContent::default(),
+            freshness: FreshnessMode::default(),
+            reranker_batch_size: DEFAULT_RERANKER_BATCH_SIZE,
+            reranker_max_candidate_tokens: DEFAULT_RERANKER_MAX_CANDIDATE_TOKENS,
+            embedding_batch_size: DEFAULT_EMBEDDING_BATCH_SIZE,
+            embedding_queue_depth: DEFAULT_EMBEDDING_QUEUE_DEPTH,
+            max_in_flight_embedding_batches: DEFAULT_MAX_IN_FLIGHT_EMBEDDING_BATCHES,
+            content_hashing: ContentHashing::default(),
+            download_timeout: DEFAULT_DOWNLOAD_TIMEOUT,
         }
     }
 }
@@ -102,6 +248,16 @@ impl MLConfig {
             reasoning_timeout: 240,
             embedding_timeout: 60,
             enable_external_timeout: true,
+            embedding_template: EmbeddingTemplate::default(),
+            embedding_content: EmbeddingContent::default(),
+            freshness: FreshnessMode::default(),
+            reranker_batch_size: DEFAULT_RERANKER_BATCH_SIZE,
+            reranker_max_candidate_tokens: DEFAULT_RERANKER_MAX_CANDIDATE_TOKENS,
+            embedding_batch_size: DEFAULT_EMBEDDING_BATCH_SIZE,
+            embedding_queue_depth: DEFAULT_EMBEDDING_QUEUE_DEPTH,
+            max_in_flight_embedding_batches: DEFAULT_MAX_IN_FLIGHT_EMBEDDING_BATCHES,
+            content_hashing: ContentHashing::default(),
+            download_timeout: DEFAULT_DOWNLOAD_TIMEOUT,
         }
     }
 
@@ -121,6 +277,16 @@ impl MLConfig {
             reasoning_timeout: 300,
             embedding_timeout: 90,
             enable_external_timeout: true,
+            embedding_template: EmbeddingTemplate::default(),
+            embedding_content: EmbeddingContent::default(),
+            freshness: FreshnessMode::default(),
+            reranker_batch_size: DEFAULT_RERANKER_BATCH_SIZE,
+            reranker_max_candidate_tokens: DEFAULT_RERANKER_MAX_CANDIDATE_TOKENS,
+            embedding_batch_size: DEFAULT_EMBEDDING_BATCH_SIZE,
+            embedding_queue_depth: DEFAULT_EMBEDDING_QUEUE_DEPTH,
+            max_in_flight_embedding_batches: DEFAULT_MAX_IN_FLIGHT_EMBEDDING_BATCHES,
+            content_hashing: ContentHashing::default(),
+            download_timeout: DEFAULT_DOWNLOAD_TIMEOUT,
         }
     }
 
@@ -140,6 +306,16 @@ impl MLConfig {
             reasoning_timeout: 480,         // 8 minutes for CPU DeepSeek
             embedding_timeout: 120,         // 2 minutes for CPU embeddings
             enable_external_timeout: true,
+            embedding_template: EmbeddingTemplate::default(),
+            embedding_content: EmbeddingContent::default(),
+            freshness: FreshnessMode::default(),
+            reranker_batch_size: DEFAULT_RERANKER_BATCH_SIZE,
+            reranker_max_candidate_tokens: DEFAULT_RERANKER_MAX_CANDIDATE_TOKENS,
+            embedding_batch_size: DEFAULT_EMBEDDING_BATCH_SIZE,
+            embedding_queue_depth: DEFAULT_EMBEDDING_QUEUE_DEPTH,
+            max_in_flight_embedding_batches: DEFAULT_MAX_IN_FLIGHT_EMBEDDING_BATCHES,
+            content_hashing: ContentHashing::default(),
+            download_timeout: DEFAULT_DOWNLOAD_TIMEOUT,
         }
     }
 
@@ -159,6 +335,16 @@ impl MLConfig {
             reasoning_timeout: 45,
             embedding_timeout: 30,
             enable_external_timeout: false, // Disable external timeout in tests
+            embedding_template: EmbeddingTemplate::default(),
+            embedding_content: EmbeddingContent::default(),
+            freshness: FreshnessMode::default(),
+            reranker_batch_size: DEFAULT_RERANKER_BATCH_SIZE,
+            reranker_max_candidate_tokens: DEFAULT_RERANKER_MAX_CANDIDATE_TOKENS,
+            embedding_batch_size: DEFAULT_EMBEDDING_BATCH_SIZE,
+            embedding_queue_depth: DEFAULT_EMBEDDING_QUEUE_DEPTH,
+            max_in_flight_embedding_batches: DEFAULT_MAX_IN_FLIGHT_EMBEDDING_BATCHES,
+            content_hashing: ContentHashing::default(),
+            download_timeout: DEFAULT_DOWNLOAD_TIMEOUT,
         }
     }
 
@@ -209,6 +395,18 @@ impl MLConfig {
             anyhow::bail!("Embedding timeout must be greater than 0");
         }
 
+        if self.reranker_batch_size == 0 {
+            anyhow::bail!("Reranker batch size must be at least 1");
+        }
+
+        if self.reranker_max_candidate_tokens == 0 {
+            anyhow::bail!("Reranker max candidate tokens must be at least 1");
+        }
+
+        if self.embedding_batch_size == 0 || self.embedding_queue_depth == 0 || self.max_in_flight_embedding_batches == 0 {
+            anyhow::bail!("Embedding batch size, queue depth and in-flight batches must be at least 1");
+        }
+
         Ok(())
     }
 
@@ -240,6 +438,12 @@ impl MLConfig {
         }
     }
 
+    /// Id recorded with every indexed entry: the embedding template, plus the
+    /// embedded content when that is not the default
+    pub fn embedding_input_id(&self) -> String {
+        self.embedding_template.input_id(self.embedding_content)
+    }
+
     /// Get timeout for DeepSeek reasoning operations
     pub fn get_reasoning_timeout(&self) -> u64 {
         self.reasoning_timeout
@@ -34,7 +34,7 @@ pub struct LayeredAnalysisResult {
 pub struct LayeredAnalysisService {
     config: MLConfig,
     plugin_manager: Arc<PluginManager>,
-    ast_analyzer: TypeScriptASTAnalyzer,
+    ast_analyzer: Option<TypeScriptASTAnalyzer>,
     ml_cache: MLResponseCache,
     timeout_wrapper: ExternalTimeoutWrapper,
 }
@@ -50,7 +50,7 @@ impl LayeredAnalysisService {
             timeout_wrapper: ExternalTimeoutWrapper::new(config.clone()),
             config,
             plugin_manager,
-            ast_analyzer: TypeScriptASTAnalyzer::new().expect("Failed to create AST analyzer"),
+            ast_analyzer: TypeScriptASTAnalyzer::new().ok(),
             ml_cache,
         }
     }
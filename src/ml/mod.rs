@@ -24,7 +24,7 @@ pub use plugins::*;
 pub use services::*;
 pub use external_timeout::ExternalTimeoutWrapper;
 pub use prompts::StructuredPrompts;
-pub use cache::MLResponseCache;
+pub use cache::{MLResponseCache, clear_ml_caches};
 
 use anyhow::Result;
 use uuid::Uuid;
@@ -45,9 +45,22 @@ impl MLCoordinator {
         }
     }
 
+    /// Coordinator around an already-initialized `plugin_manager`, e.g. one
+    /// set up with `PluginManager::initialize_with_mocks`
+    pub fn with_plugin_manager(config: MLConfig, plugin_manager: PluginManager) -> Self {
+        Self {
+            config,
+            plugin_manager,
+            session_id: Uuid::new_v4(),
+        }
+    }
+
     pub async fn initialize(&mut self) -> Result<()> {
         tracing::info!("Initializing ML coordinator with session: {}", self.session_id);
-        self.plugin_manager.initialize(&self.config).await?;
+        // A manager passed to `with_plugin_manager` already has its plugins
+        if self.plugin_manager.get_plugin_count() == 0 {
+            self.plugin_manager.initialize(&self.config).await?;
+        }
         Ok(())
     }
 
@@ -5,12 +5,16 @@ use futures::StreamExt;
 use reqwest::Client;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 use tokio::fs::File;
 use tokio::io::AsyncWriteExt;
 use tracing::{error, info, warn};
 
 use crate::ml::config::MLConfig;
 
+/// Downloads may exceed the registry size by this factor before they are aborted
+pub const DOWNLOAD_SIZE_SLACK: f64 = 1.25;
+
 /// Model download information
 #[derive(Debug, Clone)]
 pub struct ModelInfo {
@@ -21,6 +25,18 @@ pub struct ModelInfo {
     pub description: String,
 }
 
+impl ModelInfo {
+    /// Bytes the registry expects the file to take
+    pub fn expected_bytes(&self) -> u64 {
+        (self.size_gb * 1_000_000_000.0) as u64
+    }
+
+    /// Bytes after which a download of this model is aborted
+    pub fn max_download_bytes(&self) -> u64 {
+        (self.expected_bytes() as f64 * DOWNLOAD_SIZE_SLACK) as u64
+    }
+}
+
 /// Model downloader for GGUF models
 pub struct ModelDownloader {
     client: Client,
@@ -87,10 +103,11 @@ impl ModelDownloader {
         // Create cache directory if it doesn't exist
         if let Some(parent) = output_path.parent() {
             fs::create_dir_all(parent)?;
+            ensure_free_space(parent, model.expected_bytes())?;
         }
 
         // Download the model
-        self.download_file(&model.url, &output_path).await?;
+        self.download_file(&model.url, &output_path, model.max_download_bytes()).await?;
 
         info!("Model '{}' downloaded successfully", model_name);
         Ok(output_path)
@@ -173,8 +190,28 @@ impl ModelDownloader {
         Ok(())
     }
 
-    /// Download a file from URL to local path
-    async fn download_file(&self, url: &str, output_path: &Path) -> Result<()> {
+    /// Download a file from URL to local path, giving up after
+    /// `download_timeout` seconds or `max_bytes` bytes. The file is written
+    /// under a `.part` name and only renamed once complete, so a failed
+    /// download never passes for a cached model.
+    async fn download_file(&self, url: &str, output_path: &Path, max_bytes: u64) -> Result<()> {
+        let partial_path = output_path.with_extension("part");
+        let timeout = Duration::from_secs(self.config.download_timeout);
+
+        let result = match tokio::time::timeout(timeout, self.stream_to_file(url, &partial_path, max_bytes)).await {
+            Ok(result) => result,
+            Err(_) => Err(anyhow::anyhow!("Download of {} timed out after {}s", url, timeout.as_secs())),
+        };
+        if let Err(e) = result {
+            let _ = fs::remove_file(&partial_path);
+            return Err(e);
+        }
+
+        fs::rename(&partial_path, output_path)?;
+        Ok(())
+    }
+
+    async fn stream_to_file(&self, url: &str, output_path: &Path, max_bytes: u64) -> Result<()> {
         let response = self.client.get(url).send().await?;
         
         if !response.status().is_success() {
@@ -182,6 +219,9 @@ impl ModelDownloader {
         }
 
         let total_size = response.content_length().unwrap_or(0);
+        if total_size > max_bytes {
+            anyhow::bail!("Download of {} is {} bytes, over the {} byte limit for this model", url, total_size, max_bytes);
+        }
         let mut downloaded = 0u64;
         let mut stream = response.bytes_stream();
         
@@ -189,8 +229,11 @@ impl ModelDownloader {
         
         while let Some(chunk) = stream.next().await {
             let chunk = chunk?;
-            file.write_all(&chunk).await?;
             downloaded += chunk.len() as u64;
+            if downloaded > max_bytes {
+                anyhow::bail!("Download of {} exceeded the {} byte limit for this model; aborted", url, max_bytes);
+            }
+            file.write_all(&chunk).await?;
             
             if total_size > 0 {
                 let progress = (downloaded as f64 / total_size as f64 * 100.0) as u32;
@@ -208,6 +251,36 @@ impl ModelDownloader {
     }
 }
 
+/// Fails early when `dir` cannot hold `needed` more bytes. Platforms without
+/// a free space query skip the check.
+fn ensure_free_space(dir: &Path, needed: u64) -> Result<()> {
+    match available_space(dir) {
+        Some(available) if available < needed => anyhow::bail!(
+            "Not enough disk space in {}: {:.1}GB needed, {:.1}GB free",
+            dir.display(), needed as f64 / 1_000_000_000.0, available as f64 / 1_000_000_000.0
+        ),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(unix)]
+fn available_space(dir: &Path) -> Option<u64> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let path = std::ffi::CString::new(dir.as_os_str().as_bytes()).ok()?;
+    let mut stats: libc::statvfs = unsafe { std::mem::zeroed() };
+    // SAFETY: `path` is a NUL-terminated string and `stats` is a valid out-pointer
+    if unsafe { libc::statvfs(path.as_ptr(), &mut stats) } != 0 {
+        return None;
+    }
+    Some(stats.f_bavail as u64 * stats.f_frsize as u64)
+}
+
+#[cfg(not(unix))]
+fn available_space(_dir: &Path) -> Option<u64> {
+    None
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -305,6 +378,55 @@ mod tests {
         assert!(!temp_dir.path().exists() || !temp_dir.path().join("some_file").exists());
     }
 
-    // Note: We don't test actual downloads in unit tests as they require internet
-    // These would be integration tests
+    /// Serves `body` once over HTTP without a Content-Length, so only the
+    /// streaming guard can catch an oversized body
+    async fn serve_once(body: Vec<u8>) -> String {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = [0u8; 1024];
+            let _ = socket.read(&mut request).await;
+            let _ = socket.write_all(b"HTTP/1.1 200 OK\r\nConnection: close\r\n\r\n").await;
+            let _ = socket.write_all(&body).await;
+        });
+        format!("http://{}/model.gguf", address)
+    }
+
+    #[tokio::test]
+    async fn test_download_over_size_guard_is_aborted() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut config = MLConfig::for_testing();
+        config.model_cache_dir = temp_dir.path().to_path_buf();
+        let downloader = ModelDownloader::new(config);
+
+        let url = serve_once(vec![0u8; 64 * 1024]).await;
+        let output_path = temp_dir.path().join("model.gguf");
+        let error = downloader.download_file(&url, &output_path, 16 * 1024).await.unwrap_err();
+
+        assert!(error.to_string().contains("exceeded the 16384 byte limit"), "{}", error);
+        assert!(!output_path.exists());
+        assert!(!output_path.with_extension("part").exists());
+
+        let url = serve_once(b"GGUF".to_vec()).await;
+        downloader.download_file(&url, &output_path, 16 * 1024).await.unwrap();
+        assert_eq!(fs::read(&output_path).unwrap(), b"GGUF");
+    }
+
+    #[test]
+    fn test_size_guard_and_free_space_check() {
+        let config = MLConfig::for_testing();
+        let downloader = ModelDownloader::new(config);
+        let model = &downloader.get_available_models()[0];
+        assert_eq!(model.max_download_bytes(), 10_000_000_000);
+
+        let temp_dir = TempDir::new().unwrap();
+        assert!(ensure_free_space(temp_dir.path(), 1).is_ok());
+        if cfg!(unix) {
+            let error = ensure_free_space(temp_dir.path(), u64::MAX).unwrap_err();
+            assert!(error.to_string().contains("Not enough disk space"), "{}", error);
+        }
+    }
 }
\ No newline at end of file
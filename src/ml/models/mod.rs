@@ -6,6 +6,24 @@ use uuid::Uuid;
 pub mod downloader;
 pub use downloader::ModelDownloader;
 
+/// Whether a result was produced by loaded ML models or by static heuristics only
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AnalysisMode {
+    Static,
+    Ai,
+}
+
+impl AnalysisMode {
+    /// One-line notice shown above command output
+    pub fn banner(&self) -> &'static str {
+        match self {
+            AnalysisMode::Static => "⚠️  STATIC ANALYSIS ONLY: ML models are not available, results are heuristic",
+            AnalysisMode::Ai => "🤖 AI analysis: results produced with loaded ML models",
+        }
+    }
+}
+
 /// Smart context analysis result
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SmartContext {
@@ -406,7 +424,7 @@ pub struct DesignPattern {
 }
 
 /// Anti-pattern identification
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct AntiPattern {
     pub pattern_name: String,
     pub severity: Severity,
@@ -439,7 +457,7 @@ pub enum RefactoringType {
 }
 
 /// Code location
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct CodeLocation {
     pub file_path: String,
     pub line_start: usize,
@@ -482,6 +500,38 @@ pub struct TokenOptimization {
     pub estimated_tokens: usize,
 }
 
+/// Tokens saved by sending the optimized selection instead of every candidate file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavingsReport {
+    pub task: String,
+    /// Tokens in all readable candidate files
+    pub full_context_tokens: usize,
+    /// Tokens in the files the optimizer selected
+    pub optimized_tokens: usize,
+    pub tokens_saved: usize,
+    /// `tokens_saved` as a percentage of `full_context_tokens`
+    pub savings_percent: f32,
+}
+
+impl SavingsReport {
+    pub fn new(task: String, full_context_tokens: usize, optimized_tokens: usize) -> Self {
+        let tokens_saved = full_context_tokens.saturating_sub(optimized_tokens);
+        let savings_percent = if full_context_tokens > 0 {
+            tokens_saved as f32 / full_context_tokens as f32 * 100.0
+        } else {
+            0.0
+        };
+
+        Self {
+            task,
+            full_context_tokens,
+            optimized_tokens,
+            tokens_saved,
+            savings_percent,
+        }
+    }
+}
+
 /// File recommendation for token optimization
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileRecommendation {
@@ -680,6 +730,9 @@ pub struct PatternReport {
     pub architectural_patterns: Vec<ArchitecturalPattern>,
     pub refactoring_suggestions: Vec<RefactoringSuggestion>,
     pub analysis_metadata: PatternAnalysisMetadata,
+    /// Duplicates and clusters rolled up by directory, when grouping is enabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub groups: Option<PatternGroup>,
 }
 
 /// Extended pattern types for pattern detection
@@ -790,4 +843,154 @@ pub struct PatternAnalysisMetadata {
     pub embedding_model: String,
     pub similarity_threshold: f32,
     pub analysis_timestamp: std::time::SystemTime,
-}
\ No newline at end of file
+}
+
+/// How `PatternReport::group_by_directory` rolls patterns up
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PatternGroupingOptions {
+    /// Directories deeper than this are folded into their ancestor at this depth
+    pub max_depth: usize,
+    /// Patterns and clusters scoring below this similarity are left out
+    pub min_similarity: f32,
+}
+
+impl Default for PatternGroupingOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: 3,
+            min_similarity: 0.0,
+        }
+    }
+}
+
+/// Directory node of a grouped pattern report. Patterns sit on the deepest
+/// directory shared by every file they touch; counts and the refactoring
+/// estimate include all descendants.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PatternGroup {
+    /// Directory relative to the project root, empty for the root itself
+    pub path: String,
+    pub duplicate_count: usize,
+    pub cluster_count: usize,
+    pub estimated_refactoring_hours: f32,
+    pub duplicate_patterns: Vec<EnhancedDuplicatePattern>,
+    pub semantic_clusters: Vec<SemanticCluster>,
+    pub children: Vec<PatternGroup>,
+}
+
+/// Rough effort to extract a duplicate, plus each further copy to replace
+const HOURS_PER_DUPLICATE: f32 = 1.0;
+const HOURS_PER_DUPLICATE_COPY: f32 = 0.5;
+/// Rough effort to move one clustered function into a shared utility
+const HOURS_PER_CLUSTER_FUNCTION: f32 = 0.5;
+
+impl PatternReport {
+    /// Roll duplicates and clusters up into a directory tree
+    pub fn group_by_directory(&self, options: &PatternGroupingOptions) -> PatternGroup {
+        let mut root = PatternGroup::default();
+
+        for pattern in self.duplicate_patterns.iter().filter(|p| p.similarity_score >= options.min_similarity) {
+            let files = std::iter::once(&pattern.primary_function)
+                .chain(&pattern.duplicate_functions)
+                .map(|f| f.file_path.as_str());
+            let dir = self.shared_directory(files, options.max_depth);
+            root.node_mut(&dir).duplicate_patterns.push(pattern.clone());
+        }
+
+        for cluster in self.semantic_clusters.iter().filter(|c| c.similarity_score >= options.min_similarity) {
+            let dir = self.shared_directory(cluster.functions.iter().map(|f| f.file_path.as_str()), options.max_depth);
+            root.node_mut(&dir).semantic_clusters.push(cluster.clone());
+        }
+
+        root.sum_totals();
+        root
+    }
+
+    /// Deepest directory containing every file, relative to the project root
+    fn shared_directory<'a>(&self, files: impl Iterator<Item = &'a str>, max_depth: usize) -> Vec<String> {
+        let root = std::path::Path::new(&self.project_path);
+        let mut shared: Option<Vec<String>> = None;
+
+        for file in files {
+            let path = std::path::Path::new(file);
+            let relative = path.strip_prefix(root).unwrap_or(path);
+            let dirs: Vec<String> = relative.parent()
+                .map(|parent| parent.components()
+                    .filter_map(|c| match c {
+                        std::path::Component::Normal(name) => Some(name.to_string_lossy().to_string()),
+                        _ => None,
+                    })
+                    .collect())
+                .unwrap_or_default();
+
+            shared = Some(match shared {
+                None => dirs,
+                Some(current) => current.into_iter()
+                    .zip(dirs)
+                    .take_while(|(a, b)| a == b)
+                    .map(|(a, _)| a)
+                    .collect(),
+            });
+        }
+
+        let mut shared = shared.unwrap_or_default();
+        shared.truncate(max_depth);
+        shared
+    }
+}
+
+impl PatternGroup {
+    /// Directory and file paths of this group and its children, for output
+    pub fn paths_mut(&mut self) -> Vec<&mut String> {
+        let mut paths = vec![&mut self.path];
+        for pattern in &mut self.duplicate_patterns {
+            paths.push(&mut pattern.primary_function.file_path);
+            paths.extend(pattern.duplicate_functions.iter_mut().map(|f| &mut f.file_path));
+        }
+        for cluster in &mut self.semantic_clusters {
+            paths.extend(cluster.functions.iter_mut().map(|f| &mut f.file_path));
+        }
+        for child in &mut self.children {
+            paths.extend(child.paths_mut());
+        }
+        paths
+    }
+
+    fn node_mut(&mut self, dirs: &[String]) -> &mut PatternGroup {
+        let Some((first, rest)) = dirs.split_first() else {
+            return self;
+        };
+        let path = if self.path.is_empty() {
+            first.clone()
+        } else {
+            format!("{}/{}", self.path, first)
+        };
+
+        let index = match self.children.iter().position(|child| child.path == path) {
+            Some(index) => index,
+            None => {
+                self.children.push(PatternGroup { path, ..PatternGroup::default() });
+                self.children.len() - 1
+            }
+        };
+        self.children[index].node_mut(rest)
+    }
+
+    fn sum_totals(&mut self) {
+        self.duplicate_count = self.duplicate_patterns.len();
+        self.cluster_count = self.semantic_clusters.len();
+        // Folded from 0.0: `f32::sum` of nothing is -0.0, which prints as "-0.0h"
+        self.estimated_refactoring_hours = self.duplicate_patterns.iter()
+            .map(|p| HOURS_PER_DUPLICATE + HOURS_PER_DUPLICATE_COPY * p.duplicate_functions.len() as f32)
+            .chain(self.semantic_clusters.iter().map(|c| HOURS_PER_CLUSTER_FUNCTION * c.functions.len() as f32))
+            .fold(0.0, |total, hours| total + hours);
+
+        self.children.sort_by(|a, b| a.path.cmp(&b.path));
+        for child in &mut self.children {
+            child.sum_totals();
+            self.duplicate_count += child.duplicate_count;
+            self.cluster_count += child.cluster_count;
+            self.estimated_refactoring_hours += child.estimated_refactoring_hours;
+        }
+    }
+}
@@ -0,0 +1,223 @@
+//! Deterministic stand-in for the model plugins
+//!
+//! A `MockPlugin` answers `process` in the same JSON shape as the plugin it
+//! replaces, computed from the input alone, so services built on a
+//! `PluginManager` can be tested end to end without model files.
+
+use anyhow::Result;
+use async_trait::async_trait;
+
+use super::{MLCapability, MLPlugin, PluginStatus};
+use crate::ml::config::MLConfig;
+use crate::ml::vector_db::{HashingEmbeddingBackend, OverlapRerankerBackend, RerankerBackend};
+
+/// Embedding dimension of the real Qwen embedding model
+const MOCK_EMBEDDING_DIMENSION: usize = 768;
+
+pub struct MockPlugin {
+    name: String,
+    capabilities: Vec<MLCapability>,
+    loaded: bool,
+}
+
+impl MockPlugin {
+    /// Mock for the plugin registered as `name`; its capabilities follow the
+    /// default plugin of that name, anything else is a reasoning model
+    pub fn new(name: &str) -> Self {
+        let capabilities = match name {
+            "qwen_embedding" => vec![MLCapability::TextEmbedding, MLCapability::CodeEmbedding],
+            "qwen_reranker" => vec![MLCapability::TextReranking, MLCapability::CodeReranking],
+            _ => vec![MLCapability::Reasoning, MLCapability::CodeAnalysis],
+        };
+        Self { name: name.to_string(), capabilities, loaded: false }
+    }
+
+    fn has(&self, capability: MLCapability) -> bool {
+        self.capabilities.contains(&capability)
+    }
+}
+
+#[async_trait]
+impl MLPlugin for MockPlugin {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn version(&self) -> &str {
+        "mock"
+    }
+
+    fn memory_usage(&self) -> usize {
+        0
+    }
+
+    fn is_loaded(&self) -> bool {
+        self.loaded
+    }
+
+    async fn load(&mut self, _config: &MLConfig) -> Result<()> {
+        self.loaded = true;
+        Ok(())
+    }
+
+    async fn unload(&mut self) -> Result<()> {
+        self.loaded = false;
+        Ok(())
+    }
+
+    async fn health_check(&self) -> Result<PluginStatus> {
+        Ok(PluginStatus {
+            loaded: self.loaded,
+            memory_mb: 0,
+            last_used: None,
+            error: None,
+            capabilities: self.capabilities(),
+        })
+    }
+
+    fn capabilities(&self) -> Vec<MLCapability> {
+        self.capabilities.clone()
+    }
+
+    async fn process(&self, input: &str) -> Result<String> {
+        if !self.loaded {
+            anyhow::bail!("Mock plugin {} not initialized", self.name);
+        }
+
+        let result = if self.has(MLCapability::TextEmbedding) {
+            let embedding = HashingEmbeddingBackend::new(MOCK_EMBEDDING_DIMENSION).embed(input);
+            serde_json::json!({
+                "embedding": embedding,
+                "dimension": embedding.len(),
+                "text_length": input.len()
+            })
+        } else if self.has(MLCapability::TextReranking) {
+            // Same query||document protocol as the Qwen reranker
+            let (query, document) = input.split_once("||").unwrap_or(("code analysis relevance", input));
+            let scores = OverlapRerankerBackend.rank_documents(query, &[document.to_string()]).await?;
+            serde_json::json!({
+                "query": query,
+                "document": document,
+                "relevance_score": scores[0].1
+            })
+        } else {
+            serde_json::json!({
+                "analysis": format!("mock analysis of {} characters", input.len()),
+                "confidence": 1.0
+            })
+        };
+
+        Ok(result.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ml::plugins::PluginManager;
+    use crate::ml::models::AnalysisMode;
+    use crate::ml::services::enhanced_search::{
+        CodeIndexEntry, EnhancedSearchService, SearchFilters, SearchOptions, SearchRequest, SearchType,
+    };
+    use crate::ml::services::search::SemanticSearchService;
+    use crate::ml::vector_db::{CodeType, VectorDBConfig, VectorStoreFactory};
+    use crate::ml::services::embedding_template::FIXED_INPUT_ID;
+    use std::fs;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_mock_plugins_return_deterministic_output() {
+        let mut manager = PluginManager::new();
+        manager.initialize_with_mocks(&MLConfig::for_testing()).await.unwrap();
+        assert_eq!(manager.get_plugin_count(), 3);
+
+        let first = manager.process_with_plugin("qwen_embedding", "validate user token").await.unwrap();
+        let second = manager.process_with_plugin("qwen_embedding", "validate user token").await.unwrap();
+        assert_eq!(first, second);
+        let parsed: serde_json::Value = serde_json::from_str(&first).unwrap();
+        assert_eq!(parsed["dimension"], 768);
+
+        let rerank = manager.process_with_plugin("qwen_reranker", "user token||fn check(token: Token, user: User)").await.unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&rerank).unwrap();
+        assert_eq!(parsed["relevance_score"], 1.0);
+
+        let snapshot = manager.snapshot_state();
+        assert_eq!(snapshot.active_plugins, vec!["qwen_embedding", "qwen_reranker"]);
+        manager.load_plugin("deepseek").await.unwrap();
+        manager.unload_plugin("qwen_reranker").await.unwrap();
+        manager.restore_state(&snapshot).await.unwrap();
+        assert_eq!(manager.snapshot_state(), snapshot);
+    }
+
+    #[tokio::test]
+    async fn test_semantic_search_through_mock_plugins() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("auth.service.ts"), concat!(
+            "export class AuthService {\n",
+            "  validateUserToken(token: string): boolean {\n",
+            "    return token.length > 0 && !this.isExpired(token);\n",
+            "  }\n",
+            "}\n",
+        )).unwrap();
+        fs::write(temp_dir.path().join("calendar.service.ts"), concat!(
+            "export class CalendarService {\n",
+            "  scheduleMeeting(date: Date, attendees: string[]): void {\n",
+            "    this.events.push({ date, attendees });\n",
+            "  }\n",
+            "}\n",
+        )).unwrap();
+
+        let config = MLConfig::for_testing();
+        let mut manager = PluginManager::new();
+        manager.initialize_with_mocks(&config).await.unwrap();
+        let mut service = SemanticSearchService::new(config, Arc::new(manager));
+        service.initialize().await.unwrap();
+
+        let result = service
+            .search("validate user token", &temp_dir.path().to_string_lossy(), Some(5))
+            .await
+            .unwrap();
+        assert!(!result.results.is_empty());
+        assert_eq!(result.results[0].key_functions, vec!["validateUserToken"]);
+        assert!(result.results[0].file_path.ends_with("auth.service.ts"));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_enhanced_search_through_mock_plugins() {
+        let config = MLConfig::for_testing();
+        let mut manager = PluginManager::new();
+        manager.initialize_with_mocks(&config).await.unwrap();
+        let vector_db = VectorStoreFactory::create_native(VectorDBConfig {
+            similarity_threshold: 0.1,
+            ..VectorDBConfig::for_testing()
+        });
+        let service = EnhancedSearchService::with_plugin_manager(config, vector_db, Arc::new(manager)).await;
+        assert_eq!(service.analysis_mode(), AnalysisMode::Ai);
+
+        let entry = |file_path: &str, content: &str| CodeIndexEntry {
+            file_path: file_path.to_string(),
+            function_name: None,
+            line_start: 1,
+            line_end: 5,
+            code_type: CodeType::Function,
+            language: "typescript".to_string(),
+            complexity: 1.0,
+            content: content.to_string(),
+            embedding_template: FIXED_INPUT_ID.to_string(),
+        };
+        service.index_code(vec![
+            entry("auth.service.ts", "validateUserToken(token: string): boolean { return token.length > 0; }"),
+            entry("calendar.service.ts", "scheduleMeeting(date: Date, attendees: string[]): void { this.events.push(date); }"),
+        ]).await.unwrap();
+
+        let response = service.search(SearchRequest {
+            query: "validate user token".to_string(),
+            search_type: SearchType::General,
+            filters: SearchFilters::default(),
+            options: SearchOptions::default(),
+        }).await.unwrap();
+        assert_eq!(response.results[0].entry.metadata.file_path, "auth.service.ts");
+        assert_eq!(service.analysis_mode(), AnalysisMode::Ai);
+    }
+}
@@ -12,6 +12,7 @@ use uuid::Uuid;
 use crate::ml::config::MLConfig;
 
 pub mod deepseek;
+pub mod mock;
 pub mod qwen_embedding;
 pub mod qwen_reranker;
 
@@ -22,6 +23,7 @@ pub mod gguf_loader_test;
 pub mod real_embedding_test;
 
 pub use deepseek::DeepSeekPlugin;
+pub use mock::MockPlugin;
 pub use qwen_embedding::QwenEmbeddingPlugin;
 pub use qwen_reranker::QwenRerankerPlugin;
 
@@ -56,6 +58,13 @@ pub enum LoadingStrategy {
     Preloaded,
 }
 
+/// Which plugins were loaded at some point, see `PluginManager::snapshot_state`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PluginStateSnapshot {
+    /// Sorted names of the loaded plugins
+    pub active_plugins: Vec<String>,
+}
+
 /// Plugin trait for ML models
 #[async_trait]
 pub trait MLPlugin: Send + Sync {
@@ -124,6 +133,25 @@ impl PluginManager {
         Ok(())
     }
 
+    /// Like `initialize`, but registers a `MockPlugin` under each default
+    /// plugin name so services run without model files
+    pub async fn initialize_with_mocks(&mut self, config: &MLConfig) -> Result<()> {
+        self.config = Some(config.clone());
+
+        for name in ["deepseek", "qwen_embedding", "qwen_reranker"] {
+            self.register_mock(name).await?;
+        }
+
+        tracing::info!("Plugin manager initialized with {} mock plugins", self.get_plugin_count());
+        Ok(())
+    }
+
+    /// Register a `MockPlugin` under `name`, answering with deterministic
+    /// embeddings, rerank scores or analysis depending on the name
+    pub async fn register_mock(&mut self, name: &str) -> Result<()> {
+        self.register_plugin(name, Box::new(MockPlugin::new(name))).await
+    }
+
     pub async fn register_plugin(&mut self, name: &str, plugin: Box<dyn MLPlugin>) -> Result<()> {
         let mut plugins = self.plugins.write();
         
@@ -275,6 +303,28 @@ impl PluginManager {
         self.active_plugins.read().keys().cloned().collect()
     }
 
+    /// Record which plugins are loaded, for `restore_state` to return to
+    pub fn snapshot_state(&self) -> PluginStateSnapshot {
+        let mut active_plugins = self.get_active_plugins();
+        active_plugins.sort();
+        PluginStateSnapshot { active_plugins }
+    }
+
+    /// Unload the plugins loaded since `snapshot` and reload the ones unloaded since
+    pub async fn restore_state(&self, snapshot: &PluginStateSnapshot) -> Result<()> {
+        for name in self.get_active_plugins() {
+            if !snapshot.active_plugins.contains(&name) {
+                self.unload_plugin(&name).await?;
+            }
+        }
+        for name in &snapshot.active_plugins {
+            if !self.is_plugin_loaded(name) {
+                self.load_plugin(name).await?;
+            }
+        }
+        Ok(())
+    }
+
     pub async fn health_check(&self) -> Result<HashMap<String, PluginStatus>> {
         let plugins = self.plugins.read();
         let mut results = HashMap::new();
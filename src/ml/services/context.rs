@@ -15,7 +15,7 @@ use crate::analyzers::ts_ast_analyzer::TypeScriptASTAnalyzer;
 pub struct SmartContextService {
     config: MLConfig,
     plugin_manager: Arc<PluginManager>,
-    ast_analyzer: TypeScriptASTAnalyzer,
+    ast_analyzer: Option<TypeScriptASTAnalyzer>,
     is_ready: bool,
 }
 
@@ -24,7 +24,7 @@ impl SmartContextService {
         Ok(Self {
             config,
             plugin_manager,
-            ast_analyzer: TypeScriptASTAnalyzer::new()?,
+            ast_analyzer: TypeScriptASTAnalyzer::new().ok(),
             is_ready: false,
         })
     }
@@ -0,0 +1,158 @@
+//! Chunking of documentation into search index entries
+//!
+//! Markdown files are split into one entry per heading section; source files
+//! contribute their top-of-file doc comment. All entries are tagged with
+//! `CodeType::Comment` and `language = "markdown"`.
+
+use std::path::Path;
+
+use crate::ml::services::embedding_template::FIXED_INPUT_ID;
+use crate::ml::services::enhanced_search::CodeIndexEntry;
+use crate::ml::vector_db::{CodeMetadata, CodeType};
+
+/// Language tag shared by every documentation entry
+pub const DOC_LANGUAGE: &str = "markdown";
+
+/// True for `.md`/`.markdown` files and extensionless `README`s
+pub fn is_doc_file(path: &Path) -> bool {
+    match path.extension().and_then(|e| e.to_str()) {
+        Some(ext) => ext.eq_ignore_ascii_case("md") || ext.eq_ignore_ascii_case("markdown"),
+        None => path.file_name()
+            .and_then(|n| n.to_str())
+            .map(|n| n.eq_ignore_ascii_case("readme"))
+            .unwrap_or(false),
+    }
+}
+
+/// True when an indexed entry came from documentation
+pub fn is_doc_entry(metadata: &CodeMetadata) -> bool {
+    metadata.code_type == CodeType::Comment && metadata.language == DOC_LANGUAGE
+}
+
+/// Splits markdown into one entry per heading section. Text before the first
+/// heading is named after the file; headings inside code fences are ignored.
+pub fn markdown_entries(file_path: &str, content: &str) -> Vec<CodeIndexEntry> {
+    let default_title = Path::new(file_path)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| file_path.to_string());
+
+    let mut entries = Vec::new();
+    let mut title = default_title;
+    let mut section: Vec<&str> = Vec::new();
+    let mut section_start = 1;
+    let mut in_fence = false;
+
+    for (index, line) in content.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+        }
+
+        if !in_fence && trimmed.starts_with('#') {
+            push_section(&mut entries, file_path, &title, &section, section_start);
+            title = trimmed.trim_start_matches('#').trim().to_string();
+            section.clear();
+            section_start = index + 1;
+        }
+        section.push(line);
+    }
+    push_section(&mut entries, file_path, &title, &section, section_start);
+
+    entries
+}
+
+fn push_section(entries: &mut Vec<CodeIndexEntry>, file_path: &str, title: &str, lines: &[&str], line_start: usize) {
+    let text = lines.join("\n");
+    if text.trim().is_empty() {
+        return;
+    }
+
+    entries.push(doc_entry(file_path, title, line_start, line_start + lines.len() - 1, text));
+}
+
+/// Entry for the doc comment at the top of a source file: Rust `//!` lines or
+/// a leading `/*! */`, `/** */` or `/* */` block. Returns `None` when the file
+/// doesn't start with one.
+pub fn doc_comment_entry(file_path: &str, content: &str) -> Option<CodeIndexEntry> {
+    let lines: Vec<&str> = content.lines().collect();
+    let first = lines.iter().position(|line| !line.trim().is_empty())?;
+    let first_line = lines[first].trim_start();
+
+    let (text_lines, last) = if first_line.starts_with("//!") {
+        let count = lines[first..].iter()
+            .take_while(|line| line.trim_start().starts_with("//!"))
+            .count();
+        let text = lines[first..first + count].iter()
+            .map(|line| line.trim_start().trim_start_matches("//!").trim().to_string())
+            .collect::<Vec<_>>();
+        (text, first + count - 1)
+    } else if first_line.starts_with("/*") {
+        let end = lines[first..].iter().position(|line| line.contains("*/"))? + first;
+        let text = lines[first..=end].iter()
+            .map(|line| {
+                line.trim()
+                    .trim_start_matches("/**")
+                    .trim_start_matches("/*!")
+                    .trim_start_matches("/*")
+                    .trim_end_matches("*/")
+                    .trim_start_matches('*')
+                    .trim()
+                    .to_string()
+            })
+            .collect::<Vec<_>>();
+        (text, end)
+    } else {
+        return None;
+    };
+
+    let text = text_lines.join("\n").trim().to_string();
+    if text.is_empty() {
+        return None;
+    }
+
+    Some(doc_entry(file_path, "module documentation", first + 1, last + 1, text))
+}
+
+fn doc_entry(file_path: &str, title: &str, line_start: usize, line_end: usize, content: String) -> CodeIndexEntry {
+    CodeIndexEntry {
+        file_path: file_path.to_string(),
+        function_name: Some(title.to_string()),
+        line_start,
+        line_end,
+        code_type: CodeType::Comment,
+        language: DOC_LANGUAGE.to_string(),
+        complexity: 0.0,
+        content,
+        embedding_template: FIXED_INPUT_ID.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_markdown_sections_and_doc_comments() {
+        let readme = "Intro text\n\n# Install\nRun cargo install.\n```sh\n# not a heading\n```\n\n## Usage\nCall analyze.\n";
+        let entries = markdown_entries("README.md", readme);
+        let titles: Vec<_> = entries.iter().map(|e| e.function_name.clone().unwrap()).collect();
+        assert_eq!(titles, vec!["README.md", "Install", "Usage"]);
+        assert_eq!((entries[1].line_start, entries[1].line_end), (3, 8));
+        assert!(entries[1].content.contains("# not a heading"));
+        assert!(entries.iter().all(|e| e.language == "markdown" && e.code_type == CodeType::Comment));
+
+        let rust = "//! Cache manager\n//! Keeps analysis results on disk\n\nuse std::fs;\n";
+        let entry = doc_comment_entry("src/cache.rs", rust).unwrap();
+        assert_eq!(entry.content, "Cache manager\nKeeps analysis results on disk");
+        assert_eq!((entry.line_start, entry.line_end), (1, 2));
+
+        let ts = "/**\n * Auth helpers\n */\nexport const x = 1;\n";
+        assert_eq!(doc_comment_entry("auth.ts", ts).unwrap().content, "Auth helpers");
+        assert!(doc_comment_entry("plain.ts", "export const x = 1;\n").is_none());
+
+        assert!(is_doc_file(Path::new("docs/guide.md")));
+        assert!(is_doc_file(Path::new("README")));
+        assert!(!is_doc_file(Path::new("src/main.rs")));
+    }
+}
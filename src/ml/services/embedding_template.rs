@@ -0,0 +1,147 @@
+//! Embedding input for functions, built according to `MLConfig::embedding_template`
+
+use crate::analyzers::AnalyzerLanguage;
+use crate::ml::config::{EmbeddingContent, EmbeddingTemplate};
+use crate::types::FunctionInfo;
+use crate::utils::comments::{extract_comments, strip_comments};
+
+/// Embedding input id of entries the template doesn't apply to: structs,
+/// impls, traits, documentation, error-handling patterns and algorithms
+pub const FIXED_INPUT_ID: &str = "fixed";
+
+/// `async pub fn name(param: Type, ...) -> Return`
+pub fn function_signature(function: &FunctionInfo) -> String {
+    let mut signature = format!("fn {}", function.name);
+    if !function.parameters.is_empty() {
+        let params: Vec<String> = function.parameters.iter()
+            .map(|p| format!("{}: {}", p.name, p.param_type))
+            .collect();
+        signature.push_str(&format!("({})", params.join(", ")));
+    }
+    if !function.return_type.is_empty() {
+        signature.push_str(&format!(" -> {}", function.return_type));
+    }
+
+    let mut modifiers = Vec::new();
+    if function.is_async { modifiers.push("async"); }
+    for modifier in &function.modifiers {
+        if modifier == "pub" || modifier == "unsafe" || modifier == "const" {
+            modifiers.push(modifier);
+        }
+    }
+
+    if modifiers.is_empty() {
+        signature
+    } else {
+        format!("{} {}", modifiers.join(" "), signature)
+    }
+}
+
+/// Embedding input for `function`. `enriched` produces the full semantic
+/// enrichment and is only called for `EmbeddingTemplate::Enriched`.
+pub fn render_function(
+    template: EmbeddingTemplate,
+    function: &FunctionInfo,
+    enriched: impl FnOnce() -> String,
+) -> String {
+    match template {
+        EmbeddingTemplate::Signature => format!("FUNCTION: {}", function_signature(function)),
+        EmbeddingTemplate::SignatureWithDoc => match &function.description {
+            Some(doc) => format!("FUNCTION: {}\nDOCUMENTATION: {}", function_signature(function), doc),
+            None => format!("FUNCTION: {}", function_signature(function)),
+        },
+        EmbeddingTemplate::Enriched => enriched(),
+    }
+}
+
+/// The part of a function's source, doc comments included, that is embedded
+/// under `MLConfig::embedding_content`. Functions without comments embed
+/// their code under `DocOnly`.
+pub fn embedded_source(content: EmbeddingContent, source: &str, language: AnalyzerLanguage) -> String {
+    match content {
+        EmbeddingContent::Both => source.to_string(),
+        EmbeddingContent::CodeOnly => strip_comments(source, language),
+        EmbeddingContent::DocOnly => {
+            let comments = extract_comments(source, language);
+            if comments.is_empty() { source.to_string() } else { comments }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ml::config::MLConfig;
+    use crate::ml::services::enhanced_search::{CodeIndexEntry, EnhancedSearchService};
+    use crate::ml::vector_db::{
+        CodeType, HashingEmbeddingBackend, OverlapRerankerBackend, VectorDBConfig, VectorStoreFactory,
+    };
+    use crate::types::{LocationInfo, ParameterInfo};
+    use parking_lot::RwLock;
+    use std::sync::Arc;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_templates_change_embedding_and_are_recorded() {
+        let function = FunctionInfo {
+            name: "load_config".to_string(),
+            parameters: vec![ParameterInfo {
+                name: "path".to_string(),
+                param_type: "&Path".to_string(),
+                is_optional: false,
+                default_value: None,
+            }],
+            return_type: "Result<Config>".to_string(),
+            is_async: false,
+            modifiers: vec!["pub".to_string()],
+            location: LocationInfo { line: 12, column: 1 },
+            description: None,
+        };
+        let body = "let raw = fs::read_to_string(path)?;\ntoml::from_str(&raw).map_err(Into::into)";
+
+        let mut embeddings = Vec::new();
+        for template in [EmbeddingTemplate::Signature, EmbeddingTemplate::Enriched] {
+            let config = MLConfig { embedding_template: template, ..MLConfig::for_testing() };
+            let vector_db = VectorStoreFactory::create_native(VectorDBConfig::for_testing());
+            let service = EnhancedSearchService::with_backends(
+                config.clone(),
+                vector_db.clone(),
+                Arc::new(RwLock::new(HashingEmbeddingBackend::new(768))),
+                Arc::new(RwLock::new(OverlapRerankerBackend)),
+            );
+
+            let content = render_function(config.embedding_template, &function, || {
+                format!("FUNCTION: {}\nIMPLEMENTATION:\n{}", function_signature(&function), body)
+            });
+            service.index_code(vec![CodeIndexEntry {
+                file_path: "src/config.rs".to_string(),
+                function_name: Some(function.name.clone()),
+                line_start: 12,
+                line_end: 15,
+                code_type: CodeType::Function,
+                language: "rust".to_string(),
+                complexity: 1.0,
+                content,
+                embedding_template: config.embedding_input_id(),
+            }]).await.unwrap();
+
+            let entry = vector_db.read().get_by_file("src/config.rs").unwrap().remove(0);
+            assert_eq!(entry.metadata.embedding_template, template.id());
+            assert_eq!(service.get_stats().await.unwrap().embedding_templates.get(template.id()), Some(&1));
+            embeddings.push(entry.embedding);
+        }
+
+        assert_ne!(embeddings[0], embeddings[1]);
+    }
+
+    #[cfg(feature = "rust")]
+    #[test]
+    fn test_embedded_source_follows_embedding_content() {
+        let source = "/// Retries the cache lookup\nfn fetch(key: &str) -> Option<String> {\n    lookup(key)\n}";
+        let code = "fn fetch(key: &str) -> Option<String> {\n    lookup(key)\n}";
+
+        assert_eq!(embedded_source(EmbeddingContent::Both, source, AnalyzerLanguage::Rust), source);
+        assert_eq!(embedded_source(EmbeddingContent::CodeOnly, source, AnalyzerLanguage::Rust), code);
+        assert_eq!(embedded_source(EmbeddingContent::DocOnly, source, AnalyzerLanguage::Rust), "/// Retries the cache lookup");
+        assert_eq!(embedded_source(EmbeddingContent::DocOnly, code, AnalyzerLanguage::Rust), code);
+    }
+}
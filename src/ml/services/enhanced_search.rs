@@ -4,18 +4,27 @@
 
 use crate::ml::{
     MLConfig,
-    plugins::{QwenEmbeddingPlugin, QwenRerankerPlugin, MLPlugin},
+    models::AnalysisMode,
+    config::ContentHashing,
+    plugins::{QwenEmbeddingPlugin, QwenRerankerPlugin, MLPlugin, PluginManager},
     vector_db::{
         VectorDatabase, VectorStoreFactory, VectorDBConfig, VectorEntry,
         SemanticSearchPipeline, SemanticSearchFactory, SearchQuery, 
-        EnhancedSearchResult, SemanticSearchConfig, CodeType, CodeMetadata
+        EnhancedSearchResult, SemanticSearchConfig, CodeType, CodeMetadata,
+        EmbeddingBackend, RerankerBackend, PluginEmbeddingBackend, PluginRerankerBackend, rank_order,
     },
 };
+use crate::ml::services::doc_index::is_doc_entry;
+use crate::ml::services::index_pipeline::{run_index_pipeline, spawn_producer, IndexPipelineConfig, PendingBatch};
 use anyhow::Result;
-use parking_lot::RwLock;
+use futures::stream;
+use parking_lot::{Mutex, RwLock};
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use tracing::info;
+use crate::utils::decoration::status;
 
 /// Enhanced search service combining all ML components
 pub struct EnhancedSearchService {
@@ -25,6 +34,17 @@ pub struct EnhancedSearchService {
     vector_db: Arc<RwLock<dyn VectorDatabase>>,
     /// Configuration
     config: MLConfig,
+    /// Functions whose embedding was reused on index because their content hash was unchanged
+    function_embedding_hits: AtomicUsize,
+    /// Functions that had to be embedded on index
+    function_embedding_misses: AtomicUsize,
+    /// Both Qwen plugins loaded, by `new` or in the manager passed to
+    /// `with_plugin_manager`. Backends passed to `with_backends` are not
+    /// models, so services built from them start without
+    models_loaded: bool,
+    /// Entries embedded with `create_dummy_embedding_fallback` because the
+    /// embedding backend failed
+    fallback_embeddings: AtomicUsize,
 }
 
 /// Search request with rich context
@@ -49,8 +69,42 @@ pub enum SearchType {
     General,
     /// Search within specific file context
     FileContext { file_path: String },
+    /// Where a symbol is defined: exact name matches outrank mentions
+    Definition,
+    /// Call sites of a symbol, ahead of its definition
+    Usage,
+    /// Ranked by embedding similarity alone
+    Similar,
+    /// Documentation entries only
+    Documentation,
 }
 
+impl SearchType {
+    /// Parses the names accepted by `--search-type` and the MCP `search_type`
+    /// parameter
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "general" => Ok(Self::General),
+            "definition" => Ok(Self::Definition),
+            "usage" => Ok(Self::Usage),
+            "similar" => Ok(Self::Similar),
+            "documentation" | "docs" => Ok(Self::Documentation),
+            other => anyhow::bail!(
+                "Unknown search type '{}' (expected general, definition, usage, similar or documentation)",
+                other
+            ),
+        }
+    }
+}
+
+/// Share of the combined score given to the symbol match for `Definition`
+/// and `Usage` searches
+const SYMBOL_MATCH_WEIGHT: f32 = 0.7;
+
+/// `Definition` and `Usage` fetch this many times `max_results` candidates so
+/// re-ranking by symbol can surface entries embeddings ranked lower
+const SYMBOL_SEARCH_OVERFETCH: usize = 3;
+
 /// Search filters
 #[derive(Clone, Debug, Default)]
 pub struct SearchFilters {
@@ -60,6 +114,10 @@ pub struct SearchFilters {
     pub exclude_files: Option<Vec<String>>,
     pub min_complexity: Option<f32>,
     pub max_complexity: Option<f32>,
+    /// Keep documentation entries (markdown sections, file doc comments)
+    pub include_docs: bool,
+    /// Keep entries tagged `CodeType::Test` (see `apply_test_ranges`)
+    pub include_tests: bool,
 }
 
 /// Search options
@@ -69,6 +127,12 @@ pub struct SearchOptions {
     pub include_metadata: bool,
     pub explain_ranking: bool,
     pub use_cache: bool,
+    /// Drop results whose `combined_score` falls below this floor
+    pub min_relevance: Option<f32>,
+    /// Return at most `max_results_per_file` results per file, collapsing
+    /// the rest into `additional_matches` on the file's best result
+    pub group_by_file: bool,
+    pub max_results_per_file: usize,
 }
 
 impl Default for SearchOptions {
@@ -78,6 +142,9 @@ impl Default for SearchOptions {
             include_metadata: true,
             explain_ranking: false,
             use_cache: true,
+            min_relevance: None,
+            group_by_file: false,
+            max_results_per_file: 1,
         }
     }
 }
@@ -90,12 +157,21 @@ pub struct SearchResponse {
     pub search_time_ms: u64,
     pub explanation: Option<String>,
     pub suggestions: Vec<String>,
+    /// Best-effort top result, set only when every result fell below `min_relevance`
+    pub low_confidence_match: Option<EnhancedSearchResult>,
+}
+
+impl SearchResponse {
+    /// True when the relevance floor rejected every candidate
+    pub fn no_confident_matches(&self) -> bool {
+        self.results.is_empty() && self.low_confidence_match.is_some()
+    }
 }
 
 impl EnhancedSearchService {
-    /// Create new enhanced search service
-    pub async fn new(config: MLConfig) -> Result<Self> {
-        Self::new_with_cache_dir(config, None).await
+    /// Create new enhanced search service over the index of `project_path`
+    pub async fn new(config: MLConfig, project_path: &Path) -> Result<Self> {
+        Self::new_with_cache_dir(config, Some(VectorDBConfig::for_project(project_path).cache_dir)).await
     }
     
     /// Create new enhanced search service with custom cache directory (for testing)
@@ -103,8 +179,7 @@ impl EnhancedSearchService {
         info!("Initializing Enhanced Search Service");
         
         // Create vector database
-        let default_cache_dir = format!("{}/.cache/vector-db", 
-                                       std::env::current_dir()?.to_string_lossy());
+        let default_cache_dir = VectorDBConfig::default().cache_dir;
         let vector_db_config = VectorDBConfig {
             cache_dir: cache_dir.unwrap_or(default_cache_dir),
             similarity_threshold: 0.1, // Lower threshold for better recall with dummy embeddings
@@ -114,22 +189,22 @@ impl EnhancedSearchService {
         let vector_db = VectorStoreFactory::create_native(vector_db_config);
         
         // CRITICAL FIX: Load existing data from cache to avoid reindexing
-        println!("🔄 Loading existing vector database from cache...");
+        status!("🔄 Loading existing vector database from cache...");
         {
             let mut db = vector_db.write();
             match db.load() {
                 Ok(_) => {
                     let stats = db.stats();
                     if stats.total_vectors > 0 {
-                        println!("✅ Loaded {} vectors from cache (avoiding reindexing)", stats.total_vectors);
+                        status!("✅ Loaded {} vectors from cache (avoiding reindexing)", stats.total_vectors);
                         println!("   Total files: {}", stats.total_files);
                         println!("   Index size: {:.2}MB", stats.index_size_mb);
                     } else {
-                        println!("ℹ️  Empty cache - will need to index data");
+                        status!("ℹ️  Empty cache - will need to index data");
                     }
                 }
                 Err(e) => {
-                    println!("⚠️  Failed to load cache: {} - will start fresh", e);
+                    status!("⚠️  Failed to load cache: {} - will start fresh", e);
                 }
             }
         }
@@ -139,26 +214,71 @@ impl EnhancedSearchService {
         let reranker_plugin = Arc::new(RwLock::new(QwenRerankerPlugin::new()));
         
         // Load the plugins
-        println!("🔧 Loading ML plugins from: {}", config.model_cache_dir.display());
+        status!("🔧 Loading ML plugins from: {}", config.model_cache_dir.display());
         
-        println!("📥 Loading Qwen Embedding plugin...");
-        match embedding_plugin.write().load(&config).await {
-            Ok(_) => println!("✅ Qwen Embedding plugin loaded successfully"),
+        status!("📥 Loading Qwen Embedding plugin...");
+        let embedding_loaded = match embedding_plugin.write().load(&config).await {
+            Ok(_) => {
+                status!("✅ Qwen Embedding plugin loaded successfully");
+                true
+            }
             Err(e) => {
-                println!("⚠️  Failed to load Qwen Embedding plugin: {}", e);
+                status!("⚠️  Failed to load Qwen Embedding plugin: {}", e);
                 println!("   Semantic search will use fallback mode");
+                false
             }
-        }
+        };
         
-        println!("📥 Loading Qwen Reranker plugin...");
-        match reranker_plugin.write().load(&config).await {
-            Ok(_) => println!("✅ Qwen Reranker plugin loaded successfully"),
+        status!("📥 Loading Qwen Reranker plugin...");
+        let reranker_loaded = match reranker_plugin.write().load(&config).await {
+            Ok(_) => {
+                status!("✅ Qwen Reranker plugin loaded successfully");
+                true
+            }
             Err(e) => {
-                println!("⚠️  Failed to load Qwen Reranker plugin: {}", e);
+                status!("⚠️  Failed to load Qwen Reranker plugin: {}", e);
                 println!("   Reranking will use fallback mode");
+                false
             }
-        }
+        };
         
+        let mut service = Self::with_backends(config, vector_db, embedding_plugin, reranker_plugin);
+        service.models_loaded = embedding_loaded && reranker_loaded;
+        Ok(service)
+    }
+    
+    /// Create service that embeds and reranks through the `qwen_embedding`
+    /// and `qwen_reranker` plugins of an already-initialized `plugin_manager`,
+    /// loading them like `new` loads its own
+    pub async fn with_plugin_manager(
+        config: MLConfig,
+        vector_db: Arc<RwLock<dyn VectorDatabase>>,
+        plugin_manager: Arc<PluginManager>,
+    ) -> Self {
+        let mut models_loaded = true;
+        for name in ["qwen_embedding", "qwen_reranker"] {
+            if !plugin_manager.is_plugin_loaded(name) {
+                if let Err(e) = plugin_manager.load_plugin(name).await {
+                    status!("⚠️  Failed to load plugin {}: {}", name, e);
+                    models_loaded = false;
+                }
+            }
+        }
+        let embedding_backend = Arc::new(RwLock::new(PluginEmbeddingBackend::new(plugin_manager.clone(), "qwen_embedding")));
+        let reranker_backend = Arc::new(RwLock::new(PluginRerankerBackend::new(plugin_manager, "qwen_reranker")));
+        let mut service = Self::with_backends(config, vector_db, embedding_backend, reranker_backend);
+        service.models_loaded = models_loaded;
+        service
+    }
+    
+    /// Create service over already constructed components, e.g. model-free
+    /// backends for tests and benchmarks
+    pub fn with_backends(
+        config: MLConfig,
+        vector_db: Arc<RwLock<dyn VectorDatabase>>,
+        embedding_backend: Arc<RwLock<dyn EmbeddingBackend>>,
+        reranker_backend: Arc<RwLock<dyn RerankerBackend>>,
+    ) -> Self {
         // Create semantic search pipeline with optimized parameters
         let search_config = SemanticSearchConfig {
             lsh_candidates: 100,        // Increased for better recall
@@ -167,48 +287,77 @@ impl EnhancedSearchService {
             rerank_threshold: 0.02,     // CRITICAL: Model returns extremely low scores (0.024-0.164 range) - indicates reranker calibration issue
             enable_caching: true,
             embedding_cache_size: 1000,
+            rerank_batch_size: config.reranker_batch_size,
+            max_candidate_tokens: config.reranker_max_candidate_tokens,
         };
         
         let search_pipeline = SemanticSearchFactory::create_with_config(
             vector_db.clone(),
-            embedding_plugin,
-            reranker_plugin,
+            embedding_backend,
+            reranker_backend,
             search_config,
         );
         
-        Ok(Self {
+        Self {
             search_pipeline,
             vector_db,
             config,
-        })
+            function_embedding_hits: AtomicUsize::new(0),
+            function_embedding_misses: AtomicUsize::new(0),
+            models_loaded: false,
+            fallback_embeddings: AtomicUsize::new(0),
+        }
+    }
+    
+    /// `Ai` only when both models loaded and no entry indexed by this service
+    /// fell back to a dummy embedding
+    pub fn analysis_mode(&self) -> AnalysisMode {
+        if self.models_loaded && self.fallback_embeddings.load(Ordering::Relaxed) == 0 {
+            AnalysisMode::Ai
+        } else {
+            AnalysisMode::Static
+        }
     }
     
     /// Perform enhanced search
     pub async fn search(&self, request: SearchRequest) -> Result<SearchResponse> {
         let start_time = std::time::Instant::now();
-        println!("🔍 Performing enhanced search: {:?}", request.search_type);
-        println!("🔍 Query: '{}'", request.query);
+        status!("🔍 Performing enhanced search: {:?}", request.search_type);
+        status!("🔍 Query: '{}'", request.query);
         
         // Check vector DB stats before search
         let stats = {
             let vector_db = self.vector_db.read();
             vector_db.stats()
         };
-        println!("📊 Vector DB stats before search:");
+        status!("📊 Vector DB stats before search:");
         println!("   Total vectors: {}", stats.total_vectors);
         println!("   Total files: {}", stats.total_files);
         
         // Convert request to internal query
         let query = self.build_search_query(&request)?;
-        println!("🔍 Built internal query: {:?}", query);
+        status!("🔍 Built internal query: {:?}", query);
         
         // Perform search
-        println!("🔍 Executing search pipeline...");
+        status!("🔍 Executing search pipeline...");
         let results = self.search_pipeline.search(&query).await?;
-        println!("🔍 Search pipeline returned {} results", results.len());
+        status!("🔍 Search pipeline returned {} results", results.len());
+        let results = apply_search_type(results, &request.search_type, &request.query, request.options.max_results);
         
         // Apply additional filtering
-        let filtered_results = self.apply_filters(results, &request.filters).await?;
+        let mut filters = request.filters.clone();
+        if matches!(request.search_type, SearchType::Documentation) {
+            filters.include_docs = true;
+        }
+        let filtered_results = self.apply_filters(results, &filters).await?;
+        let (filtered_results, low_confidence_match) =
+            apply_relevance_floor(filtered_results, request.options.min_relevance);
+        let total_candidates = filtered_results.len();
+        let filtered_results = if request.options.group_by_file {
+            group_results_by_file(filtered_results, request.options.max_results_per_file)
+        } else {
+            filtered_results
+        };
         
         // Generate response
         let search_time_ms = start_time.elapsed().as_millis() as u64;
@@ -218,54 +367,189 @@ impl EnhancedSearchService {
             None
         };
         
-        let suggestions = self.generate_suggestions(&request, &filtered_results).await?;
+        let mut suggestions = self.generate_suggestions(&request, &filtered_results).await?;
+        if let (Some(best), Some(floor)) = (&low_confidence_match, request.options.min_relevance) {
+            suggestions.insert(0, format!(
+                "No confident matches above relevance {:.2}; best candidate {} scored {:.2}",
+                floor, best.entry.metadata.file_path, best.combined_score
+            ));
+        }
         
         Ok(SearchResponse {
-            total_candidates: filtered_results.len(),
+            total_candidates,
             results: filtered_results,
             search_time_ms,
             explanation,
             suggestions,
+            low_confidence_match,
         })
     }
     
     /// Add code to the search index
     pub async fn index_code(&self, code_entries: Vec<CodeIndexEntry>) -> Result<usize> {
-        println!("📝 Indexing {} code entries", code_entries.len());
-        
-        let mut indexed_count = 0;
-        let mut vector_db = self.vector_db.write();
+        // A file's entries must arrive together, or stale-entry removal for
+        // one group would drop the entries just indexed from another
+        let mut files: Vec<Vec<CodeIndexEntry>> = Vec::new();
+        let mut positions: HashMap<String, usize> = HashMap::new();
+        for entry in code_entries {
+            let position = *positions.entry(entry.file_path.clone()).or_insert_with(|| {
+                files.push(Vec::new());
+                files.len() - 1
+            });
+            files[position].push(entry);
+        }
+        self.index_files(files).await
+    }
+    
+    /// Indexes the entries of one file at a time, as `files` yields them. The
+    /// iterator is the pipeline's producer stage: it runs on a blocking thread
+    /// and is only advanced when the embedding stage has room, so file analysis
+    /// done lazily inside it never runs far ahead, never stalls the runtime and
+    /// the corpus is never materialized at once. Unchanged functions keep their
+    /// embeddings; a file's old entries that the new ones don't replace are
+    /// removed.
+    pub async fn index_files<I>(&self, files: I) -> Result<usize>
+    where
+        I: IntoIterator<Item = Vec<CodeIndexEntry>>,
+        I::IntoIter: Send + 'static,
+    {
+        status!("📝 Indexing code entries");
         
-        for (i, entry) in code_entries.into_iter().enumerate() {
-            println!("📝 Processing entry {}: {}", i + 1, entry.file_path);
-            match self.create_vector_entry(entry).await {
-                Ok(vector_entry) => {
-                    println!("✅ Created vector entry with ID: {}", vector_entry.id);
-                    vector_db.add_vector(vector_entry)?;
-                    indexed_count += 1;
-                    println!("✅ Added to vector DB, total indexed: {}", indexed_count);
+        let pipeline_config = IndexPipelineConfig::from(&self.config);
+        let (files, producer) = spawn_producer(files.into_iter(), pipeline_config.queue_depth);
+        let reused_count = AtomicUsize::new(0);
+        let failure = Mutex::new(None);
+        let batches = stream::unfold((files, Vec::new()), |(mut files, mut pending): (_, Vec<CodeIndexEntry>)| {
+            let (reused_count, failure) = (&reused_count, &failure);
+            async move {
+                while pending.len() < pipeline_config.batch_size && failure.lock().is_none() {
+                    let Some(file_entries) = files.recv().await else { break };
+                    match self.reuse_embeddings(file_entries) {
+                        Ok((reused, changed)) => {
+                            reused_count.fetch_add(reused, Ordering::Relaxed);
+                            pending.extend(changed);
+                        }
+                        Err(e) => *failure.lock() = Some(e),
+                    }
                 }
-                Err(e) => {
-                    println!("❌ Failed to create vector entry: {}", e);
+                if failure.lock().is_some() || pending.is_empty() {
+                    return None;
                 }
+                let chunk: Vec<CodeIndexEntry> = pending.drain(..pending.len().min(pipeline_config.batch_size)).collect();
+                Some((self.prepare_batch(chunk), (files, pending)))
             }
+        });
+        
+        let counts = run_index_pipeline(
+            batches,
+            self.search_pipeline.embedding_backend(),
+            Arc::clone(&self.vector_db),
+            pipeline_config,
+            Self::create_dummy_embedding_fallback,
+        ).await?;
+        self.fallback_embeddings.fetch_add(counts.fallback_embedded, Ordering::Relaxed);
+        let embedded_count = counts.inserted;
+        if let Some(e) = failure.into_inner() {
+            return Err(e);
+        }
+        producer.await?;
+        let reused_count = reused_count.into_inner();
+        if reused_count > 0 {
+            status!("♻️  Reused embeddings of {} unchanged functions, embedded {}", reused_count, embedded_count);
         }
+        let indexed_count = embedded_count + reused_count;
+        
+        let vector_db = self.vector_db.read();
         
         // Save to disk
-        println!("💾 Saving vector database to disk...");
+        status!("💾 Saving vector database to disk...");
         vector_db.save()?;
         
         // Check database stats
         let stats = vector_db.stats();
-        println!("📊 Vector DB stats after indexing:");
+        status!("📊 Vector DB stats after indexing:");
         println!("   Total vectors: {}", stats.total_vectors);
         println!("   Total files: {}", stats.total_files);
         println!("   Index size: {:.2}MB", stats.index_size_mb);
         
-        println!("✅ Successfully indexed {} entries", indexed_count);
+        status!("✅ Successfully indexed {} entries", indexed_count);
         Ok(indexed_count)
     }
     
+    /// Prepares one file's entries for indexing: removes its stale entries and
+    /// inserts the entries whose embeddings can be reused right away. Returns
+    /// how many were reused and the entries that still need embedding.
+    fn reuse_embeddings(&self, file_entries: Vec<CodeIndexEntry>) -> Result<(usize, Vec<CodeIndexEntry>)> {
+        let reusable = self.reusable_embeddings(&file_entries)?;
+        let removed = self.remove_stale_entries(&file_entries)?;
+        if removed > 0 {
+            status!("🧹 Removed {} entries no longer present in the reindexed files", removed);
+        }
+        
+        let mut reused = Vec::new();
+        let mut changed = Vec::new();
+        for entry in file_entries {
+            let key = (entry.embedding_template.clone(), self.calculate_content_hash(&entry.content));
+            match reusable.get(&key) {
+                Some(embedding) => reused.push(self.build_vector_entry(entry, embedding.clone())),
+                None => changed.push(entry),
+            }
+        }
+        self.function_embedding_hits.fetch_add(reused.len(), Ordering::Relaxed);
+        self.function_embedding_misses.fetch_add(changed.len(), Ordering::Relaxed);
+        let reused_count = reused.len();
+        self.vector_db.write().add_vectors(reused)?;
+        Ok((reused_count, changed))
+    }
+    
+    /// Deletes the indexed entries of the files in `code_entries` that the new
+    /// entries don't replace, e.g. functions that moved or were removed since
+    /// the file was last indexed. Returns how many were deleted.
+    fn remove_stale_entries(&self, code_entries: &[CodeIndexEntry]) -> Result<usize> {
+        let ids: HashSet<String> = code_entries.iter()
+            .map(|entry| format!("{}:{}:{}", entry.file_path, entry.line_start, entry.line_end))
+            .collect();
+        let mut files: Vec<&str> = code_entries.iter().map(|entry| entry.file_path.as_str()).collect();
+        files.sort_unstable();
+        files.dedup();
+        
+        let mut vector_db = self.vector_db.write();
+        let mut removed = 0;
+        for file in files {
+            for entry in vector_db.get_by_file(file)? {
+                if !ids.contains(&entry.id) && vector_db.delete(&entry.id)? {
+                    removed += 1;
+                }
+            }
+        }
+        Ok(removed)
+    }
+    
+    /// Embeddings already indexed for the files of `code_entries`, keyed by
+    /// embedding input id and content hash, so an entry only reuses an
+    /// embedding built the same way; nothing is reused when `content_hashing`
+    /// is off.
+    fn reusable_embeddings(&self, code_entries: &[CodeIndexEntry]) -> Result<HashMap<(String, String), Vec<f32>>> {
+        let mut reusable = HashMap::new();
+        if self.config.content_hashing == ContentHashing::Off {
+            return Ok(reusable);
+        }
+        
+        let mut files: Vec<&str> = code_entries.iter().map(|entry| entry.file_path.as_str()).collect();
+        files.sort_unstable();
+        files.dedup();
+        
+        let vector_db = self.vector_db.read();
+        for file in files {
+            for entry in vector_db.get_by_file(file)? {
+                if !entry.embedding.is_empty() {
+                    reusable.insert((entry.metadata.embedding_template, entry.metadata.hash), entry.embedding);
+                }
+            }
+        }
+        Ok(reusable)
+    }
+    
     /// Remove code from index
     pub async fn remove_from_index(&self, file_path: &str) -> Result<usize> {
         info!("Removing entries for file: {}", file_path);
@@ -286,11 +570,37 @@ impl EnhancedSearchService {
     
     /// Update index for changed files
     pub async fn update_index(&self, file_path: &str, code_entries: Vec<CodeIndexEntry>) -> Result<usize> {
-        // Remove old entries
-        self.remove_from_index(file_path).await?;
+        self.index_file(file_path, code_entries).await
+    }
+    
+    /// Index a single file: replaces the entries previously indexed for
+    /// `file_path` with `code_entries`, leaving other files untouched.
+    /// Unchanged functions keep their embeddings. Cheaper than `index_code`
+    /// for watch-mode updates.
+    pub async fn index_file(&self, file_path: &str, code_entries: Vec<CodeIndexEntry>) -> Result<usize> {
+        if let Some(entry) = code_entries.iter().find(|entry| entry.file_path != file_path) {
+            anyhow::bail!("Entry for {} passed to index_file for {}", entry.file_path, file_path);
+        }
+        if code_entries.is_empty() {
+            self.remove_from_index(file_path).await?;
+            return Ok(0);
+        }
+        
+        let (reused_count, changed) = self.reuse_embeddings(code_entries)?;
+        
+        // Embed before taking the write lock so searches are not blocked
+        let mut vector_entries = Vec::with_capacity(changed.len());
+        for entry in changed {
+            vector_entries.push(self.create_vector_entry(entry).await?);
+        }
+        
+        let mut vector_db = self.vector_db.write();
+        let indexed_count = reused_count + vector_entries.len();
+        vector_db.add_vectors(vector_entries)?;
+        vector_db.save()?;
         
-        // Add new entries
-        self.index_code(code_entries).await
+        info!("Indexed {} entries for file: {} ({} embeddings reused)", indexed_count, file_path, reused_count);
+        Ok(indexed_count)
     }
     
     /// Get search statistics
@@ -307,6 +617,9 @@ impl EnhancedSearchService {
             rerank_cache_hit_rate: pipeline_stats.rerank_cache_hit_rate,
             languages: db_stats.by_language.clone(),
             code_types: db_stats.by_code_type.clone(),
+            embedding_templates: db_stats.by_embedding_template.clone(),
+            function_embedding_hits: self.function_embedding_hits.load(Ordering::Relaxed),
+            function_embedding_misses: self.function_embedding_misses.load(Ordering::Relaxed),
         })
     }
     
@@ -316,7 +629,11 @@ impl EnhancedSearchService {
             SearchType::SimilarCode { language } => (None, Some(language.clone())),
             SearchType::SimilarFunctions => (Some(CodeType::Function), None),
             SearchType::SimilarComponents { framework } => (Some(CodeType::Component), Some(framework.clone())),
-            SearchType::General => (None, None),
+            SearchType::General
+            | SearchType::Definition
+            | SearchType::Usage
+            | SearchType::Similar
+            | SearchType::Documentation => (None, None),
             SearchType::FileContext { file_path } => {
                 // Extract language from file extension
                 let language = self.extract_language_from_path(file_path);
@@ -332,13 +649,26 @@ impl EnhancedSearchService {
                 SearchType::FileContext { file_path } => Some(file_path.clone()),
                 _ => None,
             },
-            max_results: Some(request.options.max_results),
+            max_results: Some(match request.search_type {
+                SearchType::Definition | SearchType::Usage => request.options.max_results * SYMBOL_SEARCH_OVERFETCH,
+                _ => request.options.max_results,
+            }),
         })
     }
     
     /// Apply additional filters to results
     async fn apply_filters(&self, mut results: Vec<EnhancedSearchResult>, filters: &SearchFilters) -> Result<Vec<EnhancedSearchResult>> {
         results.retain(|result| {
+            // Documentation filter
+            if !filters.include_docs && is_doc_entry(&result.entry.metadata) {
+                return false;
+            }
+            
+            // Test code filter
+            if !filters.include_tests && result.entry.metadata.code_type == CodeType::Test {
+                return false;
+            }
+            
             // Language filter
             if let Some(ref languages) = filters.languages {
                 if !languages.iter().any(|lang| {
@@ -455,7 +785,20 @@ impl EnhancedSearchService {
     async fn create_vector_entry(&self, code_entry: CodeIndexEntry) -> Result<VectorEntry> {
         // Use real embedding model to generate embedding
         let embedding = self.generate_real_embedding(&code_entry.content).await?;
-        
+        Ok(self.build_vector_entry(code_entry, embedding))
+    }
+    
+    /// Texts and embedding-less entries for one batch of the indexing pipeline
+    fn prepare_batch(&self, code_entries: Vec<CodeIndexEntry>) -> PendingBatch {
+        let texts = code_entries.iter().map(|entry| entry.content.clone()).collect();
+        let entries = code_entries.into_iter()
+            .map(|entry| self.build_vector_entry(entry, Vec::new()))
+            .collect();
+        PendingBatch { texts, entries }
+    }
+    
+    /// Vector entry for `code_entry` with the given embedding
+    fn build_vector_entry(&self, code_entry: CodeIndexEntry, embedding: Vec<f32>) -> VectorEntry {
         // Create metadata
         let metadata = CodeMetadata {
             file_path: code_entry.file_path,
@@ -466,19 +809,19 @@ impl EnhancedSearchService {
             language: code_entry.language,
             complexity: code_entry.complexity,
             tokens: self.extract_tokens(&code_entry.content),
+            calls: extract_calls(&code_entry.content),
             hash: self.calculate_content_hash(&code_entry.content),
+            embedding_template: code_entry.embedding_template,
         };
         
         // Create vector entry
-        let entry = VectorEntry {
+        VectorEntry {
             id: format!("{}:{}:{}", metadata.file_path, metadata.line_start, metadata.line_end),
             embedding,
             metadata,
             created_at: chrono::Utc::now(),
             updated_at: chrono::Utc::now(),
-        };
-        
-        Ok(entry)
+        }
     }
     
     /// Extract language from file path
@@ -508,34 +851,41 @@ impl EnhancedSearchService {
             .collect()
     }
     
-    /// Calculate content hash
+    /// Calculate content hash; with `ContentHashing::IgnoreWhitespace`,
+    /// whitespace runs are collapsed first so reformatting keeps the hash
     fn calculate_content_hash(&self, content: &str) -> String {
         use sha2::{Sha256, Digest};
         let mut hasher = Sha256::new();
-        hasher.update(content.as_bytes());
+        match self.config.content_hashing {
+            ContentHashing::IgnoreWhitespace => {
+                hasher.update(content.split_whitespace().collect::<Vec<_>>().join(" ").as_bytes());
+            }
+            ContentHashing::Off | ContentHashing::Exact => hasher.update(content.as_bytes()),
+        }
         format!("{:x}", hasher.finalize())
     }
     
     /// Generate real embedding using Qwen model
     async fn generate_real_embedding(&self, content: &str) -> Result<Vec<f32>> {
         // Use the semantic search pipeline's embedding generation method
-        println!("🤖 Generating real embedding for content: {} chars", content.len());
+        status!("🤖 Generating real embedding for content: {} chars", content.len());
         
         match self.search_pipeline.generate_query_embedding(content).await {
             Ok(embedding) => {
-                println!("✅ Generated real embedding with {} dimensions", embedding.len());
+                status!("✅ Generated real embedding with {} dimensions", embedding.len());
                 Ok(embedding)
             }
             Err(e) => {
-                println!("⚠️  Failed to generate real embedding: {}", e);
+                status!("⚠️  Failed to generate real embedding: {}", e);
                 println!("   Falling back to dummy embedding");
-                Ok(self.create_dummy_embedding_fallback(content))
+                self.fallback_embeddings.fetch_add(1, Ordering::Relaxed);
+                Ok(Self::create_dummy_embedding_fallback(content))
             }
         }
     }
     
     /// Fallback dummy embedding for testing (only used if real model fails)
-    fn create_dummy_embedding_fallback(&self, content: &str) -> Vec<f32> {
+    fn create_dummy_embedding_fallback(content: &str) -> Vec<f32> {
         use std::collections::hash_map::DefaultHasher;
         use std::hash::{Hash, Hasher};
         
@@ -565,6 +915,122 @@ impl EnhancedSearchService {
     }
 }
 
+/// Re-weights and filters pipeline results for `search_type`, keeping at most
+/// `max_results`. `Definition` and `Usage` blend in how well each entry
+/// matches the query's symbol; `Similar` keeps only the embedding similarity.
+fn apply_search_type(
+    mut results: Vec<EnhancedSearchResult>,
+    search_type: &SearchType,
+    query: &str,
+    max_results: usize,
+) -> Vec<EnhancedSearchResult> {
+    match search_type {
+        SearchType::Definition | SearchType::Usage => {
+            let Some(symbol) = query_symbol(query) else {
+                return results;
+            };
+            let definition = matches!(search_type, SearchType::Definition);
+            for result in &mut results {
+                let defines = defines_symbol(&result.entry.metadata, symbol);
+                let calls = calls_symbol(&result.entry.metadata, symbol);
+                let symbol_match = match (definition, defines, calls) {
+                    (true, true, _) => 1.0,
+                    (true, false, true) => 0.4,
+                    (false, false, true) => 1.0,
+                    (false, true, _) => 0.3,
+                    _ => 0.0,
+                };
+                result.combined_score = result.combined_score * (1.0 - SYMBOL_MATCH_WEIGHT)
+                    + symbol_match * SYMBOL_MATCH_WEIGHT;
+            }
+        }
+        SearchType::Similar => {
+            for result in &mut results {
+                result.combined_score = result.embedding_similarity;
+            }
+        }
+        SearchType::Documentation => results.retain(|result| is_doc_entry(&result.entry.metadata)),
+        _ => return results,
+    }
+
+    results.sort_by(|a, b| rank_order(a.combined_score, &a.entry, b.combined_score, &b.entry));
+    results.truncate(max_results);
+    results
+}
+
+/// The identifier a symbol search is about: the first code-looking word
+/// (`snake_case`, `camelCase`, `Path::to`, `call()`), else the last word
+fn query_symbol(query: &str) -> Option<&str> {
+    let words: Vec<&str> = query
+        .split_whitespace()
+        .map(|word| word.trim_matches(|c: char| !c.is_alphanumeric() && c != '_' && c != ':'))
+        .map(|word| word.rsplit("::").next().unwrap_or(word))
+        .filter(|word| !word.is_empty())
+        .collect();
+    words.iter()
+        .find(|word| word.contains('_') || word.chars().skip(1).any(char::is_uppercase))
+        .or(words.last())
+        .copied()
+}
+
+/// Entry is the function, method or type named `symbol`
+fn defines_symbol(metadata: &CodeMetadata, symbol: &str) -> bool {
+    metadata.function_name.as_deref().is_some_and(|name| {
+        name == symbol || name.ends_with(&format!("::{}", symbol)) || name.ends_with(&format!(".{}", symbol))
+    })
+}
+
+/// Entry calls `symbol`, going by the callees recorded at index time
+fn calls_symbol(metadata: &CodeMetadata, symbol: &str) -> bool {
+    metadata.calls.iter().any(|call| {
+        call == symbol || call.ends_with(&format!("::{}", symbol)) || call.ends_with(&format!(".{}", symbol))
+    })
+}
+
+/// Callee paths in `content`, like `Doc::new` for `Doc::new(`, in order of
+/// first use; `fn`/`function` declarations are not calls
+fn extract_calls(content: &str) -> Vec<String> {
+    let is_path_char = |c: char| c.is_alphanumeric() || c == '_' || c == ':' || c == '.';
+    let mut calls: Vec<String> = Vec::new();
+    for (index, _) in content.match_indices('(') {
+        let head = &content[..index];
+        let prefix = head.trim_end_matches(is_path_char);
+        let callee = head[prefix.len()..].trim_start_matches(['.', ':']);
+        let declared = matches!(prefix.split_whitespace().last(), Some("fn" | "function"));
+        if callee.is_empty() || callee.starts_with(|c: char| c.is_ascii_digit()) || declared {
+            continue;
+        }
+        if !calls.iter().any(|call| call == callee) {
+            calls.push(callee.to_string());
+        }
+    }
+    calls
+}
+
+/// Drops results scoring below `min_relevance`. If that empties the list, the
+/// highest-scoring result is returned separately as a low-confidence match.
+fn apply_relevance_floor(
+    results: Vec<EnhancedSearchResult>,
+    min_relevance: Option<f32>,
+) -> (Vec<EnhancedSearchResult>, Option<EnhancedSearchResult>) {
+    let Some(floor) = min_relevance else {
+        return (results, None);
+    };
+
+    let best = results.iter()
+        .max_by(|a, b| a.combined_score.partial_cmp(&b.combined_score).unwrap_or(std::cmp::Ordering::Equal))
+        .cloned();
+    let confident: Vec<EnhancedSearchResult> = results.into_iter()
+        .filter(|result| result.combined_score >= floor)
+        .collect();
+
+    if confident.is_empty() {
+        (confident, best)
+    } else {
+        (confident, None)
+    }
+}
+
 /// Code entry for indexing
 #[derive(Clone, Debug)]
 pub struct CodeIndexEntry {
@@ -576,6 +1042,35 @@ pub struct CodeIndexEntry {
     pub language: String,
     pub complexity: f32,
     pub content: String,
+    /// What `content` was built with: `EmbeddingTemplate::input_id` for
+    /// functions, `FIXED_INPUT_ID` for everything else
+    pub embedding_template: String,
+}
+
+/// Applies the `include_tests` indexing option to one file's entries, given
+/// the file's test line ranges (`RustAnalyzer::test_line_ranges`). Entries
+/// starting inside test code are dropped, so they never take LSH buckets or
+/// candidate slots, or kept and tagged `CodeType::Test` so
+/// `SearchFilters::include_tests` can still hide them at search time.
+pub fn apply_test_ranges(
+    entries: Vec<CodeIndexEntry>,
+    test_ranges: &[(usize, usize)],
+    include_tests: bool,
+) -> Vec<CodeIndexEntry> {
+    entries.into_iter()
+        .filter_map(|mut entry| {
+            let in_test = test_ranges.iter()
+                .any(|&(start, end)| (start..=end).contains(&entry.line_start));
+            if !in_test {
+                Some(entry)
+            } else if include_tests {
+                entry.code_type = CodeType::Test;
+                Some(entry)
+            } else {
+                None
+            }
+        })
+        .collect()
 }
 
 /// Search service statistics
@@ -588,11 +1083,48 @@ pub struct SearchServiceStats {
     pub rerank_cache_hit_rate: f64,
     pub languages: std::collections::HashMap<String, usize>,
     pub code_types: std::collections::HashMap<String, usize>,
+    /// Entries per `EmbeddingTemplate` id; more than one means the index mixes templates
+    pub embedding_templates: std::collections::HashMap<String, usize>,
+    /// Functions indexed with a reused embedding since the service started
+    pub function_embedding_hits: usize,
+    /// Functions embedded while indexing since the service started
+    pub function_embedding_misses: usize,
+}
+
+/// Keeps the `per_file` best results of each file, counting the rest in
+/// `additional_matches` of the file's top result. Files are ordered by their
+/// best match; results within a file by score.
+fn group_results_by_file(
+    mut results: Vec<EnhancedSearchResult>,
+    per_file: usize,
+) -> Vec<EnhancedSearchResult> {
+    results.sort_by(|a, b| rank_order(a.combined_score, &a.entry, b.combined_score, &b.entry));
+    
+    let mut groups: Vec<Vec<EnhancedSearchResult>> = Vec::new();
+    let mut group_by_path: std::collections::HashMap<String, usize> = std::collections::HashMap::new();
+    
+    for result in results {
+        let index = *group_by_path
+            .entry(result.entry.metadata.file_path.clone())
+            .or_insert_with(|| {
+                groups.push(Vec::new());
+                groups.len() - 1
+            });
+        let group = &mut groups[index];
+        if group.len() < per_file.max(1) {
+            group.push(result);
+        } else {
+            group[0].additional_matches += 1;
+        }
+    }
+    
+    groups.into_iter().flatten().collect()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ml::services::embedding_template::FIXED_INPUT_ID;
     use tempfile::TempDir;
     
     #[tokio::test]
@@ -619,6 +1151,343 @@ mod tests {
         assert!(result.is_err()); // Expect error when no embeddings are available
     }
     
+    fn scored_result(file_path: &str, combined_score: f32) -> EnhancedSearchResult {
+        EnhancedSearchResult {
+            entry: VectorEntry {
+                id: file_path.to_string(),
+                embedding: vec![1.0, 0.0],
+                metadata: CodeMetadata {
+                    file_path: file_path.to_string(),
+                    function_name: None,
+                    line_start: 1,
+                    line_end: 10,
+                    code_type: CodeType::Function,
+                    language: "typescript".to_string(),
+                    complexity: 1.0,
+                    tokens: vec![],
+                    calls: Vec::new(),
+                    hash: file_path.to_string(),
+                    embedding_template: "enriched".to_string(),
+                },
+                created_at: chrono::Utc::now(),
+                updated_at: chrono::Utc::now(),
+            },
+            embedding_similarity: combined_score,
+            rerank_score: combined_score,
+            combined_score,
+            confidence: combined_score,
+            additional_matches: 0,
+        }
+    }
+    
+    #[test]
+    fn test_relevance_floor_drops_weak_matches() {
+        let results = vec![
+            scored_result("strong.ts", 0.92),
+            scored_result("weak.ts", 0.31),
+            scored_result("weaker.ts", 0.12),
+        ];
+        
+        let (kept, low_confidence) = apply_relevance_floor(results.clone(), Some(0.8));
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].entry.metadata.file_path, "strong.ts");
+        assert!(low_confidence.is_none());
+        
+        let (kept, low_confidence) = apply_relevance_floor(results.clone(), None);
+        assert_eq!(kept.len(), 3);
+        assert!(low_confidence.is_none());
+    }
+    
+    #[test]
+    fn test_relevance_floor_flags_best_effort_match() {
+        let results = vec![
+            scored_result("weaker.ts", 0.12),
+            scored_result("weak.ts", 0.31),
+        ];
+        
+        let (kept, low_confidence) = apply_relevance_floor(results, Some(0.8));
+        assert!(kept.is_empty());
+        assert_eq!(low_confidence.unwrap().entry.metadata.file_path, "weak.ts");
+    }
+    
+    #[test]
+    fn test_search_type_ties_keep_the_ranking_order() {
+        let results = vec![
+            scored_result("src/c.ts", 0.5),
+            scored_result("src/a.ts", 0.7),
+            scored_result("src/b.ts", 0.5),
+        ];
+        
+        let ranked = apply_search_type(results, &SearchType::Similar, "parse", 10);
+        let files: Vec<_> = ranked.iter().map(|r| r.entry.metadata.file_path.as_str()).collect();
+        assert_eq!(files, vec!["src/a.ts", "src/b.ts", "src/c.ts"]);
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_search_with_model_free_backends() {
+        use crate::ml::vector_db::{HashingEmbeddingBackend, OverlapRerankerBackend};
+        
+        let vector_db = VectorStoreFactory::create_native(VectorDBConfig {
+            similarity_threshold: 0.1,
+            ..VectorDBConfig::for_testing()
+        });
+        let service = EnhancedSearchService::with_backends(
+            MLConfig::for_testing(),
+            vector_db,
+            Arc::new(RwLock::new(HashingEmbeddingBackend::new(768))),
+            Arc::new(RwLock::new(OverlapRerankerBackend)),
+        );
+        
+        let entry = |file_path: &str, content: &str| CodeIndexEntry {
+            file_path: file_path.to_string(),
+            function_name: None,
+            line_start: 1,
+            line_end: 5,
+            code_type: CodeType::Function,
+            language: "typescript".to_string(),
+            complexity: 1.0,
+            content: content.to_string(),
+            embedding_template: FIXED_INPUT_ID.to_string(),
+        };
+        service.index_code(vec![
+            entry("auth.ts", "function validateUserToken(token) { return verify(token); }"),
+            entry("math.ts", "function addNumbers(a, b) { return a + b; }"),
+        ]).await.unwrap();
+        
+        let response = service.search(SearchRequest {
+            query: "validate user token".to_string(),
+            search_type: SearchType::General,
+            filters: SearchFilters::default(),
+            options: SearchOptions::default(),
+        }).await.unwrap();
+        
+        assert_eq!(response.results[0].entry.metadata.file_path, "auth.ts");
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_index_file_upserts_single_file() {
+        use crate::ml::vector_db::{HashingEmbeddingBackend, OverlapRerankerBackend};
+        
+        let vector_db = VectorStoreFactory::create_native(VectorDBConfig {
+            similarity_threshold: 0.1,
+            ..VectorDBConfig::for_testing()
+        });
+        let service = EnhancedSearchService::with_backends(
+            MLConfig::for_testing(),
+            vector_db,
+            Arc::new(RwLock::new(HashingEmbeddingBackend::new(768))),
+            Arc::new(RwLock::new(OverlapRerankerBackend)),
+        );
+        
+        let entry = |file_path: &str, line_start: usize, content: &str| CodeIndexEntry {
+            file_path: file_path.to_string(),
+            function_name: None,
+            line_start,
+            line_end: line_start + 4,
+            code_type: CodeType::Function,
+            language: "typescript".to_string(),
+            complexity: 1.0,
+            content: content.to_string(),
+            embedding_template: FIXED_INPUT_ID.to_string(),
+        };
+        service.index_code(vec![
+            entry("math.ts", 1, "function addNumbers(a, b) { return a + b; }"),
+            entry("math.ts", 10, "function multiplyNumbers(a, b) { return a * b; }"),
+        ]).await.unwrap();
+        assert_eq!(service.get_stats().await.unwrap().total_indexed_entries, 2);
+        
+        let indexed = service.index_file("auth.ts", vec![
+            entry("auth.ts", 1, "function validateUserToken(token) { return verify(token); }"),
+        ]).await.unwrap();
+        assert_eq!(indexed, 1);
+        let stats = service.get_stats().await.unwrap();
+        assert_eq!(stats.total_indexed_entries, 3);
+        assert_eq!(stats.total_files, 2);
+        
+        // Re-indexing replaces the file's previous entries
+        service.index_file("auth.ts", vec![
+            entry("auth.ts", 20, "function validateUserSession(session) { return session.valid; }"),
+        ]).await.unwrap();
+        assert_eq!(service.get_stats().await.unwrap().total_indexed_entries, 3);
+        
+        let response = service.search(SearchRequest {
+            query: "validate user session".to_string(),
+            search_type: SearchType::General,
+            filters: SearchFilters::default(),
+            options: SearchOptions::default(),
+        }).await.unwrap();
+        assert_eq!(response.results[0].entry.metadata.file_path, "auth.ts");
+        assert_eq!(response.results[0].entry.metadata.line_start, 20);
+        
+        assert!(service.index_file("auth.ts", vec![entry("other.ts", 1, "x")]).await.is_err());
+    }
+    
+    #[test]
+    fn test_group_by_file_collapses_extra_matches() {
+        let results = vec![
+            scored_result("a.ts", 0.90),
+            scored_result("b.ts", 0.85),
+            scored_result("a.ts", 0.80),
+            scored_result("a.ts", 0.40),
+            scored_result("c.ts", 0.30),
+            scored_result("b.ts", 0.20),
+        ];
+        
+        let grouped = group_results_by_file(results.clone(), 1);
+        let summary: Vec<(&str, usize)> = grouped.iter()
+            .map(|r| (r.entry.metadata.file_path.as_str(), r.additional_matches))
+            .collect();
+        assert_eq!(summary, vec![("a.ts", 2), ("b.ts", 1), ("c.ts", 0)]);
+        
+        let grouped = group_results_by_file(results, 2);
+        let summary: Vec<(&str, f32, usize)> = grouped.iter()
+            .map(|r| (r.entry.metadata.file_path.as_str(), r.combined_score, r.additional_matches))
+            .collect();
+        assert_eq!(summary, vec![
+            ("a.ts", 0.90, 1),
+            ("a.ts", 0.80, 0),
+            ("b.ts", 0.85, 0),
+            ("b.ts", 0.20, 0),
+            ("c.ts", 0.30, 0),
+        ]);
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_readme_section_is_searchable_as_markdown() {
+        use crate::ml::services::doc_index::markdown_entries;
+        use crate::ml::vector_db::{HashingEmbeddingBackend, OverlapRerankerBackend};
+        
+        let vector_db = VectorStoreFactory::create_native(VectorDBConfig {
+            similarity_threshold: 0.1,
+            ..VectorDBConfig::for_testing()
+        });
+        let service = EnhancedSearchService::with_backends(
+            MLConfig::for_testing(),
+            vector_db,
+            Arc::new(RwLock::new(HashingEmbeddingBackend::new(768))),
+            Arc::new(RwLock::new(OverlapRerankerBackend)),
+        );
+        
+        let readme = "# Token Optimizer\nIntro.\n\n## Cache invalidation\nEntries are invalidated when the file hash changes.\n";
+        service.index_code(markdown_entries("README.md", readme)).await.unwrap();
+        
+        let request = |include_docs: bool| SearchRequest {
+            query: "cache invalidated file hash changes".to_string(),
+            search_type: SearchType::General,
+            filters: SearchFilters { include_docs, ..SearchFilters::default() },
+            options: SearchOptions::default(),
+        };
+        
+        let response = service.search(request(true)).await.unwrap();
+        let top = &response.results[0].entry.metadata;
+        assert_eq!(top.file_path, "README.md");
+        assert_eq!(top.function_name.as_deref(), Some("Cache invalidation"));
+        assert_eq!(top.language, "markdown");
+        assert_eq!(top.code_type, CodeType::Comment);
+        
+        assert!(service.search(request(false)).await.unwrap().results.is_empty());
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_equal_scores_are_ordered_by_path_then_line() {
+        use crate::ml::vector_db::{HashingEmbeddingBackend, OverlapRerankerBackend};
+        
+        let service = EnhancedSearchService::with_backends(
+            MLConfig::for_testing(),
+            VectorStoreFactory::create_native(VectorDBConfig {
+                similarity_threshold: 0.0,
+                ..VectorDBConfig::for_testing()
+            }),
+            Arc::new(RwLock::new(HashingEmbeddingBackend::new(768))),
+            Arc::new(RwLock::new(OverlapRerankerBackend)),
+        );
+        
+        // Identical content gives identical scores
+        let entry = |file_path: &str, line_start: usize| CodeIndexEntry {
+            file_path: file_path.to_string(),
+            function_name: Some("retry_request".to_string()),
+            line_start,
+            line_end: line_start + 5,
+            code_type: CodeType::Function,
+            language: "rust".to_string(),
+            complexity: 1.0,
+            content: "fn retry_request(attempts: u32) -> Result<Response>".to_string(),
+            embedding_template: FIXED_INPUT_ID.to_string(),
+        };
+        service.index_code(vec![entry("src/http/b.rs", 1), entry("src/http/a.rs", 40), entry("src/http/a.rs", 7)]).await.unwrap();
+        
+        for _ in 0..5 {
+            let response = service.search(SearchRequest {
+                query: "retry request attempts".to_string(),
+                search_type: SearchType::General,
+                filters: SearchFilters::default(),
+                options: SearchOptions { use_cache: false, ..SearchOptions::default() },
+            }).await.unwrap();
+            
+            let order: Vec<_> = response.results.iter()
+                .map(|r| (r.entry.metadata.file_path.as_str(), r.entry.metadata.line_start))
+                .collect();
+            assert_eq!(order, vec![("src/http/a.rs", 7), ("src/http/a.rs", 40), ("src/http/b.rs", 1)]);
+            assert!(response.results.windows(2).all(|pair| pair[0].combined_score == pair[1].combined_score));
+        }
+    }
+    
+    #[cfg(feature = "rust")]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_excluded_tests_are_not_indexed() {
+        use crate::analyzers::rust_analyzer::RustAnalyzer;
+        use crate::ml::vector_db::{HashingEmbeddingBackend, OverlapRerankerBackend};
+        
+        let content = "pub fn parse_header(input: &str) -> usize {\n    input.len()\n}\n\n#[test]\nfn parse_header_counts_bytes() {\n    assert_eq!(parse_header(\"ab\"), 2);\n}\n";
+        let entry = |name: &str, line_start: usize, line_end: usize| CodeIndexEntry {
+            file_path: "src/header.rs".to_string(),
+            function_name: Some(name.to_string()),
+            line_start,
+            line_end,
+            code_type: CodeType::Function,
+            language: "rust".to_string(),
+            complexity: 1.0,
+            content: content.lines().skip(line_start - 1).take(line_end - line_start + 1).collect::<Vec<_>>().join("\n"),
+            embedding_template: FIXED_INPUT_ID.to_string(),
+        };
+        let entries = vec![entry("parse_header", 1, 3), entry("parse_header_counts_bytes", 6, 8)];
+        let test_ranges = RustAnalyzer::new().unwrap().test_line_ranges(content);
+        
+        let excluded = apply_test_ranges(entries.clone(), &test_ranges, false);
+        let names: Vec<_> = excluded.iter().map(|e| e.function_name.as_deref().unwrap()).collect();
+        assert_eq!(names, vec!["parse_header"]);
+        
+        // Included test code is tagged and hidden unless the search asks for it
+        let included = apply_test_ranges(entries, &test_ranges, true);
+        let code_types: Vec<_> = included.iter().map(|e| e.code_type.clone()).collect();
+        assert_eq!(code_types, vec![CodeType::Function, CodeType::Test]);
+        
+        let vector_db = VectorStoreFactory::create_native(VectorDBConfig {
+            similarity_threshold: 0.0,
+            ..VectorDBConfig::for_testing()
+        });
+        let service = EnhancedSearchService::with_backends(
+            MLConfig::for_testing(),
+            vector_db,
+            Arc::new(RwLock::new(HashingEmbeddingBackend::new(768))),
+            Arc::new(RwLock::new(OverlapRerankerBackend)),
+        );
+        service.index_code(included).await.unwrap();
+        
+        let request = |include_tests: bool| SearchRequest {
+            query: "parse header counts bytes".to_string(),
+            search_type: SearchType::General,
+            filters: SearchFilters { include_tests, ..SearchFilters::default() },
+            options: SearchOptions::default(),
+        };
+        let names = |response: SearchResponse| response.results.iter()
+            .map(|r| r.entry.metadata.function_name.clone().unwrap())
+            .collect::<Vec<_>>();
+        
+        assert!(!names(service.search(request(false)).await.unwrap()).contains(&"parse_header_counts_bytes".to_string()));
+        assert!(names(service.search(request(true)).await.unwrap()).contains(&"parse_header_counts_bytes".to_string()));
+    }
+    
     #[tokio::test]
     async fn test_code_indexing() {
         // Create isolated test environment
@@ -640,6 +1509,7 @@ mod tests {
                 language: "typescript".to_string(),
                 complexity: 1.0,
                 content: "function testFunction() { return 42; }".to_string(),
+                embedding_template: FIXED_INPUT_ID.to_string(),
             }
         ];
         
@@ -649,4 +1519,351 @@ mod tests {
         let stats = service.get_stats().await.unwrap();
         assert_eq!(stats.total_indexed_entries, 1);
     }
-}
\ No newline at end of file
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_reindex_only_re_embeds_changed_functions() {
+        use crate::ml::vector_db::{HashingEmbeddingBackend, OverlapRerankerBackend};
+        use std::sync::atomic::AtomicUsize;
+        
+        /// Counts the texts it is asked to embed
+        struct CountingEmbedder(Arc<AtomicUsize>);
+        
+        #[async_trait::async_trait]
+        impl EmbeddingBackend for CountingEmbedder {
+            async fn embed_texts(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+                self.0.fetch_add(texts.len(), Ordering::SeqCst);
+                let hashing = HashingEmbeddingBackend::new(768);
+                Ok(texts.iter().map(|text| hashing.embed(text)).collect())
+            }
+            
+            fn get_cache_stats(&self) -> (usize, usize) {
+                (0, 0)
+            }
+        }
+        
+        let embedded = Arc::new(AtomicUsize::new(0));
+        let service = EnhancedSearchService::with_backends(
+            MLConfig::for_testing(),
+            VectorStoreFactory::create_native(VectorDBConfig::for_testing()),
+            Arc::new(RwLock::new(CountingEmbedder(embedded.clone()))),
+            Arc::new(RwLock::new(OverlapRerankerBackend)),
+        );
+        
+        let file = |parse_body: &str| vec![
+            ("parse", 1, parse_body.to_string()),
+            ("render", 5, "fn render(doc: &Doc) -> String { doc.to_string() }".to_string()),
+            ("save", 9, "fn save(doc: &Doc) -> Result<()> { write(doc) }".to_string()),
+        ].into_iter().map(|(name, line, content)| CodeIndexEntry {
+            file_path: "src/document.rs".to_string(),
+            function_name: Some(name.to_string()),
+            line_start: line,
+            line_end: line + 3,
+            code_type: CodeType::Function,
+            language: "rust".to_string(),
+            complexity: 1.0,
+            content,
+            embedding_template: FIXED_INPUT_ID.to_string(),
+        }).collect::<Vec<_>>();
+        
+        service.index_code(file("fn parse(input: &str) -> Doc { Doc::new(input) }")).await.unwrap();
+        assert_eq!(embedded.swap(0, Ordering::SeqCst), 3);
+        
+        let indexed = service.index_code(file("fn parse(input: &str) -> Doc { Doc::new(input.trim()) }")).await.unwrap();
+        assert_eq!(indexed, 3);
+        assert_eq!(embedded.swap(0, Ordering::SeqCst), 1);
+        
+        // The watch-mode path reuses embeddings the same way
+        let indexed = service.index_file("src/document.rs", file("fn parse(input: &str) -> Doc { Doc::parse(input) }")).await.unwrap();
+        assert_eq!(indexed, 3);
+        assert_eq!(embedded.load(Ordering::SeqCst), 1);
+        
+        let stats = service.get_stats().await.unwrap();
+        assert_eq!(stats.total_indexed_entries, 3);
+        assert_eq!(stats.function_embedding_hits, 4);
+        assert_eq!(stats.function_embedding_misses, 5);
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_analysis_mode_is_static_without_models_or_after_fallback() {
+        use crate::ml::vector_db::{HashingEmbeddingBackend, OverlapRerankerBackend};
+        
+        /// Embedder whose model is gone, as when the plugin failed to load
+        struct FailingEmbedder;
+        
+        #[async_trait::async_trait]
+        impl EmbeddingBackend for FailingEmbedder {
+            async fn embed_texts(&self, _texts: &[String]) -> Result<Vec<Vec<f32>>> {
+                anyhow::bail!("model not loaded")
+            }
+            
+            fn get_cache_stats(&self) -> (usize, usize) {
+                (0, 0)
+            }
+        }
+        
+        let entry = CodeIndexEntry {
+            file_path: "src/document.rs".to_string(),
+            function_name: Some("parse".to_string()),
+            line_start: 1,
+            line_end: 3,
+            code_type: CodeType::Function,
+            language: "rust".to_string(),
+            complexity: 1.0,
+            content: "fn parse(input: &str) -> Doc { Doc::new(input) }".to_string(),
+            embedding_template: FIXED_INPUT_ID.to_string(),
+        };
+        
+        // No model files: the plugins fail to load and search carries on
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let mut config = MLConfig::for_testing();
+        config.model_cache_dir = temp_dir.path().join("models");
+        let cache_dir = temp_dir.path().join("vector-db").to_string_lossy().to_string();
+        let service = EnhancedSearchService::new_with_cache_dir(config, Some(cache_dir)).await.unwrap();
+        service.index_code(vec![entry.clone()]).await.unwrap();
+        assert_eq!(service.analysis_mode(), AnalysisMode::Static);
+        
+        let service_with = |embedder: Arc<RwLock<dyn EmbeddingBackend>>| {
+            let mut service = EnhancedSearchService::with_backends(
+                MLConfig::for_testing(),
+                VectorStoreFactory::create_native(VectorDBConfig::for_testing()),
+                embedder,
+                Arc::new(RwLock::new(OverlapRerankerBackend)),
+            );
+            service.models_loaded = true;
+            service
+        };
+        
+        let working = service_with(Arc::new(RwLock::new(HashingEmbeddingBackend::new(768))));
+        working.index_code(vec![entry.clone()]).await.unwrap();
+        assert_eq!(working.analysis_mode(), AnalysisMode::Ai);
+        
+        // Loaded, but indexing fell back to hash embeddings
+        let failing = service_with(Arc::new(RwLock::new(FailingEmbedder)));
+        failing.index_code(vec![entry]).await.unwrap();
+        assert_eq!(failing.analysis_mode(), AnalysisMode::Static);
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_reindex_drops_entries_whose_lines_moved() {
+        use crate::ml::vector_db::{HashingEmbeddingBackend, OverlapRerankerBackend};
+        
+        let service = EnhancedSearchService::with_backends(
+            MLConfig::for_testing(),
+            VectorStoreFactory::create_native(VectorDBConfig::for_testing()),
+            Arc::new(RwLock::new(HashingEmbeddingBackend::new(768))),
+            Arc::new(RwLock::new(OverlapRerankerBackend)),
+        );
+        let entry = |name: &str, line_start: usize, line_end: usize| CodeIndexEntry {
+            file_path: "src/document.rs".to_string(),
+            function_name: Some(name.to_string()),
+            line_start,
+            line_end,
+            code_type: CodeType::Function,
+            language: "rust".to_string(),
+            complexity: 1.0,
+            content: format!("fn {}(doc: &Doc) {{ lines {}..{} }}", name, line_start, line_end),
+            embedding_template: FIXED_INPUT_ID.to_string(),
+        };
+        let ids = |service: &EnhancedSearchService| {
+            let mut ids: Vec<String> = service.vector_db.read().get_by_file("src/document.rs").unwrap()
+                .into_iter().map(|entry| entry.id).collect();
+            ids.sort();
+            ids
+        };
+        
+        service.index_code(vec![entry("parse", 1, 3), entry("render", 5, 7), entry("save", 9, 11)]).await.unwrap();
+        
+        // `parse` grew by two lines, shifting `render`; `save` was deleted
+        service.index_code(vec![entry("parse", 1, 5), entry("render", 7, 9)]).await.unwrap();
+        assert_eq!(ids(&service), vec!["src/document.rs:1:5", "src/document.rs:7:9"]);
+        assert_eq!(service.get_stats().await.unwrap().total_indexed_entries, 2);
+    }
+    
+    #[test]
+    fn test_content_hashing_modes() {
+        let service = |content_hashing| EnhancedSearchService::with_backends(
+            MLConfig { content_hashing, ..MLConfig::for_testing() },
+            VectorStoreFactory::create_native(VectorDBConfig::for_testing()),
+            Arc::new(RwLock::new(crate::ml::vector_db::HashingEmbeddingBackend::new(64))),
+            Arc::new(RwLock::new(crate::ml::vector_db::OverlapRerankerBackend)),
+        );
+        
+        let exact = service(ContentHashing::Exact);
+        assert_ne!(exact.calculate_content_hash("fn a() { x }"), exact.calculate_content_hash("fn a()  {\n    x\n}"));
+        
+        let ignore_whitespace = service(ContentHashing::IgnoreWhitespace);
+        assert_eq!(
+            ignore_whitespace.calculate_content_hash("fn a() { x }"),
+            ignore_whitespace.calculate_content_hash("fn a()  {\n    x\n}")
+        );
+        assert_ne!(ignore_whitespace.calculate_content_hash("fn a() {}"), ignore_whitespace.calculate_content_hash("fn b() {}"));
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_definition_search_ranks_definition_above_call_sites() {
+        use crate::ml::vector_db::{HashingEmbeddingBackend, OverlapRerankerBackend};
+        
+        let service = EnhancedSearchService::with_backends(
+            MLConfig::for_testing(),
+            VectorStoreFactory::create_native(VectorDBConfig {
+                similarity_threshold: 0.0,
+                ..VectorDBConfig::for_testing()
+            }),
+            Arc::new(RwLock::new(HashingEmbeddingBackend::new(768))),
+            Arc::new(RwLock::new(OverlapRerankerBackend)),
+        );
+        
+        let entry = |file: &str, name: &str, content: &str| CodeIndexEntry {
+            file_path: file.to_string(),
+            function_name: Some(name.to_string()),
+            line_start: 1,
+            line_end: 10,
+            code_type: CodeType::Function,
+            language: "rust".to_string(),
+            complexity: 1.0,
+            content: content.to_string(),
+            embedding_template: FIXED_INPUT_ID.to_string(),
+        };
+        service.index_code(vec![
+            entry("src/auth/token.rs", "validate_token",
+                "fn validate_token(token: &str) -> bool { !token.is_empty() }"),
+            entry("src/handlers/login.rs", "login",
+                "fn login(token: &str) -> Response { if validate_token(token) { ok(token) } else { denied(token) } }"),
+            entry("src/handlers/refresh.rs", "refresh",
+                "fn refresh(token: &str) -> Response { let valid = validate_token(token); renew(token, valid) }"),
+        ]).await.unwrap();
+        
+        let search = |search_type: SearchType| SearchRequest {
+            query: "validate_token".to_string(),
+            search_type,
+            filters: SearchFilters::default(),
+            options: SearchOptions { use_cache: false, ..SearchOptions::default() },
+        };
+        
+        let definition = service.search(search(SearchType::Definition)).await.unwrap();
+        assert_eq!(definition.results[0].entry.metadata.function_name.as_deref(), Some("validate_token"));
+        assert!(definition.results.len() >= 2);
+        
+        let usage = service.search(search(SearchType::Usage)).await.unwrap();
+        assert_ne!(usage.results[0].entry.metadata.function_name.as_deref(), Some("validate_token"));
+        assert_eq!(
+            usage.results.last().unwrap().entry.metadata.function_name.as_deref(),
+            Some("validate_token")
+        );
+    }
+    
+    #[test]
+    fn test_calls_are_found_past_the_token_limit() {
+        let filler = (0..80).map(|i| format!("let step{} = {};", i, i)).collect::<Vec<_>>().join(" ");
+        let content = format!("fn refresh(cache: &Cache) {{ {} self.validate_token(cache); Doc::new(1); }}", filler);
+        
+        let calls = extract_calls(&content);
+        assert_eq!(calls, vec!["self.validate_token", "Doc::new"]);
+        
+        let metadata = CodeMetadata {
+            file_path: "src/session.rs".to_string(),
+            function_name: Some("refresh".to_string()),
+            line_start: 1,
+            line_end: 1,
+            code_type: CodeType::Function,
+            language: "rust".to_string(),
+            complexity: 1.0,
+            tokens: content.split_whitespace().take(50).map(str::to_string).collect(),
+            calls,
+            hash: String::new(),
+            embedding_template: "enriched".to_string(),
+        };
+        assert!(!metadata.tokens.iter().any(|token| token.contains("validate_token")));
+        assert!(calls_symbol(&metadata, "validate_token"));
+        assert!(calls_symbol(&metadata, "new"));
+        assert!(!calls_symbol(&metadata, "refresh"));
+        assert!(!calls_symbol(&metadata, "token"));
+    }
+    
+    #[test]
+    fn test_search_type_names_and_query_symbol() {
+        assert!(matches!(SearchType::from_name("Definition").unwrap(), SearchType::Definition));
+        assert!(matches!(SearchType::from_name("docs").unwrap(), SearchType::Documentation));
+        assert!(SearchType::from_name("fuzzy").is_err());
+        
+        assert_eq!(query_symbol("where is validate_token defined"), Some("validate_token"));
+        assert_eq!(query_symbol("auth::validateToken()"), Some("validateToken"));
+        assert_eq!(query_symbol("login"), Some("login"));
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_overlong_candidate_is_truncated_before_reranking() {
+        use crate::ml::vector_db::{HashingEmbeddingBackend, RerankerBackend};
+        
+        /// Records every batch it is asked to score
+        struct RecordingReranker(Arc<parking_lot::Mutex<Vec<Vec<String>>>>);
+        
+        #[async_trait::async_trait]
+        impl RerankerBackend for RecordingReranker {
+            async fn rank_documents(&self, _query: &str, documents: &[String]) -> Result<Vec<(usize, f32)>> {
+                self.0.lock().push(documents.to_vec());
+                Ok((0..documents.len()).map(|idx| (idx, 1.0)).collect())
+            }
+            
+            fn get_cache_stats(&self) -> (usize, usize) {
+                (0, 0)
+            }
+        }
+        
+        let batches = Arc::new(parking_lot::Mutex::new(Vec::new()));
+        let config = MLConfig {
+            reranker_batch_size: 2,
+            reranker_max_candidate_tokens: 60,
+            ..MLConfig::for_testing()
+        };
+        let service = EnhancedSearchService::with_backends(
+            config,
+            VectorStoreFactory::create_native(VectorDBConfig {
+                similarity_threshold: 0.0,
+                ..VectorDBConfig::for_testing()
+            }),
+            Arc::new(RwLock::new(HashingEmbeddingBackend::new(768))),
+            Arc::new(RwLock::new(RecordingReranker(batches.clone()))),
+        );
+        
+        let entry = |name: &str, content: String| CodeIndexEntry {
+            file_path: format!("src/{}.rs", name),
+            function_name: Some(name.to_string()),
+            line_start: 1,
+            line_end: 10,
+            code_type: CodeType::Function,
+            language: "rust".to_string(),
+            complexity: 1.0,
+            content,
+            embedding_template: FIXED_INPUT_ID.to_string(),
+        };
+        let long_body = (0..200).map(|i| format!("retry step{}", i)).collect::<Vec<_>>().join(" ");
+        service.index_code(vec![
+            entry("retry_long", format!("fn retry_long() {}", long_body)),
+            entry("retry_short", "fn retry_short() retry once".to_string()),
+            entry("retry_twice", "fn retry_twice() retry twice".to_string()),
+            entry("retry_thrice", "fn retry_thrice() retry thrice".to_string()),
+        ]).await.unwrap();
+        
+        service.search(SearchRequest {
+            query: "retry".to_string(),
+            search_type: SearchType::General,
+            filters: SearchFilters::default(),
+            options: SearchOptions { use_cache: false, ..SearchOptions::default() },
+        }).await.unwrap();
+        
+        let batches = batches.lock();
+        assert!(batches.len() > 1);
+        assert!(batches.iter().all(|batch| batch.len() <= 2));
+        
+        let documents: Vec<&String> = batches.iter().flatten().collect();
+        let long = documents.iter().find(|doc| doc.contains("retry_long")).unwrap();
+        let tokenizer = crate::utils::TiktokenTokenizer::default();
+        assert!(crate::utils::Tokenizer::count_tokens(&tokenizer, long) <= 60, "{:?}", long);
+        assert!(long.starts_with("Function: retry_long\nFile: src/retry_long.rs"));
+        assert!(!long.contains("step199"));
+        
+        let short = documents.iter().find(|doc| doc.contains("retry_short")).unwrap();
+        assert!(short.ends_with("once"));
+    }
+}
@@ -1,15 +1,20 @@
 //! Impact analysis service for change prediction
 
 use anyhow::Result;
+use futures::stream::{self, StreamExt};
 use std::sync::Arc;
-use std::path::Path;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
 use std::time::{Instant, SystemTime};
+use tokio_util::sync::CancellationToken;
 
 use crate::ml::config::MLConfig;
 use crate::ml::plugins::PluginManager;
 use crate::ml::models::*;
 use crate::analyzers::ts_ast_analyzer::TypeScriptASTAnalyzer;
 use crate::analyzers::DiffAnalyzer;
+use crate::types::{CacheEntry, FileType};
+use crate::utils::file_utils::detect_file_type;
 
 /// Impact analysis service for predicting change effects
 pub struct ImpactAnalysisService {
@@ -17,6 +22,9 @@ pub struct ImpactAnalysisService {
     plugin_manager: Arc<PluginManager>,
     ast_analyzer: Option<TypeScriptASTAnalyzer>,
     diff_analyzer: Option<DiffAnalyzer>,
+    /// Maximum number of files scored concurrently by `analyze_project_impact`
+    concurrency: usize,
+    cancellation: CancellationToken,
     is_ready: bool,
 }
 
@@ -27,10 +35,24 @@ impl ImpactAnalysisService {
             plugin_manager,
             ast_analyzer: None, // Will be initialized later
             diff_analyzer: None, // Will be initialized later
+            concurrency: std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4),
+            cancellation: CancellationToken::new(),
             is_ready: false,
         }
     }
 
+    /// Set how many files are analyzed at once (at least one)
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Use a caller-owned token to abort long project-wide analyses
+    pub fn with_cancellation_token(mut self, token: CancellationToken) -> Self {
+        self.cancellation = token;
+        self
+    }
+
     pub async fn initialize(&mut self) -> Result<()> {
         tracing::info!("Initializing Impact Analysis service");
         
@@ -97,7 +119,7 @@ impl ImpactAnalysisService {
             anyhow::bail!("Impact Analysis service not initialized");
         }
 
-        let mut impacted_files = Vec::new();
+        let mut jobs: Vec<(String, PathBuf)> = Vec::new();
         let mut all_changed_functions = Vec::new();
 
         for file_path in changed_files {
@@ -106,13 +128,34 @@ impl ImpactAnalysisService {
             let functions = self.extract_changed_functions(&full_path).await?;
             all_changed_functions.extend(functions.clone());
 
-            // Analyze impact for each function in the file
-            for function_name in &functions {
-                let impact_analysis = self.analyze_single_file_impact(function_name, &full_path, project_path).await?;
-                impacted_files.push(impact_analysis);
-            }
+            // One job per function in the file
+            jobs.extend(functions.into_iter().map(|function_name| (function_name, full_path.clone())));
+        }
+
+        // Score files on a bounded pool; results carry their job index so the
+        // report order doesn't depend on completion order
+        let mut results: Vec<(usize, FileImpactAnalysis)> = stream::iter(jobs.iter().enumerate())
+            .map(|(index, (function_name, full_path))| async move {
+                if self.cancellation.is_cancelled() {
+                    anyhow::bail!("Impact analysis cancelled");
+                }
+                let analysis = self.analyze_single_file_impact(function_name, full_path, project_path).await?;
+                Ok((index, analysis))
+            })
+            .buffer_unordered(self.concurrency)
+            .take_while(|_| std::future::ready(!self.cancellation.is_cancelled()))
+            .collect::<Vec<Result<(usize, FileImpactAnalysis)>>>()
+            .await
+            .into_iter()
+            .collect::<Result<_>>()?;
+
+        if self.cancellation.is_cancelled() {
+            anyhow::bail!("Impact analysis cancelled");
         }
 
+        results.sort_by_key(|(index, _)| *index);
+        let impacted_files = results.into_iter().map(|(_, analysis)| analysis).collect();
+
         Ok(ProjectImpactReport {
             project_path: project_path.to_string_lossy().to_string(),
             changed_file: changed_files.join(", "),
@@ -1026,6 +1069,112 @@ impl ImpactAnalysisService {
 
 }
 
+/// File-level import graph between cached files, keyed by cache path
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DepGraph {
+    /// File -> files it imports
+    imports: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl DepGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builds the graph from the imports recorded in cached summaries.
+    /// Relative imports are resolved against the other entries; package
+    /// imports and imports of uncached files are skipped.
+    pub fn from_entries(entries: &HashMap<String, CacheEntry>) -> Self {
+        let mut graph = Self::new();
+        for (path, entry) in entries {
+            for specifier in &entry.summary.dependencies {
+                if let Some(target) = resolve_import(path, specifier, entries) {
+                    graph.add_import(path, &target);
+                }
+            }
+        }
+        graph
+    }
+
+    pub fn add_import(&mut self, from: &str, to: &str) {
+        self.imports.entry(from.to_string()).or_default().insert(to.to_string());
+    }
+
+    /// Files importing `file` directly
+    pub fn dependents(&self, file: &str) -> Vec<&str> {
+        self.imports.iter()
+            .filter(|(_, targets)| targets.contains(file))
+            .map(|(path, _)| path.as_str())
+            .collect()
+    }
+
+    /// Files importing `file` directly or through other files
+    pub fn transitive_dependents(&self, file: &str) -> BTreeSet<String> {
+        let mut found = BTreeSet::new();
+        let mut pending = vec![file.to_string()];
+        while let Some(current) = pending.pop() {
+            for dependent in self.dependents(&current) {
+                if dependent != file && found.insert(dependent.to_string()) {
+                    pending.push(dependent.to_string());
+                }
+            }
+        }
+        found
+    }
+}
+
+/// Test files to run for a change, without any ML: the spec/test counterparts
+/// of every changed file and of its transitive dependents, plus changed test
+/// files themselves. `changed` uses the same keys as `entries`.
+pub fn tests_to_run(changed: &[String], graph: &DepGraph, entries: &HashMap<String, CacheEntry>) -> Vec<String> {
+    let mut affected: BTreeSet<String> = changed.iter().cloned().collect();
+    for file in changed {
+        affected.extend(graph.transitive_dependents(file));
+    }
+
+    let mut tests: Vec<String> = entries.keys()
+        .filter(|path| is_test_file(path))
+        .filter(|path| affected.contains(path.as_str()) || affected.contains(&tested_file(path)))
+        .cloned()
+        .collect();
+    tests.sort();
+    tests
+}
+
+fn is_test_file(path: &str) -> bool {
+    matches!(detect_file_type(Path::new(path)), FileType::Test)
+}
+
+/// Source file a `.spec`/`.test` file covers: `a/b.service.spec.ts` -> `a/b.service.ts`
+fn tested_file(test_path: &str) -> String {
+    test_path.replacen(".spec.", ".", 1).replacen(".test.", ".", 1)
+}
+
+/// Cache key of the file `specifier` refers to when imported from `from`
+fn resolve_import(from: &str, specifier: &str, entries: &HashMap<String, CacheEntry>) -> Option<String> {
+    let specifier = specifier.trim().trim_end_matches(';').trim_matches(|c| c == '\'' || c == '"');
+    if !specifier.starts_with('.') {
+        return None;
+    }
+
+    let mut segments: Vec<&str> = from.split('/').collect();
+    segments.pop();
+    for segment in specifier.split('/') {
+        match segment {
+            "." | "" => {}
+            ".." if segments.last().is_some_and(|last| *last != "." && *last != "..") => {
+                segments.pop();
+            }
+            _ => segments.push(segment),
+        }
+    }
+    let base = segments.join("/");
+
+    ["", ".ts", ".tsx", ".js", "/index.ts"].iter()
+        .map(|suffix| format!("{}{}", base, suffix))
+        .find(|candidate| entries.contains_key(candidate))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1345,4 +1494,98 @@ mod tests {
         
         Ok(())
     }
-}
\ No newline at end of file
+
+    fn write_impact_fixture(project: &Path) -> Vec<String> {
+        let mut changed_files = Vec::new();
+        for i in 0..6 {
+            let name = format!("feature{}.service.ts", i);
+            let mut content = String::from("import { Injectable } from '@angular/core';\n");
+            for dep in 0..i {
+                content.push_str(&format!("import {{ Dep{} }} from './dep{}';\n", dep, dep));
+            }
+            for f in 0..3 {
+                content.push_str(&format!("export async function load{}_{}() {{ return 1; }}\n", i, f));
+            }
+            std::fs::write(project.join(&name), content).unwrap();
+            changed_files.push(name);
+        }
+        changed_files
+    }
+
+    async fn project_impact_with_concurrency(project: &Path, changed_files: &[String], concurrency: usize) -> ProjectImpactReport {
+        let plugin_manager = Arc::new(PluginManager::new());
+        let mut service = ImpactAnalysisService::new(MLConfig::for_testing(), plugin_manager)
+            .with_concurrency(concurrency);
+        service.initialize().await.unwrap();
+        service.analyze_project_impact(changed_files, project).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_parallel_project_impact_matches_serial() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let changed_files = write_impact_fixture(temp_dir.path());
+
+        let serial = project_impact_with_concurrency(temp_dir.path(), &changed_files, 1).await;
+        let parallel = project_impact_with_concurrency(temp_dir.path(), &changed_files, 8).await;
+
+        assert_eq!(serial.impacted_files.len(), 18);
+        assert_eq!(serial.changed_functions, parallel.changed_functions);
+        assert_eq!(
+            serde_json::to_value(&serial.impacted_files).unwrap(),
+            serde_json::to_value(&parallel.impacted_files).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_project_impact_respects_cancellation() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let changed_files = write_impact_fixture(temp_dir.path());
+
+        let token = CancellationToken::new();
+        let mut service = ImpactAnalysisService::new(MLConfig::for_testing(), Arc::new(PluginManager::new()))
+            .with_cancellation_token(token.clone());
+        service.initialize().await.unwrap();
+
+        token.cancel();
+        let result = service.analyze_project_impact(&changed_files, temp_dir.path()).await;
+        assert!(result.unwrap_err().to_string().contains("cancelled"));
+    }
+
+    #[test]
+    fn test_tests_to_run_follows_dependents_to_their_specs() {
+        use crate::cache::CacheManager;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let files = [
+            ("src/app/services/auth.service.ts", "export class AuthService {\n  login() {}\n}\n"),
+            ("src/app/services/auth.service.spec.ts", "import { AuthService } from './auth.service';\n"),
+            ("src/app/login/login.component.ts", "import { AuthService } from '../services/auth.service';\nexport class LoginComponent {}\n"),
+            ("src/app/login/login.component.spec.ts", "import { LoginComponent } from './login.component';\n"),
+            ("src/app/shell/shell.component.ts", "import { LoginComponent } from '../login/login.component';\nexport class ShellComponent {}\n"),
+            ("src/app/shell/shell.component.spec.ts", "import { ShellComponent } from './shell.component';\n"),
+            ("src/app/footer/footer.component.ts", "export class FooterComponent {}\n"),
+            ("src/app/footer/footer.component.spec.ts", "import { FooterComponent } from './footer.component';\n"),
+        ];
+        for (path, content) in files {
+            let full_path = temp_dir.path().join(path);
+            std::fs::create_dir_all(full_path.parent().unwrap()).unwrap();
+            std::fs::write(full_path, content).unwrap();
+        }
+        let mut cache_manager = CacheManager::new(temp_dir.path()).unwrap();
+        cache_manager.analyze_project(temp_dir.path(), false).unwrap();
+
+        let entries = &cache_manager.get_cache().entries;
+        let graph = DepGraph::from_entries(entries);
+        assert_eq!(graph.dependents("./src/app/services/auth.service.ts").len(), 2);
+
+        let tests = tests_to_run(&["./src/app/services/auth.service.ts".to_string()], &graph, entries);
+        assert_eq!(tests, vec![
+            "./src/app/login/login.component.spec.ts",
+            "./src/app/services/auth.service.spec.ts",
+            "./src/app/shell/shell.component.spec.ts",
+        ]);
+
+        let tests = tests_to_run(&["./src/app/footer/footer.component.ts".to_string()], &graph, entries);
+        assert_eq!(tests, vec!["./src/app/footer/footer.component.spec.ts"]);
+    }
+}
@@ -0,0 +1,273 @@
+//! Staged indexing with backpressure
+//!
+//! Indexing runs as three stages connected by bounded channels: analyzing
+//! files into batches of entries, embedding them and inserting them into the
+//! vector database. File analysis is blocking work, so it runs on its own
+//! thread (`spawn_producer`). A semaphore caps the embedding batches in flight,
+//! and a full channel pauses the stage feeding it, so memory stays flat no
+//! matter how large the corpus is.
+
+use anyhow::Result;
+use futures::{Stream, StreamExt};
+use parking_lot::RwLock;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::sync::{mpsc, Semaphore};
+use tokio::task::{JoinHandle, JoinSet};
+
+use crate::ml::config::MLConfig;
+use crate::ml::vector_db::{embed_batch, EmbeddingBackend, VectorDatabase, VectorEntry};
+use crate::utils::decoration::status;
+
+/// Bounds for the indexing stages
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IndexPipelineConfig {
+    pub batch_size: usize,
+    pub queue_depth: usize,
+    pub max_in_flight_batches: usize,
+}
+
+impl From<&MLConfig> for IndexPipelineConfig {
+    fn from(config: &MLConfig) -> Self {
+        Self {
+            batch_size: config.embedding_batch_size.max(1),
+            queue_depth: config.embedding_queue_depth.max(1),
+            max_in_flight_batches: config.max_in_flight_embedding_batches.max(1),
+        }
+    }
+}
+
+/// Entries waiting for their embeddings, with the text to embed for each
+pub struct PendingBatch {
+    pub texts: Vec<String>,
+    pub entries: Vec<VectorEntry>,
+}
+
+/// Entries `run_index_pipeline` inserted
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IndexedCounts {
+    pub inserted: usize,
+    /// Inserted entries whose embedding came from the fallback, not the backend
+    pub fallback_embedded: usize,
+}
+
+/// Runs `items` on a blocking thread, sending each item into a channel of
+/// `depth` slots. The iterator is only advanced when the channel has room, so
+/// the receiving side paces it; dropping the receiver stops it after the
+/// current item.
+pub fn spawn_producer<T: Send + 'static>(
+    items: impl Iterator<Item = T> + Send + 'static,
+    depth: usize,
+) -> (mpsc::Receiver<T>, JoinHandle<()>) {
+    let (tx, rx) = mpsc::channel(depth.max(1));
+    let producer = tokio::task::spawn_blocking(move || {
+        for item in items {
+            if tx.blocking_send(item).is_err() {
+                break;
+            }
+        }
+    });
+    (rx, producer)
+}
+
+/// Embeds every batch produced by `batches` and inserts the entries into
+/// `vector_db`. `batches` is only polled when the embedding stage has room,
+/// so preparation work is paced by the model. A batch the backend fails to
+/// embed falls back to `fallback` per text.
+pub async fn run_index_pipeline(
+    batches: impl Stream<Item = PendingBatch>,
+    embedding_backend: Arc<RwLock<dyn EmbeddingBackend>>,
+    vector_db: Arc<RwLock<dyn VectorDatabase>>,
+    config: IndexPipelineConfig,
+    fallback: fn(&str) -> Vec<f32>,
+) -> Result<IndexedCounts> {
+    let (prepared_tx, mut prepared_rx) = mpsc::channel::<PendingBatch>(config.queue_depth);
+    let (embedded_tx, mut embedded_rx) = mpsc::channel::<Vec<VectorEntry>>(config.queue_depth);
+    let in_flight = Arc::new(Semaphore::new(config.max_in_flight_batches));
+    let fallback_embedded = Arc::new(AtomicUsize::new(0));
+    let fallback_count = Arc::clone(&fallback_embedded);
+
+    let prepare = async move {
+        let mut batches = std::pin::pin!(batches);
+        while let Some(batch) = batches.next().await {
+            if prepared_tx.send(batch).await.is_err() {
+                break;
+            }
+        }
+        Ok::<_, anyhow::Error>(())
+    };
+
+    let embed = async move {
+        let mut tasks = JoinSet::new();
+        while let Some(batch) = prepared_rx.recv().await {
+            let permit = in_flight.clone().acquire_owned().await?;
+            let backend = Arc::clone(&embedding_backend);
+            let embedded_tx = embedded_tx.clone();
+            let fallback_count = Arc::clone(&fallback_count);
+
+            tasks.spawn(async move {
+                let PendingBatch { texts, mut entries } = batch;
+                let embeddings = match embed_batch(backend, texts.clone()).await {
+                    Ok(embeddings) if embeddings.len() == texts.len() => embeddings,
+                    Ok(_) | Err(_) => {
+                        status!("⚠️  Failed to embed batch of {}, falling back to dummy embeddings", texts.len());
+                        fallback_count.fetch_add(texts.len(), Ordering::Relaxed);
+                        texts.iter().map(|text| fallback(text)).collect()
+                    }
+                };
+                for (entry, embedding) in entries.iter_mut().zip(embeddings) {
+                    entry.embedding = embedding;
+                }
+                // Holding the permit until the insert stage accepts the batch
+                // keeps a slow database from piling up embedded batches
+                let _ = embedded_tx.send(entries).await;
+                drop(permit);
+            });
+
+            while let Some(finished) = tasks.try_join_next() {
+                finished?;
+            }
+        }
+        while let Some(finished) = tasks.join_next().await {
+            finished?;
+        }
+        Ok::<_, anyhow::Error>(())
+    };
+
+    let insert = async move {
+        let mut inserted = 0;
+        while let Some(entries) = embedded_rx.recv().await {
+            inserted += entries.len();
+            vector_db.write().add_vectors(entries)?;
+        }
+        Ok::<_, anyhow::Error>(inserted)
+    };
+
+    let (_, _, inserted) = tokio::try_join!(prepare, embed, insert)?;
+    Ok(IndexedCounts { inserted, fallback_embedded: fallback_embedded.load(Ordering::Relaxed) })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ml::services::embedding_template::FIXED_INPUT_ID;
+    use crate::ml::services::enhanced_search::{CodeIndexEntry, EnhancedSearchService};
+    use crate::ml::vector_db::{CodeType, HashingEmbeddingBackend, OverlapRerankerBackend, VectorDBConfig, VectorStoreFactory};
+    use async_trait::async_trait;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    /// Slow embedder that records how many batches it is embedding at once
+    struct CountingEmbedder {
+        in_flight: Arc<AtomicUsize>,
+        max_in_flight: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl EmbeddingBackend for CountingEmbedder {
+        async fn embed_texts(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+            let now = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_in_flight.fetch_max(now, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+
+            let hashing = HashingEmbeddingBackend::new(768);
+            Ok(texts.iter().map(|text| hashing.embed(text)).collect())
+        }
+
+        fn get_cache_stats(&self) -> (usize, usize) {
+            (0, 0)
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_in_flight_embedding_batches_stay_bounded() {
+        let max_in_flight = Arc::new(AtomicUsize::new(0));
+        let embedder = CountingEmbedder {
+            in_flight: Arc::new(AtomicUsize::new(0)),
+            max_in_flight: max_in_flight.clone(),
+        };
+        let config = MLConfig {
+            embedding_batch_size: 2,
+            embedding_queue_depth: 1,
+            max_in_flight_embedding_batches: 3,
+            ..MLConfig::for_testing()
+        };
+        let vector_db = VectorStoreFactory::create_native(VectorDBConfig::for_testing());
+        let service = EnhancedSearchService::with_backends(
+            config,
+            vector_db.clone(),
+            Arc::new(RwLock::new(embedder)),
+            Arc::new(RwLock::new(OverlapRerankerBackend)),
+        );
+
+        let entries = (0..40).map(|i| CodeIndexEntry {
+            file_path: format!("src/module_{}.rs", i),
+            function_name: Some(format!("handler_{}", i)),
+            line_start: 1,
+            line_end: 5,
+            code_type: CodeType::Function,
+            language: "rust".to_string(),
+            complexity: 1.0,
+            content: format!("fn handler_{}() -> usize {{ {} }}", i, i),
+            embedding_template: FIXED_INPUT_ID.to_string(),
+        }).collect();
+
+        assert_eq!(service.index_code(entries).await.unwrap(), 40);
+        assert_eq!(vector_db.read().stats().total_vectors, 40);
+
+        let max_in_flight = max_in_flight.load(Ordering::SeqCst);
+        assert!(max_in_flight <= 3, "{} batches were embedded at once", max_in_flight);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_file_analysis_is_paced_by_indexing() {
+        let embedder = CountingEmbedder {
+            in_flight: Arc::new(AtomicUsize::new(0)),
+            max_in_flight: Arc::new(AtomicUsize::new(0)),
+        };
+        let config = MLConfig {
+            embedding_batch_size: 2,
+            embedding_queue_depth: 1,
+            max_in_flight_embedding_batches: 1,
+            ..MLConfig::for_testing()
+        };
+        let vector_db = VectorStoreFactory::create_native(VectorDBConfig::for_testing());
+        let service = EnhancedSearchService::with_backends(
+            config,
+            vector_db.clone(),
+            Arc::new(RwLock::new(embedder)),
+            Arc::new(RwLock::new(OverlapRerankerBackend)),
+        );
+
+        // Each file is "analyzed" when pulled; record how far analysis ran
+        // ahead of the entries already in the index
+        let max_ahead = Arc::new(AtomicUsize::new(0));
+        let files = {
+            let vector_db = vector_db.clone();
+            let max_ahead = max_ahead.clone();
+            (0..60).map(move |i| {
+                let inserted = vector_db.read().stats().total_vectors;
+                max_ahead.fetch_max(i - inserted.min(i), Ordering::SeqCst);
+                vec![CodeIndexEntry {
+                    file_path: format!("src/module_{}.rs", i),
+                    function_name: Some(format!("handler_{}", i)),
+                    line_start: 1,
+                    line_end: 5,
+                    code_type: CodeType::Function,
+                    language: "rust".to_string(),
+                    complexity: 1.0,
+                    content: format!("fn handler_{}() -> usize {{ {} }}", i, i),
+                    embedding_template: FIXED_INPUT_ID.to_string(),
+                }]
+            })
+        };
+
+        assert_eq!(service.index_files(files).await.unwrap(), 60);
+        assert_eq!(vector_db.read().stats().total_vectors, 60);
+
+        // Pending, queued, in-flight and embedded batches of two, never the corpus
+        let max_ahead = max_ahead.load(Ordering::SeqCst);
+        assert!(max_ahead <= 14, "analysis ran {} files ahead of indexing", max_ahead);
+    }
+}
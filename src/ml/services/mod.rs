@@ -4,6 +4,7 @@ use anyhow::Result;
 use std::sync::Arc;
 
 use crate::ml::config::MLConfig;
+use crate::ml::models::AnalysisMode;
 use crate::ml::plugins::PluginManager;
 
 pub mod context;
@@ -12,6 +13,9 @@ pub mod pattern;
 pub mod search;
 pub mod optimization;
 pub mod enhanced_search;
+pub mod doc_index;
+pub mod embedding_template;
+pub mod index_pipeline;
 
 pub use context::SmartContextService;
 pub use impact_analysis::ImpactAnalysisService;
@@ -93,6 +97,15 @@ impl MLService {
     pub fn config(&self) -> &MLConfig {
         &self.config
     }
+
+    /// `Ai` only when at least one ML plugin is actually loaded
+    pub fn analysis_mode(&self) -> AnalysisMode {
+        if self.plugin_manager.get_active_plugin_count() > 0 {
+            AnalysisMode::Ai
+        } else {
+            AnalysisMode::Static
+        }
+    }
 }
 
 #[cfg(test)]
@@ -132,6 +145,21 @@ mod tests {
         assert!(service.search_service.is_ready());
         assert!(service.optimization_service.is_ready());
     }
+
+    #[tokio::test]
+    async fn test_analysis_mode_is_static_without_plugins() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let mut config = MLConfig::for_testing();
+        config.model_cache_dir = temp_dir.path().join("models");
+        let plugin_manager = Arc::new(PluginManager::new());
+        let mut service = MLService::new(config, plugin_manager).unwrap();
+
+        // Initialization degrades gracefully, but nothing was loaded
+        service.initialize().await.unwrap();
+        assert_eq!(service.analysis_mode(), AnalysisMode::Static);
+        assert_eq!(serde_json::to_value(service.analysis_mode()).unwrap(), serde_json::json!("static"));
+        assert_eq!(serde_json::to_value(AnalysisMode::Ai).unwrap(), serde_json::json!("ai"));
+    }
 }
 
 #[cfg(test)]
@@ -1,15 +1,185 @@
 //! Token optimization service
 
 use anyhow::Result;
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
+use crate::cache::SmartCache;
 use crate::ml::config::MLConfig;
 use crate::ml::plugins::PluginManager;
 use crate::ml::models::*;
+use crate::utils::{default_tokenizer, GitUtils, Tokenizer};
+
+/// Commits on HEAD scanned for the recent activity signal
+const ACTIVITY_COMMITS: usize = 100;
+
+/// Weights combining the ranking signals into
+/// `FileRecommendation::relevance_score`. Each signal is in `0.0..=1.0` and
+/// the score is their weighted average, so only the ratios matter.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OptimizerConfig {
+    /// Semantic similarity of the file to the task (default 0.5)
+    pub similarity_weight: f32,
+    /// Closeness in the dependency graph to the files being changed (default 0.3)
+    pub proximity_weight: f32,
+    /// How recently and often the file changed (default 0.2)
+    pub recency_weight: f32,
+}
+
+impl Default for OptimizerConfig {
+    fn default() -> Self {
+        Self {
+            similarity_weight: 0.5,
+            proximity_weight: 0.3,
+            recency_weight: 0.2,
+        }
+    }
+}
+
+impl OptimizerConfig {
+    /// Weighted average of the candidate's signals
+    pub fn relevance_score(&self, candidate: &FileCandidate) -> f32 {
+        let total_weight = self.similarity_weight + self.proximity_weight + self.recency_weight;
+        if total_weight <= 0.0 {
+            return 0.0;
+        }
+
+        (candidate.semantic_similarity * self.similarity_weight
+            + candidate.dependency_proximity * self.proximity_weight
+            + candidate.recent_activity * self.recency_weight)
+            / total_weight
+    }
+}
+
+/// A file considered for the context, with its ranking signals
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileCandidate {
+    pub file_path: String,
+    pub semantic_similarity: f32,
+    pub dependency_proximity: f32,
+    pub recent_activity: f32,
+}
+
+impl FileCandidate {
+    pub fn new(file_path: impl Into<String>) -> Self {
+        Self {
+            file_path: file_path.into(),
+            semantic_similarity: 0.0,
+            dependency_proximity: 0.0,
+            recent_activity: 0.0,
+        }
+    }
+}
+
+/// Dependency edges and change activity of a project, from which
+/// `optimize_tokens` derives the proximity and recency signals
+#[derive(Debug, Clone, Default)]
+pub struct ProjectSignals {
+    root: PathBuf,
+    /// Cache key -> files it imports or is imported by
+    neighbours: HashMap<String, BTreeSet<String>>,
+    /// Cache key -> recent commits touching the file, plus one when it has
+    /// uncommitted changes
+    changes: HashMap<String, usize>,
+    /// Cache keys of the files with uncommitted changes
+    changed: BTreeSet<String>,
+}
+
+impl ProjectSignals {
+    /// Reads the dependency edges from `cache` and the change activity from
+    /// the git history of `project_root`. Outside a git repository every file
+    /// has no activity.
+    pub fn collect(project_root: &Path, cache: &SmartCache) -> Self {
+        let mut neighbours: HashMap<String, BTreeSet<String>> = HashMap::new();
+        for (key, entry) in &cache.entries {
+            for other in entry.dependencies.iter().chain(&entry.dependents) {
+                neighbours.entry(key.clone()).or_default().insert(other.clone());
+                neighbours.entry(other.clone()).or_default().insert(key.clone());
+            }
+        }
+
+        let mut changes = HashMap::new();
+        let mut changed = BTreeSet::new();
+        if let Ok(git) = GitUtils::new(project_root) {
+            for (path, count) in git.recent_change_counts(ACTIVITY_COMMITS).unwrap_or_default() {
+                *changes.entry(format!("./{}", path)).or_insert(0) += count;
+            }
+            for path in git.get_modified_files().unwrap_or_default() {
+                let key = format!("./{}", path);
+                *changes.entry(key.clone()).or_insert(0) += 1;
+                changed.insert(key);
+            }
+        }
+
+        Self {
+            root: project_root.to_path_buf(),
+            neighbours,
+            changes,
+            changed,
+        }
+    }
+
+    /// Cache key of a file given as a path under the project root or relative to it
+    fn cache_key(&self, file: &str) -> String {
+        let path = Path::new(file);
+        let relative = path.strip_prefix(&self.root).unwrap_or(path).to_string_lossy();
+        format!("./{}", relative.trim_start_matches("./"))
+    }
+
+    /// Sets each candidate's dependency proximity to `1 / (1 + hops)` from the
+    /// nearest file with uncommitted changes (or from the first candidate when
+    /// the tree is clean), and its recent activity to its change count
+    /// relative to the most active candidate
+    pub fn apply(&self, candidates: &mut [FileCandidate]) {
+        let keys: Vec<String> = candidates.iter()
+            .map(|candidate| self.cache_key(&candidate.file_path))
+            .collect();
+
+        let seeds: Vec<&String> = if self.changed.is_empty() {
+            keys.first().into_iter().collect()
+        } else {
+            self.changed.iter().collect()
+        };
+        let mut hops: HashMap<&str, usize> = HashMap::new();
+        let mut pending = VecDeque::new();
+        for seed in seeds {
+            hops.insert(seed.as_str(), 0);
+            pending.push_back(seed.as_str());
+        }
+        while let Some(current) = pending.pop_front() {
+            let next_hops = hops[current] + 1;
+            for neighbour in self.neighbours.get(current).into_iter().flatten() {
+                if !hops.contains_key(neighbour.as_str()) {
+                    hops.insert(neighbour.as_str(), next_hops);
+                    pending.push_back(neighbour.as_str());
+                }
+            }
+        }
+
+        let most_changes = keys.iter()
+            .filter_map(|key| self.changes.get(key))
+            .max()
+            .copied()
+            .unwrap_or(0);
+
+        for (candidate, key) in candidates.iter_mut().zip(&keys) {
+            candidate.dependency_proximity = hops.get(key.as_str())
+                .map_or(0.0, |hops| 1.0 / (1.0 + *hops as f32));
+            candidate.recent_activity = match (self.changes.get(key), most_changes) {
+                (Some(count), most) if most > 0 => *count as f32 / most as f32,
+                _ => 0.0,
+            };
+        }
+    }
+}
 
 pub struct TokenOptimizationService {
     config: MLConfig,
     plugin_manager: Arc<PluginManager>,
+    tokenizer: Arc<dyn Tokenizer>,
+    optimizer_config: OptimizerConfig,
+    project_signals: Option<ProjectSignals>,
     is_ready: bool,
 }
 
@@ -18,10 +188,35 @@ impl TokenOptimizationService {
         Self {
             config,
             plugin_manager,
+            tokenizer: default_tokenizer(),
+            optimizer_config: OptimizerConfig::default(),
+            project_signals: None,
             is_ready: false,
         }
     }
 
+    /// Count tokens with a custom tokenizer instead of the tiktoken default
+    pub fn with_tokenizer(mut self, tokenizer: Arc<dyn Tokenizer>) -> Self {
+        self.tokenizer = tokenizer;
+        self
+    }
+
+    pub fn tokenizer(&self) -> &dyn Tokenizer {
+        self.tokenizer.as_ref()
+    }
+
+    pub fn with_optimizer_config(mut self, optimizer_config: OptimizerConfig) -> Self {
+        self.optimizer_config = optimizer_config;
+        self
+    }
+
+    /// Derive the proximity and recency signals of `optimize_tokens` from
+    /// the project instead of leaving them at zero
+    pub fn with_project_signals(mut self, project_signals: ProjectSignals) -> Self {
+        self.project_signals = Some(project_signals);
+        self
+    }
+
     pub async fn initialize(&mut self) -> Result<()> {
         self.is_ready = true;
         Ok(())
@@ -36,22 +231,277 @@ impl TokenOptimizationService {
         self.is_ready
     }
 
-    pub async fn optimize_tokens(&self, _task: &str, _files: &[String], _budget: usize) -> Result<TokenOptimization> {
+    /// Selects files, given most relevant first, until the budget is spent.
+    /// The position in `files` is used as the semantic similarity signal;
+    /// proximity and recency come from the project signals, when set.
+    pub async fn optimize_tokens(&self, task: &str, files: &[String], budget: usize) -> Result<TokenOptimization> {
+        let mut candidates: Vec<FileCandidate> = files.iter()
+            .enumerate()
+            .map(|(rank, file)| FileCandidate {
+                semantic_similarity: 1.0 - rank as f32 / files.len() as f32,
+                ..FileCandidate::new(file.clone())
+            })
+            .collect();
+        if let Some(project_signals) = &self.project_signals {
+            project_signals.apply(&mut candidates);
+        }
+
+        self.optimize_candidates(task, &candidates, budget).await
+    }
+
+    /// Ranks candidates by the weighted relevance score, then selects them
+    /// until the budget is spent. Files that don't fit or can't be read are
+    /// excluded.
+    pub async fn optimize_candidates(&self, task: &str, candidates: &[FileCandidate], budget: usize) -> Result<TokenOptimization> {
         if !self.is_ready {
             anyhow::bail!("Token Optimization service not initialized");
         }
+
+        let mut ranked: Vec<(f32, &FileCandidate)> = candidates.iter()
+            .map(|candidate| (self.optimizer_config.relevance_score(candidate), candidate))
+            .collect();
+        ranked.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+
+        let mut recommended_files = Vec::new();
+        let mut excluded_files = Vec::new();
+        let mut estimated_tokens = 0;
+        let mut total_tokens = 0;
+
+        for (relevance_score, candidate) in ranked {
+            let file = &candidate.file_path;
+            let tokens = match std::fs::read_to_string(file) {
+                Ok(content) => self.tokenizer.count_tokens(&content),
+                Err(_) => {
+                    excluded_files.push(file.clone());
+                    continue;
+                }
+            };
+            total_tokens += tokens;
+
+            if estimated_tokens + tokens > budget {
+                excluded_files.push(file.clone());
+                continue;
+            }
+
+            estimated_tokens += tokens;
+            recommended_files.push(FileRecommendation {
+                file_path: file.clone(),
+                priority: if recommended_files.is_empty() { Priority::High } else { Priority::Medium },
+                sections: Vec::new(),
+                estimated_tokens: tokens,
+                relevance_score,
+            });
+        }
+
+        let context_reduction = if total_tokens > 0 {
+            1.0 - estimated_tokens as f32 / total_tokens as f32
+        } else {
+            0.0
+        };
+
         Ok(TokenOptimization {
-            task: _task.to_string(),
-            token_budget: _budget,
-            recommended_files: Vec::new(),
-            excluded_files: Vec::new(),
+            task: task.to_string(),
+            token_budget: budget,
+            recommended_files,
+            excluded_files,
             optimization_strategy: OptimizationStrategy {
                 focus_areas: Vec::new(),
                 skip_areas: Vec::new(),
-                context_reduction: 0.0,
+                context_reduction,
                 summarization_level: SummarizationLevel::None,
             },
-            estimated_tokens: 0,
+            estimated_tokens,
         })
     }
-}
\ No newline at end of file
+
+    /// Compares dumping every readable candidate file into the context with
+    /// the optimizer's selection for `budget`
+    pub async fn estimate_savings(&self, task: &str, candidates: &[FileCandidate], budget: usize) -> Result<SavingsReport> {
+        let optimization = self.optimize_candidates(task, candidates, budget).await?;
+        let full_context_tokens = candidates.iter()
+            .filter_map(|candidate| std::fs::read_to_string(&candidate.file_path).ok())
+            .map(|content| self.tokenizer.count_tokens(&content))
+            .sum();
+
+        Ok(SavingsReport::new(task.to_string(), full_context_tokens, optimization.estimated_tokens))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+    use tempfile::TempDir;
+
+    /// One token per whitespace-separated word
+    struct WordTokenizer;
+
+    impl Tokenizer for WordTokenizer {
+        fn count_tokens(&self, text: &str) -> usize {
+            text.split_whitespace().count()
+        }
+
+        fn encoding_name(&self) -> &str {
+            "words"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_optimizer_respects_injected_tokenizer() {
+        let temp_dir = TempDir::new().unwrap();
+        let write = |name: &str, content: &str| {
+            let path = temp_dir.path().join(name);
+            std::fs::write(&path, content).unwrap();
+            path.to_string_lossy().to_string()
+        };
+        let files = vec![
+            write("a.ts", "export const alpha = 1;"),
+            write("b.ts", "function beta() { return gamma + delta; }"),
+            write("c.ts", "let eps;"),
+        ];
+
+        let mut service = TokenOptimizationService::new(MLConfig::for_testing(), Arc::new(PluginManager::new()))
+            .with_tokenizer(Arc::new(WordTokenizer));
+        service.initialize().await.unwrap();
+        assert_eq!(service.tokenizer().encoding_name(), "words");
+
+        // a = 5 words, b = 8 words, c = 2 words
+        let result = service.optimize_tokens("task", &files, 8).await.unwrap();
+        let selected: Vec<_> = result.recommended_files.iter().map(|f| f.file_path.clone()).collect();
+        assert_eq!(selected, vec![files[0].clone(), files[2].clone()]);
+        assert_eq!(result.excluded_files, vec![files[1].clone()]);
+        assert_eq!(result.recommended_files[0].estimated_tokens, 5);
+        assert_eq!(result.estimated_tokens, 7);
+        assert!(result.estimated_tokens <= result.token_budget);
+    }
+
+    #[tokio::test]
+    async fn test_recency_weight_favours_recently_changed_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let candidate = |name: &str, similarity: f32, activity: f32| {
+            let path = temp_dir.path().join(name);
+            std::fs::write(&path, "export const value = 1;").unwrap();
+            FileCandidate {
+                semantic_similarity: similarity,
+                recent_activity: activity,
+                ..FileCandidate::new(path.to_string_lossy())
+            }
+        };
+        let candidates = vec![
+            candidate("similar.ts", 0.9, 0.0),
+            candidate("recent.ts", 0.4, 1.0),
+        ];
+        let order = |result: &TokenOptimization| -> Vec<String> {
+            result.recommended_files.iter()
+                .map(|f| Path::new(&f.file_path).file_name().unwrap().to_string_lossy().to_string())
+                .collect()
+        };
+
+        let mut service = TokenOptimizationService::new(MLConfig::for_testing(), Arc::new(PluginManager::new()))
+            .with_tokenizer(Arc::new(WordTokenizer));
+        service.initialize().await.unwrap();
+        let result = service.optimize_candidates("task", &candidates, 100).await.unwrap();
+        assert_eq!(order(&result), vec!["similar.ts", "recent.ts"]);
+
+        let mut service = service.with_optimizer_config(OptimizerConfig {
+            recency_weight: 2.0,
+            ..OptimizerConfig::default()
+        });
+        service.initialize().await.unwrap();
+        let result = service.optimize_candidates("task", &candidates, 100).await.unwrap();
+        assert_eq!(order(&result), vec!["recent.ts", "similar.ts"]);
+        assert_eq!(result.recommended_files[0].priority, Priority::High);
+    }
+
+    #[cfg(feature = "typescript")]
+    #[tokio::test]
+    async fn test_optimize_tokens_derives_proximity_and_recency_from_the_project() {
+        use crate::cache::CacheManager;
+
+        let temp_dir = TempDir::new().unwrap();
+        let git = |args: &[&str]| {
+            std::process::Command::new("git")
+                .args(["-c", "user.name=Test User", "-c", "user.email=test@example.com"])
+                .args(args)
+                .current_dir(temp_dir.path())
+                .output()
+                .unwrap();
+        };
+        let write = |name: &str, content: &str| std::fs::write(temp_dir.path().join(name), content).unwrap();
+        write("main.ts", "import { near } from './near';\nexport const main = near;\n");
+        write("near.ts", "export const near = 1;\n");
+        write("far.ts", "export const far = 1;\n");
+        git(&["init"]);
+        git(&["add", "."]);
+        git(&["commit", "-m", "Initial commit"]);
+        for value in ["2", "3"] {
+            write("near.ts", &format!("export const near = {};\n", value));
+            git(&["commit", "-am", "Update near"]);
+        }
+
+        let mut cache_manager = CacheManager::new(temp_dir.path()).unwrap();
+        cache_manager.analyze_project(temp_dir.path(), false).unwrap();
+        let signals = ProjectSignals::collect(temp_dir.path(), cache_manager.get_cache());
+
+        let files: Vec<String> = ["main.ts", "far.ts", "near.ts"].iter()
+            .map(|name| temp_dir.path().join(name).to_string_lossy().to_string())
+            .collect();
+        let order = |result: &TokenOptimization| -> Vec<String> {
+            result.recommended_files.iter()
+                .map(|f| Path::new(&f.file_path).file_name().unwrap().to_string_lossy().to_string())
+                .collect()
+        };
+
+        let mut service = TokenOptimizationService::new(MLConfig::for_testing(), Arc::new(PluginManager::new()))
+            .with_tokenizer(Arc::new(WordTokenizer));
+        service.initialize().await.unwrap();
+        let result = service.optimize_tokens("task", &files, 100).await.unwrap();
+        assert_eq!(order(&result), vec!["main.ts", "far.ts", "near.ts"]);
+
+        // near.ts is imported by the top file and changed in every commit
+        let mut service = service.with_project_signals(signals);
+        service.initialize().await.unwrap();
+        let result = service.optimize_tokens("task", &files, 100).await.unwrap();
+        assert_eq!(order(&result), vec!["main.ts", "near.ts", "far.ts"]);
+
+        let mut service = service.with_optimizer_config(OptimizerConfig {
+            similarity_weight: 0.0,
+            proximity_weight: 0.0,
+            recency_weight: 1.0,
+        });
+        service.initialize().await.unwrap();
+        let result = service.optimize_tokens("task", &files, 100).await.unwrap();
+        assert_eq!(order(&result)[0], "near.ts");
+        assert!((result.recommended_files[0].relevance_score - 1.0).abs() < f32::EPSILON);
+    }
+
+    #[tokio::test]
+    async fn test_estimate_savings_is_full_minus_optimized_tokens() {
+        let temp_dir = TempDir::new().unwrap();
+        let candidate = |name: &str, content: &str, similarity: f32| {
+            let path = temp_dir.path().join(name);
+            std::fs::write(&path, content).unwrap();
+            FileCandidate {
+                semantic_similarity: similarity,
+                ..FileCandidate::new(path.to_string_lossy())
+            }
+        };
+        let candidates = vec![
+            candidate("auth.ts", "login logout refresh token", 0.9),
+            candidate("user.ts", "load save delete user profile avatar", 0.6),
+            candidate("util.ts", "pad trim", 0.1),
+            FileCandidate::new(temp_dir.path().join("missing.ts").to_string_lossy()),
+        ];
+
+        let mut service = TokenOptimizationService::new(MLConfig::for_testing(), Arc::new(PluginManager::new()))
+            .with_tokenizer(Arc::new(WordTokenizer));
+        service.initialize().await.unwrap();
+
+        // 4 + 6 + 2 words; a budget of 6 fits auth.ts and util.ts
+        let report = service.estimate_savings("fix login", &candidates, 6).await.unwrap();
+        assert_eq!(report.full_context_tokens, 12);
+        assert_eq!(report.optimized_tokens, 6);
+        assert_eq!(report.tokens_saved, report.full_context_tokens - report.optimized_tokens);
+        assert!((report.savings_percent - 50.0).abs() < f32::EPSILON);
+    }
+}
@@ -16,6 +16,7 @@ pub struct PatternDetectionService {
     plugin_manager: Arc<PluginManager>,
     is_ready: bool,
     embedding_cache: HashMap<String, Vec<f32>>,
+    grouping: Option<PatternGroupingOptions>,
 }
 
 impl PatternDetectionService {
@@ -25,9 +26,16 @@ impl PatternDetectionService {
             plugin_manager,
             is_ready: false,
             embedding_cache: HashMap::new(),
+            grouping: None,
         }
     }
 
+    /// Also roll reported patterns up into a directory tree (`PatternReport::groups`)
+    pub fn with_grouping(mut self, options: PatternGroupingOptions) -> Self {
+        self.grouping = Some(options);
+        self
+    }
+
     pub async fn initialize(&mut self) -> Result<()> {
         tracing::info!("Initializing Pattern Detection service");
         
@@ -97,7 +105,7 @@ impl PatternDetectionService {
         // Yield control to allow other tasks to run
         tokio::task::yield_now().await;
 
-        Ok(PatternReport {
+        let mut report = PatternReport {
             project_path: project_path.to_string_lossy().to_string(),
             duplicate_patterns,
             semantic_clusters,
@@ -113,7 +121,13 @@ impl PatternDetectionService {
                 similarity_threshold: 0.85,
                 analysis_timestamp: std::time::SystemTime::now(),
             },
-        })
+            groups: None,
+        };
+        if let Some(options) = &self.grouping {
+            report.groups = Some(report.group_by_directory(options));
+        }
+
+        Ok(report)
     }
 
     /// Detect duplicate code patterns using semantic similarity
@@ -624,4 +638,73 @@ impl Drop for PatternDetectionService {
             tracing::warn!("PatternDetectionService dropped without proper shutdown - possible resource leak");
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn duplicate(primary: &str, copies: &[&str], similarity_score: f32) -> EnhancedDuplicatePattern {
+        let function = |file_path: &str| DuplicateFunction {
+            function_name: "load".to_string(),
+            file_path: file_path.to_string(),
+            code_snippet: String::new(),
+        };
+        EnhancedDuplicatePattern {
+            pattern_type: ExtendedPatternType::DuplicateFunction,
+            primary_function: function(primary),
+            duplicate_functions: copies.iter().map(|f| function(f)).collect(),
+            similarity_score,
+            suggested_refactoring: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_patterns_in_same_directory_share_a_group() {
+        let report = PatternReport {
+            project_path: "/repo".to_string(),
+            duplicate_patterns: vec![
+                duplicate("/repo/src/app/users/list.ts", &["/repo/src/app/users/detail.ts"], 0.95),
+                duplicate("/repo/src/app/users/edit.ts", &["/repo/src/app/users/create.ts"], 0.9),
+                duplicate("/repo/src/app/orders/list.ts", &["/repo/src/app/users/list.ts"], 0.88),
+                duplicate("/repo/src/app/admin/a.ts", &["/repo/src/app/admin/b.ts"], 0.5),
+            ],
+            semantic_clusters: vec![SemanticCluster {
+                cluster_id: "cluster_0".to_string(),
+                cluster_type: "Data Access".to_string(),
+                functions: ["getUser", "getUsers"].iter().map(|name| ClusterFunction {
+                    function_name: name.to_string(),
+                    file_path: "/repo/src/app/users/api.ts".to_string(),
+                    function_signature: format!("{}()", name),
+                }).collect(),
+                similarity_score: 0.8,
+                suggested_refactoring: String::new(),
+            }],
+            architectural_patterns: vec![],
+            refactoring_suggestions: vec![],
+            analysis_metadata: PatternAnalysisMetadata {
+                total_functions: 0,
+                embedding_model: "lexical-similarity".to_string(),
+                similarity_threshold: 0.85,
+                analysis_timestamp: std::time::SystemTime::now(),
+            },
+            groups: None,
+        };
+
+        let options = PatternGroupingOptions { min_similarity: 0.8, ..PatternGroupingOptions::default() };
+        let root = report.group_by_directory(&options);
+        assert_eq!((root.duplicate_count, root.cluster_count), (3, 1));
+
+        let app = &root.children[0].children[0];
+        assert_eq!(app.path, "src/app");
+        // The cross-directory duplicate sits on the shared parent
+        assert_eq!(app.duplicate_patterns.len(), 1);
+        assert_eq!(app.children.len(), 1);
+
+        let users = &app.children[0];
+        assert_eq!(users.path, "src/app/users");
+        assert_eq!((users.duplicate_count, users.cluster_count), (2, 1));
+        assert_eq!(users.estimated_refactoring_hours, 4.0);
+        assert_eq!(root.estimated_refactoring_hours, 5.5);
+    }
+}
@@ -0,0 +1,182 @@
+//! Export of indexed vectors for analysis outside the tool
+//!
+//! CSV carries metadata only; Parquet (feature `parquet`) adds the embeddings
+
+use super::VectorEntry;
+use anyhow::Result;
+use std::io::Write;
+use std::path::Path;
+
+/// Columns shared by every export format, in order
+pub const EXPORT_COLUMNS: &[&str] = &[
+    "id",
+    "file_path",
+    "function_name",
+    "line_start",
+    "line_end",
+    "code_type",
+    "language",
+    "complexity",
+    "embedding_template",
+    "hash",
+    "updated_at",
+];
+
+/// File format for `VectorDatabase::export`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// Metadata columns only
+    Csv,
+    /// Metadata columns plus an `embedding` list column; needs the `parquet` feature
+    Parquet,
+}
+
+/// Writes `entries` to `path`, ordered by id. Returns the number of rows written.
+pub fn export_entries(entries: &[VectorEntry], path: &Path, format: ExportFormat) -> Result<usize> {
+    let mut entries: Vec<&VectorEntry> = entries.iter().collect();
+    entries.sort_by(|a, b| a.id.cmp(&b.id));
+
+    match format {
+        ExportFormat::Csv => write_csv(&entries, path),
+        ExportFormat::Parquet => write_parquet(&entries, path),
+    }
+}
+
+fn metadata_row(entry: &VectorEntry) -> Vec<String> {
+    let metadata = &entry.metadata;
+    vec![
+        entry.id.clone(),
+        metadata.file_path.clone(),
+        metadata.function_name.clone().unwrap_or_default(),
+        metadata.line_start.to_string(),
+        metadata.line_end.to_string(),
+        format!("{:?}", metadata.code_type),
+        metadata.language.clone(),
+        metadata.complexity.to_string(),
+        metadata.embedding_template.clone(),
+        metadata.hash.clone(),
+        entry.updated_at.to_rfc3339(),
+    ]
+}
+
+fn write_csv(entries: &[&VectorEntry], path: &Path) -> Result<usize> {
+    let mut writer = std::io::BufWriter::new(std::fs::File::create(path)?);
+    writeln!(writer, "{}", EXPORT_COLUMNS.join(","))?;
+    for entry in entries {
+        let row: Vec<String> = metadata_row(entry).iter().map(|field| csv_field(field)).collect();
+        writeln!(writer, "{}", row.join(","))?;
+    }
+    writer.flush()?;
+    Ok(entries.len())
+}
+
+/// Quotes fields containing separators, quotes or line breaks (RFC 4180)
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(not(feature = "parquet"))]
+fn write_parquet(_entries: &[&VectorEntry], _path: &Path) -> Result<usize> {
+    anyhow::bail!("Parquet export requires building with `--features parquet`")
+}
+
+#[cfg(feature = "parquet")]
+fn write_parquet(entries: &[&VectorEntry], path: &Path) -> Result<usize> {
+    use arrow_array::{types::Float32Type, Array, ArrayRef, ListArray, RecordBatch, StringArray};
+    use arrow_schema::{DataType, Field, Schema};
+    use parquet::arrow::ArrowWriter;
+    use std::sync::Arc;
+
+    let rows: Vec<Vec<String>> = entries.iter().map(|entry| metadata_row(entry)).collect();
+    let mut fields: Vec<Field> = EXPORT_COLUMNS.iter()
+        .map(|name| Field::new(*name, DataType::Utf8, false))
+        .collect();
+    let mut columns: Vec<ArrayRef> = (0..EXPORT_COLUMNS.len())
+        .map(|column| Arc::new(StringArray::from_iter_values(rows.iter().map(|row| row[column].as_str()))) as ArrayRef)
+        .collect();
+
+    let embeddings = ListArray::from_iter_primitive::<Float32Type, _, _>(
+        entries.iter().map(|entry| Some(entry.embedding.iter().copied().map(Some).collect::<Vec<_>>())),
+    );
+    fields.push(Field::new("embedding", embeddings.data_type().clone(), false));
+    columns.push(Arc::new(embeddings));
+
+    let batch = RecordBatch::try_new(Arc::new(Schema::new(fields)), columns)?;
+    let mut writer = ArrowWriter::try_new(std::fs::File::create(path)?, batch.schema(), None)?;
+    writer.write(&batch)?;
+    writer.close()?;
+    Ok(entries.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ml::vector_db::{CodeMetadata, CodeType, NativeVectorStore, VectorDBConfig, VectorDatabase};
+
+    fn entry(id: &str, function_name: &str) -> VectorEntry {
+        VectorEntry {
+            id: id.to_string(),
+            embedding: vec![0.5; 768],
+            metadata: CodeMetadata {
+                file_path: "src/auth.rs".to_string(),
+                function_name: Some(function_name.to_string()),
+                line_start: 3,
+                line_end: 9,
+                code_type: CodeType::Function,
+                language: "rust".to_string(),
+                complexity: 1.5,
+                tokens: vec![],
+                calls: Vec::new(),
+                hash: "abc".to_string(),
+                embedding_template: "enriched".to_string(),
+            },
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_csv_export_has_a_row_per_entry() -> Result<()> {
+        let mut store = NativeVectorStore::new(VectorDBConfig::for_testing());
+        store.add_vector(entry("src/auth.rs:3:9", "login"))?;
+        store.add_vector(entry("src/auth.rs:12:20", "parse, \"quoted\" name"))?;
+
+        let temp_dir = tempfile::TempDir::new()?;
+        let path = temp_dir.path().join("vectors.csv");
+        assert_eq!(store.export(&path, ExportFormat::Csv)?, 2);
+
+        let csv = std::fs::read_to_string(&path)?;
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], EXPORT_COLUMNS.join(","));
+        assert!(lines[1].starts_with("src/auth.rs:12:20,src/auth.rs,\"parse, \"\"quoted\"\" name\",3,9,Function,rust,1.5,enriched,abc,"));
+        assert!(lines[2].starts_with("src/auth.rs:3:9,src/auth.rs,login,3,9,Function,rust,1.5,enriched,abc,"));
+        Ok(())
+    }
+
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn test_parquet_export_includes_embeddings() -> Result<()> {
+        use parquet::file::reader::{FileReader, SerializedFileReader};
+
+        let mut store = NativeVectorStore::new(VectorDBConfig::for_testing());
+        store.add_vector(entry("src/auth.rs:3:9", "login"))?;
+
+        let temp_dir = tempfile::TempDir::new()?;
+        let path = temp_dir.path().join("vectors.parquet");
+        assert_eq!(store.export(&path, ExportFormat::Parquet)?, 1);
+
+        let reader = SerializedFileReader::new(std::fs::File::open(&path)?)?;
+        let metadata = reader.metadata();
+        assert_eq!(metadata.file_metadata().num_rows(), 1);
+        let columns: Vec<String> = metadata.file_metadata().schema_descr().columns().iter()
+            .map(|column| column.path().parts()[0].clone())
+            .collect();
+        assert!(columns.contains(&"embedding".to_string()));
+        Ok(())
+    }
+}
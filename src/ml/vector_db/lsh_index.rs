@@ -7,6 +7,7 @@ use bit_vec::BitVec;
 use fnv::FnvHashMap;
 use rand::prelude::*;
 use serde::{Deserialize, Serialize};
+use crate::utils::decoration::status;
 
 /// LSH Index for fast similarity search
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -122,21 +123,21 @@ impl LSHIndex {
         // Search in each hash table
         for (table_idx, hash_table) in self.hash_tables.iter().enumerate() {
             let hash_value = self.compute_hash(query, table_idx);
-            println!("🔍 Table {}: computed hash = {}, table has {} buckets", 
+            status!("🔍 Table {}: computed hash = {}, table has {} buckets", 
                      table_idx, hash_value, hash_table.len());
             
             if let Some(ids) = hash_table.get(&hash_value) {
-                println!("🔍 Table {}: found bucket with {} IDs", table_idx, ids.len());
+                status!("🔍 Table {}: found bucket with {} IDs", table_idx, ids.len());
                 for id in ids {
                     candidates.insert(id.clone());
                 }
             } else {
-                println!("🔍 Table {}: no bucket found for hash {}", table_idx, hash_value);
+                status!("🔍 Table {}: no bucket found for hash {}", table_idx, hash_value);
                 
                 // Debug: Show what hashes actually exist in this table
                 if hash_table.len() > 0 {
                     let existing_hashes: Vec<u64> = hash_table.keys().take(3).copied().collect();
-                    println!("🔍 Table {}: existing hashes (sample): {:?}", table_idx, existing_hashes);
+                    status!("🔍 Table {}: existing hashes (sample): {:?}", table_idx, existing_hashes);
                 }
             }
         }
@@ -218,6 +219,48 @@ impl LSHIndex {
         }
     }
     
+    /// Rebuilds every hash table from `live` vectors only, dropping ids of
+    /// vectors that no longer exist and the slack left in buckets by removals.
+    /// Returns the number of stale ids dropped.
+    pub fn compact<'a>(&mut self, live: impl IntoIterator<Item = (&'a String, &'a [f32])>) -> Result<usize> {
+        let before: std::collections::HashSet<String> = self.hash_tables.iter()
+            .flat_map(|table| table.values().flatten().cloned())
+            .collect();
+        
+        for hash_table in &mut self.hash_tables {
+            *hash_table = FnvHashMap::default();
+        }
+        let mut live_count = 0;
+        for (id, vector) in live {
+            self.add(id.clone(), vector)?;
+            live_count += 1;
+        }
+        for hash_table in &mut self.hash_tables {
+            hash_table.shrink_to_fit();
+            for bucket in hash_table.values_mut() {
+                bucket.shrink_to_fit();
+            }
+        }
+        
+        Ok(before.len().saturating_sub(live_count))
+    }
+    
+    /// Approximate heap bytes held by the hash tables, including spare capacity
+    pub fn estimated_bytes(&self) -> usize {
+        let slot = std::mem::size_of::<u64>() + std::mem::size_of::<Vec<String>>();
+        self.hash_tables.iter()
+            .map(|table| {
+                table.capacity() * slot
+                    + table.values()
+                        .map(|bucket| {
+                            bucket.capacity() * std::mem::size_of::<String>()
+                                + bucket.iter().map(|id| id.capacity()).sum::<usize>()
+                        })
+                        .sum::<usize>()
+            })
+            .sum()
+    }
+    
     /// Clear the index
     pub fn clear(&mut self) {
         for hash_table in &mut self.hash_tables {
@@ -7,14 +7,17 @@ pub mod vector_store;
 pub mod similarity;
 pub mod persistence;
 pub mod semantic_search;
+pub mod export;
 
 pub use vector_store::*;
 pub use similarity::*;
 pub use semantic_search::*;
+pub use export::ExportFormat;
 
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use crate::utils::path_normalizer::namespace_path;
 
 /// Vector database configuration
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -29,8 +32,12 @@ pub struct VectorDBConfig {
     pub max_results: usize,
     /// Enable persistence to disk
     pub enable_persistence: bool,
-    /// Cache directory for vector index
+    /// Cache directory for vector index (see `utils::vector_db_cache_dir`)
     pub cache_dir: String,
+    /// Seed for the LSH hash planes; `None` uses the `LSHConfig` default so
+    /// indexes built on different machines stay comparable
+    #[serde(default)]
+    pub random_seed: Option<u64>,
 }
 
 impl Default for VectorDBConfig {
@@ -41,7 +48,30 @@ impl Default for VectorDBConfig {
             similarity_threshold: 0.7,
             max_results: 50,
             enable_persistence: true,
-            cache_dir: ".cache/vector-db".to_string(),
+            cache_dir: crate::utils::vector_db_cache_dir(std::path::Path::new("."))
+                .to_string_lossy()
+                .to_string(),
+            random_seed: None,
+        }
+    }
+}
+
+impl VectorDBConfig {
+    /// Config persisting to the cache directory of `project_path`; `default()`
+    /// uses the one of the working directory
+    pub fn for_project(project_path: &std::path::Path) -> Self {
+        Self {
+            cache_dir: crate::utils::vector_db_cache_dir(project_path).to_string_lossy().to_string(),
+            ..Self::default()
+        }
+    }
+
+    /// In-memory config with a fixed seed for reproducible tests
+    pub fn for_testing() -> Self {
+        Self {
+            enable_persistence: false,
+            random_seed: Some(42),
+            ..Self::default()
         }
     }
 }
@@ -57,7 +87,19 @@ pub struct CodeMetadata {
     pub language: String,
     pub complexity: f32,
     pub tokens: Vec<String>,
+    /// Callees in the entry's content, like `parse` or `Doc::new`, recorded
+    /// at index time for usage searches
+    #[serde(default)]
+    pub calls: Vec<String>,
     pub hash: String,
+    /// `EmbeddingTemplate::id` of the template the entry was embedded with
+    #[serde(default = "default_embedding_template")]
+    pub embedding_template: String,
+}
+
+/// Entries indexed before templates were recorded used the enriched format
+fn default_embedding_template() -> String {
+    crate::ml::config::EmbeddingTemplate::default().id().to_string()
 }
 
 /// Types of code snippets
@@ -133,6 +175,60 @@ pub trait VectorDatabase: Send + Sync {
     
     /// Clear all data
     fn clear(&mut self) -> Result<()>;
+    
+    /// Rebuild the search index from the live vectors, dropping stale ids and
+    /// space left behind by deletes and updates
+    fn compact(&mut self) -> Result<CompactionReport>;
+    
+    /// Export every vector to `path` (CSV: metadata only; Parquet: with
+    /// embeddings). Returns the number of rows written.
+    fn export(&self, path: &std::path::Path, format: ExportFormat) -> Result<usize> {
+        export::export_entries(&self.get_all_vectors()?, path, format)
+    }
+    
+    /// Merge all vectors from another database, namespacing ids and file paths
+    /// under `prefix` so entries from different repositories never collide
+    fn merge(&mut self, other: &dyn VectorDatabase, prefix: &str) -> Result<usize> {
+        let prefix = prefix.trim_matches('/');
+        if prefix.is_empty() {
+            anyhow::bail!("Merge prefix must not be empty");
+        }
+        
+        let entries: Vec<VectorEntry> = other.get_all_vectors()?
+            .into_iter()
+            .map(|mut entry| {
+                entry.metadata.file_path = namespace_path(prefix, &entry.metadata.file_path);
+                entry.id = namespace_path(prefix, &entry.id);
+                entry
+            })
+            .collect();
+        
+        let merged = entries.len();
+        self.add_vectors(entries)?;
+        Ok(merged)
+    }
+}
+
+/// Order of ranked search results: higher score first; equal scores by file
+/// path, then `line_start`, then id, so ties come back in the same order on
+/// every run
+pub fn rank_order(a_score: f32, a: &VectorEntry, b_score: f32, b: &VectorEntry) -> std::cmp::Ordering {
+    b_score.partial_cmp(&a_score)
+        .unwrap_or(std::cmp::Ordering::Equal)
+        .then_with(|| a.metadata.file_path.cmp(&b.metadata.file_path))
+        .then_with(|| a.metadata.line_start.cmp(&b.metadata.line_start))
+        .then_with(|| a.id.cmp(&b.id))
+}
+
+/// Result of `VectorDatabase::compact`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CompactionReport {
+    /// Estimated index size before and after compaction
+    pub bytes_before: usize,
+    pub bytes_after: usize,
+    pub bytes_reclaimed: usize,
+    /// Ids that were still indexed although their vector was gone
+    pub stale_ids_removed: usize,
 }
 
 /// Database statistics
@@ -144,6 +240,9 @@ pub struct VectorDBStats {
     pub average_similarity: f32,
     pub by_language: HashMap<String, usize>,
     pub by_code_type: HashMap<String, usize>,
+    /// Entries per embedding template; more than one key means a mixed index
+    #[serde(default)]
+    pub by_embedding_template: HashMap<String, usize>,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub last_updated: chrono::DateTime<chrono::Utc>,
 }
\ No newline at end of file
@@ -4,9 +4,164 @@
 
 use super::*;
 use anyhow::Result;
+use serde::de::DeserializeOwned;
 use std::fs::File;
 use std::io::{BufReader, BufWriter, Write};
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use crate::cache::FreshnessMode;
+use crate::utils::{calculate_content_hash, calculate_file_hash};
+
+/// Version of the snapshot layout written by `write_snapshot`
+pub const SNAPSHOT_FORMAT_VERSION: u32 = 1;
+
+/// First line of every snapshot file; the JSON payload follows on the next line
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct SnapshotHeader {
+    format_version: u32,
+    checksum: String,
+}
+
+/// Writes `payload` behind a version/checksum header. The data goes to a temp
+/// file that is renamed into place, so a crash mid-save never leaves a partial file.
+pub fn write_snapshot<T: Serialize>(path: &Path, payload: &T) -> Result<()> {
+    let body = serde_json::to_string_pretty(payload)?;
+    let header = SnapshotHeader {
+        format_version: SNAPSHOT_FORMAT_VERSION,
+        checksum: calculate_content_hash(body.as_bytes()),
+    };
+
+    let tmp_path = path.with_extension("tmp");
+    {
+        let mut file = File::create(&tmp_path)?;
+        writeln!(file, "{}", serde_json::to_string(&header)?)?;
+        file.write_all(body.as_bytes())?;
+        file.sync_all()?;
+    }
+    std::fs::rename(&tmp_path, path)?;
+
+    Ok(())
+}
+
+/// Reads a file written by `write_snapshot`, rejecting unknown versions,
+/// checksum mismatches and truncated or otherwise malformed content.
+pub fn read_snapshot<T: DeserializeOwned>(path: &Path) -> Result<T> {
+    let content = std::fs::read_to_string(path)?;
+    let (header_line, body) = content
+        .split_once('\n')
+        .ok_or_else(|| anyhow::anyhow!("{} has no snapshot header", path.display()))?;
+
+    let header: SnapshotHeader = serde_json::from_str(header_line)
+        .map_err(|e| anyhow::anyhow!("{} has an invalid snapshot header: {}", path.display(), e))?;
+
+    if header.format_version != SNAPSHOT_FORMAT_VERSION {
+        anyhow::bail!(
+            "{} has snapshot format version {}, expected {}",
+            path.display(), header.format_version, SNAPSHOT_FORMAT_VERSION
+        );
+    }
+
+    if calculate_content_hash(body.as_bytes()) != header.checksum {
+        anyhow::bail!("{} failed checksum validation", path.display());
+    }
+
+    Ok(serde_json::from_str(body)?)
+}
+
+/// File next to the vector index holding its `SourceHashes`
+pub const SOURCE_HASHES_FILE: &str = "source_hashes.json";
+
+/// Hash and modification time of one indexed source file
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SourceFile {
+    pub hash: String,
+    pub modified: SystemTime,
+}
+
+/// The source files an index was built from, keyed by project-relative path.
+/// Kept with the index so its freshness never depends on another cache.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SourceHashes {
+    pub files: BTreeMap<String, SourceFile>,
+    /// Whether test code was indexed; an index without it can't serve
+    /// searches that include tests
+    #[serde(default)]
+    pub include_tests: bool,
+}
+
+impl SourceHashes {
+    /// Loads the hashes saved in `cache_dir`, `None` when there are none
+    pub fn load(cache_dir: &Path) -> Result<Option<Self>> {
+        let path = cache_dir.join(SOURCE_HASHES_FILE);
+        if !path.exists() {
+            return Ok(None);
+        }
+        read_snapshot(&path).map(Some)
+    }
+
+    pub fn save(&self, cache_dir: &Path) -> Result<()> {
+        std::fs::create_dir_all(cache_dir)?;
+        write_snapshot(&cache_dir.join(SOURCE_HASHES_FILE), self)
+    }
+
+    /// Records the current state of `files`, relative to `project_path`,
+    /// replacing what was stored for them
+    pub fn record(&mut self, project_path: &Path, files: &[String]) -> Result<()> {
+        for file in files {
+            let path = project_path.join(file);
+            let source = SourceFile {
+                hash: calculate_file_hash(&path)?,
+                modified: std::fs::metadata(&path)?.modified()?,
+            };
+            self.files.insert(file.clone(), source);
+        }
+        Ok(())
+    }
+
+    /// Whether the index still matches `files`: the same set of paths, none
+    /// of them changed since indexing as judged by `mode`
+    pub fn is_fresh(&self, project_path: &Path, files: &[String], mode: FreshnessMode) -> Result<bool> {
+        if files.len() != self.files.len() || files.iter().any(|file| !self.files.contains_key(file)) {
+            return Ok(false);
+        }
+
+        let mode = mode.resolve();
+        let mut indexed: Vec<(&String, &SourceFile)> = self.files.iter().collect();
+        if let FreshnessMode::SampledHash(sample) = mode {
+            let rotation = SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_secs() as usize);
+            indexed = sample_evenly(indexed, sample, rotation);
+        }
+
+        for (file, source) in indexed {
+            let path = project_path.join(file);
+            if !path.is_file() {
+                return Ok(false);
+            }
+            let unchanged = match mode {
+                FreshnessMode::Auto | FreshnessMode::Mtime => std::fs::metadata(&path)?.modified()? == source.modified,
+                FreshnessMode::Hash | FreshnessMode::SampledHash(_) => calculate_file_hash(&path)? == source.hash,
+            };
+            if !unchanged {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
+
+/// `sample` items spread evenly over `items`, starting `rotation` items into
+/// the first stride so successive rotations visit every item. All of them when
+/// `sample` is zero or covers them already.
+fn sample_evenly<T>(items: Vec<T>, sample: usize, rotation: usize) -> Vec<T> {
+    if sample == 0 || sample >= items.len() {
+        return items;
+    }
+    let step = items.len() / sample;
+    items.into_iter().skip(rotation % step).step_by(step).take(sample).collect()
+}
 
 /// Persistence manager for vector databases
 pub struct VectorDBPersistence {
@@ -378,7 +533,9 @@ mod tests {
                 language: "typescript".to_string(),
                 complexity: 1.0,
                 tokens: vec!["test".to_string()],
+                calls: Vec::new(),
                 hash: "hash123".to_string(),
+                embedding_template: "enriched".to_string(),
             },
             created_at: chrono::Utc::now(),
             updated_at: chrono::Utc::now(),
@@ -399,6 +556,78 @@ mod tests {
         assert_eq!(loaded_entry.unwrap().id, "test1");
     }
     
+    #[test]
+    fn test_snapshot_round_trip_and_validation() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("snapshot.json");
+        let payload: HashMap<String, Vec<String>> =
+            HashMap::from([("a.ts".to_string(), vec!["a.ts:1:10".to_string()])]);
+        
+        write_snapshot(&path, &payload).unwrap();
+        assert!(!temp_dir.path().join("snapshot.tmp").exists());
+        let loaded: HashMap<String, Vec<String>> = read_snapshot(&path).unwrap();
+        assert_eq!(loaded, payload);
+        
+        // Tampered payload fails the checksum
+        let content = std::fs::read_to_string(&path).unwrap();
+        std::fs::write(&path, content.replace("a.ts:1:10", "a.ts:1:99")).unwrap();
+        assert!(read_snapshot::<HashMap<String, Vec<String>>>(&path).is_err());
+        
+        // Unknown format version is rejected
+        let (_, body) = content.split_once('\n').unwrap();
+        let header = format!(r#"{{"format_version":99,"checksum":"{}"}}"#, calculate_content_hash(body.as_bytes()));
+        std::fs::write(&path, format!("{}\n{}", header, body)).unwrap();
+        assert!(read_snapshot::<HashMap<String, Vec<String>>>(&path).is_err());
+        
+        // Legacy files without a header are rejected
+        std::fs::write(&path, "{}").unwrap();
+        assert!(read_snapshot::<HashMap<String, Vec<String>>>(&path).is_err());
+    }
+    
+    #[test]
+    fn test_touched_file_is_fresh_by_hash_but_stale_by_mtime() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let project = temp_dir.path();
+        std::fs::create_dir_all(project.join("src"))?;
+        std::fs::write(project.join("src/lib.rs"), "pub fn version() -> u32 { 1 }\n")?;
+        std::fs::write(project.join("src/util.rs"), "pub fn twice(n: u32) -> u32 { n * 2 }\n")?;
+        let files = vec!["src/lib.rs".to_string(), "src/util.rs".to_string()];
+
+        let cache_dir = project.join("cache");
+        let mut hashes = SourceHashes::default();
+        hashes.record(project, &files)?;
+        hashes.save(&cache_dir)?;
+        let hashes = SourceHashes::load(&cache_dir)?.expect("hashes were saved");
+
+        // Same content, newer mtime, as after a fresh checkout
+        let touched = File::options().write(true).open(project.join("src/lib.rs"))?;
+        touched.set_modified(SystemTime::now() + std::time::Duration::from_secs(60))?;
+
+        assert!(!hashes.is_fresh(project, &files, FreshnessMode::Mtime)?);
+        assert!(hashes.is_fresh(project, &files, FreshnessMode::Hash)?);
+        assert!(hashes.is_fresh(project, &files, FreshnessMode::SampledHash(1))?);
+
+        // A file the index never saw makes it stale in every mode
+        let added = [files.clone(), vec!["src/new.rs".to_string()]].concat();
+        assert!(!hashes.is_fresh(project, &added, FreshnessMode::Hash)?);
+
+        std::fs::write(project.join("src/lib.rs"), "pub fn version() -> u32 { 2 }\n")?;
+        assert!(!hashes.is_fresh(project, &files, FreshnessMode::Hash)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_sampled_files_rotate_over_the_index() {
+        let files: Vec<usize> = (0..10).collect();
+        assert_eq!(sample_evenly(files.clone(), 3, 0), vec![0, 3, 6]);
+        assert_eq!(sample_evenly(files.clone(), 3, 2), vec![2, 5, 8]);
+        assert_eq!(sample_evenly(files.clone(), 0, 1), files);
+
+        let mut seen: Vec<usize> = (0..5).flat_map(|rotation| sample_evenly(files.clone(), 2, rotation)).collect();
+        seen.sort();
+        assert_eq!(seen, files);
+    }
+
     #[test]
     fn test_backup_operations() {
         let temp_dir = TempDir::new().unwrap();
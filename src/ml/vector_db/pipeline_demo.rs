@@ -50,6 +50,7 @@ impl PipelineDemo {
             rerank_threshold: 0.5,  // Moderate threshold for balance
             enable_caching: true,
             embedding_cache_size: 100,
+            ..Default::default()
         };
         
         let pipeline = SemanticSearchFactory::create_with_config(
@@ -143,7 +144,9 @@ impl PipelineDemo {
                 language: sample.language.clone(),
                 complexity: 1.0 + (i as f32 * 0.5),
                 tokens: sample.content.split_whitespace().take(10).map(|s| s.to_string()).collect(),
+                calls: Vec::new(),
                 hash: format!("hash_{}", i),
+                embedding_template: "enriched".to_string(),
             };
             
             let entry = VectorEntry {
@@ -3,23 +3,212 @@
  */
 
 use super::*;
-use crate::ml::plugins::{QwenEmbeddingPlugin, QwenRerankerPlugin};
+use crate::ml::config::{DEFAULT_RERANKER_BATCH_SIZE, DEFAULT_RERANKER_MAX_CANDIDATE_TOKENS};
+use crate::ml::plugins::{PluginManager, QwenEmbeddingPlugin, QwenRerankerPlugin};
 use crate::ml::vector_db::{VectorDatabase, SearchResult};
 use anyhow::Result;
+use async_trait::async_trait;
 use parking_lot::RwLock;
 use std::sync::Arc;
 use tracing::{debug, info, warn};
+use crate::utils::decoration::status;
+use crate::utils::{default_tokenizer, Tokenizer};
+
+/// Embedding model used by the pipeline to vectorize queries
+#[async_trait]
+pub trait EmbeddingBackend: Send + Sync {
+    async fn embed_texts(&self, texts: &[String]) -> Result<Vec<Vec<f32>>>;
+    /// (cached entries, cache size in bytes)
+    fn get_cache_stats(&self) -> (usize, usize);
+}
+
+/// Reranker used by the pipeline to score candidate documents
+#[async_trait]
+pub trait RerankerBackend: Send + Sync {
+    /// Returns (document index, score) pairs
+    async fn rank_documents(&self, query: &str, documents: &[String]) -> Result<Vec<(usize, f32)>>;
+    /// (cached entries, cache size in bytes)
+    fn get_cache_stats(&self) -> (usize, usize);
+}
+
+#[async_trait]
+impl EmbeddingBackend for QwenEmbeddingPlugin {
+    async fn embed_texts(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        QwenEmbeddingPlugin::embed_texts(self, texts).await
+    }
+
+    fn get_cache_stats(&self) -> (usize, usize) {
+        QwenEmbeddingPlugin::get_cache_stats(self)
+    }
+}
+
+#[async_trait]
+impl RerankerBackend for QwenRerankerPlugin {
+    async fn rank_documents(&self, query: &str, documents: &[String]) -> Result<Vec<(usize, f32)>> {
+        QwenRerankerPlugin::rank_documents(self, query, documents).await
+    }
+
+    fn get_cache_stats(&self) -> (usize, usize) {
+        QwenRerankerPlugin::get_cache_stats(self)
+    }
+}
+
+/// Model-free embedding for tests and benchmarks: hashes lowercase word
+/// tokens into a fixed number of buckets and L2-normalizes the result
+pub struct HashingEmbeddingBackend {
+    dimension: usize,
+}
+
+impl HashingEmbeddingBackend {
+    pub fn new(dimension: usize) -> Self {
+        Self { dimension }
+    }
+
+    pub fn embed(&self, text: &str) -> Vec<f32> {
+        let mut embedding = vec![0.0; self.dimension];
+        for token in text.split(|c: char| !c.is_alphanumeric()).filter(|t| !t.is_empty()) {
+            let hash = token
+                .to_lowercase()
+                .bytes()
+                .fold(0xcbf29ce484222325u64, |h, b| (h ^ b as u64).wrapping_mul(0x100000001b3));
+            embedding[(hash % self.dimension as u64) as usize] += 1.0;
+        }
+        super::similarity::VectorNorm::l2_normalize(&mut embedding);
+        embedding
+    }
+}
+
+#[async_trait]
+impl EmbeddingBackend for HashingEmbeddingBackend {
+    async fn embed_texts(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        Ok(texts.iter().map(|text| self.embed(text)).collect())
+    }
+
+    fn get_cache_stats(&self) -> (usize, usize) {
+        (0, 0)
+    }
+}
+
+/// Model-free reranker for tests and benchmarks: fraction of query words
+/// that occur in the document
+pub struct OverlapRerankerBackend;
+
+#[async_trait]
+impl RerankerBackend for OverlapRerankerBackend {
+    async fn rank_documents(&self, query: &str, documents: &[String]) -> Result<Vec<(usize, f32)>> {
+        let words: Vec<String> = query.split_whitespace().map(|w| w.to_lowercase()).collect();
+        Ok(documents
+            .iter()
+            .enumerate()
+            .map(|(idx, document)| {
+                let document = document.to_lowercase();
+                let hits = words.iter().filter(|w| document.contains(w.as_str())).count();
+                (idx, hits as f32 / words.len().max(1) as f32)
+            })
+            .collect())
+    }
+
+    fn get_cache_stats(&self) -> (usize, usize) {
+        (0, 0)
+    }
+}
+
+/// Embeddings from the plugin registered as `plugin_name` in a
+/// `PluginManager`, e.g. a mock set up with `initialize_with_mocks`
+pub struct PluginEmbeddingBackend {
+    plugin_manager: Arc<PluginManager>,
+    plugin_name: String,
+}
+
+impl PluginEmbeddingBackend {
+    pub fn new(plugin_manager: Arc<PluginManager>, plugin_name: &str) -> Self {
+        Self { plugin_manager, plugin_name: plugin_name.to_string() }
+    }
+}
+
+#[async_trait]
+impl EmbeddingBackend for PluginEmbeddingBackend {
+    async fn embed_texts(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        // `process_with_plugin` holds the plugin lock across its await, so
+        // it runs on a blocking thread like the pipeline's reranker calls
+        let (plugin_manager, plugin_name) = (Arc::clone(&self.plugin_manager), self.plugin_name.clone());
+        let texts = texts.to_vec();
+        tokio::task::spawn_blocking(move || {
+            tokio::runtime::Handle::current().block_on(async move {
+                let mut embeddings = Vec::with_capacity(texts.len());
+                for text in &texts {
+                    let response = plugin_manager.process_with_plugin(&plugin_name, text).await?;
+                    let parsed: serde_json::Value = serde_json::from_str(&response)?;
+                    let embedding = parsed["embedding"]
+                        .as_array()
+                        .ok_or_else(|| anyhow::anyhow!("Plugin {} returned no embedding", plugin_name))?
+                        .iter()
+                        .map(|value| value.as_f64().unwrap_or(0.0) as f32)
+                        .collect();
+                    embeddings.push(embedding);
+                }
+                Ok(embeddings)
+            })
+        }).await?
+    }
+
+    fn get_cache_stats(&self) -> (usize, usize) {
+        (0, 0)
+    }
+}
+
+/// Rerank scores from the plugin registered as `plugin_name` in a
+/// `PluginManager`, sent in the plugin's `query||document` input format
+pub struct PluginRerankerBackend {
+    plugin_manager: Arc<PluginManager>,
+    plugin_name: String,
+}
+
+impl PluginRerankerBackend {
+    pub fn new(plugin_manager: Arc<PluginManager>, plugin_name: &str) -> Self {
+        Self { plugin_manager, plugin_name: plugin_name.to_string() }
+    }
+}
+
+#[async_trait]
+impl RerankerBackend for PluginRerankerBackend {
+    async fn rank_documents(&self, query: &str, documents: &[String]) -> Result<Vec<(usize, f32)>> {
+        // See `PluginEmbeddingBackend::embed_texts`
+        let (plugin_manager, plugin_name) = (Arc::clone(&self.plugin_manager), self.plugin_name.clone());
+        let inputs: Vec<String> = documents.iter().map(|document| format!("{}||{}", query, document)).collect();
+        tokio::task::spawn_blocking(move || {
+            tokio::runtime::Handle::current().block_on(async move {
+                let mut scores = Vec::with_capacity(inputs.len());
+                for (idx, input) in inputs.iter().enumerate() {
+                    let response = plugin_manager.process_with_plugin(&plugin_name, input).await?;
+                    let parsed: serde_json::Value = serde_json::from_str(&response)?;
+                    let score = parsed["relevance_score"]
+                        .as_f64()
+                        .ok_or_else(|| anyhow::anyhow!("Plugin {} returned no relevance score", plugin_name))?;
+                    scores.push((idx, score as f32));
+                }
+                Ok(scores)
+            })
+        }).await?
+    }
+
+    fn get_cache_stats(&self) -> (usize, usize) {
+        (0, 0)
+    }
+}
 
 /// Semantic search pipeline combining multiple ML techniques
 pub struct SemanticSearchPipeline {
     /// Vector database for LSH-based fast search
     vector_db: Arc<RwLock<dyn VectorDatabase>>,
     /// Embedding model for query vectorization
-    embedding_plugin: Arc<RwLock<QwenEmbeddingPlugin>>,
+    embedding_plugin: Arc<RwLock<dyn EmbeddingBackend>>,
     /// Reranker for result refinement
-    reranker_plugin: Arc<RwLock<QwenRerankerPlugin>>,
+    reranker_plugin: Arc<RwLock<dyn RerankerBackend>>,
     /// Pipeline configuration
     config: SemanticSearchConfig,
+    /// Counts candidate tokens for `max_candidate_tokens`
+    tokenizer: Arc<dyn Tokenizer>,
 }
 
 /// Configuration for semantic search pipeline
@@ -37,6 +226,10 @@ pub struct SemanticSearchConfig {
     pub enable_caching: bool,
     /// Cache size for embeddings
     pub embedding_cache_size: usize,
+    /// Number of candidates sent to the reranker per call
+    pub rerank_batch_size: usize,
+    /// Candidates are truncated to this many tokens before reranking
+    pub max_candidate_tokens: usize,
 }
 
 impl Default for SemanticSearchConfig {
@@ -48,10 +241,58 @@ impl Default for SemanticSearchConfig {
             rerank_threshold: 0.001, // Ultra-low threshold for debugging
             enable_caching: true,
             embedding_cache_size: 1000,
+            rerank_batch_size: DEFAULT_RERANKER_BATCH_SIZE,
+            max_candidate_tokens: DEFAULT_RERANKER_MAX_CANDIDATE_TOKENS,
         }
     }
 }
 
+/// Shortens a reranker document to at most `max_tokens` tokens as counted by
+/// `tokenizer`. Lines are kept in order, so the leading signature lines
+/// survive and the body is cut from the end, mid-line at a word boundary if
+/// need be; documents within the limit are unchanged.
+pub fn truncate_candidate(document: &str, max_tokens: usize, tokenizer: &dyn Tokenizer) -> String {
+    if tokenizer.count_tokens(document) <= max_tokens {
+        return document.to_string();
+    }
+
+    let mut kept = String::new();
+    for (index, line) in document.lines().enumerate() {
+        let separator = if index == 0 { "" } else { "\n" };
+        let extended = format!("{}{}{}", kept, separator, line);
+        if tokenizer.count_tokens(&extended) <= max_tokens {
+            kept = extended;
+            continue;
+        }
+
+        // Longest run of leading words that still fits
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let fitting = (1..=words.len())
+            .take_while(|&count| {
+                tokenizer.count_tokens(&format!("{}{}{}", kept, separator, words[..count].join(" "))) <= max_tokens
+            })
+            .last()
+            .unwrap_or(0);
+        if fitting > 0 {
+            kept = format!("{}{}{}", kept, separator, words[..fitting].join(" "));
+        }
+        break;
+    }
+    kept
+}
+
+/// Embeds `texts` with `backend` on a blocking thread, so the backend lock is
+/// never held across an await of the calling task
+pub async fn embed_batch(backend: Arc<RwLock<dyn EmbeddingBackend>>, texts: Vec<String>) -> Result<Vec<Vec<f32>>> {
+    tokio::task::spawn_blocking(move || {
+        let rt = tokio::runtime::Handle::current();
+        rt.block_on(async move {
+            let plugin = backend.read();
+            plugin.embed_texts(&texts).await
+        })
+    }).await?
+}
+
 /// Enhanced search result with reranking score
 #[derive(Clone, Debug)]
 pub struct EnhancedSearchResult {
@@ -60,6 +301,9 @@ pub struct EnhancedSearchResult {
     pub rerank_score: f32,
     pub combined_score: f32,
     pub confidence: f32,
+    /// Further matches from the same file collapsed into this result
+    /// when results are grouped by file
+    pub additional_matches: usize,
 }
 
 /// Search query with metadata
@@ -76,8 +320,8 @@ impl SemanticSearchPipeline {
     /// Create new semantic search pipeline
     pub fn new(
         vector_db: Arc<RwLock<dyn VectorDatabase>>,
-        embedding_plugin: Arc<RwLock<QwenEmbeddingPlugin>>,
-        reranker_plugin: Arc<RwLock<QwenRerankerPlugin>>,
+        embedding_plugin: Arc<RwLock<dyn EmbeddingBackend>>,
+        reranker_plugin: Arc<RwLock<dyn RerankerBackend>>,
         config: SemanticSearchConfig,
     ) -> Self {
         Self {
@@ -85,9 +329,16 @@ impl SemanticSearchPipeline {
             embedding_plugin,
             reranker_plugin,
             config,
+            tokenizer: default_tokenizer(),
         }
     }
     
+    /// Use `tokenizer` to measure candidates against `max_candidate_tokens`
+    pub fn with_tokenizer(mut self, tokenizer: Arc<dyn Tokenizer>) -> Self {
+        self.tokenizer = tokenizer;
+        self
+    }
+    
     /// Perform enhanced semantic search
     pub async fn search(&self, query: &SearchQuery) -> Result<Vec<EnhancedSearchResult>> {
         info!("Starting semantic search for: '{}'", query.text);
@@ -159,19 +410,7 @@ impl SemanticSearchPipeline {
     
     /// Generate embedding for query text
     pub async fn generate_query_embedding(&self, text: &str) -> Result<Vec<f32>> {
-        // Strategy: Extract the necessary data from the plugin without holding the lock across await
-        let text_clone = text.to_string();
-        let embedding_plugin = Arc::clone(&self.embedding_plugin);
-        
-        // Spawn a task that handles the embedding generation
-        let embeddings = tokio::task::spawn_blocking(move || {
-            // This runs in a blocking thread where Send is not required
-            let rt = tokio::runtime::Handle::current();
-            rt.block_on(async move {
-                let plugin = embedding_plugin.read();
-                plugin.embed_texts(&[text_clone]).await
-            })
-        }).await??;
+        let embeddings = embed_batch(Arc::clone(&self.embedding_plugin), vec![text.to_string()]).await?;
         
         if embeddings.is_empty() {
             anyhow::bail!("Failed to generate embedding for query text");
@@ -180,21 +419,26 @@ impl SemanticSearchPipeline {
         Ok(embeddings[0].clone())
     }
     
+    /// Embedding backend shared with indexing
+    pub fn embedding_backend(&self) -> Arc<RwLock<dyn EmbeddingBackend>> {
+        Arc::clone(&self.embedding_plugin)
+    }
+    
     /// Retrieve candidates using LSH index
     async fn retrieve_candidates(&self, query_embedding: &[f32], query: &SearchQuery) -> Result<Vec<SearchResult>> {
         let vector_db = self.vector_db.read();
         let db_stats = vector_db.stats();
-        println!("🔍 Vector DB stats at search time:");
+        status!("🔍 Vector DB stats at search time:");
         println!("   Total vectors: {}", db_stats.total_vectors);
         println!("   Total files: {}", db_stats.total_files);
-        println!("🔍 Searching with query embedding len: {}, lsh_candidates: {}", 
+        status!("🔍 Searching with query embedding len: {}, lsh_candidates: {}", 
                  query_embedding.len(), self.config.lsh_candidates);
         
         let mut candidates = vector_db.search(query_embedding, self.config.lsh_candidates)?;
-        println!("🔍 Vector DB search returned {} raw candidates", candidates.len());
+        status!("🔍 Vector DB search returned {} raw candidates", candidates.len());
         
         // Apply query-specific filtering
-        println!("🔍 Applying query-specific filtering...");
+        status!("🔍 Applying query-specific filtering...");
         println!("   Query code_type: {:?}", query.code_type);
         println!("   Query language: {:?}", query.language);
         println!("   LSH threshold: {}", self.config.lsh_threshold);
@@ -202,12 +446,9 @@ impl SemanticSearchPipeline {
         self.filter_candidates(&mut candidates, query);
         
         // Sort by similarity (already done by search, but ensure consistency)
-        candidates.sort_by(|a, b| {
-            b.similarity.partial_cmp(&a.similarity)
-                .unwrap_or(std::cmp::Ordering::Equal)
-        });
+        candidates.sort_by(|a, b| rank_order(a.similarity, &a.entry, b.similarity, &b.entry));
         
-        println!("🔍 After filtering and sorting: {} candidates", candidates.len());
+        status!("🔍 After filtering and sorting: {} candidates", candidates.len());
         
         Ok(candidates)
     }
@@ -241,47 +482,53 @@ impl SemanticSearchPipeline {
     /// Rerank candidates using the reranker model
     async fn rerank_candidates(&self, query: &str, candidates: Vec<SearchResult>) -> Result<Vec<EnhancedSearchResult>> {
         if candidates.is_empty() {
-            println!("🔍 Reranker: No candidates to rerank");
+            status!("🔍 Reranker: No candidates to rerank");
             return Ok(Vec::new());
         }
         
-        println!("🔍 Reranker: Processing {} candidates", candidates.len());
+        status!("🔍 Reranker: Processing {} candidates", candidates.len());
         
-        // Prepare documents for reranking
+        // Prepare documents for reranking, truncated to the configured length
         let documents: Vec<String> = candidates.iter()
-            .map(|c| self.prepare_document_for_reranking(&c.entry))
+            .map(|c| truncate_candidate(&self.prepare_document_for_reranking(&c.entry), self.config.max_candidate_tokens, self.tokenizer.as_ref()))
             .collect();
         
-        println!("🔍 Reranker: Prepared {} documents for reranking", documents.len());
+        status!("🔍 Reranker: Prepared {} documents for reranking", documents.len());
         for (i, doc) in documents.iter().enumerate() {
-            println!("🔍 Document {}: {} chars", i, doc.len());
+            status!("🔍 Document {}: {} chars", i, doc.len());
         }
         
-        // Get reranking scores
-        println!("🔍 Reranker: Calling rank_documents with query: '{}'", query);
+        // Get reranking scores, one call per batch
+        status!("🔍 Reranker: Calling rank_documents with query: '{}'", query);
         let query_clone = query.to_string();
-        let documents_clone = documents.clone();
+        let batch_size = self.config.rerank_batch_size.max(1);
         let reranker_plugin = Arc::clone(&self.reranker_plugin);
         
         let rerank_results = tokio::task::spawn_blocking(move || {
             let rt = tokio::runtime::Handle::current();
             rt.block_on(async move {
                 let reranker = reranker_plugin.read();
-                reranker.rank_documents(&query_clone, &documents_clone).await
+                let mut results = Vec::with_capacity(documents.len());
+                for (batch_index, batch) in documents.chunks(batch_size).enumerate() {
+                    let offset = batch_index * batch_size;
+                    let scores = reranker.rank_documents(&query_clone, batch).await?;
+                    results.extend(scores.into_iter().map(|(index, score)| (offset + index, score)));
+                }
+                Ok::<_, anyhow::Error>(results)
             })
         }).await??;
-        println!("🔍 Reranker: Got {} rerank results", rerank_results.len());
+        status!("🔍 Reranker: Got {} rerank results", rerank_results.len());
         
         // Combine LSH similarity with reranking scores
         let mut enhanced_results = Vec::new();
         
         for (candidate_idx, rerank_score) in rerank_results {
-            println!("🔍 Processing rerank result: candidate_idx={}, rerank_score={:.6}", candidate_idx, rerank_score);
+            status!("🔍 Processing rerank result: candidate_idx={}, rerank_score={:.6}", candidate_idx, rerank_score);
             
             if candidate_idx < candidates.len() {
                 let candidate = &candidates[candidate_idx];
                 
-                println!("🔍 Candidate {}: embedding_similarity={:.6}", candidate_idx, candidate.similarity);
+                status!("🔍 Candidate {}: embedding_similarity={:.6}", candidate_idx, candidate.similarity);
                 
                 // Calculate combined score
                 let combined_score = self.calculate_combined_score(
@@ -295,7 +542,7 @@ impl SemanticSearchPipeline {
                     rerank_score,
                 );
                 
-                println!("🔍 Calculated scores: combined={:.6}, confidence={:.6}", combined_score, confidence);
+                status!("🔍 Calculated scores: combined={:.6}, confidence={:.6}", combined_score, confidence);
                 
                 enhanced_results.push(EnhancedSearchResult {
                     entry: candidate.entry.clone(),
@@ -303,15 +550,13 @@ impl SemanticSearchPipeline {
                     rerank_score,
                     combined_score,
                     confidence,
+                    additional_matches: 0,
                 });
             }
         }
         
-        // Sort by combined score
-        enhanced_results.sort_by(|a, b| {
-            b.combined_score.partial_cmp(&a.combined_score)
-                .unwrap_or(std::cmp::Ordering::Equal)
-        });
+        // Sort by combined score, ties by file path then line
+        enhanced_results.sort_by(|a, b| rank_order(a.combined_score, &a.entry, b.combined_score, &b.entry));
         
         Ok(enhanced_results)
     }
@@ -360,19 +605,19 @@ impl SemanticSearchPipeline {
     
     /// Apply final filtering and result limiting
     async fn finalize_results(&self, mut results: Vec<EnhancedSearchResult>, query: &SearchQuery) -> Result<Vec<EnhancedSearchResult>> {
-        println!("🔍 Finalize: Starting with {} results", results.len());
-        println!("🔍 Finalize: Rerank threshold = {:.6}", self.config.rerank_threshold);
+        status!("🔍 Finalize: Starting with {} results", results.len());
+        status!("🔍 Finalize: Rerank threshold = {:.6}", self.config.rerank_threshold);
         
         // Show all scores before filtering
         for (i, result) in results.iter().enumerate() {
-            println!("🔍 Result {}: rerank_score={:.6}, combined_score={:.6}", 
+            status!("🔍 Result {}: rerank_score={:.6}, combined_score={:.6}", 
                      i, result.rerank_score, result.combined_score);
         }
         
         // Filter by rerank threshold
         let before_filter = results.len();
         results.retain(|r| r.rerank_score >= self.config.rerank_threshold);
-        println!("🔍 Finalize: After rerank threshold filter: {} -> {} results", before_filter, results.len());
+        status!("🔍 Finalize: After rerank threshold filter: {} -> {} results", before_filter, results.len());
         
         // Apply max results limit
         let max_results = query.max_results.unwrap_or(self.config.final_results);
@@ -438,8 +683,8 @@ impl SemanticSearchFactory {
     /// Create a new semantic search pipeline
     pub fn create_pipeline(
         vector_db: Arc<RwLock<dyn VectorDatabase>>,
-        embedding_plugin: Arc<RwLock<QwenEmbeddingPlugin>>,
-        reranker_plugin: Arc<RwLock<QwenRerankerPlugin>>,
+        embedding_plugin: Arc<RwLock<dyn EmbeddingBackend>>,
+        reranker_plugin: Arc<RwLock<dyn RerankerBackend>>,
     ) -> SemanticSearchPipeline {
         SemanticSearchPipeline::new(
             vector_db,
@@ -452,8 +697,8 @@ impl SemanticSearchFactory {
     /// Create with custom configuration
     pub fn create_with_config(
         vector_db: Arc<RwLock<dyn VectorDatabase>>,
-        embedding_plugin: Arc<RwLock<QwenEmbeddingPlugin>>,
-        reranker_plugin: Arc<RwLock<QwenRerankerPlugin>>,
+        embedding_plugin: Arc<RwLock<dyn EmbeddingBackend>>,
+        reranker_plugin: Arc<RwLock<dyn RerankerBackend>>,
         config: SemanticSearchConfig,
     ) -> SemanticSearchPipeline {
         SemanticSearchPipeline::new(
@@ -530,4 +775,30 @@ mod tests {
         let confidence = pipeline.calculate_confidence(0.8, 0.85);
         assert!(confidence > 0.8);
     }
+    
+    #[test]
+    fn test_truncation_counts_tokens_with_the_tokenizer() {
+        /// One token per character, so words and tokens disagree
+        struct CharTokenizer;
+        
+        impl Tokenizer for CharTokenizer {
+            fn count_tokens(&self, text: &str) -> usize {
+                text.chars().count()
+            }
+            
+            fn encoding_name(&self) -> &str {
+                "chars"
+            }
+        }
+        
+        let document = "fn retry()\nattempt once\nattempt twice";
+        assert_eq!(truncate_candidate(document, 100, &CharTokenizer), document);
+        assert_eq!(truncate_candidate(document, 20, &CharTokenizer), "fn retry()\nattempt");
+        assert_eq!(truncate_candidate(document, 5, &CharTokenizer), "fn");
+        
+        let tiktoken = crate::utils::TiktokenTokenizer::default();
+        let truncated = truncate_candidate(document, 5, &tiktoken);
+        assert!(tiktoken.count_tokens(&truncated) <= 5, "{:?}", truncated);
+        assert!(truncated.starts_with("fn retry()"), "{:?}", truncated);
+    }
 }
\ No newline at end of file
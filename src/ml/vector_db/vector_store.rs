@@ -5,6 +5,7 @@
 use super::*;
 use crate::ml::vector_db::{
     lsh_index::{LSHIndex, LSHConfig},
+    persistence::{read_snapshot, write_snapshot},
     similarity::{CosineSimilarity, SimilarityMetric},
 };
 use anyhow::Result;
@@ -36,7 +37,7 @@ impl NativeVectorStore {
             num_hash_functions: config.num_hash_functions,
             hash_bits: 5,  // Further reduced from 6 to 5 for more hash collisions
             num_tables: 12, // Increased from 8 to 12 for better coverage
-            seed: 42,
+            seed: config.random_seed.unwrap_or(LSHConfig::default().seed),
         };
         
         // Initialize with 768 dimensions (standard embedding size)
@@ -49,6 +50,7 @@ impl NativeVectorStore {
             average_similarity: 0.0,
             by_language: HashMap::new(),
             by_code_type: HashMap::new(),
+            by_embedding_template: HashMap::new(),
             created_at: chrono::Utc::now(),
             last_updated: chrono::Utc::now(),
         };
@@ -90,6 +92,30 @@ impl NativeVectorStore {
         Ok(())
     }
     
+    /// Approximate heap bytes of the LSH and file indexes
+    fn index_bytes(&self) -> usize {
+        let file_index = self.file_index.read();
+        let file_index_bytes = file_index.capacity()
+            * (std::mem::size_of::<String>() + std::mem::size_of::<Vec<String>>())
+            + file_index.iter()
+                .map(|(path, ids)| {
+                    path.capacity()
+                        + ids.capacity() * std::mem::size_of::<String>()
+                        + ids.iter().map(|id| id.capacity()).sum::<usize>()
+                })
+                .sum::<usize>();
+        self.lsh_index.read().estimated_bytes() + file_index_bytes
+    }
+    
+    /// Group vector ids by file path
+    fn build_file_index(&self) -> HashMap<String, Vec<String>> {
+        let mut file_index: HashMap<String, Vec<String>> = HashMap::new();
+        for (id, entry) in self.vectors.read().iter() {
+            file_index.entry(entry.metadata.file_path.clone()).or_default().push(id.clone());
+        }
+        file_index
+    }
+    
     /// Get embedding for code using the ML pipeline
     async fn get_embedding_for_code(&self, code: &str) -> Result<Vec<f32>> {
         // TODO: Integration with QwenEmbeddingPlugin
@@ -138,14 +164,39 @@ impl NativeVectorStore {
         // Language and type statistics
         stats.by_language.clear();
         stats.by_code_type.clear();
+        stats.by_embedding_template.clear();
         
         for entry in vectors.values() {
             *stats.by_language.entry(entry.metadata.language.clone()).or_insert(0) += 1;
             let type_name = format!("{:?}", entry.metadata.code_type);
             *stats.by_code_type.entry(type_name).or_insert(0) += 1;
+            *stats.by_embedding_template.entry(entry.metadata.embedding_template.clone()).or_insert(0) += 1;
         }
     }
     
+    /// Adjust statistics for a single inserted (`delta = 1`) or removed
+    /// (`delta = -1`) entry without rescanning the whole store
+    fn apply_stats_delta(&self, entry: &VectorEntry, delta: isize) {
+        let total_files = self.file_index.read().len();
+        let mut stats = self.stats.write();
+        
+        stats.total_vectors = stats.total_vectors.saturating_add_signed(delta);
+        stats.total_files = total_files;
+        stats.last_updated = chrono::Utc::now();
+        stats.index_size_mb = (stats.total_vectors * 768 * 4) as f64 / 1024.0 / 1024.0;
+        
+        let adjust = |counts: &mut HashMap<String, usize>, key: String| {
+            let count = counts.entry(key.clone()).or_insert(0);
+            *count = count.saturating_add_signed(delta);
+            if *count == 0 {
+                counts.remove(&key);
+            }
+        };
+        adjust(&mut stats.by_language, entry.metadata.language.clone());
+        adjust(&mut stats.by_code_type, format!("{:?}", entry.metadata.code_type));
+        adjust(&mut stats.by_embedding_template, entry.metadata.embedding_template.clone());
+    }
+    
     /// Compute average similarity for a sample of vectors
     fn compute_average_similarity(&self) -> f32 {
         let vectors = self.vectors.read();
@@ -184,25 +235,31 @@ impl VectorDatabase for NativeVectorStore {
         let id = entry.id.clone();
         let file_path = entry.metadata.file_path.clone();
         
+        // Re-adding an ID replaces the previous entry
+        if self.vectors.read().contains_key(&id) {
+            self.delete(&id)?;
+        }
+        
         // Add to LSH index
         {
             let mut index = self.lsh_index.write();
             index.add(id.clone(), &entry.embedding)?;
         }
         
-        // Add to vectors
+        // Update file index
         {
-            let mut vectors = self.vectors.write();
-            vectors.insert(id.clone(), entry);
+            let mut file_index = self.file_index.write();
+            file_index.entry(file_path).or_insert_with(Vec::new).push(id.clone());
         }
         
-        // Update file index
+        self.apply_stats_delta(&entry, 1);
+        
+        // Add to vectors
         {
-            let mut file_index = self.file_index.write();
-            file_index.entry(file_path).or_insert_with(Vec::new).push(id);
+            let mut vectors = self.vectors.write();
+            vectors.insert(id, entry);
         }
         
-        self.update_stats();
         Ok(())
     }
     
@@ -246,11 +303,9 @@ impl VectorDatabase for NativeVectorStore {
             }
         }
         
-        // Sort by similarity (descending)
-        results.sort_by(|a, b| {
-            b.similarity.partial_cmp(&a.similarity)
-                .unwrap_or(std::cmp::Ordering::Equal)
-        });
+        // Sort by similarity (descending), ties in a fixed order so the
+        // truncation below is deterministic
+        results.sort_by(|a, b| rank_order(a.similarity, &a.entry, b.similarity, &b.entry));
         
         // Limit results
         results.truncate(limit.min(self.config.max_results));
@@ -309,7 +364,7 @@ impl VectorDatabase for NativeVectorStore {
                 }
             }
             
-            self.update_stats();
+            self.apply_stats_delta(&entry, -1);
             Ok(true)
         } else {
             Ok(false)
@@ -353,22 +408,16 @@ impl VectorDatabase for NativeVectorStore {
         std::fs::create_dir_all(&cache_dir)?;
         
         // Save vectors
-        let vectors_path = cache_dir.join("vectors.json");
         let vectors = self.vectors.read();
-        let vectors_json = serde_json::to_string_pretty(&*vectors)?;
-        std::fs::write(vectors_path, vectors_json)?;
+        write_snapshot(&cache_dir.join("vectors.json"), &*vectors)?;
         
         // Save file index
-        let file_index_path = cache_dir.join("file_index.json");
         let file_index = self.file_index.read();
-        let file_index_json = serde_json::to_string_pretty(&*file_index)?;
-        std::fs::write(file_index_path, file_index_json)?;
+        write_snapshot(&cache_dir.join("file_index.json"), &*file_index)?;
         
         // Save stats
-        let stats_path = cache_dir.join("stats.json");
         let stats = self.stats.read();
-        let stats_json = serde_json::to_string_pretty(&*stats)?;
-        std::fs::write(stats_path, stats_json)?;
+        write_snapshot(&cache_dir.join("stats.json"), &*stats)?;
         
         Ok(())
     }
@@ -380,28 +429,38 @@ impl VectorDatabase for NativeVectorStore {
         
         let cache_dir = PathBuf::from(&self.config.cache_dir);
         
-        // Load vectors
+        // Load vectors; a corrupt or outdated snapshot is discarded so the
+        // index gets rebuilt from scratch instead of failing every startup
         let vectors_path = cache_dir.join("vectors.json");
         if vectors_path.exists() {
-            let vectors_json = std::fs::read_to_string(vectors_path)?;
-            let vectors: HashMap<String, VectorEntry> = serde_json::from_str(&vectors_json)?;
-            *self.vectors.write() = vectors;
+            match read_snapshot::<HashMap<String, VectorEntry>>(&vectors_path) {
+                Ok(vectors) => *self.vectors.write() = vectors,
+                Err(e) => {
+                    tracing::warn!("Discarding invalid vector snapshot, rebuilding: {}", e);
+                    self.clear()?;
+                    for name in ["vectors.json", "file_index.json", "stats.json"] {
+                        let _ = std::fs::remove_file(cache_dir.join(name));
+                    }
+                    return Ok(());
+                }
+            }
         }
         
-        // Load file index
+        // Load file index, deriving it from the vectors if it is unusable
         let file_index_path = cache_dir.join("file_index.json");
-        if file_index_path.exists() {
-            let file_index_json = std::fs::read_to_string(file_index_path)?;
-            let file_index: HashMap<String, Vec<String>> = serde_json::from_str(&file_index_json)?;
-            *self.file_index.write() = file_index;
-        }
+        let file_index = if file_index_path.exists() {
+            read_snapshot::<HashMap<String, Vec<String>>>(&file_index_path).ok()
+        } else {
+            None
+        };
+        *self.file_index.write() = file_index.unwrap_or_else(|| self.build_file_index());
         
         // Load stats
         let stats_path = cache_dir.join("stats.json");
         if stats_path.exists() {
-            let stats_json = std::fs::read_to_string(stats_path)?;
-            let stats: VectorDBStats = serde_json::from_str(&stats_json)?;
-            *self.stats.write() = stats;
+            if let Ok(stats) = read_snapshot::<VectorDBStats>(&stats_path) {
+                *self.stats.write() = stats;
+            }
         }
         
         // Rebuild LSH index
@@ -422,10 +481,31 @@ impl VectorDatabase for NativeVectorStore {
         stats.average_similarity = 0.0;
         stats.by_language.clear();
         stats.by_code_type.clear();
+        stats.by_embedding_template.clear();
         stats.last_updated = chrono::Utc::now();
         
         Ok(())
     }
+    
+    fn compact(&mut self) -> Result<CompactionReport> {
+        let bytes_before = self.index_bytes();
+        
+        let stale_ids_removed = {
+            let mut vectors = self.vectors.write();
+            vectors.shrink_to_fit();
+            self.lsh_index.write().compact(vectors.iter().map(|(id, entry)| (id, entry.embedding.as_slice())))?
+        };
+        *self.file_index.write() = self.build_file_index();
+        self.update_stats();
+        
+        let bytes_after = self.index_bytes();
+        Ok(CompactionReport {
+            bytes_before,
+            bytes_after,
+            bytes_reclaimed: bytes_before.saturating_sub(bytes_after),
+            stale_ids_removed,
+        })
+    }
 }
 
 /// Factory for creating vector stores
@@ -466,7 +546,9 @@ mod tests {
                 language: "typescript".to_string(),
                 complexity: 1.0,
                 tokens: vec!["test".to_string()],
+                calls: Vec::new(),
                 hash: "hash123".to_string(),
+                embedding_template: "enriched".to_string(),
             },
             created_at: chrono::Utc::now(),
             updated_at: chrono::Utc::now(),
@@ -551,4 +633,178 @@ mod tests {
         assert_eq!(stats.total_files, 1);
         assert_eq!(stats.total_vectors, 2);
     }
-}
\ No newline at end of file
+    
+    #[test]
+    fn test_merge_namespaces_entries_by_prefix() {
+        // Two repos with the same relative file path and entry id
+        let mut repo_a = NativeVectorStore::new(VectorDBConfig::default());
+        let mut repo_b = NativeVectorStore::new(VectorDBConfig::default());
+        
+        let mut entry_a = create_test_entry("src/lib.rs:1:10", vec![1.0; 768]);
+        entry_a.metadata.file_path = "src/lib.rs".to_string();
+        entry_a.metadata.language = "rust".to_string();
+        let mut entry_b = create_test_entry("src/lib.rs:1:10", vec![0.9; 768]);
+        entry_b.metadata.file_path = "src/lib.rs".to_string();
+        
+        repo_a.add_vector(entry_a).unwrap();
+        repo_b.add_vector(entry_b).unwrap();
+        
+        let mut unified = NativeVectorStore::new(VectorDBConfig::default());
+        assert_eq!(unified.merge(&repo_a, "repo-a").unwrap(), 1);
+        assert_eq!(unified.merge(&repo_b, "repo-b/").unwrap(), 1);
+        
+        let results = unified.search(&vec![1.0; 768], 10).unwrap();
+        let mut paths: Vec<String> = results.iter()
+            .map(|r| r.entry.metadata.file_path.clone())
+            .collect();
+        paths.sort();
+        assert_eq!(paths, vec!["repo-a/src/lib.rs", "repo-b/src/lib.rs"]);
+        
+        assert!(unified.get_by_id("repo-a/src/lib.rs:1:10").unwrap().is_some());
+        assert_eq!(unified.get_by_file("repo-b/src/lib.rs").unwrap().len(), 1);
+        
+        let stats = unified.stats();
+        assert_eq!(stats.total_vectors, 2);
+        assert_eq!(stats.total_files, 2);
+        assert_eq!(stats.by_language.get("rust"), Some(&1));
+        
+        assert!(unified.merge(&repo_a, "").is_err());
+    }
+    
+    #[test]
+    fn test_truncated_snapshot_triggers_clean_rebuild() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let config = VectorDBConfig {
+            cache_dir: temp_dir.path().to_string_lossy().to_string(),
+            enable_persistence: true,
+            ..VectorDBConfig::default()
+        };
+        
+        let mut store = NativeVectorStore::new(config.clone());
+        store.add_vector(create_test_entry("test1", vec![1.0; 768])).unwrap();
+        store.add_vector(create_test_entry("test2", vec![0.5; 768])).unwrap();
+        store.save().unwrap();
+        
+        // A valid snapshot round-trips
+        let mut reloaded = NativeVectorStore::new(config.clone());
+        reloaded.load().unwrap();
+        assert_eq!(reloaded.stats().total_vectors, 2);
+        assert_eq!(reloaded.get_by_file("test.ts").unwrap().len(), 2);
+        
+        // Simulate a crash halfway through writing vectors.json
+        let vectors_path = temp_dir.path().join("vectors.json");
+        let content = std::fs::read(&vectors_path).unwrap();
+        std::fs::write(&vectors_path, &content[..content.len() / 2]).unwrap();
+        
+        let mut rebuilt = NativeVectorStore::new(config.clone());
+        rebuilt.load().unwrap();
+        assert_eq!(rebuilt.stats().total_vectors, 0);
+        assert!(rebuilt.get_by_file("test.ts").unwrap().is_empty());
+        assert!(!vectors_path.exists());
+        
+        // The rebuilt store is usable and persists cleanly again
+        rebuilt.add_vector(create_test_entry("test3", vec![1.0; 768])).unwrap();
+        rebuilt.save().unwrap();
+        let mut after = NativeVectorStore::new(config);
+        after.load().unwrap();
+        assert_eq!(after.stats().total_vectors, 1);
+    }
+    
+    #[test]
+    fn test_cache_dir_env_var_controls_persistence_location() {
+        use crate::utils::cache_dir::{resolve_cache_root, vector_db_cache_dir_under};
+        
+        // The environment is read through a closure, so no process-wide
+        // variable is set while other tests resolve their own cache dirs
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let env_root = temp_dir.path().to_string_lossy().to_string();
+        let root = resolve_cache_root(|key| (key == crate::utils::CACHE_DIR_ENV).then(|| env_root.clone()));
+        let project = temp_dir.path().join("project");
+        std::fs::create_dir_all(&project).unwrap();
+        
+        let config = VectorDBConfig {
+            cache_dir: vector_db_cache_dir_under(&root, &project).to_string_lossy().to_string(),
+            ..VectorDBConfig::default()
+        };
+        assert!(PathBuf::from(&config.cache_dir).starts_with(temp_dir.path().join("vector-db")));
+        // Each project root gets its own index
+        assert_ne!(vector_db_cache_dir_under(&root, temp_dir.path()), vector_db_cache_dir_under(&root, &project));
+        
+        let mut store = NativeVectorStore::new(config.clone());
+        store.add_vector(create_test_entry("test1", vec![1.0; 768])).unwrap();
+        store.save().unwrap();
+        
+        let mut reloaded = NativeVectorStore::new(config);
+        reloaded.load().unwrap();
+        
+        assert_eq!(reloaded.stats().total_vectors, 1);
+        let written: Vec<_> = walkdir::WalkDir::new(temp_dir.path())
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_name() == "vectors.json")
+            .collect();
+        assert_eq!(written.len(), 1);
+    }
+    
+    #[test]
+    fn test_same_seed_gives_identical_search_results() {
+        let build = || {
+            let mut store = NativeVectorStore::new(VectorDBConfig::for_testing());
+            for i in 0..20 {
+                let embedding: Vec<f32> = (0..768)
+                    .map(|d| ((d * 7) % 17) as f32 - 8.0 + (i * (d % 5)) as f32 * 0.05)
+                    .collect();
+                store.add_vector(create_test_entry(&format!("v{}", i), embedding)).unwrap();
+            }
+            store
+        };
+        let query: Vec<f32> = (0..768).map(|d| ((d * 7) % 17) as f32 - 8.0).collect();
+        
+        let ranked = |store: &NativeVectorStore| -> Vec<(String, f32)> {
+            store.search(&query, 10).unwrap()
+                .into_iter()
+                .map(|r| (r.entry.id, r.similarity))
+                .collect()
+        };
+        
+        let first = ranked(&build());
+        assert!(!first.is_empty());
+        assert_eq!(first, ranked(&build()));
+    }
+    
+    #[test]
+    fn test_compact_reclaims_space_and_keeps_results() {
+        let embedding = |i: usize| -> Vec<f32> {
+            (0..768).map(|d| ((d * 7 + i * 13) % 17) as f32 - 8.0).collect()
+        };
+        let mut store = NativeVectorStore::new(VectorDBConfig::for_testing());
+        
+        // Churn: every round adds a batch and deletes most of it again
+        for round in 0..10 {
+            for i in 0..50 {
+                store.add_vector(create_test_entry(&format!("r{}-{}", round, i), embedding(round * 50 + i))).unwrap();
+            }
+            for i in 5..50 {
+                store.delete(&format!("r{}-{}", round, i)).unwrap();
+            }
+        }
+        
+        let query = embedding(3);
+        let ranked = |store: &NativeVectorStore| -> Vec<(String, f32)> {
+            store.search(&query, 10).unwrap()
+                .into_iter()
+                .map(|r| (r.entry.id, r.similarity))
+                .collect()
+        };
+        let before = ranked(&store);
+        assert!(!before.is_empty());
+        
+        let report = store.compact().unwrap();
+        assert!(report.bytes_after < report.bytes_before);
+        assert_eq!(report.bytes_reclaimed, report.bytes_before - report.bytes_after);
+        assert_eq!(report.stale_ids_removed, 0);
+        
+        assert_eq!(ranked(&store), before);
+        assert_eq!(store.stats().total_vectors, 50);
+    }
+}
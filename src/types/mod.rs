@@ -16,6 +16,12 @@ pub struct FileMetadata {
     pub imports: Vec<String>,
     pub complexity: Complexity,
     pub detailed_analysis: Option<DetailedAnalysis>,
+    /// Arbitrary data attached by analysis post-processors
+    #[serde(default)]
+    pub extra: serde_json::Value,
+    /// Why the file couldn't be parsed; `detailed_analysis` is empty when set
+    #[serde(default)]
+    pub parse_error: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -32,6 +38,9 @@ pub struct DetailedAnalysis {
     pub module_info: Option<ModuleInfo>,
     // Rust-specific analysis
     pub rust_module: Option<RustModuleInfo>,
+    /// Anti-patterns found in the file, e.g. leaked RxJS subscriptions
+    #[serde(default)]
+    pub anti_patterns: Vec<crate::ml::models::AntiPattern>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -187,6 +196,11 @@ pub struct ComponentInfo {
     pub lifecycle: Vec<String>,
     pub template_summary: String,
     pub location: LocationInfo,
+    #[serde(default)]
+    pub dependencies: Vec<InjectedDependency>,
+    /// Tokens listed in the decorator's `providers` array
+    #[serde(default)]
+    pub providers: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -195,11 +209,35 @@ pub struct ServiceInfo {
     pub injectable: bool,
     pub provided_in: Option<String>,
     pub scope: ServiceScope,
-    pub dependencies: Vec<ParameterInfo>,
+    pub dependencies: Vec<InjectedDependency>,
     pub methods: Vec<FunctionInfo>,
     pub location: LocationInfo,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct InjectedDependency {
+    pub name: String,
+    pub param_type: String,
+    /// Explicit DI token: `@Inject(TOKEN)` or the first argument of `inject()`
+    pub token: Option<String>,
+    pub style: InjectionStyle,
+    /// `@Optional()`, `param?: T` or `inject(T, { optional: true })`
+    pub is_optional: bool,
+}
+
+impl InjectedDependency {
+    /// Token the injector resolves: the explicit token, else the declared type
+    pub fn injection_token(&self) -> &str {
+        self.token.as_deref().unwrap_or(&self.param_type)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum InjectionStyle {
+    Constructor,    // constructor(private http: HttpClient)
+    InjectFunction, // private http = inject(HttpClient)
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum ServiceScope {
     Root,        // providedIn: 'root'
@@ -278,6 +316,9 @@ pub struct CacheEntry {
     pub change_log: Vec<ChangeLogEntry>,
     pub dependencies: Vec<String>,
     pub dependents: Vec<String>,
+    /// Dependencies outside the focus directory the entry was analyzed with
+    #[serde(default)]
+    pub external_dependencies: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -447,6 +488,28 @@ pub struct RoutingAnalysis {
     pub protected_routes: Vec<RouteSummary>,
     pub redirects: Vec<RouteSummary>,
     pub lazy_routes: Vec<RouteSummary>,
+    /// Routes in the same file that shadow each other
+    #[serde(default)]
+    pub conflicts: Vec<RoutingConflict>,
+}
+
+/// Two or more routes of one routes file that match the same URLs
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RoutingConflict {
+    /// Path of the first declaration
+    pub path: String,
+    pub file: String,
+    pub kind: RoutingConflictKind,
+    /// Component (or `redirectTo` target) of every conflicting route, in declaration order
+    pub components: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum RoutingConflictKind {
+    /// The same path is declared more than once
+    DuplicatePath,
+    /// Paths differ only in parameter names, e.g. `users/:id` and `users/:userId`
+    ParameterCollision,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -655,6 +718,9 @@ pub struct RustModuleInfo {
     pub type_aliases: Vec<RustTypeAliasInfo>,
     pub macros: Vec<RustMacroInfo>,
     pub use_statements: Vec<RustUseInfo>,
+    /// Set when the syntax tree was too deeply nested to traverse completely
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub truncation_note: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -784,6 +850,14 @@ pub struct RustUseInfo {
     pub items: Vec<String>, // for use foo::{bar, baz}
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct PackageJsonInfo {
+    pub name: Option<String>,
+    pub version: Option<String>,
+    pub dependencies: HashMap<String, String>,
+    pub dev_dependencies: HashMap<String, String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct CargoInfo {
     pub package_name: String,
@@ -865,6 +939,8 @@ mod tests {
             imports: vec!["std::io".to_string()],
             complexity: Complexity::Low,
             detailed_analysis: None,
+            extra: serde_json::Value::Null,
+            parse_error: None,
         };
 
         assert_eq!(metadata.path, "src/main.rs");
@@ -938,6 +1014,8 @@ mod tests {
             lifecycle: vec!["ngOnInit".to_string(), "ngOnDestroy".to_string()],
             template_summary: "User profile display".to_string(),
             location: LocationInfo { line: 1, column: 1 },
+            dependencies: vec![],
+            providers: vec![],
         };
 
         assert_eq!(component.name, "UserComponent");
@@ -962,6 +1040,8 @@ mod tests {
             imports: vec![],
             complexity: Complexity::Low,
             detailed_analysis: None,
+            extra: serde_json::Value::Null,
+            parse_error: None,
         };
 
         let summary = CodeSummary {
@@ -989,6 +1069,7 @@ mod tests {
             change_log: vec![],
             dependencies: vec![],
             dependents: vec![],
+            external_dependencies: vec![],
         };
 
         let json = serde_json::to_string(&cache_entry).unwrap();
@@ -1056,6 +1137,7 @@ mod tests {
                 protected_routes: vec![],
                 redirects: vec![],
                 lazy_routes: vec![],
+                conflicts: vec![],
             },
             interceptor_analysis: InterceptorAnalysis {
                 interceptors: vec![],
@@ -0,0 +1,93 @@
+//! Cache directory resolution shared by everything that persists to disk
+
+use std::path::{Path, PathBuf};
+use crate::utils::calculate_string_hash;
+
+/// Environment variable overriding the cache root
+pub const CACHE_DIR_ENV: &str = "TOKEN_OPTIMIZER_CACHE_DIR";
+
+/// Root cache directory: `$TOKEN_OPTIMIZER_CACHE_DIR`, else the platform cache
+/// dir (`$XDG_CACHE_HOME`, `~/.cache`, `~/Library/Caches`, `%LOCALAPPDATA%`)
+/// joined with `token-optimizer`, else `.cache` in the working directory.
+pub fn cache_root() -> PathBuf {
+    resolve_cache_root(|key| std::env::var(key).ok())
+}
+
+/// Vector database directory for a project. Projects are keyed by name plus a
+/// hash of their absolute path so a shared cache root never mixes indexes.
+pub fn vector_db_cache_dir(project_path: &Path) -> PathBuf {
+    vector_db_cache_dir_under(&cache_root(), project_path)
+}
+
+/// `vector_db_cache_dir` below an already resolved cache `root`
+pub(crate) fn vector_db_cache_dir_under(root: &Path, project_path: &Path) -> PathBuf {
+    let absolute = project_path
+        .canonicalize()
+        .unwrap_or_else(|_| project_path.to_path_buf());
+    let name = absolute
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "root".to_string());
+    let hash = calculate_string_hash(&absolute.to_string_lossy());
+
+    root.join("vector-db").join(format!("{}-{}", name, &hash[..12]))
+}
+
+/// `cache_root` with environment variables read through `env`
+pub(crate) fn resolve_cache_root(env: impl Fn(&str) -> Option<String>) -> PathBuf {
+    let non_empty = |key: &str| env(key).filter(|value| !value.trim().is_empty());
+
+    if let Some(dir) = non_empty(CACHE_DIR_ENV) {
+        return PathBuf::from(dir);
+    }
+
+    let platform_dir = if cfg!(windows) {
+        non_empty("LOCALAPPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        non_empty("HOME").map(|home| PathBuf::from(home).join("Library").join("Caches"))
+    } else {
+        non_empty("XDG_CACHE_HOME")
+            .map(PathBuf::from)
+            .or_else(|| non_empty("HOME").map(|home| PathBuf::from(home).join(".cache")))
+    };
+
+    platform_dir
+        .map(|dir| dir.join("token-optimizer"))
+        .unwrap_or_else(|| PathBuf::from(".cache"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn env_from(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        move |key| vars.get(key).cloned()
+    }
+
+    #[test]
+    fn test_env_override_wins() {
+        let root = resolve_cache_root(env_from(&[
+            (CACHE_DIR_ENV, "/tmp/custom-cache"),
+            ("XDG_CACHE_HOME", "/home/u/.xdg"),
+            ("HOME", "/home/u"),
+        ]));
+        assert_eq!(root, PathBuf::from("/tmp/custom-cache"));
+    }
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    #[test]
+    fn test_xdg_and_home_fallbacks() {
+        let root = resolve_cache_root(env_from(&[("XDG_CACHE_HOME", "/home/u/.xdg"), ("HOME", "/home/u")]));
+        assert_eq!(root, PathBuf::from("/home/u/.xdg/token-optimizer"));
+
+        let root = resolve_cache_root(env_from(&[(CACHE_DIR_ENV, " "), ("HOME", "/home/u")]));
+        assert_eq!(root, PathBuf::from("/home/u/.cache/token-optimizer"));
+
+        assert_eq!(resolve_cache_root(env_from(&[])), PathBuf::from(".cache"));
+    }
+}
@@ -0,0 +1,119 @@
+//! Comment removal and extraction on the syntax tree
+//!
+//! Comments are found with the language's tree-sitter grammar, not by
+//! pattern matching, so `//` or `/*` inside string literals is left alone.
+//! Without the grammar in the build, or for content that does not parse,
+//! the content is returned unchanged.
+
+use std::ops::Range;
+use tree_sitter::Tree;
+use crate::analyzers::rust_analyzer::RustAnalyzer;
+use crate::analyzers::{AnalyzerLanguage, TypeScriptASTAnalyzer};
+
+/// `content` without its comments. Lines left blank by a removed comment
+/// are dropped and trailing whitespace before a removed comment is trimmed;
+/// every other line is kept as is.
+pub fn strip_comments(content: &str, language: AnalyzerLanguage) -> String {
+    let Some(ranges) = comment_ranges(content, language) else {
+        return content.to_string();
+    };
+    if ranges.is_empty() {
+        return content.to_string();
+    }
+
+    // Comments are replaced by the newlines they span, so the stripped text
+    // still has one line per original line
+    let mut stripped = String::with_capacity(content.len());
+    let mut position = 0;
+    for range in &ranges {
+        stripped.push_str(&content[position..range.start]);
+        stripped.extend(content[range.clone()].chars().filter(|&c| c == '\n'));
+        position = range.end;
+    }
+    stripped.push_str(&content[position..]);
+
+    let line_count = content.lines().count() + 1;
+    let mut commented_lines = vec![false; line_count];
+    for range in &ranges {
+        let first = content[..range.start].matches('\n').count();
+        let last = first + content[range.clone()].matches('\n').count();
+        commented_lines[first..=last.min(line_count - 1)].fill(true);
+    }
+
+    let mut lines = Vec::new();
+    for (line, commented) in stripped.lines().zip(commented_lines) {
+        if !commented {
+            lines.push(line);
+        } else if !line.trim().is_empty() {
+            lines.push(line.trim_end());
+        }
+    }
+
+    let mut result = lines.join("\n");
+    if content.ends_with('\n') {
+        result.push('\n');
+    }
+    result
+}
+
+/// The comments of `content`, one per line in source order, empty when
+/// there are none or the content cannot be parsed
+pub fn extract_comments(content: &str, language: AnalyzerLanguage) -> String {
+    comment_ranges(content, language)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|range| content[range].trim())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Byte ranges of the comments in `content`, in source order
+fn comment_ranges(content: &str, language: AnalyzerLanguage) -> Option<Vec<Range<usize>>> {
+    let tree: Tree = match language {
+        AnalyzerLanguage::Rust => RustAnalyzer::new().ok()?.parse(content).ok()?,
+        AnalyzerLanguage::TypeScript => TypeScriptASTAnalyzer::new().ok()?.parse_file(content).ok()?,
+    };
+
+    let mut ranges = Vec::new();
+    let mut stack = vec![tree.root_node()];
+    while let Some(node) = stack.pop() {
+        // `line_comment` and `block_comment` in Rust, `comment` in TypeScript;
+        // a Rust doc comment's inner nodes belong to the comment itself
+        if node.kind().ends_with("comment") {
+            ranges.push(node.byte_range());
+            continue;
+        }
+        let mut cursor = node.walk();
+        stack.extend(node.children(&mut cursor));
+    }
+
+    ranges.sort_by_key(|range| range.start);
+    Some(ranges)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "rust")]
+    #[test]
+    fn test_rust_comments_are_removed_but_strings_kept() {
+        let source = "/// Joins a URL\npub fn join(base: &str) -> String {\n    // scheme separator\n    let url = format!(\"https://{}\", base); /* inline */\n    url\n}\n";
+
+        let stripped = strip_comments(source, AnalyzerLanguage::Rust);
+        assert_eq!(stripped, "pub fn join(base: &str) -> String {\n    let url = format!(\"https://{}\", base);\n    url\n}\n");
+
+        let comments = extract_comments(source, AnalyzerLanguage::Rust);
+        assert_eq!(comments, "/// Joins a URL\n// scheme separator\n/* inline */");
+    }
+
+    #[cfg(feature = "typescript")]
+    #[test]
+    fn test_typescript_comments_are_removed_but_strings_kept() {
+        let source = "/**\n * Fetches a user\n */\nexport function load(id: string) {\n  const url = 'http://api/users/' + id; // absolute\n  return fetch(url /* no options */);\n}\n";
+
+        let stripped = strip_comments(source, AnalyzerLanguage::TypeScript);
+        assert_eq!(stripped, "export function load(id: string) {\n  const url = 'http://api/users/' + id;\n  return fetch(url );\n}\n");
+        assert!(extract_comments(source, AnalyzerLanguage::TypeScript).starts_with("/**\n * Fetches a user"));
+    }
+}
@@ -0,0 +1,121 @@
+//! Decorative symbols in console output
+//!
+//! Status lines and text reports lead with emoji, which Windows consoles and
+//! CI logs without UTF-8 mangle. Under `Charset::Ascii` known symbols become
+//! ASCII tags, other decorations are dropped and any remaining non-ASCII
+//! character becomes `?`. JSON output is never decorated.
+
+use std::borrow::Cow;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Process-wide setting used by `status!`, set once from `--ascii`
+static ASCII_OUTPUT: AtomicBool = AtomicBool::new(false);
+
+/// Symbols with a meaning worth keeping, and their ASCII stand-ins
+const FALLBACKS: &[(char, &str)] = &[
+    ('✅', "[ok]"),
+    ('❌', "[error]"),
+    ('⚠', "[warn]"),
+    ('ℹ', "[info]"),
+    ('💡', "[tip]"),
+    ('➕', "+"),
+    ('➖', "-"),
+    ('•', "*"),
+    ('→', "->"),
+    ('…', "..."),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Charset {
+    #[default]
+    Unicode,
+    Ascii,
+}
+
+impl Charset {
+    pub fn from_ascii(ascii: bool) -> Self {
+        if ascii { Self::Ascii } else { Self::Unicode }
+    }
+
+    /// Charset of this process's console output
+    pub fn current() -> Self {
+        Self::from_ascii(ASCII_OUTPUT.load(Ordering::Relaxed))
+    }
+
+    pub fn set_current(self) {
+        ASCII_OUTPUT.store(self == Self::Ascii, Ordering::Relaxed);
+    }
+
+    /// `text` as it should be printed in this charset
+    pub fn decorate(self, text: &str) -> Cow<'_, str> {
+        if self == Self::Unicode || text.is_ascii() {
+            return Cow::Borrowed(text);
+        }
+
+        let mut out = String::with_capacity(text.len());
+        let mut chars = text.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c.is_ascii() {
+                out.push(c);
+            } else if let Some((_, fallback)) = FALLBACKS.iter().find(|(symbol, _)| *symbol == c) {
+                out.push_str(fallback);
+            } else if c.is_alphanumeric() {
+                out.push('?');
+            } else if chars.peek() == Some(&' ') && (out.is_empty() || out.ends_with([' ', '\n'])) {
+                // A dropped leading symbol takes its separating space along
+                chars.next();
+            }
+        }
+        Cow::Owned(out)
+    }
+}
+
+/// `println!` with its line passed through `Charset::current()`
+macro_rules! status {
+    ($($arg:tt)*) => {
+        println!("{}", $crate::utils::decoration::Charset::current().decorate(&format!($($arg)*)))
+    };
+}
+
+/// `eprintln!` with its line passed through `Charset::current()`
+// Only the CLI commands, which the library target does not compile, use it
+#[allow(unused_macros)]
+macro_rules! status_err {
+    ($($arg:tt)*) => {
+        eprintln!("{}", $crate::utils::decoration::Charset::current().decorate(&format!($($arg)*)))
+    };
+}
+
+pub(crate) use status;
+#[allow(unused_imports)]
+pub(crate) use status_err;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ascii_charset_leaves_no_non_ascii_bytes() {
+        let lines = [
+            "🔍 Searching for: 'login'",
+            "⚠️  ML service initialization failed: timeout",
+            "## 🏗️ Project Structure",
+            "- ✅ Routing",
+            "🚀 Initializing ML pipeline: Embedding → LSH → Reranker",
+            "  📄 qwen3-embedding • café.rs",
+        ];
+
+        let decorated: Vec<String> = lines.iter().map(|line| Charset::Ascii.decorate(line).into_owned()).collect();
+        for line in &decorated {
+            assert!(line.is_ascii(), "{:?} is not ASCII", line);
+        }
+        assert_eq!(decorated[0], "Searching for: 'login'");
+        assert_eq!(decorated[1], "[warn]  ML service initialization failed: timeout");
+        assert_eq!(decorated[2], "## Project Structure");
+        assert_eq!(decorated[3], "- [ok] Routing");
+        assert_eq!(decorated[4], "Initializing ML pipeline: Embedding -> LSH -> Reranker");
+        assert_eq!(decorated[5], "  qwen3-embedding * caf?.rs");
+
+        assert_eq!(Charset::Unicode.decorate(lines[0]), lines[0]);
+    }
+}
@@ -1,9 +1,26 @@
 use anyhow::Result;
 use std::path::Path;
 use std::fs;
+use std::io::{BufRead, BufReader, Read};
 use walkdir::WalkDir;
 use crate::types::FileType;
 
+/// Files larger than this are skipped by `walk_project_files`
+pub const MAX_WALKED_FILE_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Only this many leading bytes of a file are analyzed
+pub const MAX_ANALYZED_FILE_BYTES: u64 = 1024 * 1024;
+
+/// Result of `read_to_string_limited`
+#[derive(Debug, Clone)]
+pub struct LimitedContent {
+    pub content: String,
+    /// True when the file was longer than the limit and `content` was cut
+    pub truncated: bool,
+    /// Size of the whole file on disk
+    pub total_bytes: u64,
+}
+
 pub fn read_file_content(path: &Path) -> Result<String> {
     match fs::read_to_string(path) {
         Ok(content) => Ok(content),
@@ -16,6 +33,43 @@ pub fn read_file_content(path: &Path) -> Result<String> {
     }
 }
 
+/// Reads at most `max_bytes` of a file. A truncated read is cut back to the
+/// last complete line so no line or UTF-8 sequence is split.
+pub fn read_to_string_limited(path: &Path, max_bytes: u64) -> Result<LimitedContent> {
+    let file = fs::File::open(path)?;
+    let total_bytes = file.metadata()?.len();
+    let truncated = total_bytes > max_bytes;
+
+    let mut bytes = Vec::with_capacity(total_bytes.min(max_bytes) as usize);
+    file.take(max_bytes).read_to_end(&mut bytes)?;
+
+    if truncated {
+        let end = bytes.iter().rposition(|&b| b == b'\n').map(|i| i + 1).unwrap_or(0);
+        bytes.truncate(end);
+    }
+
+    let content = match String::from_utf8(bytes) {
+        Ok(content) => content,
+        Err(e) => String::from_utf8_lossy(e.as_bytes()).into_owned(),
+    };
+
+    Ok(LimitedContent { content, truncated, total_bytes })
+}
+
+/// Iterates over the lines of a file without loading it into memory.
+/// Invalid UTF-8 is replaced rather than treated as an error.
+pub fn read_lines(path: &Path) -> Result<impl Iterator<Item = std::io::Result<String>>> {
+    let reader = BufReader::new(fs::File::open(path)?);
+    Ok(reader.split(b'\n').map(|line| {
+        line.map(|mut bytes| {
+            if bytes.last() == Some(&b'\r') {
+                bytes.pop();
+            }
+            String::from_utf8_lossy(&bytes).into_owned()
+        })
+    }))
+}
+
 pub fn get_file_size(path: &Path) -> Result<u64> {
     Ok(fs::metadata(path)?.len())
 }
@@ -214,7 +268,8 @@ pub fn walk_project_files(root: &Path) -> Result<Vec<String>> {
         .filter_map(|e| e.ok())
     {
         let path = entry.path();
-        if path.is_file() && !is_ignored_file(path) {
+        let oversized = entry.metadata().map(|m| m.len() > MAX_WALKED_FILE_BYTES).unwrap_or(false);
+        if path.is_file() && !is_ignored_file(path) && !oversized {
             // Support for hybrid projects with multiple languages
             if let Some(extension) = path.extension() {
                 if matches!(extension.to_str(), 
@@ -249,6 +304,29 @@ mod tests {
     use std::io::Write;
     use crate::types::Complexity;
 
+    #[test]
+    fn test_read_to_string_limited_truncates_large_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("large.ts");
+        let line = "export const value = 42;\n";
+        fs::write(&path, line.repeat(4096)).unwrap();
+        let file_size = (line.len() * 4096) as u64;
+
+        let limited = read_to_string_limited(&path, 1000).unwrap();
+        assert!(limited.truncated);
+        assert_eq!(limited.total_bytes, file_size);
+        assert!(limited.content.len() <= 1000);
+        assert!(limited.content.capacity() < file_size as usize);
+        assert!(limited.content.ends_with('\n'));
+        assert_eq!(limited.content.lines().count(), 1000 / line.len());
+
+        let whole = read_to_string_limited(&path, file_size).unwrap();
+        assert!(!whole.truncated);
+        assert_eq!(whole.content.len() as u64, file_size);
+
+        assert_eq!(read_lines(&path).unwrap().count(), 4096);
+    }
+
     #[test]
     fn test_count_lines() {
         assert_eq!(count_lines(""), 0);
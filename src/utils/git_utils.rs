@@ -1,5 +1,6 @@
 use git2::{Repository, Status};
 use anyhow::Result;
+use std::collections::HashMap;
 use std::path::Path;
 
 pub struct GitUtils {
@@ -66,6 +67,31 @@ impl GitUtils {
         Ok((lines_added, lines_removed))
     }
 
+    /// How many of the last `max_commits` commits on HEAD touched each file,
+    /// keyed by path relative to the repository root
+    pub fn recent_change_counts(&self, max_commits: usize) -> Result<HashMap<String, usize>> {
+        let mut counts = HashMap::new();
+        let mut revwalk = self.repo.revwalk()?;
+        revwalk.push_head()?;
+
+        for oid in revwalk.take(max_commits) {
+            let commit = self.repo.find_commit(oid?)?;
+            let tree = commit.tree()?;
+            let parent_tree = match commit.parent(0) {
+                Ok(parent) => Some(parent.tree()?),
+                Err(_) => None,
+            };
+            let diff = self.repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)?;
+            for delta in diff.deltas() {
+                if let Some(path) = delta.new_file().path() {
+                    *counts.entry(path.to_string_lossy().to_string()).or_insert(0) += 1;
+                }
+            }
+        }
+
+        Ok(counts)
+    }
+
     pub fn get_branch_name(&self) -> Result<String> {
         let head = self.repo.head()?;
         if let Some(name) = head.shorthand() {
@@ -75,6 +101,12 @@ impl GitUtils {
         }
     }
 
+    /// Commit hash a revision such as `HEAD~1`, a branch or a tag names
+    pub fn resolve_commit(&self, reference: &str) -> Result<String> {
+        let commit = self.repo.revparse_single(reference)?.peel_to_commit()?;
+        Ok(commit.id().to_string())
+    }
+
     pub fn get_last_commit_hash(&self) -> Result<String> {
         let head = self.repo.head()?;
         let commit = head.peel_to_commit()?;
@@ -2,7 +2,15 @@ pub mod file_utils;
 pub mod git_utils;
 pub mod hash_utils;
 pub mod path_normalizer;
+pub mod cache_dir;
+pub mod tokenizer;
+pub mod decoration;
+pub mod comments;
+pub mod projection;
 
 pub use file_utils::*;
 pub use git_utils::*;
 pub use hash_utils::*;
+pub use cache_dir::*;
+pub use tokenizer::*;
+pub use comments::*;
@@ -1,6 +1,42 @@
 //! Path normalization utilities for consistent file path handling
 
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// How file paths are rendered in command output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PathStyle {
+    /// Relative to the project root with forward slashes, e.g. `src/app/a.ts`
+    #[default]
+    Relative,
+    /// Absolute with forward slashes
+    Absolute,
+}
+
+impl FromStr for PathStyle {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "relative" => Ok(PathStyle::Relative),
+            "absolute" => Ok(PathStyle::Absolute),
+            other => Err(format!("invalid path style '{}', expected 'relative' or 'absolute'", other)),
+        }
+    }
+}
+
+/// Places `path` under the repo namespace `prefix`, keeping its `./` marker:
+/// cache keys (`./src/a.ts`) become `./repo/src/a.ts`, indexed paths and ids
+/// (`src/a.ts`) become `repo/src/a.ts`. Cache and vector merges both use it,
+/// so a merged file formats to the same `repo/src/a.ts` from either store.
+pub fn namespace_path(prefix: &str, path: &str) -> String {
+    let prefix = prefix.trim_matches('/');
+    let (marker, relative) = match path.strip_prefix("./") {
+        Some(relative) => ("./", relative),
+        None => ("", path),
+    };
+    format!("{}{}/{}", marker, prefix, relative.trim_start_matches('/'))
+}
 
 /// Normalize file paths to be consistent relative to project root
 pub struct PathNormalizer {
@@ -122,6 +158,28 @@ impl PathNormalizer {
     pub fn get_project_root(&self) -> &Path {
         &self.project_root
     }
+
+    /// Render any path representation (absolute, cache key, backslashes) for
+    /// output, so every command reports the same file the same way
+    pub fn format_path(&self, path: &str, style: PathStyle) -> String {
+        let unified = path.replace('\\', "/");
+        let absolute_root = self.project_root.canonicalize().unwrap_or_else(|_| self.project_root.clone());
+
+        let relative = if Path::new(&unified).is_absolute() {
+            match Path::new(&unified).strip_prefix(&absolute_root) {
+                Ok(relative) => relative.to_string_lossy().to_string(),
+                Err(_) => self.normalize_to_project_relative(Path::new(&unified)),
+            }
+        } else {
+            unified
+        };
+        let relative = relative.trim_start_matches("./").to_string();
+
+        match style {
+            PathStyle::Relative => relative,
+            PathStyle::Absolute => absolute_root.join(&relative).to_string_lossy().replace('\\', "/"),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -210,4 +268,37 @@ mod tests {
         // Should handle project names with special characters
         assert_eq!(normalized, "./generic-project/src/app/test.component.ts");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_format_path_is_identical_across_representations() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_root = temp_dir.path().join("shop");
+        fs::create_dir_all(project_root.join("src/app")).unwrap();
+        let normalizer = PathNormalizer::new(&project_root);
+        let canonical_root = project_root.canonicalize().unwrap();
+
+        // How analyze (cache key), search (indexed path) and overview (absolute metadata path) see one file
+        let as_cache_key = "./src/app/cart.service.ts";
+        let as_indexed = "src\\app\\cart.service.ts";
+        let as_absolute = canonical_root.join("src/app/cart.service.ts").to_string_lossy().to_string();
+
+        for (style, expected) in [
+            (PathStyle::Relative, "src/app/cart.service.ts".to_string()),
+            (PathStyle::Absolute, format!("{}/src/app/cart.service.ts", canonical_root.to_string_lossy().replace('\\', "/"))),
+        ] {
+            assert_eq!(normalizer.format_path(as_cache_key, style), expected);
+            assert_eq!(normalizer.format_path(as_indexed, style), expected);
+            assert_eq!(normalizer.format_path(&as_absolute, style), expected);
+        }
+
+        // A merged file reads the same from the cache and the vector index
+        let merged_key = namespace_path("repo-a/", as_cache_key);
+        let merged_indexed = namespace_path("repo-a", "src/app/cart.service.ts");
+        assert_eq!(merged_key, "./repo-a/src/app/cart.service.ts");
+        assert_eq!(merged_indexed, "repo-a/src/app/cart.service.ts");
+        assert_eq!(normalizer.format_path(&merged_key, PathStyle::Relative), normalizer.format_path(&merged_indexed, PathStyle::Relative));
+
+        assert_eq!("absolute".parse::<PathStyle>(), Ok(PathStyle::Absolute));
+        assert!("windows".parse::<PathStyle>().is_err());
+    }
+}
@@ -0,0 +1,91 @@
+//! Field projection of JSON results
+//!
+//! Clients on a token budget can ask for only some fields of a result,
+//! e.g. `file_path,rerank_score`. Fields are top-level keys or dotted paths
+//! into nested objects; arrays are projected element by element.
+
+use serde_json::{Map, Value};
+
+/// `value` with only `fields` kept; an empty list keeps everything.
+/// Requested fields that `value` does not have are left out.
+pub fn project_fields(value: &Value, fields: &[String]) -> Value {
+    if fields.is_empty() {
+        return value.clone();
+    }
+
+    match value {
+        Value::Array(items) => Value::Array(items.iter().map(|item| project_fields(item, fields)).collect()),
+        Value::Object(object) => {
+            let mut projected = Map::new();
+            for field in fields {
+                let path: Vec<&str> = field.split('.').collect();
+                if let Some(found) = lookup(object, &path) {
+                    insert(&mut projected, &path, found.clone());
+                }
+            }
+            Value::Object(projected)
+        }
+        other => other.clone(),
+    }
+}
+
+/// Field names from a comma-separated list such as `file_path, rerank_score`
+pub fn parse_fields(list: &str) -> Vec<String> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|field| !field.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn lookup<'a>(object: &'a Map<String, Value>, path: &[&str]) -> Option<&'a Value> {
+    let (first, rest) = path.split_first()?;
+    let value = object.get(*first)?;
+    if rest.is_empty() {
+        Some(value)
+    } else {
+        lookup(value.as_object()?, rest)
+    }
+}
+
+fn insert(object: &mut Map<String, Value>, path: &[&str], value: Value) {
+    match path {
+        [] => {}
+        [last] => {
+            object.insert(last.to_string(), value);
+        }
+        [first, rest @ ..] => {
+            let child = object.entry(first.to_string()).or_insert_with(|| Value::Object(Map::new()));
+            if let Value::Object(child) = child {
+                insert(child, rest, value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_projection_keeps_only_requested_fields() {
+        let results = json!([
+            { "file_path": "src/auth.rs", "rerank_score": 0.9, "embedding_similarity": 0.7, "snippet": "fn login()",
+              "entry": { "metadata": { "language": "rust", "complexity": 3.0 } } },
+            { "file_path": "src/user.rs", "rerank_score": 0.4, "embedding_similarity": 0.5, "snippet": "fn load()",
+              "entry": { "metadata": { "language": "rust", "complexity": 1.0 } } },
+        ]);
+
+        let projected = project_fields(&results, &parse_fields("file_path, rerank_score"));
+        assert_eq!(projected, json!([
+            { "file_path": "src/auth.rs", "rerank_score": 0.9 },
+            { "file_path": "src/user.rs", "rerank_score": 0.4 },
+        ]));
+
+        let nested = project_fields(&results[0], &parse_fields("entry.metadata.language,missing"));
+        assert_eq!(nested, json!({ "entry": { "metadata": { "language": "rust" } } }));
+
+        assert_eq!(project_fields(&results, &[]), results);
+    }
+}
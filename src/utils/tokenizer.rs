@@ -0,0 +1,59 @@
+//! Pluggable token counting so budgets match the target model's tokenizer
+
+use std::sync::Arc;
+use tiktoken_rs::CoreBPE;
+
+/// Counts tokens for a specific encoding
+pub trait Tokenizer: Send + Sync {
+    fn count_tokens(&self, text: &str) -> usize;
+    fn encoding_name(&self) -> &str;
+}
+
+/// tiktoken-backed tokenizer (`cl100k_base` by default)
+pub struct TiktokenTokenizer {
+    bpe: &'static CoreBPE,
+    encoding: &'static str,
+}
+
+impl TiktokenTokenizer {
+    pub fn cl100k() -> Self {
+        Self {
+            bpe: tiktoken_rs::cl100k_base_singleton(),
+            encoding: "cl100k_base",
+        }
+    }
+}
+
+impl Default for TiktokenTokenizer {
+    fn default() -> Self {
+        Self::cl100k()
+    }
+}
+
+impl Tokenizer for TiktokenTokenizer {
+    fn count_tokens(&self, text: &str) -> usize {
+        self.bpe.encode_ordinary(text).len()
+    }
+
+    fn encoding_name(&self) -> &str {
+        self.encoding
+    }
+}
+
+/// Tokenizer used when none is injected
+pub fn default_tokenizer() -> Arc<dyn Tokenizer> {
+    Arc::new(TiktokenTokenizer::default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tiktoken_counts_tokens() {
+        let tokenizer = TiktokenTokenizer::default();
+        assert_eq!(tokenizer.encoding_name(), "cl100k_base");
+        assert_eq!(tokenizer.count_tokens(""), 0);
+        assert_eq!(tokenizer.count_tokens("hello world"), 2);
+    }
+}